@@ -124,7 +124,7 @@ pub trait ImageApi: Send + Sync {
     fn image_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse2009, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::InlineResponse2009, Error = Error<serde_json::Value>> + Send>;
     fn image_push(
         &self,
         name: &str,
@@ -142,7 +142,7 @@ pub trait ImageApi: Send + Sync {
         name: &str,
         repo: &str,
         tag: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
 }
 
 impl<C> ImageApi for ImageApiClient<C>
@@ -827,7 +827,8 @@ where
     fn image_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse2009, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::InlineResponse2009, Error = Error<serde_json::Value>> + Send>
+    {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -995,7 +996,7 @@ where
         name: &str,
         repo: &str,
         tag: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
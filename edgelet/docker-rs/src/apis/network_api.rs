@@ -38,7 +38,7 @@ pub trait NetworkApi: Send + Sync {
         &self,
         id: &str,
         container: ::models::Container,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
     fn network_create(
         &self,
         network_config: ::models::NetworkConfig,
@@ -75,7 +75,7 @@ where
         &self,
         id: &str,
         container: ::models::Container,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
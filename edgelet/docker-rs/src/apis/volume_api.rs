@@ -54,7 +54,7 @@ pub trait VolumeApi: Send + Sync {
     fn volume_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>> + Send>;
 }
 
 impl<C> VolumeApi for VolumeApiClient<C>
@@ -278,7 +278,8 @@ where
     fn volume_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>> + Send>
+    {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -0,0 +1,334 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use failure::{Fail, ResultExt};
+use futures::{future, Future, Stream};
+use log::Level;
+use tokio::timer::Interval;
+
+use edgelet_utils::log_failure;
+
+use certificate_properties::CertificateProperties;
+use crypto::{Certificate, CreateCertificate, GetTrustBundle};
+use error::{Error, ErrorKind};
+
+/// This is the default time before a certificate expires from which on it is
+/// warned about (14 days).
+const DEFAULT_WARNING_THRESHOLD_SECS: u64 = 1_209_600;
+
+/// This is the default time between checks of the certificates' expiration.
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// This is the default time before a server certificate expires from which on
+/// it is renewed (1 day).
+const DEFAULT_SERVER_CERT_RENEWAL_THRESHOLD_SECS: u64 = 86400;
+
+const TRUST_BUNDLE_NAME: &str = "trust bundle";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CertificateExpirySettings {
+    /// Certificates are warned about once they expire within this many
+    /// seconds.
+    #[serde(default = "default_warning_threshold_secs")]
+    warning_threshold_secs: u64,
+    #[serde(default = "default_check_interval_secs")]
+    check_interval_secs: u64,
+    /// Server certificates issued to modules are renewed once they expire
+    /// within this many seconds.
+    #[serde(default = "default_server_cert_renewal_threshold_secs")]
+    server_cert_renewal_threshold_secs: u64,
+}
+
+fn default_warning_threshold_secs() -> u64 {
+    DEFAULT_WARNING_THRESHOLD_SECS
+}
+
+fn default_check_interval_secs() -> u64 {
+    DEFAULT_CHECK_INTERVAL_SECS
+}
+
+fn default_server_cert_renewal_threshold_secs() -> u64 {
+    DEFAULT_SERVER_CERT_RENEWAL_THRESHOLD_SECS
+}
+
+impl Default for CertificateExpirySettings {
+    fn default() -> Self {
+        CertificateExpirySettings {
+            warning_threshold_secs: DEFAULT_WARNING_THRESHOLD_SECS,
+            check_interval_secs: DEFAULT_CHECK_INTERVAL_SECS,
+            server_cert_renewal_threshold_secs: DEFAULT_SERVER_CERT_RENEWAL_THRESHOLD_SECS,
+        }
+    }
+}
+
+impl CertificateExpirySettings {
+    pub fn warning_threshold(&self) -> Duration {
+        Duration::from_secs(self.warning_threshold_secs)
+    }
+
+    pub fn with_warning_threshold(mut self, warning_threshold: Duration) -> Self {
+        self.warning_threshold_secs = warning_threshold.as_secs();
+        self
+    }
+
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval_secs)
+    }
+
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval_secs = check_interval.as_secs();
+        self
+    }
+
+    pub fn server_cert_renewal_threshold(&self) -> Duration {
+        Duration::from_secs(self.server_cert_renewal_threshold_secs)
+    }
+
+    pub fn with_server_cert_renewal_threshold(mut self, renewal_threshold: Duration) -> Self {
+        self.server_cert_renewal_threshold_secs = renewal_threshold.as_secs();
+        self
+    }
+}
+
+/// A certificate that expires within the warning threshold, or has already
+/// expired.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpiringCertificate {
+    name: String,
+    valid_to: DateTime<Utc>,
+}
+
+impl ExpiringCertificate {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn valid_to(&self) -> DateTime<Utc> {
+        self.valid_to
+    }
+}
+
+impl fmt::Display for ExpiringCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Certificate {} expires at {}",
+            self.name,
+            self.valid_to.to_rfc3339()
+        )
+    }
+}
+
+/// Periodically checks when the trust bundle and the certificates created
+/// with the given properties expire, and warns about those that expire soon.
+/// The certificates are looked up by creating them, which returns the
+/// existing certificate for an alias, so that one that has already expired
+/// is created anew.
+pub struct CertificateExpiryMonitor<C> {
+    crypto: C,
+    certificates: Vec<CertificateProperties>,
+    settings: CertificateExpirySettings,
+}
+
+impl<C> CertificateExpiryMonitor<C>
+where
+    C: 'static + CreateCertificate + GetTrustBundle,
+{
+    pub fn new(
+        crypto: C,
+        certificates: Vec<CertificateProperties>,
+        settings: CertificateExpirySettings,
+    ) -> Self {
+        CertificateExpiryMonitor {
+            crypto,
+            certificates,
+            settings,
+        }
+    }
+
+    /// Returns the certificates that expire within the warning threshold of
+    /// `now`.
+    pub fn check(&self, now: DateTime<Utc>) -> Result<Vec<ExpiringCertificate>, Error> {
+        let threshold = ChronoDuration::from_std(self.settings.warning_threshold())
+            .unwrap_or_else(|_| ChronoDuration::max_value());
+        let warn_after = now.checked_add_signed(threshold);
+
+        let mut valid_to = vec![];
+        for props in &self.certificates {
+            let cert = self
+                .crypto
+                .create_certificate(props)
+                .context(ErrorKind::CertificateExpiry)?;
+            valid_to.push((
+                props.alias().to_string(),
+                cert.get_valid_to().context(ErrorKind::CertificateExpiry)?,
+            ));
+        }
+        let trust_bundle = self
+            .crypto
+            .get_trust_bundle()
+            .context(ErrorKind::CertificateExpiry)?;
+        valid_to.push((
+            TRUST_BUNDLE_NAME.to_string(),
+            trust_bundle
+                .get_valid_to()
+                .context(ErrorKind::CertificateExpiry)?,
+        ));
+
+        Ok(valid_to
+            .into_iter()
+            .filter(|(_, valid_to)| warn_after.map_or(true, |warn_after| *valid_to <= warn_after))
+            .map(|(name, valid_to)| ExpiringCertificate { name, valid_to })
+            .collect())
+    }
+
+    /// Checks the certificates right away and then at every check interval,
+    /// logging a warning for each certificate that expires soon. The
+    /// returned future only completes if the timer fails.
+    pub fn run(self) -> impl Future<Item = (), Error = Error> {
+        info!(
+            "Starting certificate expiration check with {} second frequency...",
+            self.settings.check_interval().as_secs()
+        );
+
+        Interval::new(Instant::now(), self.settings.check_interval())
+            .map_err(|err| Error::from(err.context(ErrorKind::CertificateExpiryTimer)))
+            .for_each(move |_| {
+                match self.check(Utc::now()) {
+                    Ok(expiring) => {
+                        for cert in expiring {
+                            warn!("{}", cert);
+                        }
+                    }
+                    Err(err) => log_failure(Level::Warn, &err),
+                }
+                future::ok(())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use certificate_properties::CertificateType;
+    use crypto::PrivateKey;
+
+    struct TestCert {
+        valid_to: DateTime<Utc>,
+    }
+
+    impl Certificate for TestCert {
+        type Buffer = Vec<u8>;
+        type KeyBuffer = Vec<u8>;
+
+        fn pem(&self) -> Result<Self::Buffer, Error> {
+            Ok(vec![])
+        }
+
+        fn get_private_key(&self) -> Result<Option<PrivateKey<Self::KeyBuffer>>, Error> {
+            Ok(None)
+        }
+
+        fn get_valid_to(&self) -> Result<DateTime<Utc>, Error> {
+            Ok(self.valid_to)
+        }
+    }
+
+    struct TestCrypto {
+        cert_valid_to: DateTime<Utc>,
+        trust_bundle_valid_to: DateTime<Utc>,
+    }
+
+    impl CreateCertificate for TestCrypto {
+        type Certificate = TestCert;
+
+        fn create_certificate(
+            &self,
+            _properties: &CertificateProperties,
+        ) -> Result<Self::Certificate, Error> {
+            Ok(TestCert {
+                valid_to: self.cert_valid_to,
+            })
+        }
+
+        fn destroy_certificate(&self, _alias: String) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl GetTrustBundle for TestCrypto {
+        type Certificate = TestCert;
+
+        fn get_trust_bundle(&self) -> Result<Self::Certificate, Error> {
+            Ok(TestCert {
+                valid_to: self.trust_bundle_valid_to,
+            })
+        }
+    }
+
+    fn monitor(
+        cert_valid_to: DateTime<Utc>,
+        trust_bundle_valid_to: DateTime<Utc>,
+    ) -> CertificateExpiryMonitor<TestCrypto> {
+        CertificateExpiryMonitor::new(
+            TestCrypto {
+                cert_valid_to,
+                trust_bundle_valid_to,
+            },
+            vec![CertificateProperties::new(
+                3600,
+                "workload ca".to_string(),
+                CertificateType::Ca,
+                "workload-ca".to_string(),
+            )],
+            CertificateExpirySettings::default()
+                .with_warning_threshold(Duration::from_secs(7 * 24 * 3600)),
+        )
+    }
+
+    #[test]
+    fn near_expiry_certificate_is_reported() {
+        let now = Utc::now();
+        let monitor = monitor(
+            now + ChronoDuration::days(2),
+            now + ChronoDuration::days(365),
+        );
+
+        let expiring = monitor.check(now).unwrap();
+        assert_eq!(
+            vec![ExpiringCertificate {
+                name: "workload-ca".to_string(),
+                valid_to: now + ChronoDuration::days(2),
+            }],
+            expiring
+        );
+    }
+
+    #[test]
+    fn expired_trust_bundle_is_reported() {
+        let now = Utc::now();
+        let monitor = monitor(
+            now + ChronoDuration::days(30),
+            now - ChronoDuration::days(1),
+        );
+
+        let expiring = monitor.check(now).unwrap();
+        assert_eq!(1, expiring.len());
+        assert_eq!("trust bundle", expiring[0].name());
+    }
+
+    #[test]
+    fn far_off_certificates_are_not_reported() {
+        let now = Utc::now();
+        let monitor = monitor(
+            now + ChronoDuration::days(30),
+            now + ChronoDuration::days(365),
+        );
+
+        assert!(monitor.check(now).unwrap().is_empty());
+    }
+}
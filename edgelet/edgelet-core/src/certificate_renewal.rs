@@ -0,0 +1,274 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use failure::{Fail, ResultExt};
+use futures::{future, Future, Stream};
+use log::Level;
+use tokio::timer::Interval;
+
+use edgelet_utils::log_failure;
+
+use certificate_expiry::CertificateExpirySettings;
+use certificate_properties::CertificateProperties;
+use crypto::{Certificate, CreateCertificate};
+use error::{Error, ErrorKind};
+
+/// The server certificates that were issued to modules, by alias, so that
+/// they can be renewed before they expire. Issuing a certificate again for an
+/// alias replaces its properties.
+#[derive(Clone, Default)]
+pub struct IssuedCertificates {
+    certificates: Arc<Mutex<HashMap<String, CertificateProperties>>>,
+}
+
+impl IssuedCertificates {
+    pub fn new() -> Self {
+        IssuedCertificates::default()
+    }
+
+    pub fn insert(&self, props: CertificateProperties) {
+        self.certificates
+            .lock()
+            .expect("Lock on issued certificates failed")
+            .insert(props.alias().to_string(), props);
+    }
+
+    pub fn len(&self) -> usize {
+        self.certificates
+            .lock()
+            .expect("Lock on issued certificates failed")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn to_vec(&self) -> Vec<CertificateProperties> {
+        self.certificates
+            .lock()
+            .expect("Lock on issued certificates failed")
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Periodically reissues the issued server certificates that expire within
+/// the renewal threshold, so that the certificate in the key store is valid
+/// again before modules that use it hit TLS failures.
+pub struct CertificateRenewer<C> {
+    crypto: C,
+    issued: IssuedCertificates,
+    settings: CertificateExpirySettings,
+}
+
+impl<C> CertificateRenewer<C>
+where
+    C: 'static + CreateCertificate,
+{
+    pub fn new(crypto: C, issued: IssuedCertificates, settings: CertificateExpirySettings) -> Self {
+        CertificateRenewer {
+            crypto,
+            issued,
+            settings,
+        }
+    }
+
+    /// Renews the certificates that expire within the renewal threshold of
+    /// `now`, with the properties they were issued with, and returns their
+    /// aliases.
+    pub fn renew(&self, now: DateTime<Utc>) -> Result<Vec<String>, Error> {
+        let threshold = ChronoDuration::from_std(self.settings.server_cert_renewal_threshold())
+            .unwrap_or_else(|_| ChronoDuration::max_value());
+        let renew_after = now.checked_add_signed(threshold);
+
+        let mut renewed = vec![];
+        for props in self.issued.to_vec() {
+            // Creating a certificate returns the existing one for the alias.
+            let valid_to = self
+                .crypto
+                .create_certificate(&props)
+                .context(ErrorKind::CertificateRenewal)?
+                .get_valid_to()
+                .context(ErrorKind::CertificateRenewal)?;
+            if renew_after.map_or(true, |renew_after| valid_to <= renew_after) {
+                self.crypto
+                    .destroy_certificate(props.alias().to_string())
+                    .context(ErrorKind::CertificateRenewal)?;
+                self.crypto
+                    .create_certificate(&props)
+                    .context(ErrorKind::CertificateRenewal)?;
+                renewed.push(props.alias().to_string());
+            }
+        }
+
+        Ok(renewed)
+    }
+
+    /// Renews the certificates right away and then at every check interval.
+    /// The returned future only completes if the timer fails.
+    pub fn run(self) -> impl Future<Item = (), Error = Error> {
+        info!(
+            "Starting server certificate renewal with {} second frequency...",
+            self.settings.check_interval().as_secs()
+        );
+
+        Interval::new(Instant::now(), self.settings.check_interval())
+            .map_err(|err| Error::from(err.context(ErrorKind::CertificateExpiryTimer)))
+            .for_each(move |_| {
+                match self.renew(Utc::now()) {
+                    Ok(renewed) => {
+                        for alias in renewed {
+                            info!("Renewed server certificate {}", alias);
+                        }
+                    }
+                    Err(err) => log_failure(Level::Warn, &err),
+                }
+                future::ok(())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    use super::*;
+
+    use certificate_properties::CertificateType;
+    use crypto::PrivateKey;
+
+    struct TestCert {
+        valid_to: DateTime<Utc>,
+    }
+
+    impl Certificate for TestCert {
+        type Buffer = Vec<u8>;
+        type KeyBuffer = Vec<u8>;
+
+        fn pem(&self) -> Result<Self::Buffer, Error> {
+            Ok(vec![])
+        }
+
+        fn get_private_key(&self) -> Result<Option<PrivateKey<Self::KeyBuffer>>, Error> {
+            Ok(None)
+        }
+
+        fn get_valid_to(&self) -> Result<DateTime<Utc>, Error> {
+            Ok(self.valid_to)
+        }
+    }
+
+    // Keeps the expiration of the certificates by alias, and issues new ones
+    // with the requested validity from `now`.
+    struct TestCrypto {
+        now: DateTime<Utc>,
+        certs: RefCell<HashMap<String, DateTime<Utc>>>,
+        destroyed: RefCell<Vec<String>>,
+    }
+
+    impl TestCrypto {
+        fn new(now: DateTime<Utc>) -> Self {
+            TestCrypto {
+                now,
+                certs: RefCell::new(HashMap::new()),
+                destroyed: RefCell::new(vec![]),
+            }
+        }
+
+        fn with_cert(self, alias: &str, valid_to: DateTime<Utc>) -> Self {
+            self.certs.borrow_mut().insert(alias.to_string(), valid_to);
+            self
+        }
+    }
+
+    impl CreateCertificate for TestCrypto {
+        type Certificate = TestCert;
+
+        #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap))]
+        fn create_certificate(
+            &self,
+            properties: &CertificateProperties,
+        ) -> Result<Self::Certificate, Error> {
+            let now = self.now;
+            let valid_to = *self
+                .certs
+                .borrow_mut()
+                .entry(properties.alias().to_string())
+                .or_insert_with(|| {
+                    now + ChronoDuration::seconds(*properties.validity_in_secs() as i64)
+                });
+            Ok(TestCert { valid_to })
+        }
+
+        fn destroy_certificate(&self, alias: String) -> Result<(), Error> {
+            self.certs.borrow_mut().remove(&alias);
+            self.destroyed.borrow_mut().push(alias);
+            Ok(())
+        }
+    }
+
+    fn server_cert_props() -> CertificateProperties {
+        CertificateProperties::new(
+            30 * 24 * 3600,
+            "edgehub".to_string(),
+            CertificateType::Server,
+            "edgeHubIserver".to_string(),
+        )
+    }
+
+    fn renewer(crypto: TestCrypto) -> CertificateRenewer<TestCrypto> {
+        let issued = IssuedCertificates::new();
+        issued.insert(server_cert_props());
+        CertificateRenewer::new(
+            crypto,
+            issued,
+            CertificateExpirySettings::default()
+                .with_server_cert_renewal_threshold(Duration::from_secs(24 * 3600)),
+        )
+    }
+
+    #[test]
+    fn expiring_certificate_is_renewed_before_expiry() {
+        let now = Utc::now();
+        let expires = now + ChronoDuration::hours(2);
+        let renewer = renewer(TestCrypto::new(now).with_cert("edgeHubIserver", expires));
+
+        let renewed = renewer.renew(now).unwrap();
+
+        assert_eq!(vec!["edgeHubIserver".to_string()], renewed);
+        assert_eq!(
+            vec!["edgeHubIserver".to_string()],
+            *renewer.crypto.destroyed.borrow()
+        );
+        let valid_to = renewer.crypto.certs.borrow()["edgeHubIserver"];
+        assert_eq!(now + ChronoDuration::days(30), valid_to);
+        assert!(valid_to > expires);
+    }
+
+    #[test]
+    fn far_off_certificate_is_not_renewed() {
+        let now = Utc::now();
+        let expires = now + ChronoDuration::days(10);
+        let renewer = renewer(TestCrypto::new(now).with_cert("edgeHubIserver", expires));
+
+        assert!(renewer.renew(now).unwrap().is_empty());
+        assert!(renewer.crypto.destroyed.borrow().is_empty());
+        assert_eq!(expires, renewer.crypto.certs.borrow()["edgeHubIserver"]);
+    }
+
+    #[test]
+    fn issuing_again_replaces_certificate() {
+        let issued = IssuedCertificates::new();
+        issued.insert(server_cert_props());
+        issued.insert(server_cert_props());
+
+        assert_eq!(1, issued.len());
+    }
+}
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use std::convert::{AsRef, From};
 use std::fmt;
+use std::str::FromStr;
 use std::string::ToString;
 use std::sync::{Arc, RwLock};
 
@@ -11,7 +12,7 @@ use chrono::{DateTime, Utc};
 use consistenttime::ct_u8_slice_eq;
 use failure::ResultExt;
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sha2::{Sha256, Sha384, Sha512};
 
 use certificate_properties::CertificateProperties;
 use error::{Error, ErrorKind};
@@ -61,9 +62,45 @@ pub trait KeyStore {
     fn get(&self, identity: &KeyIdentity, key_name: &str) -> Result<Self::Key, Error>;
 }
 
-#[derive(Clone, Copy)]
+/// The digest a signature is computed with. Signatures default to
+/// HMAC-SHA256, the others are for services that require a stronger hash.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SignatureAlgorithm {
     HMACSHA256,
+    HMACSHA384,
+    HMACSHA512,
+}
+
+impl Default for SignatureAlgorithm {
+    fn default() -> Self {
+        SignatureAlgorithm::HMACSHA256
+    }
+}
+
+impl FromStr for SignatureAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HMACSHA256" => Ok(SignatureAlgorithm::HMACSHA256),
+            "HMACSHA384" => Ok(SignatureAlgorithm::HMACSHA384),
+            "HMACSHA512" => Ok(SignatureAlgorithm::HMACSHA512),
+            _ => Err(Error::from(ErrorKind::InvalidSignatureAlgorithm(
+                s.to_string(),
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            SignatureAlgorithm::HMACSHA256 => "HMACSHA256",
+            SignatureAlgorithm::HMACSHA384 => "HMACSHA384",
+            SignatureAlgorithm::HMACSHA512 => "HMACSHA512",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 pub trait Signature {
@@ -148,6 +185,82 @@ pub trait MasterEncryptionKey {
     fn destroy_key(&self) -> Result<(), Error>;
 }
 
+/// Replaces the master encryption key with a new one. Since the old key is
+/// destroyed, anything encrypted with it has to be handed in to be encrypted
+/// again with the new key.
+pub trait RotateMasterEncryptionKey {
+    /// Rotates the key, returning `data` encrypted with the new key in the
+    /// same order. Nothing is changed if any of `data` can't be decrypted.
+    fn rotate_key(&self, data: &[EncryptedData]) -> Result<Vec<EncryptedData>, Error>;
+}
+
+/// Exports the master encryption key protected by a passphrase, and restores
+/// it from such an export, so that encrypted data survives reinstalling the
+/// device or moving to another one.
+pub trait BackupMasterEncryptionKey {
+    /// Returns the master encryption key wrapped with a key derived from
+    /// `passphrase`.
+    fn export_key(&self, passphrase: &str) -> Result<Vec<u8>, Error>;
+
+    /// Replaces the master encryption key with the one in `backup`. Fails
+    /// without changing the key if `passphrase` is not the one `backup` was
+    /// exported with.
+    fn import_key(&self, passphrase: &str, backup: &[u8]) -> Result<(), Error>;
+}
+
+/// Holds master encryption keys in more than one slot, so that the key data
+/// is encrypted with can be rotated while data encrypted with the previous
+/// key can still be decrypted. Decryption tries the active slot first and then
+/// the others.
+pub trait MasterEncryptionKeySlots {
+    fn create_key_in_slot(&self, slot: usize) -> Result<(), Error>;
+    fn destroy_key_in_slot(&self, slot: usize) -> Result<(), Error>;
+
+    /// Makes the key in `slot` the one that data is encrypted with. Fails if
+    /// the slot has no key. The active slot is kept across restarts.
+    fn set_active_key_slot(&self, slot: usize) -> Result<(), Error>;
+
+    /// Returns the slot whose key data is encrypted with.
+    fn active_key_slot(&self) -> Result<usize, Error>;
+}
+
+/// Probes the HSM with a cheap operation, so that an HSM that stopped
+/// responding is noticed before a crypto operation that needs it fails.
+pub trait CheckHsmHealth {
+    fn check_hsm_health(&self) -> Result<(), Error>;
+}
+
+/// Ciphertext produced by `Encrypt`, along with the client ID and
+/// initialization vector it was encrypted with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedData {
+    client_id: Vec<u8>,
+    ciphertext: Vec<u8>,
+    initialization_vector: Vec<u8>,
+}
+
+impl EncryptedData {
+    pub fn new(client_id: Vec<u8>, ciphertext: Vec<u8>, initialization_vector: Vec<u8>) -> Self {
+        EncryptedData {
+            client_id,
+            ciphertext,
+            initialization_vector,
+        }
+    }
+
+    pub fn client_id(&self) -> &[u8] {
+        &self.client_id
+    }
+
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    pub fn initialization_vector(&self) -> &[u8] {
+        &self.initialization_vector
+    }
+}
+
 pub trait Encrypt {
     type Buffer: AsRef<[u8]>;
 
@@ -214,24 +327,32 @@ impl Sign for MemoryKey {
         signature_algorithm: SignatureAlgorithm,
         data: &[u8],
     ) -> Result<Self::Signature, Error> {
-        let signature = match signature_algorithm {
+        // `result` has type `MacResult` which is a thin wrapper around array of
+        // bytes for providing constant time equality check. To get underlying
+        // array use `code` method, but be careful, since incorrect use of the
+        // code value may permit timing attacks which defeat the security
+        // provided by the `MacResult` (https://docs.rs/hmac/0.5.0/hmac/)
+        let code_bytes = match signature_algorithm {
             SignatureAlgorithm::HMACSHA256 => {
-                // Create `Mac` trait implementation, namely HMAC-SHA256
                 let mut mac = Hmac::<Sha256>::new(&self.key)
                     .map_err(|_| ErrorKind::SignInvalidKeyLength(self.key.len()))?;
                 mac.input(data);
-
-                // `result` has type `MacResult` which is a thin wrapper around array of
-                // bytes for providing constant time equality check
-                let result = mac.result();
-                // To get underlying array use `code` method, but be careful, since
-                // incorrect use of the code value may permit timing attacks which defeat
-                // the security provided by the `MacResult` (https://docs.rs/hmac/0.5.0/hmac/)
-                let code_bytes = result.code();
-
-                Digest::new(Bytes::from(code_bytes.as_ref()))
+                Bytes::from(mac.result().code().as_ref())
+            }
+            SignatureAlgorithm::HMACSHA384 => {
+                let mut mac = Hmac::<Sha384>::new(&self.key)
+                    .map_err(|_| ErrorKind::SignInvalidKeyLength(self.key.len()))?;
+                mac.input(data);
+                Bytes::from(mac.result().code().as_ref())
+            }
+            SignatureAlgorithm::HMACSHA512 => {
+                let mut mac = Hmac::<Sha512>::new(&self.key)
+                    .map_err(|_| ErrorKind::SignInvalidKeyLength(self.key.len()))?;
+                mac.input(data);
+                Bytes::from(mac.result().code().as_ref())
             }
         };
+        let signature = Digest::new(code_bytes);
         Ok(signature)
     }
 }
@@ -344,19 +465,26 @@ impl<K> DerivedKeyStore<K> {
     }
 }
 
+/// Returns the data a key is derived from, which is namespaced by the scope
+/// of the identity. The module ID is length-prefixed, so that no two
+/// identities and key names result in the same data. Key stores that derive
+/// keys themselves, like the TPM, use the same data.
+pub fn derivation_data(identity: &KeyIdentity, key_name: &str) -> String {
+    match identity {
+        KeyIdentity::Device => format!("device/{}", key_name),
+        KeyIdentity::Module(ref m) => format!("module/{}/{}/{}", m.len(), m, key_name),
+    }
+}
+
 impl<K: Sign> KeyStore for DerivedKeyStore<K> {
     type Key = MemoryKey;
 
     fn get(&self, identity: &KeyIdentity, key_name: &str) -> Result<Self::Key, Error> {
-        let data = match identity {
-            KeyIdentity::Device => "",
-            KeyIdentity::Module(ref m) => m,
-        };
         let signature = self
             .root
             .sign(
                 SignatureAlgorithm::HMACSHA256,
-                format!("{}{}", data, key_name).as_bytes(),
+                derivation_data(identity, key_name).as_bytes(),
             )
             .context(ErrorKind::Sign)?;
         Ok(MemoryKey::new(signature.as_bytes()))
@@ -436,6 +564,81 @@ mod tests {
         assert_ne!(expected, result_hmac256.as_bytes());
     }
 
+    #[test]
+    fn sha384_sign_test_positive() {
+        //Arrange
+        let in_memory_key = MemoryKey {
+            key: Bytes::from("key"),
+        };
+        let data = b"The quick brown fox jumps over the lazy dog";
+        //Act
+        let result_hmac384 = in_memory_key
+            .sign(SignatureAlgorithm::HMACSHA384, data)
+            .unwrap();
+        let result_hmac256 = in_memory_key
+            .sign(SignatureAlgorithm::HMACSHA256, data)
+            .unwrap();
+
+        //Assert
+        let expected_bytes = [
+            0xd7, 0xf4, 0x72, 0x7e, 0x2c, 0x0b, 0x39, 0xae, 0x0f, 0x1e, 0x40, 0xcc, 0x96, 0xf6,
+            0x02, 0x42, 0xd5, 0xb7, 0x80, 0x18, 0x41, 0xce, 0xa6, 0xfc, 0x59, 0x2c, 0x5d, 0x3e,
+            0x1a, 0xe5, 0x07, 0x00, 0x58, 0x2a, 0x96, 0xcf, 0x35, 0xe1, 0xe5, 0x54, 0x99, 0x5f,
+            0xe4, 0xe0, 0x33, 0x81, 0xc2, 0x37,
+        ];
+
+        assert_eq!(expected_bytes.as_ref(), result_hmac384.as_bytes());
+        assert_ne!(result_hmac256.as_bytes(), result_hmac384.as_bytes());
+    }
+
+    #[test]
+    fn sha512_sign_test_positive() {
+        //Arrange
+        let in_memory_key = MemoryKey {
+            key: Bytes::from("key"),
+        };
+        let data = b"The quick brown fox jumps over the lazy dog";
+        //Act
+        let result_hmac512 = in_memory_key
+            .sign(SignatureAlgorithm::HMACSHA512, data)
+            .unwrap();
+
+        //Assert
+        let expected_bytes = [
+            0xb4, 0x2a, 0xf0, 0x90, 0x57, 0xba, 0xc1, 0xe2, 0xd4, 0x17, 0x08, 0xe4, 0x8a, 0x90,
+            0x2e, 0x09, 0xb5, 0xff, 0x7f, 0x12, 0xab, 0x42, 0x8a, 0x4f, 0xe8, 0x66, 0x53, 0xc7,
+            0x3d, 0xd2, 0x48, 0xfb, 0x82, 0xf9, 0x48, 0xa5, 0x49, 0xf7, 0xb7, 0x91, 0xa5, 0xb4,
+            0x19, 0x15, 0xee, 0x4d, 0x1e, 0xc3, 0x93, 0x53, 0x57, 0xe4, 0xe2, 0x31, 0x72, 0x50,
+            0xd0, 0x37, 0x2a, 0xfa, 0x2e, 0xbe, 0xeb, 0x3a,
+        ];
+
+        assert_eq!(expected_bytes.as_ref(), result_hmac512.as_bytes());
+    }
+
+    #[test]
+    fn signature_algorithm_defaults_to_sha256() {
+        assert_eq!(
+            SignatureAlgorithm::HMACSHA256,
+            SignatureAlgorithm::default()
+        );
+    }
+
+    #[test]
+    fn signature_algorithm_parses_names() {
+        assert_eq!(
+            SignatureAlgorithm::HMACSHA384,
+            "HMACSHA384".parse::<SignatureAlgorithm>().unwrap()
+        );
+        assert_eq!(
+            "HMACSHA512",
+            SignatureAlgorithm::HMACSHA512.to_string().as_str()
+        );
+        match *"SHA1".parse::<SignatureAlgorithm>().unwrap_err().kind() {
+            ErrorKind::InvalidSignatureAlgorithm(ref s) => assert_eq!("SHA1", s),
+            ref kind => panic!("Unexpected error kind {:?}", kind),
+        }
+    }
+
     //MemoryKeyStoreTests
     #[test]
     fn create_empty_memory_keystore() {
@@ -538,8 +741,49 @@ mod tests {
             )
             .unwrap();
         assert_eq!(
-            "wBXO109hMjTfjUtQGtTmeqiqoqboLl8F5b7tR0of5yE=",
+            "rWgjVy21qUwrwPb4gnpGK8Z04eqop2beFfnMypSSeoM=",
             base64::encode(digest.as_bytes())
         );
     }
+
+    #[test]
+    fn derived_key_store_distinct_identities_yield_distinct_keys() {
+        let key_store = DerivedKeyStore::new(MemoryKey::new("key"));
+        let keys = vec![
+            key_store
+                .get(&KeyIdentity::Module("mod1".to_string()), "key")
+                .unwrap(),
+            key_store
+                .get(&KeyIdentity::Module("mod".to_string()), "1key")
+                .unwrap(),
+            key_store
+                .get(&KeyIdentity::Module("mod1/3".to_string()), "key")
+                .unwrap(),
+            key_store.get(&KeyIdentity::Device, "mod1key").unwrap(),
+            key_store
+                .get(&KeyIdentity::Device, "module/4/mod1/key")
+                .unwrap(),
+        ];
+
+        for (i, key1) in keys.iter().enumerate() {
+            for key2 in &keys[i + 1..] {
+                assert_ne!(key1.key, key2.key);
+            }
+        }
+    }
+
+    #[test]
+    fn derived_key_store_same_identity_is_stable() {
+        let key_store = DerivedKeyStore::new(MemoryKey::new("key"));
+        let identity = KeyIdentity::Module("mod1".to_string());
+
+        let key1 = key_store.get(&identity, "primary").unwrap();
+        let key2 = key_store.get(&identity, "primary").unwrap();
+        let key3 = DerivedKeyStore::new(MemoryKey::new("key"))
+            .get(&identity, "primary")
+            .unwrap();
+
+        assert_eq!(key1.key, key2.key);
+        assert_eq!(key1.key, key3.key);
+    }
 }
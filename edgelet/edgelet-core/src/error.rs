@@ -5,6 +5,8 @@ use std::fmt::Display;
 
 use failure::{Backtrace, Context, Fail};
 
+use crypto::SignatureAlgorithm;
+
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -19,6 +21,15 @@ pub enum ErrorKind {
     #[fail(display = "Identity error")]
     Certificate,
 
+    #[fail(display = "Could not check when certificates expire.")]
+    CertificateExpiry,
+
+    #[fail(display = "The timer that checks certificate expiration encountered an error.")]
+    CertificateExpiryTimer,
+
+    #[fail(display = "A server certificate could not be renewed.")]
+    CertificateRenewal,
+
     #[fail(
         display = "Edge runtime module has not been created in IoT Hub. Please make sure this device is an IoT Edge capable device."
     )]
@@ -27,9 +38,15 @@ pub enum ErrorKind {
     #[fail(display = "The timer that checks the edge runtime status encountered an error.")]
     EdgeRuntimeStatusCheckerTimer,
 
+    #[fail(display = "The timer that bounds the edge runtime shutdown encountered an error.")]
+    EdgeRuntimeShutdownTimer,
+
     #[fail(display = "An identity manager error occurred.")]
     IdentityManager,
 
+    #[fail(display = "The HSM health check failed.")]
+    HsmHealthCheck,
+
     #[fail(display = "A error occurred in the key store.")]
     KeyStore,
 
@@ -42,9 +59,17 @@ pub enum ErrorKind {
     #[fail(display = "Invalid module type {:?}", _0)]
     InvalidModuleType(String),
 
+    #[fail(display = "Invalid signature algorithm {:?}", _0)]
+    InvalidSignatureAlgorithm(String),
+
     #[fail(display = "Item not found.")]
     KeyStoreItemNotFound,
 
+    #[fail(
+        display = "The timer that lists the modules to forward their logs encountered an error."
+    )]
+    ModuleLogForwardingTimer,
+
     #[fail(display = "A module runtime error occurred.")]
     ModuleRuntime,
 
@@ -53,6 +78,12 @@ pub enum ErrorKind {
 
     #[fail(display = "Signing error occurred. Invalid key length: {}", _0)]
     SignInvalidKeyLength(usize),
+
+    #[fail(
+        display = "Signing error occurred. Unsupported signature algorithm: {}",
+        _0
+    )]
+    SignUnsupportedAlgorithm(SignatureAlgorithm),
 }
 
 impl Fail for Error {
@@ -15,12 +15,14 @@ extern crate bytes;
 extern crate chrono;
 extern crate consistenttime;
 extern crate failure;
+#[macro_use]
 extern crate futures;
 extern crate hmac;
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate rand;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
@@ -30,10 +32,13 @@ extern crate tokio;
 extern crate edgelet_utils;
 
 mod authorization;
+pub mod certificate_expiry;
 mod certificate_properties;
+pub mod certificate_renewal;
 pub mod crypto;
 mod error;
 mod identity;
+pub mod logs;
 mod module;
 pub mod pid;
 pub mod watchdog;
@@ -42,15 +47,16 @@ pub mod workload;
 pub use authorization::{Authorization, Policy};
 pub use certificate_properties::{CertificateIssuer, CertificateProperties, CertificateType};
 pub use crypto::{
-    Certificate, CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyBytes, KeyIdentity,
-    KeyStore, MasterEncryptionKey, PrivateKey, Signature, IOTEDGED_CA_ALIAS,
+    BackupMasterEncryptionKey, Certificate, CheckHsmHealth, CreateCertificate, Decrypt, Encrypt,
+    EncryptedData, GetTrustBundle, KeyBytes, KeyIdentity, KeyStore, MasterEncryptionKey,
+    MasterEncryptionKeySlots, PrivateKey, RotateMasterEncryptionKey, Signature, IOTEDGED_CA_ALIAS,
 };
 pub use error::{Error, ErrorKind};
 pub use identity::{AuthType, Identity, IdentityManager, IdentityOperation, IdentitySpec};
 pub use module::{
-    LogOptions, LogTail, Module, ModuleOperation, ModuleRegistry, ModuleRuntime,
-    ModuleRuntimeErrorReason, ModuleRuntimeState, ModuleSpec, ModuleStatus, RegistryOperation,
-    RuntimeOperation, SystemInfo,
+    HealthStatus, ImagePullPolicy, LogOptions, LogTail, Module, ModuleOperation, ModuleRegistry,
+    ModuleRuntime, ModuleRuntimeErrorReason, ModuleRuntimeState, ModuleSpec, ModuleStatus,
+    RegistryOperation, RuntimeOperation, SystemInfo,
 };
 pub use workload::WorkloadConfig;
 
@@ -65,3 +71,13 @@ lazy_static! {
 pub fn version() -> &'static str {
     &VERSION
 }
+
+/// The version of the build, without the commit it was built from.
+pub fn build_version() -> &'static str {
+    option_env!("VERSION").unwrap_or(env!("CARGO_PKG_VERSION"))
+}
+
+/// The commit the build was made from, if it was recorded.
+pub fn source_version() -> Option<&'static str> {
+    option_env!("BUILD_SOURCEVERSION")
+}
@@ -0,0 +1,382 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::collections::HashSet;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut, IntoBuf};
+use failure::Fail;
+use futures::prelude::*;
+use futures::stream;
+use log::Level;
+use tokio;
+use tokio::codec::length_delimited;
+use tokio::codec::FramedRead;
+use tokio::io::AsyncRead;
+use tokio::timer::Interval;
+
+use edgelet_utils::log_failure;
+
+use error::{Error, ErrorKind};
+use module::{LogOptions, LogTail, Module, ModuleRuntime};
+
+/// This is the time between listing the modules to start forwarding the logs
+/// of the ones that were created or started since.
+const FORWARDER_LIST_INTERVAL_SECS: u64 = 5;
+
+/// Forwards the logs of all modules to the log of the daemon, each line
+/// prefixed with the name of the module, so that a single stream carries the
+/// logs of the whole device. Only lines logged after forwarding started are
+/// forwarded.
+pub struct ModuleLogForwarder<M> {
+    runtime: M,
+    forwarded: Arc<Mutex<HashSet<String>>>,
+}
+
+impl<M> ModuleLogForwarder<M>
+where
+    M: 'static + ModuleRuntime + Clone + Send,
+    <M as ModuleRuntime>::ListFuture: 'static,
+    <M as ModuleRuntime>::Logs: 'static,
+    <M as ModuleRuntime>::LogsFuture: 'static,
+{
+    pub fn new(runtime: M) -> Self {
+        ModuleLogForwarder {
+            runtime,
+            forwarded: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Lists the modules right away and then periodically, and follows the
+    /// logs of each module whose logs are not followed yet. A module is
+    /// followed until its logs end, which they do when it stops. The
+    /// returned future only completes if the timer fails.
+    pub fn run(self) -> impl Future<Item = (), Error = Error> {
+        info!("Forwarding module logs...");
+
+        let ModuleLogForwarder { runtime, forwarded } = self;
+        Interval::new(
+            Instant::now(),
+            Duration::from_secs(FORWARDER_LIST_INTERVAL_SECS),
+        )
+        .map_err(|err| Error::from(err.context(ErrorKind::ModuleLogForwardingTimer)))
+        .for_each(move |_| {
+            let runtime = runtime.clone();
+            let forwarded = forwarded.clone();
+            runtime.list().then(move |result| {
+                match result {
+                    Ok(modules) => {
+                        for module in modules {
+                            let name = module.name().to_string();
+                            if forwarded.lock().unwrap().insert(name.clone()) {
+                                tokio::spawn(forward(&runtime, name, forwarded.clone()));
+                            }
+                        }
+                    }
+                    Err(err) => log_failure(Level::Warn, &err),
+                }
+                Ok(())
+            })
+        })
+    }
+}
+
+fn forward<M>(
+    runtime: &M,
+    name: String,
+    forwarded: Arc<Mutex<HashSet<String>>>,
+) -> impl Future<Item = (), Error = ()> + Send
+where
+    M: ModuleRuntime,
+    <M as ModuleRuntime>::Logs: 'static,
+    <M as ModuleRuntime>::LogsFuture: 'static,
+{
+    debug!("Forwarding the logs of module {}", name);
+
+    let options = LogOptions::new()
+        .with_follow(true)
+        .with_tail(LogTail::Num(0));
+    let prefix = name.clone();
+    runtime
+        .logs(&name, &options)
+        .map_err(|err| log_failure(Level::Warn, &err))
+        .and_then(move |logs| {
+            let logs = logs.map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()));
+            module_log_lines(&prefix, logs)
+                .for_each(|line| {
+                    info!("{}", line);
+                    Ok(())
+                })
+                .map_err(move |err| {
+                    warn!("Could not forward the logs of module {}: {}", prefix, err);
+                })
+        })
+        .then(move |_| {
+            debug!("Stopped forwarding the logs of module {}", name);
+            forwarded.lock().unwrap().remove(&name);
+            Ok(())
+        })
+}
+
+/// Decodes the logs of the module `name` into lines prefixed with its name.
+pub fn module_log_lines<S, C>(name: &str, logs: S) -> impl Stream<Item = String, Error = io::Error>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C, Error = io::Error>,
+{
+    let name = name.to_string();
+    LogDecode::new(Chunked::new(logs))
+        .map(move |chunk| {
+            let payload = match chunk {
+                LogChunk::Stdin(b)
+                | LogChunk::Stdout(b)
+                | LogChunk::Stderr(b)
+                | LogChunk::Unknown(b) => b,
+            };
+            let lines: Vec<_> = String::from_utf8_lossy(&payload)
+                .lines()
+                .map(|line| format!("[{}] {}", name, line))
+                .collect();
+            stream::iter_ok(lines)
+        })
+        .flatten()
+}
+
+/// Logs parser
+/// Logs are emitted with a simple header to specify stdout or stderr
+///
+///
+/// 01 00 00 00 00 00 00 1f 52 6f 73 65 73 20 61 72  65 ...
+/// │  ─────┬── ─────┬─────  R  o  s  e  s     a  r   e ...
+/// │       │        │
+/// └stdout │        │
+///         │        └ 0x0000001f = log message is 31 bytes
+///       unused
+///
+/// The following set of structs converts a `Stream<&[u8]>` into a `Stream<LogChunk>`
+/// by implementing [`AsyncRead`] on `Stream<&[u8]>` and then using the `length_delimited`
+/// decoder in tokio to emit [`BytesMut`] with complete frames. The [`LogChunk`]
+/// is then constructed from these [`BytesMut`]s
+
+#[derive(Debug, PartialEq)]
+pub enum LogChunk {
+    Stdin(Bytes),
+    Stdout(Bytes),
+    Stderr(Bytes),
+    Unknown(Bytes),
+}
+
+pub struct LogDecode<T: AsyncRead> {
+    inner: FramedRead<T, length_delimited::LengthDelimitedCodec>,
+}
+
+impl<T: AsyncRead> LogDecode<T> {
+    pub fn new(inner: T) -> Self {
+        let delimited = length_delimited::Builder::new()
+            .length_field_offset(4)
+            .length_field_length(4)
+            .length_adjustment(8)
+            .num_skip(0)
+            .new_read(inner);
+        LogDecode { inner: delimited }
+    }
+}
+
+impl<T: AsyncRead> Stream for LogDecode<T> {
+    type Item = LogChunk;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        // This parser assumes that the length_delimited decoder
+        // wraps this decoder. In this case, the BytesMut is
+        // guaranteed to have a full frame worth of bytes.
+        // We can simply read the needed bytes out of the BytesMut
+        // to construct the parsed Frame.
+
+        let option = try_ready!(self.inner.poll());
+        let result = option.map(|bytes| {
+            let mut buf = bytes.into_buf();
+            let stream_type = buf.get_u8();
+            buf.advance(3);
+            let _length = buf.get_u32_be();
+            let payload: Bytes = buf.collect();
+
+            match stream_type {
+                0 => LogChunk::Stdin(payload),
+                1 => LogChunk::Stdout(payload),
+                2 => LogChunk::Stderr(payload),
+                _ => LogChunk::Unknown(payload),
+            }
+        });
+        Ok(Async::Ready(result))
+    }
+}
+
+pub struct Chunked<S, C>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C, Error = io::Error>,
+{
+    inner: S,
+    remaining: Option<Bytes>,
+}
+
+impl<S, C> Chunked<S, C>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C, Error = io::Error>,
+{
+    pub fn new(inner: S) -> Self {
+        Chunked {
+            inner,
+            remaining: None,
+        }
+    }
+
+    fn read_remaining(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (result, r) = if let Some(ref remaining) = self.remaining {
+            let amt = cmp::min(remaining.len(), buf.len());
+            let (a, b) = remaining.split_at(amt);
+            buf[..amt].copy_from_slice(a);
+
+            if b.is_empty() {
+                (Ok(amt), None)
+            } else {
+                (Ok(amt), Some(Bytes::from(b)))
+            }
+        } else {
+            (Ok(0), None)
+        };
+        self.remaining = r;
+        result
+    }
+}
+
+impl<S, C> io::Read for Chunked<S, C>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C, Error = io::Error>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.poll() {
+            Ok(Async::Ready(Some(ref t))) => {
+                let read = self.read_remaining(buf)?;
+                let (result, new_remaining) = if let Some(ref mut remaining) = self.remaining {
+                    // There's still some data waiting to be written into the read buffer.
+                    // Append to the remaining buffer
+                    // Return the amount read from remaining
+                    let mut r = BytesMut::with_capacity(remaining.len() + t.as_ref().len());
+                    r.put_slice(remaining);
+                    r.put_slice(t.as_ref());
+                    (Ok(read), Some(r.freeze()))
+                } else {
+                    // The remaining buffer was cleared.
+                    // Attempt to read everything from the poll and add to the remaining buffer
+                    // if needed.
+                    let data = Bytes::from(t.as_ref());
+                    let amt = cmp::min(data.len(), buf.len() - read);
+                    let (a, b) = data.split_at(amt);
+                    buf[read..read + amt].copy_from_slice(a);
+
+                    if b.is_empty() {
+                        (Ok(amt + read), None)
+                    } else {
+                        (Ok(amt + read), Some(Bytes::from(b)))
+                    }
+                };
+                self.remaining = new_remaining;
+                result
+            }
+            Ok(Async::Ready(None)) => self.read_remaining(buf),
+            Ok(Async::NotReady) => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<S, C> AsyncRead for Chunked<S, C>
+where
+    C: AsRef<[u8]>,
+    S: Stream<Item = C, Error = io::Error>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    use futures::stream::iter_ok;
+
+    #[test]
+    fn smoke_test() {
+        let chunks = vec![
+            &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0d, b'R', b'o'][..],
+            &b"ses are"[..],
+            &[b' ', b'r', b'e', b'd', 0x02, 0x00][..],
+            &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10][..],
+            &b"violets"[..],
+            &b" are blue"[..],
+        ];
+
+        let stream = iter_ok::<Vec<&[u8]>, io::Error>(chunks);
+        let decoded = LogDecode::new(Chunked::new(stream))
+            .collect()
+            .wait()
+            .unwrap();
+        let expected = vec![
+            LogChunk::Stdout(Bytes::from("Roses are red")),
+            LogChunk::Stderr(Bytes::from("violets are blue")),
+        ];
+
+        assert_eq!(expected, decoded);
+    }
+
+    #[test]
+    fn module_log_lines_are_prefixed() {
+        let chunks = vec![
+            &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0e][..],
+            &b"Roses are red\n"[..],
+            &[0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1b][..],
+            &b"violets are blue\nsugar is"[..],
+            &b" \n"[..],
+        ];
+
+        let stream = iter_ok::<Vec<&[u8]>, io::Error>(chunks);
+        let lines = module_log_lines("tempSensor", stream)
+            .collect()
+            .wait()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                "[tempSensor] Roses are red".to_string(),
+                "[tempSensor] violets are blue".to_string(),
+                "[tempSensor] sugar is ".to_string(),
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let chunks = vec![
+            &b"Ro"[..],
+            &b"ses are"[..],
+            &b" red"[..],
+            &b" violets"[..],
+            &b" are blue"[..],
+        ];
+
+        let mut stream = Chunked::new(iter_ok::<Vec<&[u8]>, io::Error>(chunks));
+        let read_buffer = &mut [0_u8; 30];
+
+        for slice in &mut read_buffer.chunks_mut(2) {
+            stream.read_exact(slice).unwrap();
+        }
+        assert_eq!(b"Roses are red violets are blue", read_buffer);
+    }
+}
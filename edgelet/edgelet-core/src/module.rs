@@ -14,7 +14,7 @@ use futures::{Future, Stream};
 use pid::Pid;
 use serde_json;
 
-use edgelet_utils::ensure_not_empty_with_context;
+use edgelet_utils::{ensure_not_empty_with_context, is_default};
 
 use error::{Error, ErrorKind, Result};
 
@@ -27,6 +27,15 @@ pub enum ModuleStatus {
     Failed,
 }
 
+/// The result of a module's health check, for modules that have one.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
 impl FromStr for ModuleStatus {
     type Err = serde_json::Error;
 
@@ -47,6 +56,18 @@ impl fmt::Display for ModuleStatus {
     }
 }
 
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}",
+            serde_json::to_string(self)
+                .map(|s| s.trim_matches('"').to_string())
+                .map_err(|_| fmt::Error)?
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ModuleRuntimeState {
     status: ModuleStatus,
@@ -56,6 +77,7 @@ pub struct ModuleRuntimeState {
     finished_at: Option<DateTime<Utc>>,
     image_id: Option<String>,
     pid: Pid,
+    health: Option<HealthStatus>,
 }
 
 impl Default for ModuleRuntimeState {
@@ -68,6 +90,7 @@ impl Default for ModuleRuntimeState {
             finished_at: None,
             image_id: None,
             pid: Pid::None,
+            health: None,
         }
     }
 }
@@ -135,6 +158,33 @@ impl ModuleRuntimeState {
         self.pid = pid;
         self
     }
+
+    pub fn health(&self) -> Option<HealthStatus> {
+        self.health
+    }
+
+    pub fn with_health(mut self, health: Option<HealthStatus>) -> Self {
+        self.health = health;
+        self
+    }
+}
+
+/// Controls when the image for a module is pulled before the module is created.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImagePullPolicy {
+    /// Always pull the image before creating the module.
+    Always,
+    /// Only pull the image if it is not available locally.
+    IfNotPresent,
+    /// Never pull the image. It must already be available locally.
+    Never,
+}
+
+impl Default for ImagePullPolicy {
+    fn default() -> Self {
+        ImagePullPolicy::Always
+    }
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -145,6 +195,8 @@ pub struct ModuleSpec<T> {
     config: T,
     #[serde(default = "HashMap::new")]
     env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    image_pull_policy: ImagePullPolicy,
 }
 
 impl<T> Clone for ModuleSpec<T>
@@ -157,6 +209,7 @@ where
             type_: self.type_.clone(),
             config: self.config.clone(),
             env: self.env.clone(),
+            image_pull_policy: self.image_pull_policy,
         }
     }
 }
@@ -176,6 +229,7 @@ impl<T> ModuleSpec<T> {
             type_,
             config,
             env,
+            image_pull_policy: ImagePullPolicy::default(),
         })
     }
 
@@ -222,6 +276,15 @@ impl<T> ModuleSpec<T> {
         self.env = env;
         self
     }
+
+    pub fn image_pull_policy(&self) -> ImagePullPolicy {
+        self.image_pull_policy
+    }
+
+    pub fn with_image_pull_policy(mut self, image_pull_policy: ImagePullPolicy) -> Self {
+        self.image_pull_policy = image_pull_policy;
+        self
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -309,10 +372,13 @@ pub trait ModuleRegistry {
     type Error: Fail;
     type PullFuture: Future<Item = (), Error = Self::Error> + Send;
     type RemoveFuture: Future<Item = (), Error = Self::Error>;
+    type PruneFuture: Future<Item = (), Error = Self::Error> + Send;
     type Config;
 
     fn pull(&self, config: &Self::Config) -> Self::PullFuture;
     fn remove(&self, name: &str) -> Self::RemoveFuture;
+    /// Removes dangling images. Images used by any container are kept.
+    fn prune(&self) -> Self::PruneFuture;
 }
 
 #[derive(Debug)]
@@ -359,10 +425,8 @@ pub trait ModuleRuntime {
     type CreateFuture: Future<Item = (), Error = Self::Error> + Send;
     type InitFuture: Future<Item = (), Error = Self::Error> + Send;
     type ListFuture: Future<Item = Vec<Self::Module>, Error = Self::Error> + Send;
-    type ListWithDetailsStream: Stream<
-            Item = (Self::Module, ModuleRuntimeState),
-            Error = Self::Error,
-        > + Send;
+    type ListWithDetailsStream: Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error>
+        + Send;
     type LogsFuture: Future<Item = Self::Logs, Error = Self::Error> + Send;
     type RemoveFuture: Future<Item = (), Error = Self::Error> + Send;
     type RestartFuture: Future<Item = (), Error = Self::Error> + Send;
@@ -370,6 +434,7 @@ pub trait ModuleRuntime {
     type StopFuture: Future<Item = (), Error = Self::Error> + Send;
     type SystemInfoFuture: Future<Item = SystemInfo, Error = Self::Error> + Send;
     type RemoveAllFuture: Future<Item = (), Error = Self::Error> + Send;
+    type PruneVolumesFuture: Future<Item = (), Error = Self::Error> + Send;
 
     fn init(&self) -> Self::InitFuture;
     fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture;
@@ -383,6 +448,8 @@ pub trait ModuleRuntime {
     fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture;
     fn registry(&self) -> &Self::ModuleRegistry;
     fn remove_all(&self) -> Self::RemoveAllFuture;
+    /// Removes volumes that are not referenced by any container.
+    fn prune_volumes(&self) -> Self::PruneVolumesFuture;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -408,6 +475,7 @@ impl fmt::Display for ModuleOperation {
 // Useful for error contexts
 #[derive(Clone, Debug)]
 pub enum RegistryOperation {
+    PruneImages,
     PullImage(String),
     RemoveImage(String),
 }
@@ -415,6 +483,7 @@ pub enum RegistryOperation {
 impl fmt::Display for RegistryOperation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            RegistryOperation::PruneImages => write!(f, "Could not prune images"),
             RegistryOperation::PullImage(name) => write!(f, "Could not pull image {}", name),
             RegistryOperation::RemoveImage(name) => write!(f, "Could not remove image {}", name),
         }
@@ -428,6 +497,7 @@ pub enum RuntimeOperation {
     GetModuleLogs(String),
     Init,
     ListModules,
+    PruneVolumes,
     RemoveModule(String),
     RestartModule(String),
     StartModule(String),
@@ -444,6 +514,7 @@ impl fmt::Display for RuntimeOperation {
             }
             RuntimeOperation::Init => write!(f, "Could not initialize module runtime"),
             RuntimeOperation::ListModules => write!(f, "Could not list modules"),
+            RuntimeOperation::PruneVolumes => write!(f, "Could not prune volumes"),
             RuntimeOperation::RemoveModule(name) => write!(f, "Could not remove module {}", name),
             RuntimeOperation::RestartModule(name) => write!(f, "Could not restart module {}", name),
             RuntimeOperation::StartModule(name) => write!(f, "Could not start module {}", name),
@@ -569,4 +640,18 @@ mod tests {
             current_value_architecture_type
         );
     }
+
+    #[test]
+    fn module_spec_image_pull_policy_defaults_to_always() {
+        let spec: ModuleSpec<String> =
+            serde_json::from_str(r#"{"name":"edgeAgent","type":"docker","config":"image"}"#)
+                .unwrap();
+        assert_eq!(ImagePullPolicy::Always, spec.image_pull_policy());
+
+        let spec: ModuleSpec<String> = serde_json::from_str(
+            r#"{"name":"edgeAgent","type":"docker","config":"image","image_pull_policy":"if-not-present"}"#,
+        )
+        .unwrap();
+        assert_eq!(ImagePullPolicy::IfNotPresent, spec.image_pull_policy());
+    }
 }
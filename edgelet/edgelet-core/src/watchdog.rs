@@ -1,20 +1,29 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use failure::Fail;
 use futures::future::{self, Either, FutureResult};
+use futures::stream;
+use futures::sync::mpsc::{self, UnboundedSender};
 use futures::Future;
 use log::Level;
+use rand::{self, Rng};
 use tokio::prelude::*;
-use tokio::timer::Interval;
+use tokio::timer::{Delay, Timeout};
 
 use edgelet_utils::log_failure;
 
 use error::{Error, ErrorKind};
 use identity::{Identity, IdentityManager, IdentitySpec};
 use module::{
-    Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeErrorReason, ModuleSpec, ModuleStatus,
+    HealthStatus, ImagePullPolicy, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeErrorReason,
+    ModuleRuntimeState, ModuleSpec, ModuleStatus,
 };
 
 // Time to allow EdgeAgent to gracefully shutdown (including stopping all modules, and updating reported properties)
@@ -26,9 +35,551 @@ const MODULE_GENERATIONID: &str = "IOTEDGE_MODULEGENERATIONID";
 /// This is the frequency with which the watchdog checks for the status of the edge runtime module.
 const WATCHDOG_FREQUENCY_SECS: u64 = 60;
 
+/// The watchdog is considered unhealthy if it has not checked the status of
+/// the edge runtime module within this many seconds (three check intervals).
+const WATCHDOG_HEALTHY_MAX_AGE_SECS: i64 = 180;
+
+/// This is the default period over which edge runtime module restarts are counted.
+const DEFAULT_RESTART_WINDOW_SECS: u64 = 3600;
+
+/// This is the default number of restarts within the restart window after
+/// which the edge runtime module is considered to be crash looping.
+const DEFAULT_CRASH_LOOP_RESTARTS: u32 = 5;
+
+/// This is the default time to wait for the edge runtime module to stop
+/// during shutdown before giving up on it.
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 90;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchdogSettings {
+    /// Maximum number of times the watchdog starts the edge runtime module
+    /// within the restart window. There is no limit if this is not set.
+    #[serde(default)]
+    max_restarts: Option<u32>,
+    #[serde(default = "default_restart_window_secs")]
+    restart_window_secs: u64,
+    /// The edge runtime module is reported as crash looping once it has been
+    /// started more than this many times within the restart window.
+    #[serde(default = "default_crash_loop_restarts")]
+    crash_loop_restarts: u32,
+    /// Time to wait for the edge runtime module to stop when the daemon
+    /// shuts down. Shutdown continues once this has elapsed.
+    #[serde(default = "default_shutdown_timeout_secs")]
+    shutdown_timeout_secs: u64,
+    /// Randomly varies the time between checks of the edge runtime module by
+    /// up to this percentage in either direction.
+    #[serde(default)]
+    jitter_percent: u32,
+    /// Restart the edge runtime module when it is running but its health
+    /// check reports it as unhealthy.
+    #[serde(default)]
+    liveness_probe: bool,
+}
+
+fn default_restart_window_secs() -> u64 {
+    DEFAULT_RESTART_WINDOW_SECS
+}
+
+fn default_crash_loop_restarts() -> u32 {
+    DEFAULT_CRASH_LOOP_RESTARTS
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    DEFAULT_SHUTDOWN_TIMEOUT_SECS
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        WatchdogSettings {
+            max_restarts: None,
+            restart_window_secs: DEFAULT_RESTART_WINDOW_SECS,
+            crash_loop_restarts: DEFAULT_CRASH_LOOP_RESTARTS,
+            shutdown_timeout_secs: DEFAULT_SHUTDOWN_TIMEOUT_SECS,
+            jitter_percent: 0,
+            liveness_probe: false,
+        }
+    }
+}
+
+impl WatchdogSettings {
+    pub fn max_restarts(&self) -> Option<u32> {
+        self.max_restarts
+    }
+
+    pub fn with_max_restarts(mut self, max_restarts: Option<u32>) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    pub fn restart_window(&self) -> Duration {
+        Duration::from_secs(self.restart_window_secs)
+    }
+
+    pub fn with_restart_window(mut self, restart_window: Duration) -> Self {
+        self.restart_window_secs = restart_window.as_secs();
+        self
+    }
+
+    pub fn crash_loop_restarts(&self) -> u32 {
+        self.crash_loop_restarts
+    }
+
+    pub fn with_crash_loop_restarts(mut self, crash_loop_restarts: u32) -> Self {
+        self.crash_loop_restarts = crash_loop_restarts;
+        self
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout_secs = shutdown_timeout.as_secs();
+        self
+    }
+
+    pub fn jitter_percent(&self) -> u32 {
+        self.jitter_percent
+    }
+
+    pub fn with_jitter_percent(mut self, jitter_percent: u32) -> Self {
+        self.jitter_percent = jitter_percent;
+        self
+    }
+
+    pub fn liveness_probe(&self) -> bool {
+        self.liveness_probe
+    }
+
+    pub fn with_liveness_probe(mut self, liveness_probe: bool) -> Self {
+        self.liveness_probe = liveness_probe;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchdogStatus {
+    Unknown,
+    Running,
+    Stopped,
+    CrashLooping,
+    GaveUp,
+}
+
+impl Default for WatchdogStatus {
+    fn default() -> Self {
+        WatchdogStatus::Unknown
+    }
+}
+
+impl fmt::Display for WatchdogStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            WatchdogStatus::Unknown => "unknown",
+            WatchdogStatus::Running => "running",
+            WatchdogStatus::Stopped => "stopped",
+            WatchdogStatus::CrashLooping => "crash-looping",
+            WatchdogStatus::GaveUp => "gave-up",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Describes a single start of the edge runtime module by the watchdog.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartEvent {
+    count: u32,
+    reason: String,
+    exit_code: Option<i64>,
+    timestamp: DateTime<Utc>,
+}
+
+impl RestartEvent {
+    /// The total number of times the watchdog has started the edge runtime
+    /// module, including this one.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Why the module was started, i.e. the status it was found in, or
+    /// "missing" if it did not exist.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn exit_code(&self) -> Option<i64> {
+        self.exit_code
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl fmt::Display for RestartEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "count={} reason={} exit_code={} timestamp={}",
+            self.count,
+            self.reason,
+            self.exit_code
+                .map_or_else(|| "none".to_string(), |c| c.to_string()),
+            self.timestamp.to_rfc3339(),
+        )
+    }
+}
+
+/// Upper bounds, in milliseconds, of the buckets of the watchdog iteration
+/// duration histogram.
+pub const ITERATION_DURATION_BUCKETS_MILLIS: [u64; 6] = [50, 100, 250, 500, 1000, 5000];
+
+/// Counters describing how often the watchdog checks the edge runtime module
+/// and how long each check takes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WatchdogMetrics {
+    iterations: u64,
+    restarts: u64,
+    iteration_duration_sum: Duration,
+    iteration_duration_counts: [u64; 6],
+}
+
+impl WatchdogMetrics {
+    /// The number of times the watchdog has checked the edge runtime module.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The number of times the watchdog has started the edge runtime module.
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+
+    /// The total time spent checking the edge runtime module.
+    pub fn iteration_duration_sum(&self) -> Duration {
+        self.iteration_duration_sum
+    }
+
+    /// The number of iterations that took at most each of
+    /// `ITERATION_DURATION_BUCKETS_MILLIS`, paired with the bucket's upper
+    /// bound. Iterations that took longer than the largest bound are only
+    /// counted in `iterations`.
+    pub fn iteration_duration_buckets(&self) -> Vec<(Duration, u64)> {
+        ITERATION_DURATION_BUCKETS_MILLIS
+            .iter()
+            .zip(self.iteration_duration_counts.iter())
+            .scan(0, |total, (bound, count)| {
+                *total += count;
+                Some((Duration::from_millis(*bound), *total))
+            })
+            .collect()
+    }
+
+    fn record_iteration(&mut self, duration: Duration) {
+        self.iterations += 1;
+        self.iteration_duration_sum += duration;
+
+        if let Some(index) = ITERATION_DURATION_BUCKETS_MILLIS
+            .iter()
+            .position(|bound| duration <= Duration::from_millis(*bound))
+        {
+            self.iteration_duration_counts[index] += 1;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct StateInner {
+    status: WatchdogStatus,
+    restarts: VecDeque<Instant>,
+    last_check: Option<DateTime<Utc>>,
+    restart_count: u32,
+    last_restart: Option<RestartEvent>,
+    paused: bool,
+    resume_notifier: Option<UnboundedSender<()>>,
+    last_error: Option<String>,
+    metrics: WatchdogMetrics,
+}
+
+impl StateInner {
+    // Forget about restarts that happened before the restart window
+    fn prune_restarts(&mut self, window: Duration, now: Instant) {
+        while self
+            .restarts
+            .front()
+            .map_or(false, |t| now.duration_since(*t) > window)
+        {
+            self.restarts.pop_front();
+        }
+    }
+
+    fn is_crash_looping(&self, settings: &WatchdogSettings) -> bool {
+        self.restarts.len() > settings.crash_loop_restarts() as usize
+    }
+}
+
+/// The state of the watchdog as seen from outside of it (e.g. by the
+/// management API). Clones share the same underlying state.
+#[derive(Clone, Debug, Default)]
+pub struct WatchdogState {
+    inner: Arc<Mutex<StateInner>>,
+}
+
+impl WatchdogState {
+    pub fn new() -> Self {
+        WatchdogState::default()
+    }
+
+    pub fn status(&self) -> WatchdogStatus {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .status
+    }
+
+    /// The last time the watchdog successfully queried the module runtime for
+    /// the status of the edge runtime module.
+    pub fn last_check(&self) -> Option<DateTime<Utc>> {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .last_check
+    }
+
+    pub fn set_last_check(&self, last_check: DateTime<Utc>) {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .last_check = Some(last_check);
+    }
+
+    /// The watchdog is healthy if it has recently been able to reach the module
+    /// runtime and has not given up on the edge runtime module.
+    pub fn is_healthy(&self, now: DateTime<Utc>) -> bool {
+        let inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        let max_age = ChronoDuration::seconds(WATCHDOG_HEALTHY_MAX_AGE_SECS);
+        inner.status != WatchdogStatus::GaveUp
+            && inner.last_check.map_or(false, |last_check| {
+                now.signed_duration_since(last_check) <= max_age
+            })
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .paused
+    }
+
+    /// Stops the watchdog from restarting the edge runtime module until
+    /// `resume` is called. The module's status is still checked.
+    pub fn pause(&self) {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .paused = true;
+    }
+
+    /// Lets the watchdog restart the edge runtime module again, and has it
+    /// check the module right away rather than on its next tick.
+    pub fn resume(&self) {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        inner.paused = false;
+        if let Some(ref notifier) = inner.resume_notifier {
+            notifier.unbounded_send(()).unwrap_or(());
+        }
+    }
+
+    fn set_resume_notifier(&self, notifier: UnboundedSender<()>) {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .resume_notifier = Some(notifier);
+    }
+
+    /// The most recent error the watchdog encountered while checking on or
+    /// starting the edge runtime module. This is cleared once the module is
+    /// running again.
+    pub fn last_error(&self) -> Option<String> {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .last_error
+            .clone()
+    }
+
+    pub fn set_last_error(&self, error: &Fail) {
+        let mut message = error.to_string();
+        for cause in error.iter_causes() {
+            message.push_str(&format!("\n\tcaused by: {}", cause));
+        }
+
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .last_error = Some(message);
+    }
+
+    /// Marks the edge runtime module as running, unless it has been restarted
+    /// often enough recently to be considered crash looping.
+    fn set_running(&self, settings: &WatchdogSettings, now: Instant) {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        inner.last_error = None;
+
+        if inner.status != WatchdogStatus::GaveUp {
+            inner.prune_restarts(settings.restart_window(), now);
+            inner.status = if inner.is_crash_looping(settings) {
+                WatchdogStatus::CrashLooping
+            } else {
+                WatchdogStatus::Running
+            };
+        }
+    }
+
+    fn set_status(&self, status: WatchdogStatus) {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        // Once the watchdog has given up it stays that way
+        if inner.status != WatchdogStatus::GaveUp {
+            inner.status = status;
+        }
+    }
+
+    /// The number of times the watchdog has started the edge runtime module.
+    pub fn restart_count(&self) -> u32 {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .restart_count
+    }
+
+    pub fn metrics(&self) -> WatchdogMetrics {
+        let inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        WatchdogMetrics {
+            restarts: u64::from(inner.restart_count),
+            ..inner.metrics.clone()
+        }
+    }
+
+    fn record_iteration(&self, duration: Duration) {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .metrics
+            .record_iteration(duration);
+    }
+
+    pub fn last_restart(&self) -> Option<RestartEvent> {
+        self.inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock")
+            .last_restart
+            .clone()
+    }
+
+    fn add_restart_event(
+        &self,
+        reason: String,
+        exit_code: Option<i64>,
+        timestamp: DateTime<Utc>,
+    ) -> RestartEvent {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        inner.restart_count += 1;
+        let event = RestartEvent {
+            count: inner.restart_count,
+            reason,
+            exit_code,
+            timestamp,
+        };
+        inner.last_restart = Some(event.clone());
+        event
+    }
+
+    /// Records an attempt to start the edge runtime module at `now`. Returns
+    /// `false` if the attempt exceeds the maximum number of restarts allowed
+    /// within the restart window, in which case the watchdog gives up.
+    fn record_restart(&self, settings: &WatchdogSettings, now: Instant) -> bool {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("Failed to acquire watchdog state lock");
+
+        inner.prune_restarts(settings.restart_window(), now);
+
+        match settings.max_restarts() {
+            Some(max) if inner.restarts.len() >= max as usize => {
+                inner.status = WatchdogStatus::GaveUp;
+                false
+            }
+            _ => {
+                inner.restarts.push_back(now);
+                if inner.is_crash_looping(settings) {
+                    inner.status = WatchdogStatus::CrashLooping;
+                }
+                true
+            }
+        }
+    }
+}
+
+/// An edge runtime module supervised by the watchdog, along with the state
+/// that tracks its restarts.
+pub struct WatchedModule<T> {
+    spec: ModuleSpec<T>,
+    module_id: String,
+    state: WatchdogState,
+}
+
+impl<T> WatchedModule<T> {
+    pub fn new(spec: ModuleSpec<T>, module_id: &str, state: WatchdogState) -> Self {
+        WatchedModule {
+            spec,
+            module_id: module_id.to_string(),
+            state,
+        }
+    }
+
+    pub fn spec(&self) -> &ModuleSpec<T> {
+        &self.spec
+    }
+
+    pub fn module_id(&self) -> &str {
+        &self.module_id
+    }
+
+    pub fn state(&self) -> &WatchdogState {
+        &self.state
+    }
+}
+
 pub struct Watchdog<M, I> {
     runtime: M,
     id_mgr: I,
+    settings: WatchdogSettings,
 }
 
 impl<M, I> Watchdog<M, I>
@@ -38,29 +589,48 @@ where
     <M::Module as Module>::Config: Clone,
     I: 'static + IdentityManager + Clone,
 {
-    pub fn new(runtime: M, id_mgr: I) -> Self {
-        Watchdog { runtime, id_mgr }
+    pub fn new(runtime: M, id_mgr: I, settings: WatchdogSettings) -> Self {
+        Watchdog {
+            runtime,
+            id_mgr,
+            settings,
+        }
     }
 
-    // Start the edge runtime module (EdgeAgent). This also updates the identity of the module (module_id)
+    // Start the edge runtime modules (e.g. EdgeAgent). This also updates the identity of each module (module_id)
     // to make sure it is configured for the right authentication type (sas token)
     // spec.name = edgeAgent / module_id = $edgeAgent
     pub fn run_until<F>(
         self,
-        spec: ModuleSpec<<M::Module as Module>::Config>,
-        module_id: &str,
+        modules: Vec<WatchedModule<<M::Module as Module>::Config>>,
         shutdown_signal: F,
     ) -> impl Future<Item = (), Error = Error>
     where
         F: Future<Item = (), Error = ()> + 'static,
     {
-        let runtime = self.runtime.clone();
         let runtime_copy = self.runtime.clone();
-        let name = spec.name().to_string();
-        let id_mgr = self.id_mgr.clone();
-        let module_id = module_id.to_string();
+        let names: Vec<String> = modules
+            .iter()
+            .map(|module| module.spec.name().to_string())
+            .collect();
+        let shutdown_timeout = self.settings.shutdown_timeout();
 
-        let watchdog = start_watchdog(runtime, id_mgr, spec, module_id);
+        // Each module gets its own watchdog so that restarts are accounted
+        // for independently
+        let watchdogs: Vec<_> = modules
+            .into_iter()
+            .map(|module| {
+                start_watchdog(
+                    self.runtime.clone(),
+                    self.id_mgr.clone(),
+                    module.spec,
+                    module.module_id,
+                    self.settings.clone(),
+                    module.state,
+                )
+            })
+            .collect();
+        let watchdog = future::join_all(watchdogs).map(|_| ());
 
         // Swallow any errors from shutdown_signal
         let shutdown_signal = shutdown_signal.then(|_| Ok(()));
@@ -71,27 +641,66 @@ where
         shutdown_signal
             .select(watchdog)
             .then(move |result| match result {
-                Ok(((), _)) => Ok(stop_runtime(&runtime_copy, &name)),
+                Ok(((), _)) => Ok(stop_runtimes(&runtime_copy, &names, shutdown_timeout)),
                 Err((err, _)) => Err(err),
             })
             .flatten()
     }
 }
 
-// Stop EdgeAgent
-fn stop_runtime<M>(runtime: &M, name: &str) -> impl Future<Item = (), Error = Error>
+// Stop all the supervised edge runtime modules
+fn stop_runtimes<M>(
+    runtime: &M,
+    names: &[String],
+    timeout: Duration,
+) -> impl Future<Item = (), Error = Error>
+where
+    M: 'static + ModuleRuntime + Clone,
+    for<'r> &'r <M as ModuleRuntime>::Error: Into<ModuleRuntimeErrorReason>,
+    <M::Module as Module>::Config: Clone,
+{
+    let stops: Vec<_> = names
+        .iter()
+        .map(|name| stop_runtime(runtime, name, timeout))
+        .collect();
+    future::join_all(stops).map(|_| ())
+}
+
+// Stop an edge runtime module, giving up on it if it does not stop within `timeout`
+fn stop_runtime<M>(
+    runtime: &M,
+    name: &str,
+    timeout: Duration,
+) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
     for<'r> &'r <M as ModuleRuntime>::Error: Into<ModuleRuntimeErrorReason>,
     <M::Module as Module>::Config: Clone,
 {
     info!("Stopping edge runtime module {}", name);
-    runtime
-        .stop(name, Some(EDGE_RUNTIME_STOP_TIME))
+    let name = name.to_string();
+    let stop = runtime
+        .stop(&name, Some(EDGE_RUNTIME_STOP_TIME))
         .or_else(|err| match (&err).into() {
             ModuleRuntimeErrorReason::NotFound => Ok(()),
             _ => Err(Error::from(err.context(ErrorKind::ModuleRuntime))),
-        })
+        });
+
+    Timeout::new(stop, timeout).or_else(move |err| {
+        if err.is_elapsed() {
+            warn!(
+                "Edge runtime module {} did not stop within {} seconds. Continuing shutdown without it.",
+                name,
+                timeout.as_secs(),
+            );
+            Ok(())
+        } else if err.is_inner() {
+            Err(err.into_inner().expect("timeout error is an inner error"))
+        } else {
+            let err = err.into_timer().expect("timeout error is a timer error");
+            Err(Error::from(err.context(ErrorKind::EdgeRuntimeShutdownTimer)))
+        }
+    })
 }
 
 // Start watchdog on a timer for 1 minute
@@ -100,32 +709,66 @@ pub fn start_watchdog<M, I>(
     id_mgr: I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: String,
+    settings: WatchdogSettings,
+    state: WatchdogState,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
+    for<'r> &'r <M as ModuleRuntime>::Error: Into<ModuleRuntimeErrorReason>,
     <M::Module as Module>::Config: Clone,
     I: 'static + IdentityManager + Clone,
 {
+    let jitter_percent = settings.jitter_percent();
     info!(
-        "Starting watchdog with {} second frequency...",
-        WATCHDOG_FREQUENCY_SECS
+        "Starting watchdog with {} second frequency and {}% jitter...",
+        WATCHDOG_FREQUENCY_SECS, jitter_percent
     );
-    Interval::new(Instant::now(), Duration::from_secs(WATCHDOG_FREQUENCY_SECS))
-        .map_err(|err| Error::from(err.context(ErrorKind::EdgeRuntimeStatusCheckerTimer)))
-        .for_each(move |_| {
-            info!("Checking edge runtime status");
-            check_runtime(
-                runtime.clone(),
-                id_mgr.clone(),
-                spec.clone(),
-                module_id.clone(),
-            )
-            .or_else(|e| {
-                warn!("Error in watchdog when checking for edge runtime status:");
-                log_failure(Level::Warn, &e);
-                future::ok(())
-            })
+
+    // Check right away, and then again after each (jittered) interval
+    let frequency = Duration::from_secs(WATCHDOG_FREQUENCY_SECS);
+    let ticks = stream::once(Ok(())).chain(stream::repeat(()).and_then(move |_| {
+        let interval = jittered_interval(frequency, jitter_percent, &mut rand::thread_rng());
+        Delay::new(Instant::now() + interval)
+            .map_err(|err| Error::from(err.context(ErrorKind::EdgeRuntimeStatusCheckerTimer)))
+    }));
+
+    // Resuming the watchdog triggers a check in addition to the timer
+    let (resume_tx, resume_rx) = mpsc::unbounded();
+    state.set_resume_notifier(resume_tx);
+    let resumed = resume_rx.map_err(|_| Error::from(ErrorKind::EdgeRuntimeStatusCheckerTimer));
+
+    ticks.select(resumed).for_each(move |_| {
+        info!("Checking edge runtime status");
+        let started = Instant::now();
+        let iteration_state = state.clone();
+        check_runtime(
+            runtime.clone(),
+            id_mgr.clone(),
+            spec.clone(),
+            module_id.clone(),
+            settings.clone(),
+            state.clone(),
+        )
+        .or_else(|e| {
+            warn!("Error in watchdog when checking for edge runtime status:");
+            log_failure(Level::Warn, &e);
+            future::ok(())
         })
+        .map(move |_| iteration_state.record_iteration(started.elapsed()))
+    })
+}
+
+// Varies `frequency` by a random amount of up to `jitter_percent` percent in
+// either direction, so that devices sharing a configuration don't all check
+// their runtime at the same time.
+fn jittered_interval<R: Rng>(frequency: Duration, jitter_percent: u32, rng: &mut R) -> Duration {
+    let millis = frequency.as_secs() * 1000;
+    let jitter = millis * u64::from(cmp::min(jitter_percent, 100)) / 100;
+    if jitter == 0 {
+        frequency
+    } else {
+        Duration::from_millis(rng.gen_range(millis - jitter, millis + jitter + 1))
+    }
 }
 
 // Check if the edge runtime module is running, and if not, start it.
@@ -134,13 +777,18 @@ fn check_runtime<M, I>(
     id_mgr: I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: String,
+    settings: WatchdogSettings,
+    state: WatchdogState,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
+    for<'r> &'r <M as ModuleRuntime>::Error: Into<ModuleRuntimeErrorReason>,
     <M::Module as Module>::Config: Clone,
     I: 'static + IdentityManager + Clone,
 {
     let module = spec.name().to_string();
+    let check_state = state.clone();
+    let error_state = state.clone();
     get_edge_runtime_mod(&runtime, module.clone())
         .and_then(|m| {
             m.map(|m| {
@@ -148,28 +796,107 @@ where
                     .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime)))
             })
         })
-        .and_then(move |state| match state {
-            Some(state) => {
-                let res = if *state.status() == ModuleStatus::Running {
-                    info!("Edge runtime is running.");
-                    future::Either::A(future::ok(()))
-                } else {
+        .map(move |rt_state| {
+            check_state.set_last_check(Utc::now());
+            rt_state
+        })
+        .and_then(move |rt_state| match rt_state {
+            Some(ref rt_state)
+                if *rt_state.status() == ModuleStatus::Running
+                    && !is_unhealthy(rt_state, &settings) =>
+            {
+                info!("Edge runtime is running.");
+                state.set_running(&settings, Instant::now());
+                Either::A(future::ok(()))
+            }
+
+            rt_state => {
+                if state.status() == WatchdogStatus::GaveUp {
+                    debug!("Not restarting edge runtime module {}.", module);
+                    return Either::A(future::ok(()));
+                }
+
+                if state.is_paused() {
                     info!(
-                        "Edge runtime status is {}, starting module now...",
-                        *state.status(),
+                        "Watchdog is paused. Not restarting edge runtime module {}.",
+                        module
                     );
-                    future::Either::B(
-                        runtime
-                            .start(&module)
-                            .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime))),
-                    )
+                    state.set_status(WatchdogStatus::Stopped);
+                    return Either::A(future::ok(()));
+                }
+
+                state.set_status(WatchdogStatus::Stopped);
+                if !state.record_restart(&settings, Instant::now()) {
+                    error!(
+                        "Edge runtime module {} was started {} times within {} seconds. \
+                         The watchdog will no longer restart it. Please check the module's \
+                         logs and configuration and restart the daemon.",
+                        module,
+                        settings.max_restarts().unwrap_or_default(),
+                        settings.restart_window().as_secs(),
+                    );
+                    return Either::A(future::ok(()));
+                }
+
+                if state.status() == WatchdogStatus::CrashLooping {
+                    warn!(
+                        "Edge runtime module {} is crash looping. It was started more than {} times within {} seconds.",
+                        module,
+                        settings.crash_loop_restarts(),
+                        settings.restart_window().as_secs(),
+                    );
+                }
+
+                let event = match rt_state {
+                    Some(ref rt_state) if *rt_state.status() == ModuleStatus::Running => state
+                        .add_restart_event("unhealthy".to_string(), rt_state.exit_code(), Utc::now()),
+                    Some(ref rt_state) => state.add_restart_event(
+                        rt_state.status().to_string(),
+                        rt_state.exit_code(),
+                        Utc::now(),
+                    ),
+                    None => state.add_restart_event("missing".to_string(), None, Utc::now()),
                 };
-                Either::A(res)
-            }
+                info!("Edge runtime restart event: {}", event);
+
+                let res = match rt_state {
+                    Some(ref rt_state) if *rt_state.status() == ModuleStatus::Running => {
+                        warn!("Edge runtime is running but unhealthy, restarting module now...");
+                        Either::A(Either::A(
+                            runtime
+                                .restart(&module)
+                                .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime))),
+                        ))
+                    }
+
+                    Some(rt_state) => {
+                        info!(
+                            "Edge runtime status is {}, starting module now...",
+                            *rt_state.status(),
+                        );
+                        Either::A(Either::B(
+                            runtime
+                                .start(&module)
+                                .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime))),
+                        ))
+                    }
 
-            None => Either::B(create_and_start(runtime, &id_mgr, spec, module_id)),
+                    None => Either::B(create_and_start(runtime, &id_mgr, spec, module_id)),
+                };
+                Either::B(res)
+            }
         })
         .map(|_| ())
+        .map_err(move |err| {
+            error_state.set_last_error(&err);
+            err
+        })
+}
+
+// A running module is only restarted for failing its health check if the
+// liveness probe is enabled.
+fn is_unhealthy(rt_state: &ModuleRuntimeState, settings: &WatchdogSettings) -> bool {
+    settings.liveness_probe() && rt_state.health() == Some(HealthStatus::Unhealthy)
 }
 
 // Gets the edge runtime module, if it exists.
@@ -230,6 +957,7 @@ fn create_and_start<M, I>(
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
+    for<'r> &'r <M as ModuleRuntime>::Error: Into<ModuleRuntimeErrorReason>,
     <M::Module as Module>::Config: Clone,
     I: 'static + IdentityManager + Clone,
 {
@@ -246,24 +974,61 @@ where
             id.generation_id().to_string(),
         );
         let spec = spec.with_env(env);
-        runtime
-            .registry()
-            .pull(spec.clone().config())
-            .and_then(move |_| runtime.create(spec))
+        let create = match spec.image_pull_policy() {
+            ImagePullPolicy::Always => Either::A(pull_and_create(runtime, spec)),
+            ImagePullPolicy::IfNotPresent => Either::B(Either::A(
+                runtime.create(spec.clone()).or_else(move |err| match (&err).into() {
+                    ModuleRuntimeErrorReason::NotFound => {
+                        info!(
+                            "Image for edge runtime module {} was not found locally, pulling it now...",
+                            spec.name()
+                        );
+                        Either::A(pull_and_create(runtime, spec))
+                    }
+                    ModuleRuntimeErrorReason::Other => Either::B(future::err(err)),
+                }),
+            )),
+            ImagePullPolicy::Never => {
+                info!(
+                    "Using local image for edge runtime module {}",
+                    spec.name()
+                );
+                Either::B(Either::B(runtime.create(spec)))
+            }
+        };
+        create
             .and_then(move |_| runtime_copy.start(&module_name))
             .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime)))
     })
 }
 
+fn pull_and_create<M>(
+    runtime: M,
+    spec: ModuleSpec<<M::Module as Module>::Config>,
+) -> impl Future<Item = (), Error = M::Error>
+where
+    M: 'static + ModuleRuntime + Clone,
+    <M::Module as Module>::Config: Clone,
+{
+    runtime
+        .registry()
+        .pull(spec.clone().config())
+        .and_then(move |_| runtime.create(spec))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::cell::RefCell;
+    use std::collections::HashMap;
     use std::rc::Rc;
 
     use futures::future::{self, FutureResult};
+    use futures::stream::Empty;
+    use tokio::runtime::current_thread::Runtime;
 
     use identity::{AuthType, Identity, IdentityManager, IdentitySpec};
+    use module::{LogOptions, ModuleRuntimeState, SystemInfo};
 
     #[derive(Clone, Copy, Debug, Fail)]
     pub enum Error {
@@ -274,6 +1039,15 @@ mod tests {
         ModuleNotFound,
     }
 
+    impl<'a> From<&'a Error> for ModuleRuntimeErrorReason {
+        fn from(err: &'a Error) -> Self {
+            match err {
+                Error::ModuleNotFound => ModuleRuntimeErrorReason::NotFound,
+                Error::General => ModuleRuntimeErrorReason::Other,
+            }
+        }
+    }
+
     #[derive(Clone, Debug, Deserialize, Serialize)]
     pub struct TestIdentity {
         #[serde(rename = "moduleId")]
@@ -442,6 +1216,273 @@ mod tests {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TestConfig;
+
+    #[derive(Clone, Debug)]
+    pub struct TestModule {
+        name: String,
+        status: ModuleStatus,
+        exit_code: Option<i64>,
+        health: Option<HealthStatus>,
+    }
+
+    impl TestModule {
+        pub fn new(name: &str, status: ModuleStatus) -> Self {
+            TestModule {
+                name: name.to_string(),
+                status,
+                exit_code: None,
+                health: None,
+            }
+        }
+
+        pub fn with_exit_code(mut self, exit_code: i64) -> Self {
+            self.exit_code = Some(exit_code);
+            self
+        }
+
+        pub fn with_health(mut self, health: HealthStatus) -> Self {
+            self.health = Some(health);
+            self
+        }
+    }
+
+    impl Module for TestModule {
+        type Config = TestConfig;
+        type Error = Error;
+        type RuntimeStateFuture = FutureResult<ModuleRuntimeState, Self::Error>;
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn type_(&self) -> &str {
+            "test"
+        }
+
+        fn config(&self) -> &Self::Config {
+            &TestConfig
+        }
+
+        fn runtime_state(&self) -> Self::RuntimeStateFuture {
+            future::ok(
+                ModuleRuntimeState::default()
+                    .with_status(self.status)
+                    .with_exit_code(self.exit_code)
+                    .with_health(self.health),
+            )
+        }
+    }
+
+    struct RuntimeState {
+        modules: Vec<TestModule>,
+        start_called: HashMap<String, u32>,
+        restart_called: u32,
+        pull_called: u32,
+        create_called: u32,
+        stop_hangs: bool,
+        pull_fails: bool,
+    }
+
+    #[derive(Clone)]
+    pub struct TestRuntime {
+        state: Arc<Mutex<RuntimeState>>,
+    }
+
+    impl TestRuntime {
+        pub fn new(module: Option<TestModule>) -> Self {
+            TestRuntime {
+                state: Arc::new(Mutex::new(RuntimeState {
+                    modules: module.into_iter().collect(),
+                    start_called: HashMap::new(),
+                    restart_called: 0,
+                    pull_called: 0,
+                    create_called: 0,
+                    stop_hangs: false,
+                    pull_fails: false,
+                })),
+            }
+        }
+
+        pub fn with_module(self, module: TestModule) -> Self {
+            self.state.lock().unwrap().modules.push(module);
+            self
+        }
+
+        pub fn with_hanging_stop(self) -> Self {
+            self.state.lock().unwrap().stop_hangs = true;
+            self
+        }
+
+        pub fn with_failing_pull(self) -> Self {
+            self.state.lock().unwrap().pull_fails = true;
+            self
+        }
+
+        pub fn start_called(&self) -> u32 {
+            self.state.lock().unwrap().start_called.values().sum()
+        }
+
+        pub fn start_called_for(&self, name: &str) -> u32 {
+            self.state
+                .lock()
+                .unwrap()
+                .start_called
+                .get(name)
+                .cloned()
+                .unwrap_or(0)
+        }
+
+        pub fn restart_called(&self) -> u32 {
+            self.state.lock().unwrap().restart_called
+        }
+
+        pub fn pull_called(&self) -> u32 {
+            self.state.lock().unwrap().pull_called
+        }
+
+        pub fn create_called(&self) -> u32 {
+            self.state.lock().unwrap().create_called
+        }
+
+        pub fn set_module(&self, module: TestModule) {
+            let mut state = self.state.lock().unwrap();
+            state.modules.retain(|m| m.name != module.name);
+            state.modules.push(module);
+        }
+
+        pub fn set_module_status(&self, status: ModuleStatus) {
+            for module in &mut self.state.lock().unwrap().modules {
+                module.status = status;
+            }
+        }
+    }
+
+    impl ModuleRegistry for TestRuntime {
+        type Error = Error;
+        type PullFuture = FutureResult<(), Self::Error>;
+        type RemoveFuture = FutureResult<(), Self::Error>;
+        type PruneFuture = FutureResult<(), Self::Error>;
+        type Config = TestConfig;
+
+        fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
+            let mut state = self.state.lock().unwrap();
+            state.pull_called += 1;
+            if state.pull_fails {
+                future::err(Error::General)
+            } else {
+                future::ok(())
+            }
+        }
+
+        fn remove(&self, _name: &str) -> Self::RemoveFuture {
+            future::ok(())
+        }
+
+        fn prune(&self) -> Self::PruneFuture {
+            future::ok(())
+        }
+    }
+
+    impl ModuleRuntime for TestRuntime {
+        type Error = Error;
+        type Config = TestConfig;
+        type Module = TestModule;
+        type ModuleRegistry = Self;
+        type Chunk = String;
+        type Logs = Empty<Self::Chunk, Self::Error>;
+
+        type CreateFuture = FutureResult<(), Self::Error>;
+        type InitFuture = FutureResult<(), Self::Error>;
+        type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
+        type ListWithDetailsStream =
+            Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
+        type LogsFuture = FutureResult<Self::Logs, Self::Error>;
+        type RemoveFuture = FutureResult<(), Self::Error>;
+        type RestartFuture = FutureResult<(), Self::Error>;
+        type StartFuture = FutureResult<(), Self::Error>;
+        type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+        type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
+        type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type PruneVolumesFuture = FutureResult<(), Self::Error>;
+
+        fn init(&self) -> Self::InitFuture {
+            future::ok(())
+        }
+
+        fn create(&self, _module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+            self.state.lock().unwrap().create_called += 1;
+            future::ok(())
+        }
+
+        fn start(&self, id: &str) -> Self::StartFuture {
+            *self
+                .state
+                .lock()
+                .unwrap()
+                .start_called
+                .entry(id.to_string())
+                .or_insert(0) += 1;
+            future::ok(())
+        }
+
+        fn stop(&self, _id: &str, _wait_before_kill: Option<Duration>) -> Self::StopFuture {
+            if self.state.lock().unwrap().stop_hangs {
+                Box::new(future::empty())
+            } else {
+                Box::new(future::ok(()))
+            }
+        }
+
+        fn restart(&self, _id: &str) -> Self::RestartFuture {
+            self.state.lock().unwrap().restart_called += 1;
+            future::ok(())
+        }
+
+        fn remove(&self, _id: &str) -> Self::RemoveFuture {
+            future::ok(())
+        }
+
+        fn system_info(&self) -> Self::SystemInfoFuture {
+            unimplemented!()
+        }
+
+        fn list(&self) -> Self::ListFuture {
+            future::ok(self.state.lock().unwrap().modules.clone())
+        }
+
+        fn list_with_details(&self) -> Self::ListWithDetailsStream {
+            unimplemented!()
+        }
+
+        fn logs(&self, _id: &str, _options: &LogOptions) -> Self::LogsFuture {
+            unimplemented!()
+        }
+
+        fn registry(&self) -> &Self::ModuleRegistry {
+            self
+        }
+
+        fn remove_all(&self) -> Self::RemoveAllFuture {
+            future::ok(())
+        }
+
+        fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+            future::ok(())
+        }
+    }
+
+    fn agent_spec() -> ModuleSpec<TestConfig> {
+        ModuleSpec::new(
+            "edgeAgent".to_string(),
+            "test".to_string(),
+            TestConfig,
+            HashMap::new(),
+        )
+        .unwrap()
+    }
+
     #[test]
     fn update_identity_get_fails() {
         let mut manager = TestIdentityManager::new(vec![]).with_fail_get(true);
@@ -498,4 +1539,485 @@ mod tests {
                 .auth_type
         );
     }
+
+    #[test]
+    fn check_runtime_gives_up_after_max_restarts() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Stopped)));
+        let settings = WatchdogSettings::default().with_max_restarts(Some(3));
+        let state = WatchdogState::new();
+
+        for _ in 0..5 {
+            check_runtime(
+                runtime.clone(),
+                TestIdentityManager::new(vec![]),
+                agent_spec(),
+                "$edgeAgent".to_string(),
+                settings.clone(),
+                state.clone(),
+            )
+            .wait()
+            .unwrap();
+        }
+
+        assert_eq!(3, runtime.start_called());
+        assert_eq!(WatchdogStatus::GaveUp, state.status());
+    }
+
+    #[test]
+    fn check_runtime_without_max_restarts_keeps_restarting() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Failed)));
+        let state = WatchdogState::new();
+
+        for _ in 0..5 {
+            check_runtime(
+                runtime.clone(),
+                TestIdentityManager::new(vec![]),
+                agent_spec(),
+                "$edgeAgent".to_string(),
+                WatchdogSettings::default(),
+                state.clone(),
+            )
+            .wait()
+            .unwrap();
+        }
+
+        assert_eq!(5, runtime.start_called());
+        assert_eq!(WatchdogStatus::Stopped, state.status());
+    }
+
+    #[test]
+    fn restarts_outside_window_are_not_counted() {
+        let settings = WatchdogSettings::default()
+            .with_max_restarts(Some(1))
+            .with_restart_window(Duration::from_secs(10));
+        let state = WatchdogState::new();
+        let now = Instant::now();
+
+        assert!(state.record_restart(&settings, now));
+        assert!(state.record_restart(&settings, now + Duration::from_secs(11)));
+        assert!(!state.record_restart(&settings, now + Duration::from_secs(12)));
+        assert_eq!(WatchdogStatus::GaveUp, state.status());
+    }
+
+    #[test]
+    fn watchdog_is_healthy_after_recent_check() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Running)));
+        let state = WatchdogState::new();
+        assert!(!state.is_healthy(Utc::now()));
+
+        check_runtime(
+            runtime,
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        )
+        .wait()
+        .unwrap();
+
+        let last_check = state.last_check().unwrap();
+        assert!(state.is_healthy(last_check));
+        assert!(!state.is_healthy(last_check + ChronoDuration::seconds(181)));
+    }
+
+    #[test]
+    fn shutdown_completes_when_runtime_stop_hangs() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Running)))
+            .with_hanging_stop();
+        let settings = WatchdogSettings::default().with_shutdown_timeout(Duration::from_secs(1));
+        let watchdog = Watchdog::new(runtime, TestIdentityManager::new(vec![]), settings);
+        let modules = vec![WatchedModule::new(
+            agent_spec(),
+            "$edgeAgent",
+            WatchdogState::new(),
+        )];
+
+        let start = Instant::now();
+        Runtime::new()
+            .unwrap()
+            .block_on(watchdog.run_until(modules, future::ok(())))
+            .unwrap();
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_secs(1));
+        assert!(elapsed < Duration::from_secs(30));
+    }
+
+    #[test]
+    fn watched_modules_are_restarted_independently() {
+        let runtime = TestRuntime::new(Some(
+            TestModule::new("edgeAgent", ModuleStatus::Failed).with_exit_code(1),
+        ))
+        .with_module(TestModule::new("edgeHub", ModuleStatus::Stopped));
+        let agent_state = WatchdogState::new();
+        let hub_state = WatchdogState::new();
+        let hub_spec = ModuleSpec::new(
+            "edgeHub".to_string(),
+            "test".to_string(),
+            TestConfig,
+            HashMap::new(),
+        )
+        .unwrap();
+        let modules = vec![
+            WatchedModule::new(agent_spec(), "$edgeAgent", agent_state.clone()),
+            WatchedModule::new(hub_spec, "$edgeHub", hub_state.clone()),
+        ];
+
+        let watchdog = Watchdog::new(
+            runtime.clone(),
+            TestIdentityManager::new(vec![]),
+            WatchdogSettings::default(),
+        );
+        let shutdown = Delay::new(Instant::now() + Duration::from_millis(100)).map_err(|_| ());
+        Runtime::new()
+            .unwrap()
+            .block_on(watchdog.run_until(modules, shutdown))
+            .unwrap();
+
+        assert_eq!(1, runtime.start_called_for("edgeAgent"));
+        assert_eq!(1, runtime.start_called_for("edgeHub"));
+        assert_eq!(1, agent_state.restart_count());
+        assert_eq!(1, hub_state.restart_count());
+        assert_eq!(Some(1), agent_state.last_restart().unwrap().exit_code());
+        assert_eq!(None, hub_state.last_restart().unwrap().exit_code());
+    }
+
+    #[test]
+    fn watchdog_iterations_are_counted() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Stopped)));
+        let state = WatchdogState::new();
+        let watchdog = start_watchdog(
+            runtime.clone(),
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        );
+
+        // Each resume triggers a check in addition to the initial one
+        state.resume();
+        state.resume();
+
+        let stop = Delay::new(Instant::now() + Duration::from_millis(100))
+            .map_err(|err| Error::from(err.context(ErrorKind::EdgeRuntimeStatusCheckerTimer)));
+        Runtime::new()
+            .unwrap()
+            .block_on(watchdog.select(stop))
+            .map_err(|(err, _)| err)
+            .unwrap();
+
+        let metrics = state.metrics();
+        assert_eq!(3, metrics.iterations());
+        assert_eq!(3, metrics.restarts());
+        assert_eq!(3, runtime.start_called());
+        assert_eq!(
+            Some(&(Duration::from_secs(5), 3)),
+            metrics.iteration_duration_buckets().last()
+        );
+    }
+
+    #[test]
+    fn iteration_durations_are_bucketed() {
+        let mut metrics = WatchdogMetrics::default();
+        metrics.record_iteration(Duration::from_millis(10));
+        metrics.record_iteration(Duration::from_millis(300));
+        metrics.record_iteration(Duration::from_secs(10));
+
+        assert_eq!(3, metrics.iterations());
+        assert_eq!(
+            Duration::from_millis(10_310),
+            metrics.iteration_duration_sum()
+        );
+        assert_eq!(
+            vec![
+                (Duration::from_millis(50), 1),
+                (Duration::from_millis(100), 1),
+                (Duration::from_millis(250), 1),
+                (Duration::from_millis(500), 2),
+                (Duration::from_millis(1000), 2),
+                (Duration::from_millis(5000), 2),
+            ],
+            metrics.iteration_duration_buckets()
+        );
+    }
+
+    #[test]
+    fn unhealthy_running_agent_is_restarted() {
+        let runtime = TestRuntime::new(Some(
+            TestModule::new("edgeAgent", ModuleStatus::Running)
+                .with_health(HealthStatus::Unhealthy),
+        ));
+        let state = WatchdogState::new();
+
+        check_runtime(
+            runtime.clone(),
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default().with_liveness_probe(true),
+            state.clone(),
+        )
+        .wait()
+        .unwrap();
+
+        assert_eq!(1, runtime.restart_called());
+        assert_eq!(0, runtime.start_called());
+        assert_eq!(1, state.restart_count());
+        assert_eq!("unhealthy", state.last_restart().unwrap().reason());
+    }
+
+    #[test]
+    fn unhealthy_running_agent_is_left_alone_without_liveness_probe() {
+        let runtime = TestRuntime::new(Some(
+            TestModule::new("edgeAgent", ModuleStatus::Running)
+                .with_health(HealthStatus::Unhealthy),
+        ));
+        let state = WatchdogState::new();
+
+        check_runtime(
+            runtime.clone(),
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        )
+        .wait()
+        .unwrap();
+
+        assert_eq!(0, runtime.restart_called());
+        assert_eq!(WatchdogStatus::Running, state.status());
+    }
+
+    #[test]
+    fn agent_exit_increments_restart_counter() {
+        let runtime = TestRuntime::new(Some(
+            TestModule::new("edgeAgent", ModuleStatus::Failed).with_exit_code(137),
+        ));
+        let state = WatchdogState::new();
+        assert_eq!(0, state.restart_count());
+        assert_eq!(None, state.last_restart());
+
+        check_runtime(
+            runtime,
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        )
+        .wait()
+        .unwrap();
+
+        assert_eq!(1, state.restart_count());
+        let event = state.last_restart().unwrap();
+        assert_eq!(1, event.count());
+        assert_eq!("failed", event.reason());
+        assert_eq!(Some(137), event.exit_code());
+    }
+
+    #[test]
+    fn repeated_exits_are_reported_as_crash_looping() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Failed)));
+        let settings = WatchdogSettings::default().with_crash_loop_restarts(2);
+        let state = WatchdogState::new();
+        let check = || {
+            check_runtime(
+                runtime.clone(),
+                TestIdentityManager::new(vec![]),
+                agent_spec(),
+                "$edgeAgent".to_string(),
+                settings.clone(),
+                state.clone(),
+            )
+            .wait()
+            .unwrap()
+        };
+
+        check();
+        check();
+        assert_eq!(WatchdogStatus::Stopped, state.status());
+
+        check();
+        assert_eq!(WatchdogStatus::CrashLooping, state.status());
+        assert_eq!(3, runtime.start_called());
+
+        // the module coming back up briefly does not clear the crash loop
+        runtime.set_module_status(ModuleStatus::Running);
+        check();
+        assert_eq!(WatchdogStatus::CrashLooping, state.status());
+    }
+
+    #[test]
+    fn crash_loop_clears_once_restarts_leave_window() {
+        let settings = WatchdogSettings::default()
+            .with_crash_loop_restarts(1)
+            .with_restart_window(Duration::from_secs(10));
+        let state = WatchdogState::new();
+        let now = Instant::now();
+
+        assert!(state.record_restart(&settings, now));
+        assert!(state.record_restart(&settings, now + Duration::from_secs(1)));
+        assert_eq!(WatchdogStatus::CrashLooping, state.status());
+
+        state.set_running(&settings, now + Duration::from_secs(12));
+        assert_eq!(WatchdogStatus::Running, state.status());
+    }
+
+    #[test]
+    fn paused_watchdog_does_not_restart_agent() {
+        let runtime = TestRuntime::new(Some(TestModule::new("edgeAgent", ModuleStatus::Stopped)));
+        let state = WatchdogState::new();
+        let check = || {
+            check_runtime(
+                runtime.clone(),
+                TestIdentityManager::new(vec![]),
+                agent_spec(),
+                "$edgeAgent".to_string(),
+                WatchdogSettings::default(),
+                state.clone(),
+            )
+            .wait()
+            .unwrap()
+        };
+
+        state.pause();
+        assert!(state.is_paused());
+        check();
+        check();
+        assert_eq!(0, runtime.start_called());
+        assert_eq!(WatchdogStatus::Stopped, state.status());
+
+        state.resume();
+        assert!(!state.is_paused());
+        check();
+        assert_eq!(1, runtime.start_called());
+    }
+
+    #[test]
+    fn resume_notifies_watchdog() {
+        let state = WatchdogState::new();
+        let (tx, rx) = mpsc::unbounded();
+        state.set_resume_notifier(tx);
+
+        state.pause();
+        state.resume();
+
+        let (notification, _) = rx.into_future().wait().map_err(|_| ()).unwrap();
+        assert_eq!(Some(()), notification);
+    }
+
+    #[test]
+    fn image_pull_policy_never_uses_local_image() {
+        let runtime = TestRuntime::new(None);
+        let spec = agent_spec().with_image_pull_policy(ImagePullPolicy::Never);
+        let manager = TestIdentityManager::new(vec![TestIdentity::new(
+            "$edgeAgent",
+            "iotedge",
+            "1",
+            AuthType::Sas,
+        )]);
+
+        create_and_start(runtime.clone(), &manager, spec, "$edgeAgent".to_string())
+            .wait()
+            .unwrap();
+
+        assert_eq!(0, runtime.pull_called());
+        assert_eq!(1, runtime.create_called());
+        assert_eq!(1, runtime.start_called());
+    }
+
+    #[test]
+    fn image_pull_policy_always_pulls_image() {
+        let runtime = TestRuntime::new(None);
+        let manager = TestIdentityManager::new(vec![TestIdentity::new(
+            "$edgeAgent",
+            "iotedge",
+            "1",
+            AuthType::Sas,
+        )]);
+
+        create_and_start(
+            runtime.clone(),
+            &manager,
+            agent_spec(),
+            "$edgeAgent".to_string(),
+        )
+        .wait()
+        .unwrap();
+
+        assert_eq!(1, runtime.pull_called());
+        assert_eq!(1, runtime.create_called());
+        assert_eq!(1, runtime.start_called());
+    }
+
+    #[test]
+    fn jittered_interval_varies_within_band() {
+        let frequency = Duration::from_secs(60);
+        let mut rng = rand::thread_rng();
+
+        let intervals: Vec<Duration> = (0..100)
+            .map(|_| jittered_interval(frequency, 10, &mut rng))
+            .collect();
+
+        for interval in &intervals {
+            assert!(*interval >= Duration::from_secs(54));
+            assert!(*interval <= Duration::from_secs(66));
+        }
+        assert!(intervals.iter().any(|interval| *interval != intervals[0]));
+    }
+
+    #[test]
+    fn jittered_interval_without_jitter_is_constant() {
+        let frequency = Duration::from_secs(60);
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            assert_eq!(frequency, jittered_interval(frequency, 0, &mut rng));
+        }
+    }
+
+    #[test]
+    fn pull_failure_is_recorded_as_last_error() {
+        let runtime = TestRuntime::new(None).with_failing_pull();
+        let manager = TestIdentityManager::new(vec![TestIdentity::new(
+            "$edgeAgent",
+            "iotedge",
+            "1",
+            AuthType::Sas,
+        )]);
+        let state = WatchdogState::new();
+
+        let result = check_runtime(
+            runtime.clone(),
+            manager,
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        )
+        .wait();
+
+        assert!(result.is_err());
+        assert_eq!(
+            Some("A module runtime error occurred.\n\tcaused by: General error".to_string()),
+            state.last_error()
+        );
+
+        // the error is cleared once the agent is running
+        runtime.set_module(TestModule::new("edgeAgent", ModuleStatus::Running));
+        check_runtime(
+            runtime,
+            TestIdentityManager::new(vec![]),
+            agent_spec(),
+            "$edgeAgent".to_string(),
+            WatchdogSettings::default(),
+            state.clone(),
+        )
+        .wait()
+        .unwrap();
+        assert_eq!(None, state.last_error());
+    }
 }
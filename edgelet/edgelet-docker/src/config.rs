@@ -2,7 +2,7 @@
 
 use failure::ResultExt;
 
-use docker::models::{AuthConfig, ContainerCreateBody};
+use docker::models::{AuthConfig, ContainerCreateBody, DeviceMapping, ResourcesUlimits};
 use edgelet_utils::{ensure_not_empty_with_context, serde_clone};
 
 use error::{ErrorKind, Result};
@@ -18,6 +18,18 @@ pub struct DockerConfig {
     create_options: ContainerCreateBody,
     #[serde(skip_serializing_if = "Option::is_none")]
     auth: Option<AuthConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    readonly_rootfs: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    devices: Vec<DeviceMapping>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ulimits: Vec<ResourcesUlimits>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    security_opt: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extra_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tmpfs: Vec<TmpfsMount>,
 }
 
 impl DockerConfig {
@@ -33,6 +45,12 @@ impl DockerConfig {
             image_id: None,
             create_options,
             auth,
+            readonly_rootfs: None,
+            devices: vec![],
+            ulimits: vec![],
+            security_opt: vec![],
+            extra_hosts: vec![],
+            tmpfs: vec![],
         };
         Ok(config)
     }
@@ -80,6 +98,129 @@ impl DockerConfig {
         self.auth = Some(auth);
         self
     }
+
+    /// Whether the module's container gets a read-only root filesystem. The
+    /// runtime's default is used if this is not set.
+    pub fn readonly_rootfs(&self) -> Option<bool> {
+        self.readonly_rootfs
+    }
+
+    pub fn with_readonly_rootfs(mut self, readonly_rootfs: bool) -> Self {
+        self.readonly_rootfs = Some(readonly_rootfs);
+        self
+    }
+
+    /// Host devices, e.g. serial ports, that are mapped into the module's
+    /// container in addition to any in its create options.
+    pub fn devices(&self) -> &[DeviceMapping] {
+        &self.devices
+    }
+
+    pub fn with_devices(mut self, devices: Vec<DeviceMapping>) -> Self {
+        self.devices = devices;
+        self
+    }
+
+    /// Ulimits for the module's container. They override the runtime's
+    /// default ulimits of the same name, and are overridden by ulimits in the
+    /// create options.
+    pub fn ulimits(&self) -> &[ResourcesUlimits] {
+        &self.ulimits
+    }
+
+    pub fn with_ulimits(mut self, ulimits: Vec<ResourcesUlimits>) -> Self {
+        self.ulimits = ulimits;
+        self
+    }
+
+    /// Security options for the module's container in docker's format, e.g.
+    /// "apparmor=<profile>" or "seccomp=<profile JSON>". They override the
+    /// runtime's default options of the same kind.
+    pub fn security_opt(&self) -> &[String] {
+        &self.security_opt
+    }
+
+    pub fn with_security_opt(mut self, security_opt: Vec<String>) -> Self {
+        self.security_opt = security_opt;
+        self
+    }
+
+    /// Static "hostname:IP" entries added to the module container's hosts
+    /// file. They override the runtime's default entries for the same host.
+    pub fn extra_hosts(&self) -> &[String] {
+        &self.extra_hosts
+    }
+
+    pub fn with_extra_hosts(mut self, extra_hosts: Vec<String>) -> Self {
+        self.extra_hosts = extra_hosts;
+        self
+    }
+
+    /// RAM-backed scratch directories mounted into the module's container.
+    pub fn tmpfs(&self) -> &[TmpfsMount] {
+        &self.tmpfs
+    }
+
+    pub fn with_tmpfs(mut self, tmpfs: Vec<TmpfsMount>) -> Self {
+        self.tmpfs = tmpfs;
+        self
+    }
+}
+
+/// A tmpfs mount at `target` in a module's container, with an optional size,
+/// e.g. "64m", and octal mode, e.g. "1777".
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TmpfsMount {
+    target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+}
+
+impl TmpfsMount {
+    pub fn new(target: String) -> Self {
+        TmpfsMount {
+            target,
+            size: None,
+            mode: None,
+        }
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn size(&self) -> Option<&str> {
+        self.size.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_size(mut self, size: String) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_mode(mut self, mode: String) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// The mount options in the format of `HostConfig.Tmpfs`, e.g.
+    /// "size=64m,mode=1777".
+    pub fn options(&self) -> String {
+        let mut options = vec![];
+        if let Some(ref size) = self.size {
+            options.push(format!("size={}", size));
+        }
+        if let Some(ref mode) = self.mode {
+            options.push(format!("mode={}", mode));
+        }
+        options.join(",")
+    }
 }
 
 #[cfg(test)]
@@ -209,6 +350,42 @@ mod tests {
         assert_eq!(config.image, "ubuntu");
     }
 
+    #[test]
+    fn docker_config_deser_devices() {
+        let input_json = json!({
+            "image": "ubuntu",
+            "devices": [
+                {
+                    "PathOnHost": "/dev/ttyS0",
+                    "PathInContainer": "/dev/ttyS0",
+                    "CgroupPermissions": "rwm"
+                }
+            ]
+        });
+        let config = serde_json::from_str::<DockerConfig>(&input_json.to_string()).unwrap();
+        assert_eq!(1, config.devices().len());
+        assert_eq!(Some("/dev/ttyS0"), config.devices()[0].path_on_host());
+        assert_eq!(Some("/dev/ttyS0"), config.devices()[0].path_in_container());
+        assert_eq!(Some("rwm"), config.devices()[0].cgroup_permissions());
+    }
+
+    #[test]
+    fn docker_config_deser_tmpfs() {
+        let input_json = json!({
+            "image": "ubuntu",
+            "tmpfs": [
+                { "target": "/scratch", "size": "64m", "mode": "1777" },
+                { "target": "/run" }
+            ]
+        });
+        let config = serde_json::from_str::<DockerConfig>(&input_json.to_string()).unwrap();
+        assert_eq!(2, config.tmpfs().len());
+        assert_eq!("/scratch", config.tmpfs()[0].target());
+        assert_eq!("size=64m,mode=1777", config.tmpfs()[0].options());
+        assert_eq!("/run", config.tmpfs()[1].target());
+        assert_eq!("", config.tmpfs()[1].options());
+    }
+
     #[test]
     fn docker_config_deser_from_map() {
         let input_json = json!({
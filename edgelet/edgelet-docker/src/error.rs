@@ -47,6 +47,9 @@ pub enum ErrorKind {
     #[fail(display = "Conflict with current operation")]
     Conflict,
 
+    #[fail(display = "Could not get trust data for image {:?}", _0)]
+    ContentTrust(String),
+
     #[fail(display = "Container runtime error")]
     Docker,
 
@@ -56,12 +59,24 @@ pub enum ErrorKind {
     #[fail(display = "{}", _0)]
     FormattedDockerRuntime(String),
 
+    #[fail(display = "Image {:?} is not signed", _0)]
+    ImageNotSigned(String),
+
+    #[fail(display = "Image pull did not complete within {} seconds", _0)]
+    ImagePullTimeout(u64),
+
     #[fail(display = "Could not initialize module runtime")]
     Initialization,
 
+    #[fail(display = "Invalid docker API version {:?}", _0)]
+    InvalidApiVersion(String),
+
     #[fail(display = "Invalid docker image {:?}", _0)]
     InvalidImage(String),
 
+    #[fail(display = "Invalid trust data for role {:?}", _0)]
+    InvalidTrustData(String),
+
     #[fail(display = "Invalid module name {:?}", _0)]
     InvalidModuleName(String),
 
@@ -82,6 +97,33 @@ pub enum ErrorKind {
 
     #[fail(display = "{}", _0)]
     RuntimeOperation(RuntimeOperation),
+
+    #[fail(display = "Timer error")]
+    Timer,
+
+    #[fail(display = "Trust data for role {:?} has expired", _0)]
+    TrustDataExpired(String),
+
+    #[fail(
+        display = "Trust data for role {:?} is older than the trusted version",
+        _0
+    )]
+    TrustDataRollback(String),
+
+    #[fail(
+        display = "Trust data for role {:?} is not signed by enough trusted keys",
+        _0
+    )]
+    TrustDataSignature(String),
+
+    #[fail(display = "Could not communicate with the trust server")]
+    TrustServer,
+
+    #[fail(display = "Trust server returned {}", _0)]
+    TrustServerResponse(StatusCode),
+
+    #[fail(display = "Could not access the trusted root metadata in {}", _0)]
+    TrustStore(String),
 }
 
 impl Fail for Error {
@@ -18,6 +18,7 @@ extern crate hyper;
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate openssl;
 #[macro_use]
 extern crate serde_derive;
 #[cfg(test)]
@@ -25,7 +26,6 @@ extern crate serde;
 // Need stuff other than macros from serde_json for non-test code.
 #[cfg(not(test))]
 extern crate serde_json;
-#[cfg(test)]
 extern crate tokio;
 extern crate url;
 
@@ -52,9 +52,12 @@ mod config;
 mod error;
 mod module;
 mod runtime;
+mod trust;
+mod tuf;
 
-pub use config::DockerConfig;
+pub use config::{DockerConfig, TmpfsMount};
 pub use error::{Error, ErrorKind};
 pub use module::{DockerModule, MODULE_TYPE};
 
 pub use runtime::DockerModuleRuntime;
+pub use trust::{ContentTrust, NotaryTrust};
@@ -11,7 +11,7 @@ use edgelet_utils::ensure_not_empty_with_context;
 use client::DockerClient;
 use config::DockerConfig;
 use edgelet_core::pid::Pid;
-use edgelet_core::{Module, ModuleOperation, ModuleRuntimeState, ModuleStatus};
+use edgelet_core::{HealthStatus, Module, ModuleOperation, ModuleRuntimeState, ModuleStatus};
 use error::{Error, ErrorKind, Result};
 
 pub const MODULE_TYPE: &str = "docker";
@@ -45,6 +45,16 @@ fn status_from_exit_code(exit_code: Option<i64>) -> Option<ModuleStatus> {
     })
 }
 
+// Docker reports "none" for containers without a health check
+fn health_from_status(status: &str) -> Option<HealthStatus> {
+    match status {
+        "starting" => Some(HealthStatus::Starting),
+        "healthy" => Some(HealthStatus::Healthy),
+        "unhealthy" => Some(HealthStatus::Unhealthy),
+        _ => None,
+    }
+}
+
 impl<C: 'static + Connect> Module for DockerModule<C> {
     type Config = DockerConfig;
     type Error = Error;
@@ -103,6 +113,12 @@ impl<C: 'static + Connect> Module for DockerModule<C> {
                                 )
                                 .with_image_id(resp.id().map(ToOwned::to_owned))
                                 .with_pid(state.pid().map_or(Pid::None, Pid::Value))
+                                .with_health(
+                                    state
+                                        .health()
+                                        .and_then(|health| health.status())
+                                        .and_then(health_from_status),
+                                )
                         })
                 })
                 .map_err(|err| {
@@ -128,7 +144,10 @@ mod tests {
 
     use docker::apis::client::APIClient;
     use docker::apis::configuration::Configuration;
-    use docker::models::{ContainerCreateBody, InlineResponse200, InlineResponse200State};
+    use docker::models::{
+        ContainerCreateBody, InlineResponse200, InlineResponse200State,
+        InlineResponse200StateHealth,
+    };
     use edgelet_core::pid::Pid;
     use edgelet_core::{Module, ModuleStatus};
     use edgelet_test_utils::JsonConnector;
@@ -262,6 +281,38 @@ mod tests {
         assert_eq!(Pid::Value(1234), runtime_state.pid());
     }
 
+    #[test]
+    fn module_runtime_state_includes_health() {
+        let inputs = vec![
+            (None, None),
+            (Some("none"), None),
+            (Some("starting"), Some(HealthStatus::Starting)),
+            (Some("healthy"), Some(HealthStatus::Healthy)),
+            (Some("unhealthy"), Some(HealthStatus::Unhealthy)),
+        ];
+
+        for (docker_health, health) in inputs {
+            let mut state = InlineResponse200State::new().with_status("running".to_string());
+            if let Some(docker_health) = docker_health {
+                state.set_health(
+                    InlineResponse200StateHealth::new().with_status(docker_health.to_string()),
+                );
+            }
+            let docker_module = DockerModule::new(
+                create_api_client(InlineResponse200::new().with_state(state)),
+                "mod1".to_string(),
+                DockerConfig::new("ubuntu".to_string(), ContainerCreateBody::new(), None).unwrap(),
+            )
+            .unwrap();
+
+            let runtime_state = tokio::runtime::current_thread::Runtime::new()
+                .unwrap()
+                .block_on(docker_module.runtime_state())
+                .unwrap();
+            assert_eq!(health, runtime_state.health());
+        }
+    }
+
     #[test]
     fn module_runtime_state_failed_from_dead() {
         let started_at = Utc::now().to_rfc3339();
@@ -1,24 +1,30 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
 use std::ops::Deref;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use base64;
 use failure::{Fail, ResultExt};
+use futures::future::{Either, Loop};
 use futures::prelude::*;
 use futures::{future, stream, Async, Stream};
 use hyper::{Body, Chunk as HyperChunk, Client};
 use log::Level;
 use serde_json;
+use tokio::timer::{Delay, Timeout};
 use url::Url;
 
 use client::DockerClient;
-use config::DockerConfig;
+use config::{DockerConfig, TmpfsMount};
 use docker::apis::client::APIClient;
 use docker::apis::configuration::Configuration;
-use docker::models::{ContainerCreateBody, NetworkConfig};
+use docker::models::{
+    AuthConfig, Container, ContainerCreateBody, DeviceMapping, HostConfig, HostConfigLogConfig,
+    Ipam, NetworkConfig, ResourcesUlimits,
+};
 use edgelet_core::{
     LogOptions, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeState, ModuleSpec,
     RegistryOperation, RuntimeOperation, SystemInfo as CoreSystemInfo,
@@ -28,9 +34,13 @@ use edgelet_utils::{ensure_not_empty_with_context, log_failure};
 
 use error::{Error, ErrorKind, Result};
 use module::{DockerModule, MODULE_TYPE as DOCKER_MODULE_TYPE};
+use trust::{split_tag, ContentTrust};
 
 const WAIT_BEFORE_KILL_SECONDS: i32 = 10;
 
+/// Registry that images without a registry hostname are pulled from
+pub(crate) const DOCKER_HUB_REGISTRY: &str = "docker.io";
+
 static LABEL_KEY: &str = "net.azure-devices.edge.owner";
 static LABEL_VALUE: &str = "Microsoft.Azure.Devices.Edge.Agent";
 
@@ -46,10 +56,44 @@ lazy_static! {
 pub struct DockerModuleRuntime {
     client: DockerClient<UrlConnector>,
     network_id: Option<String>,
+    network_driver: Option<String>,
+    network_ipv6_subnet: Option<String>,
+    network_subnet: Option<(String, Option<String>)>,
+    additional_networks: Vec<String>,
+    log_config: Option<HostConfigLogConfig>,
+    default_resources: Option<HostConfig>,
+    labels: HashMap<String, String>,
+    readonly_rootfs: bool,
+    dns: Vec<String>,
+    ulimits: Vec<ResourcesUlimits>,
+    security_opt: Vec<String>,
+    extra_hosts: Vec<String>,
+    content_trust: Option<Arc<ContentTrust>>,
+    image_pull_timeout: Option<Duration>,
+    stop_timeout: Option<Duration>,
+    init_retries: u32,
+    init_retry_interval: Duration,
+    registries: BTreeMap<String, AuthConfig>,
 }
 
 impl DockerModuleRuntime {
     pub fn new(docker_url: &Url) -> Result<Self> {
+        DockerModuleRuntime::create(docker_url, None)
+    }
+
+    /// Like `new`, but all requests to the docker engine use API version
+    /// `api_version`, e.g. "1.25", instead of the engine's latest version.
+    pub fn new_with_api_version(docker_url: &Url, api_version: &str) -> Result<Self> {
+        if api_version.is_empty() || !api_version.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return Err(Error::from(ErrorKind::InvalidApiVersion(
+                api_version.to_string(),
+            )));
+        }
+
+        DockerModuleRuntime::create(docker_url, Some(api_version.to_string()))
+    }
+
+    fn create(docker_url: &Url, api_version: Option<String>) -> Result<Self> {
         // build the hyper client
         let client = Client::builder()
             .build(UrlConnector::new(docker_url).context(ErrorKind::Initialization)?);
@@ -66,13 +110,35 @@ impl DockerModuleRuntime {
 
         let scheme = docker_url.scheme().to_string();
         configuration.uri_composer = Box::new(move |base_path, path| {
-            Ok(UrlConnector::build_hyper_uri(&scheme, base_path, path)
+            let path = api_version.as_ref().map_or_else(
+                || path.to_string(),
+                |version| format!("/v{}{}", version, path),
+            );
+            Ok(UrlConnector::build_hyper_uri(&scheme, base_path, &path)
                 .context(ErrorKind::Initialization)?)
         });
 
         Ok(DockerModuleRuntime {
             client: DockerClient::new(APIClient::new(configuration)),
             network_id: None,
+            network_driver: None,
+            network_ipv6_subnet: None,
+            network_subnet: None,
+            additional_networks: Vec::new(),
+            log_config: None,
+            default_resources: None,
+            labels: HashMap::new(),
+            readonly_rootfs: false,
+            dns: vec![],
+            ulimits: vec![],
+            security_opt: vec![],
+            extra_hosts: vec![],
+            content_trust: None,
+            image_pull_timeout: None,
+            stop_timeout: None,
+            init_retries: 0,
+            init_retry_interval: Duration::from_secs(0),
+            registries: BTreeMap::new(),
         })
     }
 
@@ -81,6 +147,477 @@ impl DockerModuleRuntime {
         self
     }
 
+    /// Driver to use when creating the network, e.g. "macvlan". Docker's
+    /// default driver (bridge) is used if this is not set.
+    pub fn with_network_driver(mut self, network_driver: String) -> Self {
+        self.network_driver = Some(network_driver);
+        self
+    }
+
+    /// Enables IPv6 on the network when creating it, assigning modules
+    /// addresses from `subnet` (e.g. "fd00:1::/64").
+    pub fn with_network_ipv6_subnet(mut self, subnet: String) -> Self {
+        self.network_ipv6_subnet = Some(subnet);
+        self
+    }
+
+    /// Assigns the network the IPv4 subnet `subnet` (e.g. "172.20.0.0/16") and
+    /// optionally its gateway address when creating it, instead of the subnet
+    /// docker would pick, which can collide with networks of the host.
+    pub fn with_network_subnet(mut self, subnet: String, gateway: Option<String>) -> Self {
+        self.network_subnet = Some((subnet, gateway));
+        self
+    }
+
+    /// Existing networks, besides the edge network, that module containers
+    /// are connected to when they are created.
+    pub fn with_additional_networks(mut self, networks: Vec<String>) -> Self {
+        self.additional_networks = networks;
+        self
+    }
+
+    /// Log driver and options applied to module containers whose create
+    /// options don't specify their own, e.g. "json-file" with a "max-size".
+    pub fn with_log_config(mut self, log_config: HostConfigLogConfig) -> Self {
+        self.log_config = Some(log_config);
+        self
+    }
+
+    /// Memory and CPU limits (memory, memory swap, CPU shares and nano CPUs)
+    /// taken from `resources` for module containers whose create options
+    /// don't set them. Other fields of `resources` are ignored.
+    pub fn with_default_resources(mut self, resources: HostConfig) -> Self {
+        self.default_resources = Some(resources);
+        self
+    }
+
+    /// Labels added to every module container, unless its create options
+    /// already set them. The label marking the container as managed by the
+    /// edge runtime can't be overridden.
+    pub fn with_labels(mut self, labels: HashMap<String, String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Give module containers a read-only root filesystem unless their module
+    /// config or create options say otherwise.
+    pub fn with_readonly_rootfs(mut self, readonly_rootfs: bool) -> Self {
+        self.readonly_rootfs = readonly_rootfs;
+        self
+    }
+
+    /// DNS servers for module containers whose create options don't set their
+    /// own, instead of docker's default.
+    pub fn with_dns(mut self, dns: Vec<String>) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    /// Default ulimits for module containers, e.g. "nofile". A module's own
+    /// ulimits of the same name take precedence.
+    pub fn with_ulimits(mut self, ulimits: Vec<ResourcesUlimits>) -> Self {
+        self.ulimits = ulimits;
+        self
+    }
+
+    /// Default security options for module containers, e.g. the AppArmor or
+    /// seccomp profile to run them with. A module's own options of the same
+    /// kind take precedence.
+    pub fn with_security_opt(mut self, security_opt: Vec<String>) -> Self {
+        self.security_opt = security_opt;
+        self
+    }
+
+    /// Default "hostname:IP" entries for the hosts file of module containers,
+    /// e.g. for the hostname of a parent device. A module's own entries for
+    /// the same host take precedence.
+    pub fn with_extra_hosts(mut self, extra_hosts: Vec<String>) -> Self {
+        self.extra_hosts = extra_hosts;
+        self
+    }
+
+    /// Only pull images that are signed according to `content_trust`. Images
+    /// are then pulled by their signed digest and tagged locally, so that
+    /// modules are created from the signed image.
+    pub fn with_content_trust<T>(mut self, content_trust: T) -> Self
+    where
+        T: 'static + ContentTrust,
+    {
+        self.content_trust = Some(Arc::new(content_trust));
+        self
+    }
+
+    /// Fail image pulls that don't complete within `timeout`, so that a hung
+    /// registry doesn't block the pull forever.
+    pub fn with_image_pull_timeout(mut self, timeout: Duration) -> Self {
+        self.image_pull_timeout = Some(timeout);
+        self
+    }
+
+    /// How long docker waits for module containers to exit when stopping or
+    /// restarting them before killing them, unless the caller of `stop` asks
+    /// for a different time. Also set as the stop timeout of module containers
+    /// whose create options don't set their own.
+    pub fn with_stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = Some(timeout);
+        self
+    }
+
+    /// Retry initialization up to `retries` times, waiting `interval` between
+    /// attempts, to give the docker daemon and its network time to come up.
+    pub fn with_init_retries(mut self, retries: u32, interval: Duration) -> Self {
+        self.init_retries = retries;
+        self.init_retry_interval = interval;
+        self
+    }
+
+    /// Credentials to use when pulling images from each registry, keyed by
+    /// registry hostname. These are used for images whose config does not
+    /// have its own credentials.
+    pub fn with_registries(mut self, registries: BTreeMap<String, AuthConfig>) -> Self {
+        self.registries = registries;
+        self
+    }
+
+    fn registry_auth(&self, image: &str) -> Option<&AuthConfig> {
+        self.registries.get(registry_host(image))
+    }
+
+    // With content trust enabled, resolves `image` to its repository pinned to
+    // the digest it was signed with, failing if it isn't signed. Images that
+    // are already referenced by digest are pulled as they are.
+    fn signed_image(
+        &self,
+        image: &str,
+    ) -> Box<Future<Item = Option<String>, Error = Error> + Send> {
+        match self.content_trust {
+            Some(ref content_trust) if !image.contains('@') => {
+                let image = image.to_string();
+                Box::new(
+                    content_trust
+                        .signed_digest(&image)
+                        .and_then(move |digest| match digest {
+                            Some(digest) => {
+                                let (repository, _) = split_tag(&image);
+                                Ok(Some(format!("{}@{}", repository, digest)))
+                            }
+                            None => Err(Error::from(
+                                ErrorKind::ImageNotSigned(image.clone()).context(
+                                    ErrorKind::RegistryOperation(RegistryOperation::PullImage(
+                                        image,
+                                    )),
+                                ),
+                            )),
+                        }),
+                )
+            }
+            _ => Box::new(future::ok(None)),
+        }
+    }
+
+    // Makes sure the network modules are attached to exists.
+    fn ensure_network(&self) -> impl Future<Item = (), Error = Error> {
+        self.network_id.clone().map_or_else(
+            || future::Either::B(future::ok(())),
+            |id| {
+                let filter = format!(r#"{{"name":{{"{}":true}}}}"#, id);
+                let network_config = self.network_config(id);
+                let client_copy = self.client.clone();
+                let fut = self
+                    .client
+                    .network_api()
+                    .network_list(&filter)
+                    .and_then(move |existing_networks| {
+                        if existing_networks.is_empty() {
+                            let fut = client_copy
+                                .network_api()
+                                .network_create(network_config)
+                                .map(|_| ());
+                            future::Either::A(fut)
+                        } else {
+                            future::Either::B(future::ok(()))
+                        }
+                    })
+                    .map_err(|err| {
+                        let e = Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::Init),
+                        );
+                        log_failure(Level::Warn, &e);
+                        e
+                    });
+                future::Either::A(fut)
+            },
+        )
+    }
+
+    // Connects a newly created module container to each additional network.
+    fn connect_networks(&self, name: &str) -> impl Future<Item = (), Error = Error> + Send {
+        let connected = self
+            .additional_networks
+            .iter()
+            .map(|network| {
+                let name = name.to_string();
+                self.client
+                    .network_api()
+                    .network_connect(network, Container::new().with_container(name.clone()))
+                    .map_err(|err| {
+                        Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(name)),
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        future::join_all(connected).map(|_| ())
+    }
+
+    fn apply_log_config(&self, create_options: ContainerCreateBody) -> ContainerCreateBody {
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        match self.log_config {
+            Some(ref log_config) if host_config.log_config().is_none() => {
+                create_options.with_host_config(host_config.with_log_config(log_config.clone()))
+            }
+            _ => create_options,
+        }
+    }
+
+    fn apply_default_resources(&self, create_options: ContainerCreateBody) -> ContainerCreateBody {
+        let defaults = match self.default_resources {
+            Some(ref defaults) => defaults,
+            None => return create_options,
+        };
+
+        let mut host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        if let (None, Some(memory)) = (host_config.memory(), defaults.memory()) {
+            host_config.set_memory(memory);
+        }
+        if let (None, Some(memory_swap)) = (host_config.memory_swap(), defaults.memory_swap()) {
+            host_config.set_memory_swap(memory_swap);
+        }
+        if let (None, Some(cpu_shares)) = (host_config.cpu_shares(), defaults.cpu_shares()) {
+            host_config.set_cpu_shares(cpu_shares);
+        }
+        if let (None, Some(nano_cpus)) = (host_config.nano_cp_us(), defaults.nano_cp_us()) {
+            host_config.set_nano_cp_us(nano_cpus);
+        }
+        create_options.with_host_config(host_config)
+    }
+
+    fn apply_dns(&self, create_options: ContainerCreateBody) -> ContainerCreateBody {
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        if self.dns.is_empty() || host_config.dns().is_some() {
+            create_options
+        } else {
+            create_options.with_host_config(host_config.with_dns(self.dns.clone()))
+        }
+    }
+
+    // Adds the module's devices to any that its create options map.
+    fn apply_devices(
+        create_options: ContainerCreateBody,
+        devices: &[DeviceMapping],
+    ) -> ContainerCreateBody {
+        if devices.is_empty() {
+            return create_options;
+        }
+
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        let mut all_devices = host_config
+            .devices()
+            .map_or_else(Vec::new, ToOwned::to_owned);
+        all_devices.extend_from_slice(devices);
+        create_options.with_host_config(host_config.with_devices(all_devices))
+    }
+
+    // Combines the runtime's default ulimits with the module's and those in its
+    // create options, which take precedence in that order.
+    fn apply_ulimits(
+        &self,
+        create_options: ContainerCreateBody,
+        ulimits: &[ResourcesUlimits],
+    ) -> ContainerCreateBody {
+        if self.ulimits.is_empty() && ulimits.is_empty() {
+            return create_options;
+        }
+
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        let mut all_ulimits: Vec<ResourcesUlimits> = vec![];
+        for ulimit in self
+            .ulimits
+            .iter()
+            .chain(ulimits)
+            .chain(host_config.ulimits().unwrap_or(&[]))
+        {
+            all_ulimits.retain(|existing| existing.name() != ulimit.name());
+            all_ulimits.push(ulimit.clone());
+        }
+        create_options.with_host_config(host_config.with_ulimits(all_ulimits))
+    }
+
+    // Combines the runtime's default security options with the module's and
+    // those in its create options, which take precedence in that order. Options
+    // are of the same kind if they have the same key, like "seccomp".
+    fn apply_security_opt(
+        &self,
+        create_options: ContainerCreateBody,
+        security_opt: &[String],
+    ) -> ContainerCreateBody {
+        if self.security_opt.is_empty() && security_opt.is_empty() {
+            return create_options;
+        }
+
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        let mut all_security_opt: Vec<String> = vec![];
+        for opt in self
+            .security_opt
+            .iter()
+            .chain(security_opt)
+            .chain(host_config.security_opt().unwrap_or(&[]))
+        {
+            all_security_opt.retain(|existing| security_opt_key(existing) != security_opt_key(opt));
+            all_security_opt.push(opt.clone());
+        }
+        create_options.with_host_config(host_config.with_security_opt(all_security_opt))
+    }
+
+    // Combines the runtime's default extra hosts with the module's and those in
+    // its create options, which take precedence in that order.
+    fn apply_extra_hosts(
+        &self,
+        create_options: ContainerCreateBody,
+        extra_hosts: &[String],
+    ) -> ContainerCreateBody {
+        if self.extra_hosts.is_empty() && extra_hosts.is_empty() {
+            return create_options;
+        }
+
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        let mut all_extra_hosts: Vec<String> = vec![];
+        for host in self
+            .extra_hosts
+            .iter()
+            .chain(extra_hosts)
+            .chain(host_config.extra_hosts().unwrap_or(&[]))
+        {
+            all_extra_hosts.retain(|existing| extra_host_name(existing) != extra_host_name(host));
+            all_extra_hosts.push(host.clone());
+        }
+        create_options.with_host_config(host_config.with_extra_hosts(all_extra_hosts))
+    }
+
+    // Adds the module's tmpfs mounts to those in its create options, which take
+    // precedence for the same target.
+    fn apply_tmpfs(
+        create_options: ContainerCreateBody,
+        tmpfs: &[TmpfsMount],
+    ) -> ContainerCreateBody {
+        if tmpfs.is_empty() {
+            return create_options;
+        }
+
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        let mut all_tmpfs = host_config.tmpfs().cloned().unwrap_or_else(HashMap::new);
+        for mount in tmpfs {
+            all_tmpfs
+                .entry(mount.target().to_string())
+                .or_insert_with(|| mount.options());
+        }
+        create_options.with_host_config(host_config.with_tmpfs(all_tmpfs))
+    }
+
+    // Sets `StopTimeout` to the runtime's stop timeout if the create options
+    // don't set it themselves, so that docker also waits that long when it stops
+    // the container on its own, e.g. when the daemon shuts down.
+    fn apply_stop_timeout(&self, create_options: ContainerCreateBody) -> ContainerCreateBody {
+        match self.stop_timeout {
+            Some(timeout) if create_options.stop_timeout().is_none() => {
+                create_options.with_stop_timeout(timeout_secs(timeout))
+            }
+            _ => create_options,
+        }
+    }
+
+    // Seconds docker waits for a container to exit before killing it.
+    fn wait_before_kill_secs(&self, wait_before_kill: Option<Duration>) -> i32 {
+        wait_before_kill
+            .or(self.stop_timeout)
+            .map_or(WAIT_BEFORE_KILL_SECONDS, timeout_secs)
+    }
+
+    // Sets `ReadonlyRootfs` from the module's config, or else the runtime's
+    // default, if the create options don't set it themselves.
+    fn apply_readonly_rootfs(
+        &self,
+        create_options: ContainerCreateBody,
+        readonly_rootfs: Option<bool>,
+    ) -> ContainerCreateBody {
+        let host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+        if host_config.readonly_rootfs().is_some() {
+            return create_options;
+        }
+
+        if readonly_rootfs.unwrap_or(self.readonly_rootfs) {
+            create_options.with_host_config(host_config.with_readonly_rootfs(true))
+        } else {
+            create_options
+        }
+    }
+
+    fn network_config(&self, id: String) -> NetworkConfig {
+        let mut network_config = NetworkConfig::new(id);
+        if let Some(ref driver) = self.network_driver {
+            network_config = network_config.with_driver(driver.clone());
+        }
+        let mut ipam_configs = vec![];
+        if let Some((ref subnet, ref gateway)) = self.network_subnet {
+            let mut ipam_config = HashMap::new();
+            ipam_config.insert("Subnet".to_string(), subnet.clone());
+            if let Some(ref gateway) = *gateway {
+                ipam_config.insert("Gateway".to_string(), gateway.clone());
+            }
+            ipam_configs.push(ipam_config);
+        }
+        if let Some(ref subnet) = self.network_ipv6_subnet {
+            let mut ipam_config = HashMap::new();
+            ipam_config.insert("Subnet".to_string(), subnet.clone());
+            ipam_configs.push(ipam_config);
+            network_config = network_config.with_enable_i_pv6(true);
+        }
+        if !ipam_configs.is_empty() {
+            network_config = network_config.with_IPAM(Ipam::new().with_config(ipam_configs));
+        }
+        network_config
+    }
+
     fn merge_env(cur_env: Option<&[String]>, new_env: &HashMap<String, String>) -> Vec<String> {
         // build a new merged hashmap containing string slices for keys and values
         // pointing into String instances in new_env
@@ -104,10 +641,61 @@ impl DockerModuleRuntime {
     }
 }
 
+// The hostname of an extra hosts entry, e.g. "parent" for "parent:10.0.0.1".
+// IPv6 addresses contain ':' themselves, so the name ends at the first one.
+fn extra_host_name(host: &str) -> &str {
+    host.split(':').next().unwrap_or(host)
+}
+
+// A timeout in whole seconds, as docker expects it, capped at `i32::MAX`.
+#[cfg_attr(
+    feature = "cargo-clippy",
+    allow(cast_possible_truncation, cast_sign_loss)
+)]
+fn timeout_secs(timeout: Duration) -> i32 {
+    match timeout.as_secs() {
+        s if s > i32::max_value() as u64 => i32::max_value(),
+        s => s as i32,
+    }
+}
+
+// The kind of a security option, e.g. "seccomp" for "seccomp=unconfined".
+// Docker also accepts ':' as the separator. SELinux labels of different
+// kinds can be combined, so "label=user:USER" is of kind "label=user".
+fn security_opt_key(opt: &str) -> &str {
+    let end = opt
+        .find(|c: char| c == '=' || c == ':')
+        .unwrap_or_else(|| opt.len());
+    if &opt[..end] == "label" && end < opt.len() {
+        if let Some(index) = opt[end + 1..].find(':') {
+            return &opt[..end + 1 + index];
+        }
+    }
+    &opt[..end]
+}
+
+// Docker treats the first component of an image reference as a registry
+// hostname if it contains a '.' or ':' or is "localhost". Any other image is
+// pulled from Docker Hub.
+pub(crate) fn registry_host(image: &str) -> &str {
+    match image.find('/') {
+        Some(index) => {
+            let host = &image[..index];
+            if host.contains('.') || host.contains(':') || host == "localhost" {
+                host
+            } else {
+                DOCKER_HUB_REGISTRY
+            }
+        }
+        None => DOCKER_HUB_REGISTRY,
+    }
+}
+
 impl ModuleRegistry for DockerModuleRuntime {
     type Error = Error;
     type PullFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error>>;
+    type PruneFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type Config = DockerConfig;
 
     fn pull(&self, config: &Self::Config) -> Self::PullFuture {
@@ -115,7 +703,8 @@ impl ModuleRegistry for DockerModuleRuntime {
 
         info!("Pulling image {}...", image);
 
-        let creds: Result<String> = config.auth().map_or_else(
+        let auth = config.auth().or_else(|| self.registry_auth(&image));
+        let creds: Result<String> = auth.map_or_else(
             || Ok("".to_string()),
             |a| {
                 let json = serde_json::to_string(a).with_context(|_| {
@@ -125,31 +714,68 @@ impl ModuleRegistry for DockerModuleRuntime {
             },
         );
 
+        let client = self.client.clone();
+        let signed_image = self.signed_image(&image);
+        let timeout_image = image.clone();
         let response = creds
-            .map(|creds| {
-                self.client
-                    .image_api()
-                    .image_create(&image, "", "", "", "", &creds, "")
-                    .then(|result| match result {
-                        Ok(()) => Ok(image),
-                        Err(err) => Err(Error::from_docker_error(
-                            err,
-                            ErrorKind::RegistryOperation(RegistryOperation::PullImage(image)),
-                        )),
-                    })
+            .map(move |creds| {
+                signed_image.and_then(move |signed_image| {
+                    // a signed image is pulled by digest and then given the tag
+                    // its module spec refers to
+                    let from_image = signed_image.clone().unwrap_or_else(|| image.clone());
+                    let tag_image = image.clone();
+                    let tag_client = client.clone();
+                    client
+                        .image_api()
+                        .image_create(&from_image, "", "", "", "", &creds, "")
+                        .and_then(move |()| match signed_image {
+                            Some(signed_image) => {
+                                let (repository, tag) = split_tag(&tag_image);
+                                Either::A(tag_client.image_api().image_tag(
+                                    &signed_image,
+                                    repository,
+                                    tag,
+                                ))
+                            }
+                            None => Either::B(future::ok(())),
+                        })
+                        .then(|result| match result {
+                            Ok(()) => Ok(image),
+                            Err(err) => Err(Error::from_docker_error(
+                                err,
+                                ErrorKind::RegistryOperation(RegistryOperation::PullImage(image)),
+                            )),
+                        })
+                })
             })
             .into_future()
-            .flatten()
-            .then(move |result| match result {
-                Ok(image) => {
-                    info!("Successfully pulled image {}", image);
-                    Ok(())
-                }
-                Err(err) => {
-                    log_failure(Level::Warn, &err);
-                    Err(err)
+            .flatten();
+
+        let response = match self.image_pull_timeout {
+            Some(timeout) => Either::A(Timeout::new(response, timeout).map_err(move |err| {
+                if err.is_elapsed() {
+                    Error::from(ErrorKind::ImagePullTimeout(timeout.as_secs()).context(
+                        ErrorKind::RegistryOperation(RegistryOperation::PullImage(timeout_image)),
+                    ))
+                } else if err.is_inner() {
+                    err.into_inner().expect("timeout error is an inner error")
+                } else {
+                    let err = err.into_timer().expect("timeout error is a timer error");
+                    Error::from(err.context(ErrorKind::Timer))
                 }
-            });
+            })),
+            None => Either::B(response),
+        }
+        .then(move |result| match result {
+            Ok(image) => {
+                info!("Successfully pulled image {}", image);
+                Ok(())
+            }
+            Err(err) => {
+                log_failure(Level::Warn, &err);
+                Err(err)
+            }
+        });
 
         Box::new(response)
     }
@@ -185,6 +811,35 @@ impl ModuleRegistry for DockerModuleRuntime {
                 }),
         )
     }
+
+    fn prune(&self) -> Self::PruneFuture {
+        info!("Pruning dangling images...");
+
+        // Only dangling images are removed, and docker never prunes an image
+        // that is used by a container, so the images of running modules are kept.
+        Box::new(
+            self.client
+                .image_api()
+                .image_prune(r#"{"dangling":{"true":true}}"#)
+                .then(|result| match result {
+                    Ok(response) => {
+                        info!(
+                            "Successfully pruned {} images",
+                            response.images_deleted().map_or(0, <[_]>::len)
+                        );
+                        Ok(())
+                    }
+                    Err(err) => {
+                        let err = Error::from_docker_error(
+                            err,
+                            ErrorKind::RegistryOperation(RegistryOperation::PruneImages),
+                        );
+                        log_failure(Level::Warn, &err);
+                        Err(err)
+                    }
+                }),
+        )
+    }
 }
 
 impl ModuleRuntime for DockerModuleRuntime {
@@ -207,41 +862,36 @@ impl ModuleRuntime for DockerModuleRuntime {
     type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error> + Send>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type PruneVolumesFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
 
     fn init(&self) -> Self::InitFuture {
         info!("Initializing module runtime...");
 
-        let created = self.network_id.clone().map_or_else(
-            || future::Either::B(future::ok(())),
-            |id| {
-                let filter = format!(r#"{{"name":{{"{}":true}}}}"#, id);
-                let client_copy = self.client.clone();
-                let fut = self
-                    .client
-                    .network_api()
-                    .network_list(&filter)
-                    .and_then(move |existing_networks| {
-                        if existing_networks.is_empty() {
-                            let fut = client_copy
-                                .network_api()
-                                .network_create(NetworkConfig::new(id))
-                                .map(|_| ());
-                            future::Either::A(fut)
-                        } else {
-                            future::Either::B(future::ok(()))
+        let runtime = self.clone();
+        let created = future::loop_fn(0, move |attempt| {
+            let retries = runtime.init_retries;
+            let interval = runtime.init_retry_interval;
+            runtime.ensure_network().then(
+                move |result| -> Box<Future<Item = Loop<(), u32>, Error = Error> + Send> {
+                    match result {
+                        Ok(()) => Box::new(future::ok(Loop::Break(()))),
+                        Err(_) if attempt < retries => {
+                            warn!(
+                                "Module runtime is not ready, retrying in {} seconds ({}/{})...",
+                                interval.as_secs(),
+                                attempt + 1,
+                                retries
+                            );
+                            Box::new(
+                                Delay::new(Instant::now() + interval)
+                                    .then(move |_| Ok::<_, Error>(Loop::Continue(attempt + 1))),
+                            )
                         }
-                    })
-                    .map_err(|err| {
-                        let e = Error::from_docker_error(
-                            err,
-                            ErrorKind::RuntimeOperation(RuntimeOperation::Init),
-                        );
-                        log_failure(Level::Warn, &e);
-                        e
-                    });
-                future::Either::A(fut)
-            },
-        );
+                        Err(err) => Box::new(future::err(err)),
+                    }
+                },
+            )
+        });
         let created = created.then(|result| {
             match result {
                 Ok(()) => info!("Successfully initialized module runtime"),
@@ -275,6 +925,9 @@ impl ModuleRuntime for DockerModuleRuntime {
                     .labels()
                     .cloned()
                     .unwrap_or_else(HashMap::new);
+                for (key, value) in &self.labels {
+                    labels.entry(key.clone()).or_insert_with(|| value.clone());
+                }
                 labels.insert(LABEL_KEY.to_string(), LABEL_VALUE.to_string());
 
                 debug!(
@@ -283,6 +936,24 @@ impl ModuleRuntime for DockerModuleRuntime {
                     module.config().image()
                 );
 
+                let create_options = self.apply_readonly_rootfs(
+                    self.apply_dns(
+                        self.apply_log_config(self.apply_default_resources(create_options)),
+                    ),
+                    module.config().readonly_rootfs(),
+                );
+                let create_options = self.apply_ulimits(
+                    DockerModuleRuntime::apply_devices(create_options, module.config().devices()),
+                    module.config().ulimits(),
+                );
+                let create_options = self.apply_extra_hosts(
+                    self.apply_security_opt(create_options, module.config().security_opt()),
+                    module.config().extra_hosts(),
+                );
+                let create_options = self.apply_stop_timeout(DockerModuleRuntime::apply_tmpfs(
+                    create_options,
+                    module.config().tmpfs(),
+                ));
                 let create_options = create_options
                     .with_image(module.config().image().to_string())
                     .with_env(merged_env)
@@ -290,6 +961,8 @@ impl ModuleRuntime for DockerModuleRuntime {
 
                 // Here we don't add the container to the iot edge docker network as the edge-agent is expected to do that.
                 // It contains the logic to add a container to the iot edge network only if a network is not already specified.
+                // The container is however connected to any additional networks once it has been created.
+                let runtime = self.clone();
 
                 Ok(self
                     .client
@@ -303,6 +976,9 @@ impl ModuleRuntime for DockerModuleRuntime {
                                 module.name().to_string(),
                             )),
                         )),
+                    })
+                    .and_then(move |module| {
+                        runtime.connect_networks(module.name()).map(move |_| module)
                     }))
             })
             .into_future()
@@ -364,20 +1040,10 @@ impl ModuleRuntime for DockerModuleRuntime {
             return Box::new(future::err(Error::from(err)));
         }
 
-        #[cfg_attr(
-            feature = "cargo-clippy",
-            allow(cast_possible_truncation, cast_sign_loss)
-        )]
         Box::new(
             self.client
                 .container_api()
-                .container_stop(
-                    &id,
-                    wait_before_kill.map_or(WAIT_BEFORE_KILL_SECONDS, |s| match s.as_secs() {
-                        s if s > i32::max_value() as u64 => i32::max_value(),
-                        s => s as i32,
-                    }),
-                )
+                .container_stop(&id, self.wait_before_kill_secs(wait_before_kill))
                 .then(|result| match result {
                     Ok(_) => {
                         info!("Successfully stopped module {}", id);
@@ -443,7 +1109,7 @@ impl ModuleRuntime for DockerModuleRuntime {
         Box::new(
             self.client
                 .container_api()
-                .container_restart(&id, WAIT_BEFORE_KILL_SECONDS)
+                .container_restart(&id, self.wait_before_kill_secs(None))
                 .then(|result| match result {
                     Ok(_) => {
                         info!("Successfully restarted module {}", id);
@@ -606,6 +1272,35 @@ impl ModuleRuntime for DockerModuleRuntime {
             future::join_all(n).map(|_| ())
         }))
     }
+
+    fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+        info!("Pruning unused volumes...");
+
+        // Docker never prunes a volume that is referenced by a container,
+        // whether it is running or not.
+        Box::new(
+            self.client
+                .volume_api()
+                .volume_prune("")
+                .then(|result| match result {
+                    Ok(response) => {
+                        info!(
+                            "Successfully pruned {} volumes",
+                            response.volumes_deleted().map_or(0, <[_]>::len)
+                        );
+                        Ok(())
+                    }
+                    Err(err) => {
+                        let err = Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::PruneVolumes),
+                        );
+                        log_failure(Level::Warn, &err);
+                        Err(err)
+                    }
+                }),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -705,12 +1400,299 @@ mod tests {
     use tokio;
     use url::Url;
 
-    use docker::models::ContainerCreateBody;
+    use docker::models::{
+        AuthConfig, ContainerCreateBody, HostConfig, HostConfigLogConfig, ResourcesUlimits,
+    };
     use edgelet_core::pid::Pid;
     use edgelet_core::ModuleRegistry;
 
     use error::{Error, ErrorKind};
 
+    #[test]
+    fn registry_host_is_parsed_from_image() {
+        assert_eq!("docker.io", registry_host("nginx"));
+        assert_eq!("docker.io", registry_host("nginx:latest"));
+        assert_eq!(
+            "docker.io",
+            registry_host("microsoft/azureiotedge-agent:1.0")
+        );
+        assert_eq!("docker.io", registry_host("docker.io/library/nginx"));
+        assert_eq!(
+            "mcr.microsoft.com",
+            registry_host("mcr.microsoft.com/azureiotedge-agent:1.0")
+        );
+        assert_eq!("localhost:5000", registry_host("localhost:5000/nginx"));
+        assert_eq!("localhost", registry_host("localhost/nginx"));
+    }
+
+    #[test]
+    fn registry_auth_is_selected_by_image_registry() {
+        let mut registries = BTreeMap::new();
+        registries.insert(
+            "myregistry.azurecr.io".to_string(),
+            AuthConfig::new().with_username("u1".to_string()),
+        );
+        registries.insert(
+            "localhost:5000".to_string(),
+            AuthConfig::new().with_username("u2".to_string()),
+        );
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_registries(registries);
+
+        assert_eq!(
+            Some("u1"),
+            mri.registry_auth("myregistry.azurecr.io/module1:1.0")
+                .and_then(AuthConfig::username)
+        );
+        assert_eq!(
+            Some("u2"),
+            mri.registry_auth("localhost:5000/module2")
+                .and_then(AuthConfig::username)
+        );
+        assert!(mri
+            .registry_auth("otherregistry.azurecr.io/module1")
+            .is_none());
+    }
+
+    #[test]
+    fn registry_auth_is_not_used_for_docker_hub_unless_configured() {
+        let mut registries = BTreeMap::new();
+        registries.insert(
+            "myregistry.azurecr.io".to_string(),
+            AuthConfig::new().with_username("u1".to_string()),
+        );
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_registries(registries);
+
+        assert!(mri.registry_auth("nginx:latest").is_none());
+        assert!(mri
+            .registry_auth("microsoft/azureiotedge-agent:1.0")
+            .is_none());
+    }
+
+    #[test]
+    fn log_config_is_applied_unless_module_overrides_it() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_log_config(HostConfigLogConfig::new().with__type("local".to_string()));
+
+        let create_options = mri.apply_log_config(ContainerCreateBody::new());
+        assert_eq!(
+            Some("local"),
+            create_options
+                .host_config()
+                .and_then(HostConfig::log_config)
+                .and_then(HostConfigLogConfig::_type)
+        );
+
+        let create_options = mri.apply_log_config(
+            ContainerCreateBody::new().with_host_config(
+                HostConfig::new()
+                    .with_log_config(HostConfigLogConfig::new().with__type("syslog".to_string())),
+            ),
+        );
+        assert_eq!(
+            Some("syslog"),
+            create_options
+                .host_config()
+                .and_then(HostConfig::log_config)
+                .and_then(HostConfigLogConfig::_type)
+        );
+    }
+
+    #[test]
+    fn default_resources_are_applied_unless_module_overrides_them() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_default_resources(
+                HostConfig::new()
+                    .with_memory(268_435_456)
+                    .with_cpu_shares(512),
+            );
+
+        let create_options = mri.apply_default_resources(
+            ContainerCreateBody::new().with_host_config(HostConfig::new().with_memory(1_048_576)),
+        );
+        let host_config = create_options.host_config().unwrap();
+        assert_eq!(Some(1_048_576), host_config.memory());
+        assert_eq!(Some(512), host_config.cpu_shares());
+        assert_eq!(None, host_config.nano_cp_us());
+    }
+
+    #[test]
+    fn readonly_rootfs_is_applied_unless_module_overrides_it() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_readonly_rootfs(true);
+
+        let create_options = mri.apply_readonly_rootfs(ContainerCreateBody::new(), None);
+        assert_eq!(
+            Some(&true),
+            create_options
+                .host_config()
+                .and_then(HostConfig::readonly_rootfs)
+        );
+
+        let create_options = mri.apply_readonly_rootfs(ContainerCreateBody::new(), Some(false));
+        assert!(create_options.host_config().is_none());
+
+        let create_options = mri.apply_readonly_rootfs(
+            ContainerCreateBody::new()
+                .with_host_config(HostConfig::new().with_readonly_rootfs(false)),
+            Some(true),
+        );
+        assert_eq!(
+            Some(&false),
+            create_options
+                .host_config()
+                .and_then(HostConfig::readonly_rootfs)
+        );
+    }
+
+    #[test]
+    fn stop_timeout_is_applied_unless_create_options_set_it() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_stop_timeout(Duration::from_secs(45));
+
+        let create_options = mri.apply_stop_timeout(ContainerCreateBody::new());
+        assert_eq!(Some(45), create_options.stop_timeout());
+
+        let create_options =
+            mri.apply_stop_timeout(ContainerCreateBody::new().with_stop_timeout(5));
+        assert_eq!(Some(5), create_options.stop_timeout());
+
+        assert_eq!(45, mri.wait_before_kill_secs(None));
+        assert_eq!(
+            600,
+            mri.wait_before_kill_secs(Some(Duration::from_secs(600)))
+        );
+    }
+
+    #[test]
+    fn ulimits_are_merged_by_name() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_ulimits(vec![
+                ResourcesUlimits::new()
+                    .with_name("nofile".to_string())
+                    .with_soft(1024)
+                    .with_hard(2048),
+                ResourcesUlimits::new()
+                    .with_name("memlock".to_string())
+                    .with_soft(-1)
+                    .with_hard(-1),
+            ]);
+
+        let create_options = mri.apply_ulimits(
+            ContainerCreateBody::new().with_host_config(HostConfig::new().with_ulimits(vec![
+                ResourcesUlimits::new()
+                    .with_name("memlock".to_string())
+                    .with_soft(65536)
+                    .with_hard(65536),
+            ])),
+            &[ResourcesUlimits::new()
+                .with_name("nofile".to_string())
+                .with_soft(4096)
+                .with_hard(8192)],
+        );
+        let ulimits: Vec<(Option<&str>, Option<i32>, Option<i32>)> = create_options
+            .host_config()
+            .and_then(HostConfig::ulimits)
+            .unwrap()
+            .iter()
+            .map(|ulimit| (ulimit.name(), ulimit.soft(), ulimit.hard()))
+            .collect();
+        assert_eq!(
+            vec![
+                (Some("nofile"), Some(4096), Some(8192)),
+                (Some("memlock"), Some(65536), Some(65536)),
+            ],
+            ulimits
+        );
+    }
+
+    #[test]
+    fn extra_hosts_are_merged_by_hostname() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_extra_hosts(vec![
+                "parent:10.0.0.1".to_string(),
+                "registry:10.0.0.2".to_string(),
+            ]);
+
+        let create_options = mri.apply_extra_hosts(
+            ContainerCreateBody::new().with_host_config(
+                HostConfig::new().with_extra_hosts(vec!["registry:fd00::2".to_string()]),
+            ),
+            &["parent:10.0.1.1".to_string()],
+        );
+        assert_eq!(
+            Some(
+                &[
+                    "parent:10.0.1.1".to_string(),
+                    "registry:fd00::2".to_string()
+                ][..]
+            ),
+            create_options
+                .host_config()
+                .and_then(HostConfig::extra_hosts)
+        );
+    }
+
+    #[test]
+    fn security_opt_key_is_its_kind() {
+        assert_eq!(
+            "seccomp",
+            security_opt_key("seccomp={\"defaultAction\":\"SCMP_ACT_ERRNO\"}")
+        );
+        assert_eq!("apparmor", security_opt_key("apparmor:unconfined"));
+        assert_eq!("no-new-privileges", security_opt_key("no-new-privileges"));
+        assert_eq!("label=user", security_opt_key("label=user:USER"));
+        assert_eq!("label", security_opt_key("label=disable"));
+    }
+
+    #[test]
+    fn security_opt_is_merged_by_key() {
+        let mri = DockerModuleRuntime::new(&Url::parse("http://localhost:2375/").unwrap())
+            .unwrap()
+            .with_security_opt(vec![
+                "apparmor=iotedge-module".to_string(),
+                "no-new-privileges".to_string(),
+            ]);
+
+        let create_options = mri.apply_security_opt(
+            ContainerCreateBody::new().with_host_config(
+                HostConfig::new().with_security_opt(vec!["seccomp:unconfined".to_string()]),
+            ),
+            &["apparmor=unconfined".to_string(), "seccomp={}".to_string()],
+        );
+        assert_eq!(
+            Some(
+                &[
+                    "no-new-privileges".to_string(),
+                    "apparmor=unconfined".to_string(),
+                    "seccomp:unconfined".to_string(),
+                ][..]
+            ),
+            create_options
+                .host_config()
+                .and_then(HostConfig::security_opt)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid docker API version")]
+    fn invalid_api_version_fails() {
+        let _mri = DockerModuleRuntime::new_with_api_version(
+            &Url::parse("http://localhost:2375/").unwrap(),
+            "latest",
+        )
+        .unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "URL does not have a recognized scheme")]
     fn invalid_uri_prefix_fails() {
@@ -1141,6 +2123,7 @@ mod tests {
         type Error = Error;
         type PullFuture = FutureResult<(), Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type PruneFuture = FutureResult<(), Self::Error>;
 
         fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
             unimplemented!()
@@ -1149,6 +2132,10 @@ mod tests {
         fn remove(&self, _name: &str) -> Self::RemoveFuture {
             unimplemented!()
         }
+
+        fn prune(&self) -> Self::PruneFuture {
+            unimplemented!()
+        }
     }
 
     impl ModuleRuntime for TestModuleList {
@@ -1171,6 +2158,7 @@ mod tests {
         type StopFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<CoreSystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type PruneVolumesFuture = FutureResult<(), Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             unimplemented!()
@@ -1219,5 +2207,9 @@ mod tests {
         fn remove_all(&self) -> Self::RemoveAllFuture {
             unimplemented!()
         }
+
+        fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+            unimplemented!()
+        }
     }
 }
@@ -0,0 +1,471 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use futures::future::{self, Either};
+use futures::{Future, Stream};
+use hyper::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use hyper::{Body, Request, Response, StatusCode};
+use openssl::sha::sha256;
+use serde_json::{self, Value};
+use url::Url;
+
+use edgelet_http::client::ClientImpl;
+
+use error::{Error, ErrorKind, Result};
+use runtime::{registry_host, DOCKER_HUB_REGISTRY};
+use tuf::{hex, Metadata, Root, Targets, ROOT_ROLE, SNAPSHOT_ROLE, TARGETS_ROLE, TIMESTAMP_ROLE};
+
+const DOCKER_HUB_TRUST_SERVER: &str = "https://notary.docker.io";
+const DEFAULT_TAG: &str = "latest";
+
+/// `docker trust sign` signs tags in the releases delegation, while
+/// repositories without delegations only have them in the top-level targets
+/// role. Like the docker CLI, the delegation is searched first.
+const RELEASES_ROLE: &str = "targets/releases";
+
+/// Looks up the digest an image tag was signed with, so that only signed
+/// images are pulled when content trust is enabled.
+pub trait ContentTrust: Send + Sync {
+    /// Returns the digest `image` was signed with, e.g. "sha256:...", or
+    /// `None` if the image is not signed.
+    fn signed_digest(
+        &self,
+        image: &str,
+    ) -> Box<Future<Item = Option<String>, Error = Error> + Send>;
+}
+
+/// Content trust backed by notary, the trust server docker content trust
+/// uses. The TUF metadata of an image's trust collection is verified from
+/// its root keys down to the role that signed the tag before the signed
+/// digest is used.
+///
+/// The root keys of a collection are either pinned, or trusted the first
+/// time the collection is used, like the docker CLI does. The root metadata
+/// that was trusted is kept in the trust directory, and is only replaced by
+/// newer root metadata that its root keys signed.
+pub struct NotaryTrust<C> {
+    client: Arc<C>,
+    server: Option<Url>,
+    trust_dir: PathBuf,
+    root_keys: Vec<String>,
+}
+
+impl<C: ClientImpl> NotaryTrust<C> {
+    /// Uses `server` as the trust server for all images. Otherwise, like the
+    /// docker CLI, notary.docker.io is used for Docker Hub images and the
+    /// image's registry for all other images. Trusted root metadata is kept
+    /// in `trust_dir`.
+    pub fn new(client: C, server: Option<Url>, trust_dir: PathBuf) -> Self {
+        NotaryTrust {
+            client: Arc::new(client),
+            server,
+            trust_dir,
+            root_keys: vec![],
+        }
+    }
+
+    /// Only trusts root metadata signed by these root key IDs, instead of
+    /// trusting the root keys of a collection the first time it is used.
+    pub fn with_root_keys(mut self, root_keys: Vec<String>) -> Self {
+        self.root_keys = root_keys;
+        self
+    }
+
+    fn server_for(&self, registry: &str) -> ::std::result::Result<Url, ::url::ParseError> {
+        match self.server {
+            Some(ref server) => Ok(server.clone()),
+            None if registry == DOCKER_HUB_REGISTRY => Url::parse(DOCKER_HUB_TRUST_SERVER),
+            None => Url::parse(&format!("https://{}", registry)),
+        }
+    }
+}
+
+impl<C> ContentTrust for NotaryTrust<C>
+where
+    C: 'static + ClientImpl,
+{
+    fn signed_digest(
+        &self,
+        image: &str,
+    ) -> Box<Future<Item = Option<String>, Error = Error> + Send> {
+        let (repository, tag) = split_tag(image);
+        let registry = registry_host(repository);
+        let collection = trust_collection(repository, registry);
+
+        let base = self
+            .server_for(registry)
+            .and_then(|server| server.join(&format!("v2/{}/_trust/tuf/", collection)));
+        let base = match base {
+            Ok(base) => base,
+            Err(err) => {
+                return Box::new(future::err(Error::from(
+                    err.context(ErrorKind::ContentTrust(image.to_string())),
+                )));
+            }
+        };
+
+        let repository = Arc::new(Repository {
+            client: self.client.clone(),
+            base,
+            trusted_root: self
+                .trust_dir
+                .join(format!("{}.json", hex(&sha256(collection.as_bytes())))),
+            root_keys: self.root_keys.clone(),
+            now: Utc::now(),
+        });
+        let tag = tag.to_string();
+        let image = image.to_string();
+        let digest = repository
+            .fetch(ROOT_ROLE)
+            .and_then(move |root| match root {
+                // the image is not signed if its collection has no trust data
+                None => Either::A(future::ok(None)),
+                Some(root) => Either::B(signed_digest(repository, root, tag)),
+            })
+            .map_err(move |err| Error::from(err.context(ErrorKind::ContentTrust(image))));
+
+        Box::new(digest)
+    }
+}
+
+// The metadata of a trust collection on the trust server.
+struct Repository<C> {
+    client: Arc<C>,
+    base: Url,
+    trusted_root: PathBuf,
+    root_keys: Vec<String>,
+    now: DateTime<Utc>,
+}
+
+impl<C> Repository<C>
+where
+    C: 'static + ClientImpl,
+{
+    // Verifies the root metadata the server has against the pinned root
+    // keys, or the root metadata that was trusted before, and keeps it if it
+    // is trusted for the first time or is newer.
+    fn trust_root(&self, fetched: Metadata) -> Result<Root> {
+        let store_err = |err: ::std::io::Error| {
+            Error::from(err.context(ErrorKind::TrustStore(
+                self.trusted_root.display().to_string(),
+            )))
+        };
+
+        let trusted = if self.trusted_root.exists() {
+            let bytes = fs::read(&self.trusted_root).map_err(store_err)?;
+            Some(Root::trusted(Metadata::parse(ROOT_ROLE, bytes)?)?)
+        } else {
+            None
+        };
+        let trusted_version = trusted.as_ref().map(|root| root.metadata().version());
+
+        let root = Root::verify(fetched, trusted.as_ref(), &self.root_keys, self.now)?;
+        if trusted_version.map_or(true, |version| version < root.metadata().version()) {
+            if let Some(dir) = self.trusted_root.parent() {
+                fs::create_dir_all(dir).map_err(store_err)?;
+            }
+            fs::write(&self.trusted_root, root.metadata().bytes()).map_err(store_err)?;
+        }
+
+        Ok(root)
+    }
+
+    fn fetch(&self, role: &str) -> impl Future<Item = Option<Metadata>, Error = Error> {
+        let role = role.to_string();
+        let url = match self.base.join(&format!("{}.json", role)) {
+            Ok(url) => url,
+            Err(err) => {
+                return Either::A(future::err(Error::from(
+                    err.context(ErrorKind::InvalidTrustData(role)),
+                )))
+            }
+        };
+
+        Either::B(
+            get_metadata(self.client.clone(), url).and_then(move |bytes| match bytes {
+                Some(bytes) => Metadata::parse(&role, bytes).map(Some),
+                None => Ok(None),
+            }),
+        )
+    }
+
+    // Fetches the metadata of a role that has to exist once the collection
+    // has root metadata.
+    fn fetch_required(&self, role: &str) -> impl Future<Item = Metadata, Error = Error> {
+        let role = role.to_string();
+        self.fetch(&role).and_then(move |metadata| {
+            metadata.ok_or_else(|| Error::from(ErrorKind::InvalidTrustData(role)))
+        })
+    }
+}
+
+// Walks the metadata of a collection from the root role down to the role
+// that signed `tag`, verifying each against the keys of the role before it.
+fn signed_digest<C>(
+    repository: Arc<Repository<C>>,
+    root: Metadata,
+    tag: String,
+) -> impl Future<Item = Option<String>, Error = Error>
+where
+    C: 'static + ClientImpl,
+{
+    let root = match repository.trust_root(root) {
+        Ok(root) => root,
+        Err(err) => return Either::A(future::err(err)),
+    };
+    let now = repository.now;
+
+    let snapshot_repository = repository.clone();
+    let snapshot = repository
+        .fetch_required(TIMESTAMP_ROLE)
+        .and_then(move |timestamp| {
+            root.verify_role("Timestamp", &timestamp, now)?;
+            Ok((root, timestamp))
+        })
+        .and_then(move |(root, timestamp)| {
+            snapshot_repository
+                .fetch_required(SNAPSHOT_ROLE)
+                .and_then(move |snapshot| {
+                    timestamp.verify_meta(&snapshot)?;
+                    root.verify_role("Snapshot", &snapshot, now)?;
+                    Ok((root, snapshot))
+                })
+        });
+
+    let targets_repository = repository.clone();
+    let targets = snapshot.and_then(move |(root, snapshot)| {
+        targets_repository
+            .fetch_required(TARGETS_ROLE)
+            .and_then(move |targets| {
+                snapshot.verify_meta(&targets)?;
+                root.verify_role("Targets", &targets, now)?;
+                Ok((snapshot, Targets::new(targets)))
+            })
+    });
+
+    let digest = targets.and_then(move |(snapshot, targets)| {
+        if targets.delegates_to(RELEASES_ROLE) && snapshot.lists(RELEASES_ROLE) {
+            Either::A(
+                repository
+                    .fetch_required(RELEASES_ROLE)
+                    .and_then(move |releases| {
+                        snapshot.verify_meta(&releases)?;
+                        let releases = targets.verify_delegation(releases, now)?;
+                        Ok(releases.digest(&tag).or_else(|| targets.digest(&tag)))
+                    }),
+            )
+        } else {
+            Either::B(future::ok(targets.digest(&tag)))
+        }
+    });
+
+    Either::B(digest)
+}
+
+// Splits an image into its repository and tag, e.g. "localhost:5000/nginx:1.0"
+// into "localhost:5000/nginx" and "1.0".
+pub fn split_tag(image: &str) -> (&str, &str) {
+    match image.rfind(':') {
+        Some(index) if !image[index..].contains('/') => (&image[..index], &image[index + 1..]),
+        _ => (image, DEFAULT_TAG),
+    }
+}
+
+// The trust collection (GUN) of a repository is its fully qualified name,
+// e.g. "docker.io/library/nginx" for "nginx".
+fn trust_collection(repository: &str, registry: &str) -> String {
+    if registry == DOCKER_HUB_REGISTRY {
+        let prefix = format!("{}/", DOCKER_HUB_REGISTRY);
+        let name = if repository.starts_with(&prefix) {
+            &repository[prefix.len()..]
+        } else {
+            repository
+        };
+        if name.contains('/') {
+            format!("{}{}", prefix, name)
+        } else {
+            format!("{}library/{}", prefix, name)
+        }
+    } else {
+        repository.to_string()
+    }
+}
+
+// Gets a role's metadata from the trust server, or `None` if there is none.
+// Servers that require a bearer token, like notary.docker.io, are sent an
+// anonymous one.
+fn get_metadata<C>(client: Arc<C>, url: Url) -> impl Future<Item = Option<Vec<u8>>, Error = Error>
+where
+    C: 'static + ClientImpl,
+{
+    let metadata = send(&*client, &url, None);
+    metadata.and_then(move |response| {
+        let token_url = if response.status() == StatusCode::UNAUTHORIZED {
+            response
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .and_then(|challenge| challenge.to_str().ok())
+                .and_then(token_url)
+        } else {
+            None
+        };
+
+        match token_url {
+            Some(token_url) => {
+                let token = send(&*client, &token_url, None).and_then(read_json);
+                Either::A(token.and_then(move |token| {
+                    let token = token.as_ref().and_then(|token| {
+                        token["token"]
+                            .as_str()
+                            .or_else(|| token["access_token"].as_str())
+                            .map(ToString::to_string)
+                    });
+                    match token {
+                        Some(token) => Either::A(
+                            send(&*client, &url, Some(token.as_str())).and_then(read_body),
+                        ),
+                        None => Either::B(future::err(Error::from(
+                            ErrorKind::TrustServerResponse(StatusCode::UNAUTHORIZED),
+                        ))),
+                    }
+                }))
+            }
+            None => Either::B(read_body(response)),
+        }
+    })
+}
+
+// Builds the url to request an anonymous token from, from a bearer challenge
+// like `Bearer realm="https://auth.docker.io/token",service="notary.docker.io"`.
+fn token_url(challenge: &str) -> Option<Url> {
+    if !challenge.starts_with("Bearer ") {
+        return None;
+    }
+
+    let parts: Vec<&str> = challenge["Bearer ".len()..].split('"').collect();
+    let mut realm = None;
+    let mut params = vec![];
+    for pair in parts.chunks(2).filter(|pair| pair.len() == 2) {
+        let key = pair[0].trim_matches(|c: char| c == ',' || c == '=' || c.is_whitespace());
+        if key == "realm" {
+            realm = Some(pair[1]);
+        } else {
+            params.push((key, pair[1]));
+        }
+    }
+
+    let mut url = Url::parse(realm?).ok()?;
+    url.query_pairs_mut().extend_pairs(params);
+    Some(url)
+}
+
+fn send<C: ClientImpl>(
+    client: &C,
+    url: &Url,
+    token: Option<&str>,
+) -> impl Future<Item = Response<Body>, Error = Error> {
+    let mut request = Request::get(url.as_str());
+    if let Some(token) = token {
+        request.header(AUTHORIZATION, format!("Bearer {}", token).as_str());
+    }
+
+    match request.body(Body::empty()) {
+        Ok(request) => Either::A(
+            client
+                .call(request)
+                .map_err(|err| Error::from(err.context(ErrorKind::TrustServer))),
+        ),
+        Err(err) => Either::B(future::err(Error::from(
+            err.context(ErrorKind::TrustServer),
+        ))),
+    }
+}
+
+fn read_json(response: Response<Body>) -> impl Future<Item = Option<Value>, Error = Error> {
+    read_body(response).and_then(|body| match body {
+        Some(body) => serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(|err| Error::from(err.context(ErrorKind::TrustServer))),
+        None => Ok(None),
+    })
+}
+
+fn read_body(response: Response<Body>) -> impl Future<Item = Option<Vec<u8>>, Error = Error> {
+    let status = response.status();
+    response
+        .into_body()
+        .concat2()
+        .map_err(|err| Error::from(err.context(ErrorKind::TrustServer)))
+        .and_then(move |body| match status {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(body.to_vec())),
+            status => Err(Error::from(ErrorKind::TrustServerResponse(status))),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_is_split_from_image() {
+        assert_eq!(("nginx", "latest"), split_tag("nginx"));
+        assert_eq!(("nginx", "1.15"), split_tag("nginx:1.15"));
+        assert_eq!(
+            ("localhost:5000/nginx", "latest"),
+            split_tag("localhost:5000/nginx")
+        );
+        assert_eq!(
+            ("localhost:5000/nginx", "1.15"),
+            split_tag("localhost:5000/nginx:1.15")
+        );
+    }
+
+    #[test]
+    fn trust_collection_is_fully_qualified() {
+        assert_eq!(
+            "docker.io/library/nginx",
+            trust_collection("nginx", "docker.io")
+        );
+        assert_eq!(
+            "docker.io/library/nginx",
+            trust_collection("docker.io/nginx", "docker.io")
+        );
+        assert_eq!(
+            "docker.io/microsoft/azureiotedge-agent",
+            trust_collection("microsoft/azureiotedge-agent", "docker.io")
+        );
+        assert_eq!(
+            "myregistry.azurecr.io/module1",
+            trust_collection("myregistry.azurecr.io/module1", "myregistry.azurecr.io")
+        );
+    }
+
+    #[test]
+    fn token_url_is_built_from_bearer_challenge() {
+        let url = token_url(
+            r#"Bearer realm="https://auth.docker.io/token",service="notary.docker.io",scope="repository:docker.io/library/nginx:pull""#,
+        )
+        .unwrap();
+        assert_eq!("auth.docker.io", url.host_str().unwrap());
+        assert_eq!("/token", url.path());
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            vec![
+                ("service".to_string(), "notary.docker.io".to_string()),
+                (
+                    "scope".to_string(),
+                    "repository:docker.io/library/nginx:pull".to_string()
+                ),
+            ],
+            params
+        );
+
+        assert!(token_url(r#"Basic realm="registry""#).is_none());
+    }
+}
@@ -0,0 +1,786 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Verification of the TUF metadata notary serves for a trust collection.
+//!
+//! Trust starts at the root role, whose keys are either pinned or trusted on
+//! first use. The root role's keys sign the root metadata, and the keys it
+//! lists for the timestamp, snapshot and targets roles sign their metadata.
+//! The timestamp metadata pins the hash of the snapshot metadata, which in
+//! turn pins the hashes of the targets metadata and its delegations, whose
+//! keys are listed in the targets metadata.
+//!
+//! Only the ECDSA keys docker content trust generates are supported.
+//! Signatures by other keys don't count towards a role's threshold.
+
+use std::collections::HashMap;
+
+use base64;
+use chrono::{DateTime, Utc};
+use failure::Fail;
+use openssl::bn::BigNum;
+use openssl::ecdsa::EcdsaSig;
+use openssl::pkey::{PKey, Public};
+use openssl::sha::sha256;
+use openssl::x509::X509;
+use serde_json::{self, Value};
+
+use error::{Error, ErrorKind, Result};
+
+pub const ROOT_ROLE: &str = "root";
+pub const SNAPSHOT_ROLE: &str = "snapshot";
+pub const TARGETS_ROLE: &str = "targets";
+pub const TIMESTAMP_ROLE: &str = "timestamp";
+
+/// The keys that can sign a role's metadata, and how many of them have to.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RoleKeys {
+    keyids: Vec<String>,
+    threshold: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Signature {
+    keyid: String,
+    method: String,
+    sig: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    signed: Value,
+    signatures: Vec<Signature>,
+}
+
+/// The metadata of a role, as served by the trust server.
+#[derive(Debug)]
+pub struct Metadata {
+    role: String,
+    bytes: Vec<u8>,
+    signed: Value,
+    signatures: Vec<Signature>,
+}
+
+impl Metadata {
+    pub fn parse(role: &str, bytes: Vec<u8>) -> Result<Self> {
+        let envelope: Envelope = serde_json::from_slice(&bytes).map_err(|err| {
+            Error::from(err.context(ErrorKind::InvalidTrustData(role.to_string())))
+        })?;
+        Ok(Metadata {
+            role: role.to_string(),
+            bytes,
+            signed: envelope.signed,
+            signatures: envelope.signatures,
+        })
+    }
+
+    pub fn role(&self) -> &str {
+        &self.role
+    }
+
+    /// The metadata as it was served, to be stored and parsed again later.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn version(&self) -> u64 {
+        self.signed["version"].as_u64().unwrap_or(0)
+    }
+
+    /// Checks that this is metadata of the `kind` ("Root", "Targets", ...)
+    /// its role should have, that at least the threshold of `role`'s keys
+    /// signed it, and that it has not expired at `now`.
+    pub fn verify(
+        &self,
+        kind: &str,
+        keys: &HashMap<String, Value>,
+        role: &RoleKeys,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        if self.signed["_type"].as_str() != Some(kind) {
+            return Err(self.invalid());
+        }
+        self.verify_signatures(keys, role)?;
+        self.verify_expiry(now)
+    }
+
+    fn verify_signatures(&self, keys: &HashMap<String, Value>, role: &RoleKeys) -> Result<()> {
+        let message = canonical(&self.signed);
+        let mut signers: Vec<&str> = self
+            .signatures
+            .iter()
+            .filter(|signature| role.keyids.contains(&signature.keyid))
+            .filter(|signature| {
+                keys.get(&signature.keyid).map_or(false, |key| {
+                    key_id(key).as_ref() == Some(&signature.keyid)
+                        && verify_signature(key, signature, &message)
+                })
+            })
+            .map(|signature| signature.keyid.as_str())
+            .collect();
+        signers.sort();
+        signers.dedup();
+
+        if role.threshold > 0 && signers.len() >= role.threshold {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::TrustDataSignature(
+                self.role.clone(),
+            )))
+        }
+    }
+
+    fn verify_expiry(&self, now: DateTime<Utc>) -> Result<()> {
+        let expires = self.signed["expires"]
+            .as_str()
+            .and_then(|expires| DateTime::parse_from_rfc3339(expires).ok())
+            .ok_or_else(|| self.invalid())?
+            .with_timezone(&Utc);
+        if expires > now {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::TrustDataExpired(self.role.clone())))
+        }
+    }
+
+    /// Checks that `metadata` is the version of its role this timestamp or
+    /// snapshot metadata lists, by its length and hash.
+    pub fn verify_meta(&self, metadata: &Metadata) -> Result<()> {
+        let meta = &self.signed["meta"][metadata.role()];
+        let hash = meta["hashes"]["sha256"]
+            .as_str()
+            .and_then(|hash| base64::decode(hash).ok());
+        let length_matches = meta["length"]
+            .as_u64()
+            .map_or(true, |length| length == metadata.bytes.len() as u64);
+        let version_matches = meta["version"]
+            .as_u64()
+            .map_or(true, |version| version == metadata.version());
+
+        if hash.as_ref().map(|hash| &hash[..]) == Some(&sha256(&metadata.bytes)[..])
+            && length_matches
+            && version_matches
+        {
+            Ok(())
+        } else {
+            Err(metadata.invalid())
+        }
+    }
+
+    /// Whether this timestamp or snapshot metadata lists metadata for `role`.
+    pub fn lists(&self, role: &str) -> bool {
+        self.signed["meta"][role].is_object()
+    }
+
+    fn invalid(&self) -> Error {
+        Error::from(ErrorKind::InvalidTrustData(self.role.clone()))
+    }
+}
+
+/// The root metadata of a trust collection, once it is trusted.
+#[derive(Debug)]
+pub struct Root {
+    metadata: Metadata,
+    keys: HashMap<String, Value>,
+    roles: HashMap<String, RoleKeys>,
+}
+
+impl Root {
+    /// Verifies the root metadata a trust server served. It has to be
+    /// signed by its own root keys, which have to include enough of
+    /// `pinned_keys` if any are pinned. If a root was trusted before,
+    /// `fetched` has to be the same version, or a newer one signed by the
+    /// previously trusted root keys.
+    pub fn verify(
+        fetched: Metadata,
+        trusted: Option<&Root>,
+        pinned_keys: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let root = Root::parse(fetched)?;
+        let root_keys = root.role(ROOT_ROLE)?;
+        root.metadata.verify("Root", &root.keys, &root_keys, now)?;
+
+        if !pinned_keys.is_empty() {
+            let pinned = RoleKeys {
+                keyids: root_keys
+                    .keyids
+                    .iter()
+                    .filter(|keyid| pinned_keys.contains(keyid))
+                    .cloned()
+                    .collect(),
+                threshold: root_keys.threshold,
+            };
+            root.metadata.verify_signatures(&root.keys, &pinned)?;
+        }
+
+        if let Some(trusted) = trusted {
+            if root.metadata.version() < trusted.metadata.version() {
+                return Err(Error::from(ErrorKind::TrustDataRollback(
+                    ROOT_ROLE.to_string(),
+                )));
+            } else if canonical(&root.metadata.signed) != canonical(&trusted.metadata.signed) {
+                // only the trusted root keys can replace the trusted root,
+                // and only with a newer version
+                root.metadata
+                    .verify_signatures(&trusted.keys, &trusted.role(ROOT_ROLE)?)?;
+                if root.metadata.version() == trusted.metadata.version() {
+                    return Err(root.metadata.invalid());
+                }
+            }
+        }
+
+        Ok(root)
+    }
+
+    /// Parses root metadata that was verified before, without verifying it
+    /// again.
+    pub fn trusted(metadata: Metadata) -> Result<Self> {
+        Root::parse(metadata)
+    }
+
+    fn parse(metadata: Metadata) -> Result<Self> {
+        let keys = serde_json::from_value(metadata.signed["keys"].clone());
+        let roles = serde_json::from_value(metadata.signed["roles"].clone());
+        match (keys, roles) {
+            (Ok(keys), Ok(roles)) => Ok(Root {
+                metadata,
+                keys,
+                roles,
+            }),
+            _ => Err(metadata.invalid()),
+        }
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Verifies the metadata of one of the top-level timestamp, snapshot or
+    /// targets roles against the keys this root lists for it.
+    pub fn verify_role(&self, kind: &str, metadata: &Metadata, now: DateTime<Utc>) -> Result<()> {
+        metadata.verify(kind, &self.keys, &self.role(metadata.role())?, now)
+    }
+
+    fn role(&self, name: &str) -> Result<RoleKeys> {
+        self.roles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| self.metadata.invalid())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Delegation {
+    name: String,
+    #[serde(flatten)]
+    keys: RoleKeys,
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+/// Targets metadata, of the top-level targets role or of a delegation.
+#[derive(Debug)]
+pub struct Targets {
+    metadata: Metadata,
+    paths: Option<Vec<String>>,
+}
+
+impl Targets {
+    /// Top-level targets metadata, which can sign any target.
+    pub fn new(metadata: Metadata) -> Self {
+        Targets {
+            metadata,
+            paths: None,
+        }
+    }
+
+    /// Verifies the metadata of the delegation `metadata` is for, against the
+    /// keys these targets delegate to it.
+    pub fn verify_delegation(&self, metadata: Metadata, now: DateTime<Utc>) -> Result<Targets> {
+        let delegation = self
+            .delegation(metadata.role())?
+            .ok_or_else(|| metadata.invalid())?;
+        let keys: HashMap<String, Value> =
+            serde_json::from_value(self.metadata.signed["delegations"]["keys"].clone())
+                .map_err(|_| self.metadata.invalid())?;
+        metadata.verify("Targets", &keys, &delegation.keys, now)?;
+        Ok(Targets {
+            metadata,
+            paths: Some(delegation.paths),
+        })
+    }
+
+    /// Whether these targets delegate to `role`.
+    pub fn delegates_to(&self, role: &str) -> bool {
+        match self.delegation(role) {
+            Ok(Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn delegation(&self, role: &str) -> Result<Option<Delegation>> {
+        let delegations = &self.metadata.signed["delegations"]["roles"];
+        if delegations.is_null() {
+            return Ok(None);
+        }
+        let delegations: Vec<Delegation> =
+            serde_json::from_value(delegations.clone()).map_err(|_| self.metadata.invalid())?;
+        Ok(delegations
+            .into_iter()
+            .find(|delegation| delegation.name == role))
+    }
+
+    /// The digest `tag` was signed with, e.g. "sha256:...", if these targets
+    /// sign it and are allowed to.
+    pub fn digest(&self, tag: &str) -> Option<String> {
+        let allowed = self.paths.as_ref().map_or(true, |paths| {
+            paths.iter().any(|path| tag.starts_with(path.as_str()))
+        });
+        if !allowed {
+            return None;
+        }
+
+        self.metadata.signed["targets"][tag]["hashes"]["sha256"]
+            .as_str()
+            .and_then(|hash| base64::decode(hash).ok())
+            .map(|hash| format!("sha256:{}", hex(&hash)))
+    }
+}
+
+// The ID of a key is the SHA-256 hash of its canonical JSON, without its
+// private part.
+fn key_id(key: &Value) -> Option<String> {
+    let key = json_key(key["keytype"].as_str()?, key["keyval"]["public"].as_str()?);
+    Some(hex(&sha256(&canonical(&key))))
+}
+
+fn json_key(keytype: &str, public: &str) -> Value {
+    let mut keyval = serde_json::Map::new();
+    keyval.insert("private".to_string(), Value::Null);
+    keyval.insert("public".to_string(), Value::String(public.to_string()));
+    let mut key = serde_json::Map::new();
+    key.insert("keytype".to_string(), Value::String(keytype.to_string()));
+    key.insert("keyval".to_string(), Value::Object(keyval));
+    Value::Object(key)
+}
+
+fn public_key(key: &Value) -> Option<PKey<Public>> {
+    let public = base64::decode(key["keyval"]["public"].as_str()?).ok()?;
+    match key["keytype"].as_str()? {
+        "ecdsa" => PKey::public_key_from_der(&public).ok(),
+        "ecdsa-x509" => X509::from_pem(&public)
+            .ok()
+            .and_then(|cert| cert.public_key().ok()),
+        _ => None,
+    }
+}
+
+// Notary ECDSA signatures are the r and s values of the signature over the
+// SHA-256 hash of the message, concatenated.
+fn verify_signature(key: &Value, signature: &Signature, message: &[u8]) -> bool {
+    if signature.method != "ecdsa" {
+        return false;
+    }
+
+    let verified = public_key(key)
+        .and_then(|key| key.ec_key().ok())
+        .and_then(|key| {
+            let sig = base64::decode(&signature.sig).ok()?;
+            if sig.is_empty() || sig.len() % 2 != 0 {
+                return None;
+            }
+            let (r, s) = sig.split_at(sig.len() / 2);
+            let sig = EcdsaSig::from_private_components(
+                BigNum::from_slice(r).ok()?,
+                BigNum::from_slice(s).ok()?,
+            )
+            .ok()?;
+            sig.verify(&sha256(message), &key).ok()
+        });
+    verified == Some(true)
+}
+
+/// Serializes `value` as canonical JSON, which is what notary signs: object
+/// keys sorted and no insignificant whitespace.
+pub fn canonical(value: &Value) -> Vec<u8> {
+    let mut out = vec![];
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) {
+    match *value {
+        Value::Array(ref values) => {
+            out.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(ref map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push(b'{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend(serde_json::to_vec(key).expect("strings serialize to JSON"));
+                out.push(b':');
+                write_canonical(&map[key], out);
+            }
+            out.push(b'}');
+        }
+        ref value => out.extend(serde_json::to_vec(value).expect("JSON values serialize")),
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::nid::Nid;
+    use openssl::pkey::Private;
+
+    struct TestKey {
+        key: EcKey<Private>,
+        json: Value,
+        id: String,
+    }
+
+    impl TestKey {
+        fn new() -> Self {
+            let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+            let key = EcKey::generate(&group).unwrap();
+            let public = key.public_key_to_der().unwrap();
+            let json = json_key("ecdsa", &base64::encode(&public));
+            let id = key_id(&json).unwrap();
+            TestKey { key, json, id }
+        }
+
+        fn sign(&self, signed: &Value) -> Value {
+            let sig = EcdsaSig::sign(&sha256(&canonical(signed)), &self.key).unwrap();
+            let mut raw = vec![0; 64];
+            let r = sig.r().to_vec();
+            let s = sig.s().to_vec();
+            raw[32 - r.len()..32].copy_from_slice(&r);
+            raw[64 - s.len()..].copy_from_slice(&s);
+            json!({ "keyid": self.id, "method": "ecdsa", "sig": base64::encode(&raw) })
+        }
+    }
+
+    fn metadata(role: &str, signed: &Value, signers: &[&TestKey]) -> Metadata {
+        let signatures: Vec<Value> = signers.iter().map(|key| key.sign(signed)).collect();
+        let bytes = serde_json::to_vec(&json!({ "signed": signed, "signatures": signatures }));
+        Metadata::parse(role, bytes.unwrap()).unwrap()
+    }
+
+    fn expires(now: DateTime<Utc>, days: i64) -> String {
+        (now + Duration::days(days)).to_rfc3339()
+    }
+
+    fn root_signed(root: &TestKey, targets: &TestKey, version: u64, expires: &str) -> Value {
+        json!({
+            "_type": "Root",
+            "expires": expires,
+            "version": version,
+            "keys": {
+                root.id.clone(): root.json,
+                targets.id.clone(): targets.json,
+            },
+            "roles": {
+                "root": { "keyids": [root.id], "threshold": 1 },
+                "targets": { "keyids": [targets.id], "threshold": 1 },
+            }
+        })
+    }
+
+    fn targets_signed(expires: &str) -> Value {
+        json!({
+            "_type": "Targets",
+            "expires": expires,
+            "version": 1,
+            "targets": {
+                "1.0": {
+                    "hashes": { "sha256": "q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=" },
+                    "length": 1234
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_without_whitespace() {
+        let value: Value =
+            serde_json::from_str(r#"{ "b": [1, "x"], "a": { "d": null, "c": true } }"#).unwrap();
+        assert_eq!(
+            r#"{"a":{"c":true,"d":null},"b":[1,"x"]}"#,
+            String::from_utf8(canonical(&value)).unwrap()
+        );
+    }
+
+    #[test]
+    fn target_digest_is_read_from_metadata() {
+        let now = Utc::now();
+        let targets = Targets::new(metadata(
+            TARGETS_ROLE,
+            &targets_signed(&expires(now, 30)),
+            &[],
+        ));
+
+        assert_eq!(
+            Some(format!("sha256:{}", "ab".repeat(32))),
+            targets.digest("1.0")
+        );
+        assert_eq!(None, targets.digest("2.0"));
+    }
+
+    #[test]
+    fn signed_targets_are_verified_against_root() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let root = metadata(
+            ROOT_ROLE,
+            &root_signed(&root_key, &targets_key, 1, &expires(now, 365)),
+            &[&root_key],
+        );
+        let root = Root::verify(root, None, &[], now).unwrap();
+
+        let targets = metadata(
+            TARGETS_ROLE,
+            &targets_signed(&expires(now, 30)),
+            &[&targets_key],
+        );
+        root.verify_role("Targets", &targets, now).unwrap();
+        assert_eq!(
+            Some(format!("sha256:{}", "ab".repeat(32))),
+            Targets::new(targets).digest("1.0")
+        );
+    }
+
+    #[test]
+    fn tampered_targets_are_rejected() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let root = metadata(
+            ROOT_ROLE,
+            &root_signed(&root_key, &targets_key, 1, &expires(now, 365)),
+            &[&root_key],
+        );
+        let root = Root::verify(root, None, &[], now).unwrap();
+
+        let targets = targets_signed(&expires(now, 30));
+        let mut tampered = targets.clone();
+        tampered["targets"]["1.0"]["hashes"]["sha256"] = json!(base64::encode(&[0xcd; 32]));
+        let signatures = vec![targets_key.sign(&targets)];
+        let bytes = serde_json::to_vec(&json!({ "signed": tampered, "signatures": signatures }));
+        let tampered = Metadata::parse(TARGETS_ROLE, bytes.unwrap()).unwrap();
+
+        let err = root.verify_role("Targets", &tampered, now).unwrap_err();
+        match err.kind() {
+            ErrorKind::TrustDataSignature(role) if role == TARGETS_ROLE => (),
+            kind => panic!("Expected tampered targets to be rejected. Got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn targets_signed_by_another_key_are_rejected() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let root = metadata(
+            ROOT_ROLE,
+            &root_signed(&root_key, &targets_key, 1, &expires(now, 365)),
+            &[&root_key],
+        );
+        let root = Root::verify(root, None, &[], now).unwrap();
+
+        let targets = metadata(
+            TARGETS_ROLE,
+            &targets_signed(&expires(now, 30)),
+            &[&root_key],
+        );
+        match root
+            .verify_role("Targets", &targets, now)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::TrustDataSignature(_) => (),
+            kind => panic!("Expected foreign signature to be rejected. Got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn expired_targets_are_rejected() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let root = metadata(
+            ROOT_ROLE,
+            &root_signed(&root_key, &targets_key, 1, &expires(now, 365)),
+            &[&root_key],
+        );
+        let root = Root::verify(root, None, &[], now).unwrap();
+
+        let targets = metadata(
+            TARGETS_ROLE,
+            &targets_signed(&expires(now, -1)),
+            &[&targets_key],
+        );
+        match root
+            .verify_role("Targets", &targets, now)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::TrustDataExpired(role) if role == TARGETS_ROLE => (),
+            kind => panic!("Expected expired targets to be rejected. Got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn root_must_be_signed_by_pinned_key() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let signed = root_signed(&root_key, &targets_key, 1, &expires(now, 365));
+
+        let root = metadata(ROOT_ROLE, &signed, &[&root_key]);
+        Root::verify(root, None, &[root_key.id.clone()], now).unwrap();
+
+        let root = metadata(ROOT_ROLE, &signed, &[&root_key]);
+        match Root::verify(root, None, &[TestKey::new().id], now)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::TrustDataSignature(role) if role == ROOT_ROLE => (),
+            kind => panic!(
+                "Expected root with unpinned key to be rejected. Got {:?}",
+                kind
+            ),
+        }
+    }
+
+    #[test]
+    fn root_can_only_be_replaced_by_trusted_root_keys() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let trusted = || {
+            let signed = root_signed(&root_key, &targets_key, 2, &expires(now, 365));
+            Root::trusted(metadata(ROOT_ROLE, &signed, &[&root_key])).unwrap()
+        };
+
+        // a newer root signed by the trusted root key is accepted
+        let (new_root_key, new_targets_key) = (TestKey::new(), TestKey::new());
+        let signed = root_signed(&new_root_key, &new_targets_key, 3, &expires(now, 365));
+        let rotated = metadata(ROOT_ROLE, &signed, &[&new_root_key, &root_key]);
+        Root::verify(rotated, Some(&trusted()), &[], now).unwrap();
+
+        // a root the attacker signed with their own keys is not
+        let spoofed = metadata(ROOT_ROLE, &signed, &[&new_root_key]);
+        match Root::verify(spoofed, Some(&trusted()), &[], now)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::TrustDataSignature(_) => (),
+            kind => panic!("Expected spoofed root to be rejected. Got {:?}", kind),
+        }
+
+        // and neither is an older one
+        let signed = root_signed(&root_key, &targets_key, 1, &expires(now, 365));
+        let old = metadata(ROOT_ROLE, &signed, &[&root_key]);
+        match Root::verify(old, Some(&trusted()), &[], now)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::TrustDataRollback(_) => (),
+            kind => panic!("Expected older root to be rejected. Got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn expired_root_is_rejected() {
+        let now = Utc::now();
+        let (root_key, targets_key) = (TestKey::new(), TestKey::new());
+        let signed = root_signed(&root_key, &targets_key, 1, &expires(now, -1));
+        let root = metadata(ROOT_ROLE, &signed, &[&root_key]);
+        match Root::verify(root, None, &[], now).unwrap_err().kind() {
+            ErrorKind::TrustDataExpired(role) if role == ROOT_ROLE => (),
+            kind => panic!("Expected expired root to be rejected. Got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn snapshot_pins_targets_hash() {
+        let now = Utc::now();
+        let targets_key = TestKey::new();
+        let targets = metadata(
+            TARGETS_ROLE,
+            &targets_signed(&expires(now, 30)),
+            &[&targets_key],
+        );
+        let snapshot = |hash: &[u8]| {
+            let signed = json!({
+                "_type": "Snapshot",
+                "expires": expires(now, 30),
+                "version": 1,
+                "meta": {
+                    "targets": {
+                        "hashes": { "sha256": base64::encode(hash) },
+                        "length": targets.bytes().len()
+                    }
+                }
+            });
+            metadata(SNAPSHOT_ROLE, &signed, &[&targets_key])
+        };
+
+        snapshot(&sha256(targets.bytes()))
+            .verify_meta(&targets)
+            .unwrap();
+        match snapshot(&[0; 32]).verify_meta(&targets).unwrap_err().kind() {
+            ErrorKind::InvalidTrustData(role) if role == TARGETS_ROLE => (),
+            kind => panic!(
+                "Expected targets not in snapshot to be rejected. Got {:?}",
+                kind
+            ),
+        }
+    }
+
+    #[test]
+    fn delegation_is_verified_with_delegated_keys() {
+        let now = Utc::now();
+        let (targets_key, releases_key) = (TestKey::new(), TestKey::new());
+        let mut signed = targets_signed(&expires(now, 30));
+        signed["delegations"] = json!({
+            "keys": { releases_key.id.clone(): releases_key.json },
+            "roles": [{
+                "name": "targets/releases",
+                "keyids": [releases_key.id],
+                "threshold": 1,
+                "paths": ["1."]
+            }]
+        });
+        let targets = Targets::new(metadata(TARGETS_ROLE, &signed, &[&targets_key]));
+        assert!(targets.delegates_to("targets/releases"));
+        assert!(!targets.delegates_to("targets/other"));
+
+        let mut releases_signed = targets_signed(&expires(now, 30));
+        releases_signed["targets"]["2.0"] = releases_signed["targets"]["1.0"].clone();
+        let releases = metadata("targets/releases", &releases_signed, &[&releases_key]);
+        let releases = targets.verify_delegation(releases, now).unwrap();
+        assert!(releases.digest("1.0").is_some());
+        // outside of the paths delegated to the role
+        assert_eq!(None, releases.digest("2.0"));
+
+        let forged = metadata("targets/releases", &releases_signed, &[&targets_key]);
+        match targets.verify_delegation(forged, now).unwrap_err().kind() {
+            ErrorKind::TrustDataSignature(_) => (),
+            kind => panic!("Expected forged delegation to be rejected. Got {:?}", kind),
+        }
+    }
+}
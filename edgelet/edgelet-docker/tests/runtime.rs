@@ -11,11 +11,17 @@
 #[cfg(unix)]
 extern crate base64;
 #[cfg(unix)]
+extern crate chrono;
+#[cfg(unix)]
 extern crate failure;
 extern crate futures;
 extern crate hyper;
+#[cfg(unix)]
+extern crate openssl;
 #[macro_use]
 extern crate serde_json;
+#[cfg(unix)]
+extern crate tempfile;
 extern crate tokio;
 extern crate typed_headers;
 extern crate url;
@@ -26,15 +32,31 @@ extern crate edgelet_docker;
 extern crate edgelet_test_utils;
 
 use std::collections::HashMap;
+#[cfg(unix)]
+use std::path::Path;
 use std::str;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+#[cfg(unix)]
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 #[cfg(unix)]
 use failure::Fail;
 use futures::prelude::*;
 use futures::{future, Stream};
 use hyper::{Body, Error as HyperError, Method, Request, Response};
+#[cfg(unix)]
+use openssl::ec::{EcGroup, EcKey};
+#[cfg(unix)]
+use openssl::ecdsa::EcdsaSig;
+#[cfg(unix)]
+use openssl::nid::Nid;
+#[cfg(unix)]
+use openssl::pkey::Private;
+#[cfg(unix)]
+use openssl::sha::sha256;
+#[cfg(unix)]
+use tempfile::TempDir;
 use typed_headers::{mime, ContentLength, ContentType, HeaderMapExt};
 use url::form_urlencoded::parse as parse_query;
 use url::Url;
@@ -42,11 +64,17 @@ use url::Url;
 #[cfg(unix)]
 use docker::models::AuthConfig;
 use docker::models::{
-    ContainerCreateBody, ContainerHostConfig, ContainerNetworkSettings, ContainerSummary,
-    HostConfig, HostConfigPortBindings, ImageDeleteResponseItem,
+    Container, ContainerCreateBody, ContainerHostConfig, ContainerNetworkSettings,
+    ContainerSummary, DeviceMapping, HostConfig, HostConfigLogConfig, HostConfigPortBindings,
+    ImageDeleteResponseItem, NetworkConfig, ResourcesUlimits,
+};
+use edgelet_core::{
+    HealthStatus, LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleSpec,
+    ModuleStatus,
 };
-use edgelet_core::{LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleSpec};
-use edgelet_docker::{DockerConfig, DockerModuleRuntime};
+#[cfg(unix)]
+use edgelet_docker::{ContentTrust, NotaryTrust};
+use edgelet_docker::{DockerConfig, DockerModuleRuntime, TmpfsMount};
 use edgelet_test_utils::{get_unused_tcp_port, run_tcp_server};
 
 const IMAGE_NAME: &str = "nginx:latest";
@@ -507,6 +535,502 @@ fn image_pull_with_creds_succeeds() {
     runtime.block_on(task).unwrap();
 }
 
+#[cfg(unix)]
+const SIGNED_IMAGE_DIGEST: &str =
+    "sha256:abababababababababababababababababababababababababababababababab";
+
+#[cfg(unix)]
+const SIGNED_IMAGE_HASH: &str = "q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=";
+
+#[cfg(unix)]
+struct SigningKey {
+    key: EcKey<Private>,
+    json: serde_json::Value,
+    id: String,
+}
+
+#[cfg(unix)]
+impl SigningKey {
+    fn new() -> Self {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let json = json!({
+            "keytype": "ecdsa",
+            "keyval": {
+                "private": null,
+                "public": base64::encode(&key.public_key_to_der().unwrap()),
+            }
+        });
+        let id = hex(&sha256(&serde_json::to_vec(&json).unwrap()));
+        SigningKey { key, json, id }
+    }
+
+    fn sign(&self, signed: &serde_json::Value) -> Vec<u8> {
+        self.sign_as(signed, signed)
+    }
+
+    // Signs the canonical JSON of `signed`, which is what serde_json writes
+    // for a value with sorted keys, but serves `served` with the signature.
+    fn sign_as(&self, signed: &serde_json::Value, served: &serde_json::Value) -> Vec<u8> {
+        let digest = sha256(&serde_json::to_vec(signed).unwrap());
+        let sig = EcdsaSig::sign(&digest, &self.key).unwrap();
+        let mut raw = vec![0; 64];
+        let (r, s) = (sig.r().to_vec(), sig.s().to_vec());
+        raw[32 - r.len()..32].copy_from_slice(&r);
+        raw[64 - s.len()..].copy_from_slice(&s);
+
+        serde_json::to_vec(&json!({
+            "signed": served,
+            "signatures": [{ "keyid": self.id, "method": "ecdsa", "sig": base64::encode(&raw) }],
+        }))
+        .unwrap()
+    }
+}
+
+#[cfg(unix)]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(unix)]
+fn meta(metadata: &[u8]) -> serde_json::Value {
+    json!({
+        "hashes": { "sha256": base64::encode(&sha256(metadata)) },
+        "length": metadata.len(),
+    })
+}
+
+// The keys of a notary trust collection, with the root and targets keys
+// held by the publisher and the snapshot and timestamp key by the server.
+#[cfg(unix)]
+struct TrustCollection {
+    root: SigningKey,
+    targets: SigningKey,
+    releases: SigningKey,
+    server: SigningKey,
+}
+
+#[cfg(unix)]
+impl TrustCollection {
+    fn new() -> Self {
+        TrustCollection {
+            root: SigningKey::new(),
+            targets: SigningKey::new(),
+            releases: SigningKey::new(),
+            server: SigningKey::new(),
+        }
+    }
+
+    // Builds the metadata of the collection with `targets` signed in the
+    // releases delegation, keyed by the path the trust server serves it at.
+    // The delegation serves `served_targets` instead, with the signature
+    // over `targets`, like a server that tampered with it would.
+    fn metadata(
+        &self,
+        targets: &serde_json::Value,
+        served_targets: &serde_json::Value,
+        expires: DateTime<Utc>,
+    ) -> HashMap<String, Vec<u8>> {
+        let expires = expires.to_rfc3339();
+        let root = self.root.sign(&json!({
+            "_type": "Root",
+            "consistent_snapshot": false,
+            "expires": expires,
+            "version": 1,
+            "keys": {
+                self.root.id.clone(): self.root.json,
+                self.targets.id.clone(): self.targets.json,
+                self.server.id.clone(): self.server.json,
+            },
+            "roles": {
+                "root": { "keyids": [self.root.id], "threshold": 1 },
+                "targets": { "keyids": [self.targets.id], "threshold": 1 },
+                "snapshot": { "keyids": [self.server.id], "threshold": 1 },
+                "timestamp": { "keyids": [self.server.id], "threshold": 1 },
+            },
+        }));
+        let releases = |targets: &serde_json::Value| {
+            json!({
+                "_type": "Targets",
+                "expires": expires,
+                "version": 1,
+                "targets": targets,
+            })
+        };
+        let releases = self
+            .releases
+            .sign_as(&releases(targets), &releases(served_targets));
+        let top_level_targets = self.targets.sign(&json!({
+            "_type": "Targets",
+            "expires": expires,
+            "version": 1,
+            "targets": {},
+            "delegations": {
+                "keys": { self.releases.id.clone(): self.releases.json },
+                "roles": [{
+                    "name": "targets/releases",
+                    "keyids": [self.releases.id],
+                    "threshold": 1,
+                    "paths": [""],
+                }],
+            },
+        }));
+        let snapshot = self.server.sign(&json!({
+            "_type": "Snapshot",
+            "expires": expires,
+            "version": 1,
+            "meta": {
+                "root": meta(&root),
+                "targets": meta(&top_level_targets),
+                "targets/releases": meta(&releases),
+            },
+        }));
+        let timestamp = self.server.sign(&json!({
+            "_type": "Timestamp",
+            "expires": expires,
+            "version": 1,
+            "meta": { "snapshot": meta(&snapshot) },
+        }));
+
+        let path = |role: &str| format!("/v2/docker.io/library/nginx/_trust/tuf/{}.json", role);
+        let mut metadata = HashMap::new();
+        metadata.insert(path("root"), root);
+        metadata.insert(path("targets"), top_level_targets);
+        metadata.insert(path("targets/releases"), releases);
+        metadata.insert(path("snapshot"), snapshot);
+        metadata.insert(path("timestamp"), timestamp);
+        metadata
+    }
+
+    // The metadata of a collection that signs "latest" with
+    // `SIGNED_IMAGE_DIGEST`, expiring after `days`.
+    fn signed_latest(&self, days: i64) -> HashMap<String, Vec<u8>> {
+        let targets = latest_targets(SIGNED_IMAGE_HASH);
+        self.metadata(&targets, &targets, Utc::now() + ChronoDuration::days(days))
+    }
+}
+
+#[cfg(unix)]
+fn latest_targets(hash: &str) -> serde_json::Value {
+    json!({ "latest": { "hashes": { "sha256": hash }, "length": 1234 } })
+}
+
+// Mocks a trust server that serves `metadata`, or no trust data at all.
+#[cfg(unix)]
+fn notary_trust(
+    metadata: Option<HashMap<String, Vec<u8>>>,
+    trust_dir: &Path,
+) -> NotaryTrust<impl Fn(Request<Body>) -> Result<Response<Body>, HyperError> + Send + Sync> {
+    NotaryTrust::new(
+        move |req: Request<Body>| -> Result<Response<Body>, HyperError> {
+            assert_eq!(req.method(), &Method::GET);
+            match metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get(req.uri().path()))
+            {
+                Some(body) => Ok(Response::new(body.clone().into())),
+                None => {
+                    let mut response = Response::new(Body::empty());
+                    *response.status_mut() = hyper::StatusCode::NOT_FOUND;
+                    Ok(response)
+                }
+            }
+        },
+        Some(Url::parse("https://notary.example.com").unwrap()),
+        trust_dir.to_path_buf(),
+    )
+}
+
+// Pulls nginx:latest from a registry that must not be asked for the image,
+// and checks the kind of the trust data error the pull failed with.
+#[cfg(unix)]
+fn assert_pull_rejected<T, F>(trust: T, is_expected: F)
+where
+    T: 'static + ContentTrust,
+    F: Fn(&edgelet_docker::ErrorKind) -> bool,
+{
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            panic!(
+                "Image with untrusted trust data must not be pulled, got {}",
+                req.uri()
+            );
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_content_trust(trust);
+
+    let config =
+        DockerConfig::new(IMAGE_NAME.to_string(), ContainerCreateBody::new(), None).unwrap();
+
+    let task = mri.pull(&config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+
+    // Assert
+    let err = runtime
+        .block_on(task)
+        .expect_err("Expected runtime pull method to fail for untrusted trust data.");
+
+    let cause = err
+        .cause()
+        .and_then(Fail::downcast_ref::<edgelet_docker::Error>)
+        .map(edgelet_docker::Error::kind);
+    match (err.kind(), cause) {
+        (edgelet_docker::ErrorKind::ContentTrust(name), Some(cause))
+            if name == IMAGE_NAME && is_expected(cause) => {}
+
+        _ => panic!(
+            "Expected trust data to be rejected. Got {:?} caused by {:?}",
+            err.kind(),
+            cause
+        ),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_rejects_unsigned_image() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            panic!("Unsigned image must not be pulled, got {}", req.uri());
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let trust_dir = TempDir::new().unwrap();
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_content_trust(notary_trust(None, trust_dir.path()));
+
+    let config =
+        DockerConfig::new(IMAGE_NAME.to_string(), ContainerCreateBody::new(), None).unwrap();
+
+    let task = mri.pull(&config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+
+    // Assert
+    let err = runtime
+        .block_on(task)
+        .expect_err("Expected runtime pull method to fail for an unsigned image.");
+
+    match (err.kind(), err.cause().and_then(Fail::downcast_ref)) {
+        (
+            edgelet_docker::ErrorKind::RegistryOperation(
+                edgelet_core::RegistryOperation::PullImage(name),
+            ),
+            Some(edgelet_docker::ErrorKind::ImageNotSigned(_)),
+        ) if name == IMAGE_NAME => (),
+
+        _ => panic!(
+            "Expected unsigned image to be rejected. Got {:?}",
+            err.kind()
+        ),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_pulls_signed_digest() {
+    let requests_lock: Arc<RwLock<Vec<(String, HashMap<String, String>)>>> =
+        Arc::new(RwLock::new(Vec::new()));
+    let requests_lock_cloned = requests_lock.clone();
+
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            let query: HashMap<String, String> =
+                parse_query(req.uri().query().unwrap_or("").as_bytes())
+                    .into_owned()
+                    .collect();
+            requests_lock
+                .write()
+                .unwrap()
+                .push((req.uri().path().to_string(), query));
+            Box::new(future::ok(Response::new(Body::empty())))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let trust_dir = TempDir::new().unwrap();
+    let collection = TrustCollection::new();
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_content_trust(notary_trust(
+                Some(collection.signed_latest(30)),
+                trust_dir.path(),
+            ));
+
+    let config =
+        DockerConfig::new(IMAGE_NAME.to_string(), ContainerCreateBody::new(), None).unwrap();
+
+    let task = mri.pull(&config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    // Assert
+    let signed_image = format!("nginx@{}", SIGNED_IMAGE_DIGEST);
+    let requests = requests_lock_cloned.read().unwrap();
+    assert_eq!(2, requests.len());
+
+    assert_eq!("/images/create", requests[0].0);
+    assert_eq!(Some(&signed_image), requests[0].1.get("fromImage"));
+
+    assert_eq!(format!("/images/{}/tag", signed_image), requests[1].0);
+    assert_eq!(Some(&"nginx".to_string()), requests[1].1.get("repo"));
+    assert_eq!(Some(&"latest".to_string()), requests[1].1.get("tag"));
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_rejects_tampered_signature() {
+    let trust_dir = TempDir::new().unwrap();
+    let collection = TrustCollection::new();
+
+    // the server re-signs the snapshot and timestamp with its own key, but
+    // can't re-sign the delegation that signed the tag
+    let tampered = latest_targets(&base64::encode(&[0xcd; 32]));
+    let metadata = collection.metadata(
+        &latest_targets(SIGNED_IMAGE_HASH),
+        &tampered,
+        Utc::now() + ChronoDuration::days(30),
+    );
+
+    assert_pull_rejected(
+        notary_trust(Some(metadata), trust_dir.path()),
+        |kind| match kind {
+            edgelet_docker::ErrorKind::TrustDataSignature(role) => role == "targets/releases",
+            _ => false,
+        },
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_rejects_expired_metadata() {
+    let trust_dir = TempDir::new().unwrap();
+    let metadata = TrustCollection::new().signed_latest(-1);
+
+    assert_pull_rejected(
+        notary_trust(Some(metadata), trust_dir.path()),
+        |kind| match kind {
+            edgelet_docker::ErrorKind::TrustDataExpired(_) => true,
+            _ => false,
+        },
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_rejects_root_keys_replaced_after_first_use() {
+    let trust_dir = TempDir::new().unwrap();
+
+    // the root keys are trusted the first time the collection is used...
+    let trust = notary_trust(
+        Some(TrustCollection::new().signed_latest(30)),
+        trust_dir.path(),
+    );
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let digest = runtime.block_on(trust.signed_digest(IMAGE_NAME)).unwrap();
+    assert_eq!(Some(SIGNED_IMAGE_DIGEST.to_string()), digest);
+
+    // ...so a server that later serves a collection with other keys is not
+    let spoofed = notary_trust(
+        Some(TrustCollection::new().signed_latest(30)),
+        trust_dir.path(),
+    );
+    assert_pull_rejected(spoofed, |kind| match kind {
+        edgelet_docker::ErrorKind::TrustDataSignature(role) => role == "root",
+        _ => false,
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_with_content_trust_requires_pinned_root_key() {
+    let trust_dir = TempDir::new().unwrap();
+    let collection = TrustCollection::new();
+
+    let trust = notary_trust(Some(collection.signed_latest(30)), trust_dir.path())
+        .with_root_keys(vec![collection.root.id.clone()]);
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let digest = runtime.block_on(trust.signed_digest(IMAGE_NAME)).unwrap();
+    assert_eq!(Some(SIGNED_IMAGE_DIGEST.to_string()), digest);
+
+    let trust = notary_trust(Some(collection.signed_latest(30)), trust_dir.path())
+        .with_root_keys(vec![SigningKey::new().id]);
+    assert_pull_rejected(trust, |kind| match kind {
+        edgelet_docker::ErrorKind::TrustDataSignature(role) => role == "root",
+        _ => false,
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn image_pull_times_out() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.uri().path(), "/images/create");
+
+            // the registry never responds
+            Box::new(future::empty())
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_image_pull_timeout(Duration::from_millis(100));
+
+    let config =
+        DockerConfig::new(IMAGE_NAME.to_string(), ContainerCreateBody::new(), None).unwrap();
+
+    let task = mri.pull(&config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+
+    // Assert
+    let err = runtime
+        .block_on(task)
+        .expect_err("Expected runtime pull method to time out.");
+
+    match (err.kind(), err.cause().and_then(Fail::downcast_ref)) {
+        (
+            edgelet_docker::ErrorKind::RegistryOperation(
+                edgelet_core::RegistryOperation::PullImage(name),
+            ),
+            Some(edgelet_docker::ErrorKind::ImagePullTimeout(_)),
+        ) if name == IMAGE_NAME => (),
+
+        _ => panic!("Expected image pull to time out. Got {:?}", err.kind()),
+    }
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 fn image_remove_handler(
     req: Request<Body>,
@@ -530,6 +1054,36 @@ fn image_remove_handler(
     Box::new(future::ok(response))
 }
 
+#[test]
+fn pinned_api_version_is_used_in_request_urls() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::DELETE);
+            assert_eq!(req.uri().path(), &format!("/v1.25/images/{}", IMAGE_NAME));
+
+            Box::new(future::ok(json_response(&json!([
+                { "Deleted": IMAGE_NAME }
+            ]))))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri = DockerModuleRuntime::new_with_api_version(
+        &Url::parse(&format!("http://localhost:{}/", port)).unwrap(),
+        "1.25",
+    )
+    .unwrap();
+
+    let task = ModuleRegistry::remove(&mri, IMAGE_NAME);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+}
+
 #[test]
 fn image_remove_succeeds() {
     let port = get_unused_tcp_port();
@@ -693,45 +1247,768 @@ fn container_create_succeeds() {
     runtime.block_on(task).unwrap();
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
-fn container_start_handler(
-    req: Request<Body>,
-) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
-    assert_eq!(req.method(), &Method::POST);
-    assert_eq!(req.uri().path(), "/containers/m1/start");
-
-    Box::new(future::ok(Response::new(Body::empty())))
-}
-
 #[test]
-fn container_start_succeeds() {
+fn container_create_connects_to_additional_networks() {
+    let connected_lock: Arc<RwLock<Vec<(String, String)>>> = Arc::new(RwLock::new(Vec::new()));
+    let connected_lock_cloned = connected_lock.clone();
+
     let port = get_unused_tcp_port();
-    let server = run_tcp_server("127.0.0.1", port, container_start_handler)
-        .map_err(|err| eprintln!("{}", err));
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            let path = req.uri().path().to_string();
+            if path == "/containers/create" {
+                Box::new(future::ok(json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))))
+            } else {
+                let network = path
+                    .trim_left_matches("/networks/")
+                    .trim_right_matches("/connect")
+                    .to_string();
+                let connected_lock = connected_lock.clone();
+                Box::new(req.into_body().concat2().map(move |body| {
+                    let container: Container = serde_json::from_slice(&body).unwrap();
+                    connected_lock
+                        .write()
+                        .unwrap()
+                        .push((network, container.container().unwrap().to_string()));
+                    Response::new(Body::empty())
+                }))
+            }
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
 
     let mri =
         DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
-            .unwrap();
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_additional_networks(vec!["fieldbus".to_string(), "external".to_string()]);
 
-    let task = mri.start("m1");
+    //act
+    let task = mri.create(module_config);
 
     let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
     runtime.spawn(server);
     runtime.block_on(task).unwrap();
-}
-
-#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
-fn container_stop_handler(
-    req: Request<Body>,
-) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
-    assert_eq!(req.method(), &Method::POST);
-    assert_eq!(req.uri().path(), "/containers/m1/stop");
 
-    Box::new(future::ok(Response::new(Body::empty())))
+    //assert
+    let mut connected = connected_lock_cloned.read().unwrap().clone();
+    connected.sort();
+    assert_eq!(
+        vec![
+            ("external".to_string(), "m1".to_string()),
+            ("fieldbus".to_string(), "m1".to_string()),
+        ],
+        connected
+    );
 }
 
 #[test]
-fn container_stop_succeeds() {
+fn container_create_applies_default_log_config() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mut log_options = HashMap::new();
+    log_options.insert("max-size".to_string(), "10m".to_string());
+    log_options.insert("max-file".to_string(), "3".to_string());
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_log_config(
+                HostConfigLogConfig::new()
+                    .with__type("json-file".to_string())
+                    .with_config(log_options.clone()),
+            );
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let log_config = created
+        .as_ref()
+        .and_then(ContainerCreateBody::host_config)
+        .and_then(HostConfig::log_config)
+        .expect("container was created without a log config");
+    assert_eq!(Some("json-file"), log_config._type());
+    assert_eq!(Some(&log_options), log_config.config());
+}
+
+#[test]
+fn container_create_applies_default_resources() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let create_options =
+        ContainerCreateBody::new().with_host_config(HostConfig::new().with_memory(1_048_576));
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), create_options, None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_default_resources(
+                HostConfig::new()
+                    .with_memory(268_435_456)
+                    .with_memory_swap(536_870_912)
+                    .with_cpu_shares(512)
+                    .with_nano_cp_us(500_000_000),
+            );
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let host_config = created
+        .as_ref()
+        .and_then(ContainerCreateBody::host_config)
+        .expect("container was created without a host config");
+    assert_eq!(Some(1_048_576), host_config.memory());
+    assert_eq!(Some(536_870_912), host_config.memory_swap());
+    assert_eq!(Some(512), host_config.cpu_shares());
+    assert_eq!(Some(500_000_000), host_config.nano_cp_us());
+}
+
+#[test]
+fn container_create_adds_configured_labels() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mut labels = HashMap::new();
+    labels.insert("com.example.device-id".to_string(), "device1".to_string());
+    labels.insert(
+        "net.azure-devices.edge.owner".to_string(),
+        "iotedged".to_string(),
+    );
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_labels(labels);
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let labels = created
+        .as_ref()
+        .and_then(ContainerCreateBody::labels)
+        .expect("container was created without labels");
+    assert_eq!(
+        Some(&"device1".to_string()),
+        labels.get("com.example.device-id")
+    );
+    assert_eq!(
+        Some(&"Microsoft.Azure.Devices.Edge.Agent".to_string()),
+        labels.get("net.azure-devices.edge.owner")
+    );
+}
+
+#[test]
+fn container_create_sets_readonly_rootfs_when_enabled() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_readonly_rootfs(true),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string());
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    assert_eq!(
+        Some(&true),
+        created
+            .as_ref()
+            .and_then(ContainerCreateBody::host_config)
+            .and_then(HostConfig::readonly_rootfs)
+    );
+}
+
+#[test]
+fn container_create_applies_configured_dns() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_dns(vec!["10.0.0.2".to_string(), "10.0.0.3".to_string()]);
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    assert_eq!(
+        Some(&["10.0.0.2".to_string(), "10.0.0.3".to_string()][..]),
+        created
+            .as_ref()
+            .and_then(ContainerCreateBody::host_config)
+            .and_then(HostConfig::dns)
+    );
+}
+
+#[test]
+fn container_create_maps_configured_devices() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_devices(vec![DeviceMapping::new()
+                .with_path_on_host("/dev/ttyUSB0".to_string())
+                .with_path_in_container("/dev/ttyS0".to_string())
+                .with_cgroup_permissions("rwm".to_string())]),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string());
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let devices = created
+        .as_ref()
+        .and_then(ContainerCreateBody::host_config)
+        .and_then(HostConfig::devices)
+        .expect("container was created without devices");
+    assert_eq!(1, devices.len());
+    assert_eq!(Some("/dev/ttyUSB0"), devices[0].path_on_host());
+    assert_eq!(Some("/dev/ttyS0"), devices[0].path_in_container());
+    assert_eq!(Some("rwm"), devices[0].cgroup_permissions());
+}
+
+#[test]
+fn container_create_applies_configured_ulimits() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_ulimits(vec![ResourcesUlimits::new()
+                .with_name("memlock".to_string())
+                .with_soft(-1)
+                .with_hard(-1)]),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_ulimits(vec![ResourcesUlimits::new()
+                .with_name("nofile".to_string())
+                .with_soft(65536)
+                .with_hard(65536)]);
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let ulimits: Vec<(Option<&str>, Option<i32>, Option<i32>)> = created
+        .as_ref()
+        .and_then(ContainerCreateBody::host_config)
+        .and_then(HostConfig::ulimits)
+        .expect("container was created without ulimits")
+        .iter()
+        .map(|ulimit| (ulimit.name(), ulimit.soft(), ulimit.hard()))
+        .collect();
+    assert_eq!(
+        vec![
+            (Some("nofile"), Some(65536), Some(65536)),
+            (Some("memlock"), Some(-1), Some(-1)),
+        ],
+        ulimits
+    );
+}
+
+#[test]
+fn container_create_applies_configured_security_opt() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_security_opt(vec!["apparmor=iotedge-module".to_string()]),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_security_opt(vec![
+                "apparmor=docker-default".to_string(),
+                "no-new-privileges".to_string(),
+            ]);
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    assert_eq!(
+        Some(
+            &[
+                "no-new-privileges".to_string(),
+                "apparmor=iotedge-module".to_string(),
+            ][..]
+        ),
+        created
+            .as_ref()
+            .and_then(ContainerCreateBody::host_config)
+            .and_then(HostConfig::security_opt)
+    );
+}
+
+#[test]
+fn container_create_adds_configured_extra_hosts() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_extra_hosts(vec!["opcserver:192.168.1.20".to_string()]),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string())
+            .with_extra_hosts(vec!["parent-edge:10.0.0.1".to_string()]);
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    assert_eq!(
+        Some(
+            &[
+                "parent-edge:10.0.0.1".to_string(),
+                "opcserver:192.168.1.20".to_string(),
+            ][..]
+        ),
+        created
+            .as_ref()
+            .and_then(ContainerCreateBody::host_config)
+            .and_then(HostConfig::extra_hosts)
+    );
+}
+
+#[test]
+fn container_create_adds_configured_tmpfs_mounts() {
+    let created_lock: Arc<RwLock<Option<ContainerCreateBody>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            assert_eq!(req.method(), &Method::POST);
+            assert_eq!(req.uri().path(), "/containers/create");
+            let created_lock = created_lock.clone();
+            Box::new(req.into_body().concat2().map(move |body| {
+                let create_options: ContainerCreateBody = serde_json::from_slice(&body).unwrap();
+                *created_lock.write().unwrap() = Some(create_options);
+                json_response(&json!({
+                    "Id": "12345",
+                    "Warnings": []
+                }))
+            }))
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_tmpfs(vec![TmpfsMount::new("/scratch".to_string())
+                .with_size("64m".to_string())
+                .with_mode("1777".to_string())]),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("edge-network".to_string());
+
+    //act
+    let task = mri.create(module_config);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let tmpfs = created
+        .as_ref()
+        .and_then(ContainerCreateBody::host_config)
+        .and_then(HostConfig::tmpfs)
+        .expect("container was created without tmpfs mounts");
+    assert_eq!(1, tmpfs.len());
+    assert_eq!(
+        Some(&"size=64m,mode=1777".to_string()),
+        tmpfs.get("/scratch")
+    );
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn container_start_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    assert_eq!(req.method(), &Method::POST);
+    assert_eq!(req.uri().path(), "/containers/m1/start");
+
+    Box::new(future::ok(Response::new(Body::empty())))
+}
+
+#[test]
+fn container_start_succeeds() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server("127.0.0.1", port, container_start_handler)
+        .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let task = mri.start("m1");
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn container_stop_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    assert_eq!(req.method(), &Method::POST);
+    assert_eq!(req.uri().path(), "/containers/m1/stop");
+
+    Box::new(future::ok(Response::new(Body::empty())))
+}
+
+#[test]
+fn container_stop_succeeds() {
     let port = get_unused_tcp_port();
     let server = run_tcp_server("127.0.0.1", port, container_stop_handler)
         .map_err(|err| eprintln!("{}", err));
@@ -775,6 +2052,39 @@ fn container_stop_with_timeout_succeeds() {
     runtime.block_on(task).unwrap();
 }
 
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn container_stop_with_configured_timeout_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    assert_eq!(req.method(), &Method::POST);
+    assert_eq!(req.uri().path(), "/containers/m1/stop");
+    assert_eq!(req.uri().query().unwrap(), "t=45");
+
+    Box::new(future::ok(Response::new(Body::empty())))
+}
+
+#[test]
+fn container_stop_uses_configured_stop_timeout() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        container_stop_with_configured_timeout_handler,
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_stop_timeout(Duration::from_secs(45));
+
+    let task = mri.stop("m1", None);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 fn container_remove_handler(
     req: Request<Body>,
@@ -940,6 +2250,73 @@ fn container_list_succeeds() {
     }
 }
 
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn container_health_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    assert_eq!(req.method(), &Method::GET);
+
+    let response = match req.uri().path() {
+        "/containers/json" => json_response(&json!([{
+            "Id": "m1",
+            "Names": ["/m1"],
+            "Image": "nginx:latest",
+            "ImageID": "img1",
+            "Command": "",
+            "Created": 10,
+            "Ports": [],
+            "Labels": {},
+            "State": "running",
+            "Status": "Up 10 minutes (unhealthy)",
+            "HostConfig": { "NetworkMode": "" },
+            "NetworkSettings": { "Networks": {} },
+            "Mounts": [],
+        }])),
+        "/containers/m1/json" => json_response(&json!({
+            "Id": "img1",
+            "State": {
+                "Status": "running",
+                "Running": true,
+                "Pid": 1234,
+                "ExitCode": 0,
+                "StartedAt": "2018-10-01T10:00:00.000000000Z",
+                "FinishedAt": "0001-01-01T00:00:00Z",
+                "Health": {
+                    "Status": "unhealthy",
+                    "FailingStreak": 3,
+                    "Log": [],
+                },
+            },
+        })),
+        path => panic!("unexpected request to {}", path),
+    };
+
+    Box::new(future::ok(response))
+}
+
+#[test]
+fn container_health_is_mapped_to_module_state() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server("127.0.0.1", port, container_health_handler)
+        .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let task = mri.list_with_details().collect();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let modules = runtime.block_on(task).unwrap();
+
+    assert_eq!(1, modules.len());
+    let (module, state) = &modules[0];
+    assert_eq!("m1", module.name());
+    assert_eq!(&ModuleStatus::Running, state.status());
+    assert_eq!(Some(HealthStatus::Unhealthy), state.health());
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 fn container_logs_handler(
     req: Request<Body>,
@@ -1171,6 +2548,318 @@ fn runtime_init_network_exist_do_not_create() {
     assert_eq!(false, *create_got_called_lock_cloned.read().unwrap());
 }
 
+#[test]
+fn runtime_init_creates_network_with_configured_driver() {
+    let created_lock: Arc<RwLock<Option<NetworkConfig>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            match *req.method() {
+                Method::GET => {
+                    assert_eq!(req.uri().path(), "/networks");
+                    Box::new(future::ok(json_response(&json!([]))))
+                }
+                Method::POST => {
+                    assert_eq!(req.uri().path(), "/networks/create");
+                    let created_lock = created_lock.clone();
+                    Box::new(req.into_body().concat2().map(move |body| {
+                        let config: NetworkConfig = serde_json::from_slice(&body).unwrap();
+                        *created_lock.write().unwrap() = Some(config);
+                        json_response(&json!({
+                            "Id": "12345",
+                            "Warnings": ""
+                        }))
+                    }))
+                }
+                _ => panic!("Method is not a get neither a post."),
+            }
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("azure-iot-edge".to_string())
+            .with_network_driver("macvlan".to_string());
+
+    //act
+    let task = mri.init();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let created = created.as_ref().expect("network was not created");
+    assert_eq!("azure-iot-edge", *created.name());
+    assert_eq!(Some("macvlan"), created.driver());
+}
+
+#[test]
+fn runtime_init_creates_ipv6_network_with_configured_subnet() {
+    let created_lock: Arc<RwLock<Option<NetworkConfig>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            match *req.method() {
+                Method::GET => {
+                    assert_eq!(req.uri().path(), "/networks");
+                    Box::new(future::ok(json_response(&json!([]))))
+                }
+                Method::POST => {
+                    assert_eq!(req.uri().path(), "/networks/create");
+                    let created_lock = created_lock.clone();
+                    Box::new(req.into_body().concat2().map(move |body| {
+                        let config: NetworkConfig = serde_json::from_slice(&body).unwrap();
+                        *created_lock.write().unwrap() = Some(config);
+                        json_response(&json!({
+                            "Id": "12345",
+                            "Warnings": ""
+                        }))
+                    }))
+                }
+                _ => panic!("Method is not a get neither a post."),
+            }
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("azure-iot-edge".to_string())
+            .with_network_ipv6_subnet("fd00:1::/64".to_string());
+
+    //act
+    let task = mri.init();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let created = created.as_ref().expect("network was not created");
+    assert_eq!("azure-iot-edge", *created.name());
+    assert_eq!(Some(&true), created.enable_i_pv6());
+    let ipam_config = created.IPAM().and_then(|ipam| ipam.config()).unwrap();
+    assert_eq!(1, ipam_config.len());
+    assert_eq!(
+        Some(&"fd00:1::/64".to_string()),
+        ipam_config[0].get("Subnet")
+    );
+}
+
+#[test]
+fn runtime_init_creates_network_with_configured_subnet() {
+    let created_lock: Arc<RwLock<Option<NetworkConfig>>> = Arc::new(RwLock::new(None));
+    let created_lock_cloned = created_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server(
+        "127.0.0.1",
+        port,
+        move |req: Request<Body>| -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+            match *req.method() {
+                Method::GET => {
+                    assert_eq!(req.uri().path(), "/networks");
+                    Box::new(future::ok(json_response(&json!([]))))
+                }
+                Method::POST => {
+                    assert_eq!(req.uri().path(), "/networks/create");
+                    let created_lock = created_lock.clone();
+                    Box::new(req.into_body().concat2().map(move |body| {
+                        let config: NetworkConfig = serde_json::from_slice(&body).unwrap();
+                        *created_lock.write().unwrap() = Some(config);
+                        json_response(&json!({
+                            "Id": "12345",
+                            "Warnings": ""
+                        }))
+                    }))
+                }
+                _ => panic!("Method is not a get neither a post."),
+            }
+        },
+    )
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("azure-iot-edge".to_string())
+            .with_network_subnet("172.20.0.0/16".to_string(), Some("172.20.0.1".to_string()));
+
+    //act
+    let task = mri.init();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    let created = created_lock_cloned.read().unwrap();
+    let created = created.as_ref().expect("network was not created");
+    assert_eq!("azure-iot-edge", *created.name());
+    assert_eq!(None, created.enable_i_pv6());
+    let ipam_config = created.IPAM().and_then(|ipam| ipam.config()).unwrap();
+    assert_eq!(1, ipam_config.len());
+    assert_eq!(
+        Some(&"172.20.0.0/16".to_string()),
+        ipam_config[0].get("Subnet")
+    );
+    assert_eq!(
+        Some(&"172.20.0.1".to_string()),
+        ipam_config[0].get("Gateway")
+    );
+}
+
+fn json_response(body: &serde_json::Value) -> Response<Body> {
+    let body = body.to_string();
+    let body_len = body.len();
+
+    let mut response = Response::new(body.into());
+    response
+        .headers_mut()
+        .typed_insert(&ContentLength(body_len as u64));
+    response
+        .headers_mut()
+        .typed_insert(&ContentType(mime::APPLICATION_JSON));
+    response
+}
+
+#[test]
+fn runtime_init_waits_for_network_to_become_available() {
+    let list_calls_lock = Arc::new(RwLock::new(0));
+    let list_calls_lock_cloned = list_calls_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        assert_eq!(Method::GET, *req.method());
+        assert_eq!(req.uri().path(), "/networks");
+
+        let mut list_calls_w = list_calls_lock.write().unwrap();
+        *list_calls_w += 1;
+
+        // the network is not available for the first two attempts
+        let (response, status) = if *list_calls_w <= 2 {
+            (
+                json!({ "message": "daemon is not ready" }).to_string(),
+                hyper::StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        } else {
+            (
+                json!([{
+                    "Name": "azure-iot-edge",
+                    "Id": "8e3209d08ed5e73d1c9c8e7580ddad232b6dceb5bf0c6d74cadbed75422eef0e",
+                    "Created": "0001-01-01T00:00:00Z",
+                    "Scope": "local",
+                    "Driver": "bridge",
+                    "EnableIPv6": false,
+                    "Internal": false,
+                    "Attachable": false,
+                    "Ingress": false,
+                    "IPAM": {
+                        "Driver": "bridge",
+                        "Config": []
+                    },
+                    "Containers": {},
+                    "Options": {}
+                }])
+                .to_string(),
+                hyper::StatusCode::OK,
+            )
+        };
+        let response_len = response.len();
+
+        let mut response = Response::new(response.into());
+        response
+            .headers_mut()
+            .typed_insert(&ContentLength(response_len as u64));
+        response
+            .headers_mut()
+            .typed_insert(&ContentType(mime::APPLICATION_JSON));
+        *response.status_mut() = status;
+        Box::new(future::ok(response))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("azure-iot-edge".to_string())
+            .with_init_retries(5, Duration::from_millis(10));
+
+    //act
+    let task = mri.init();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(task).unwrap();
+
+    //assert
+    assert_eq!(3, *list_calls_lock_cloned.read().unwrap());
+}
+
+#[test]
+fn runtime_init_fails_when_retries_are_exhausted() {
+    let list_calls_lock = Arc::new(RwLock::new(0));
+    let list_calls_lock_cloned = list_calls_lock.clone();
+
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        assert_eq!(req.uri().path(), "/networks");
+
+        let mut list_calls_w = list_calls_lock.write().unwrap();
+        *list_calls_w += 1;
+
+        let response = json!({ "message": "daemon is not ready" }).to_string();
+        let response_len = response.len();
+
+        let mut response = Response::new(response.into());
+        response
+            .headers_mut()
+            .typed_insert(&ContentLength(response_len as u64));
+        response
+            .headers_mut()
+            .typed_insert(&ContentType(mime::APPLICATION_JSON));
+        *response.status_mut() = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+        Box::new(future::ok(response))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_network_id("azure-iot-edge".to_string())
+            .with_init_retries(2, Duration::from_millis(10));
+
+    //act
+    let task = mri.init();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let result = runtime.block_on(task);
+
+    //assert
+    assert!(result.is_err());
+    assert_eq!(3, *list_calls_lock_cloned.read().unwrap());
+}
+
 #[test]
 fn runtime_system_info_succeed() {
     let system_info_got_called_lock = Arc::new(RwLock::new(false));
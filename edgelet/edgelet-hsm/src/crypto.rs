@@ -6,11 +6,14 @@ use std::sync::{Arc, Mutex};
 use failure::Fail;
 
 use edgelet_core::{
-    Certificate as CoreCertificate, CertificateProperties as CoreCertificateProperties,
+    BackupMasterEncryptionKey as CoreBackupMasterEncryptionKey, Certificate as CoreCertificate,
+    CertificateProperties as CoreCertificateProperties, CheckHsmHealth as CoreCheckHsmHealth,
     CreateCertificate as CoreCreateCertificate, Decrypt as CoreDecrypt, Encrypt as CoreEncrypt,
-    Error as CoreError, ErrorKind as CoreErrorKind, GetTrustBundle as CoreGetTrustBundle,
-    KeyBytes as CoreKeyBytes, MasterEncryptionKey as CoreMasterEncryptionKey,
-    PrivateKey as CorePrivateKey,
+    EncryptedData as CoreEncryptedData, Error as CoreError, ErrorKind as CoreErrorKind,
+    GetTrustBundle as CoreGetTrustBundle, KeyBytes as CoreKeyBytes,
+    MasterEncryptionKey as CoreMasterEncryptionKey,
+    MasterEncryptionKeySlots as CoreMasterEncryptionKeySlots, PrivateKey as CorePrivateKey,
+    RotateMasterEncryptionKey as CoreRotateMasterEncryptionKey,
 };
 
 use certificate_properties::convert_properties;
@@ -22,9 +25,16 @@ pub use hsm::{
 use hsm::{
     CreateCertificate as HsmCreateCertificate,
     CreateMasterEncryptionKey as HsmCreateMasterEncryptionKey, Crypto as HsmCrypto,
-    DestroyMasterEncryptionKey as HsmDestroyMasterEncryptionKey,
+    DestroyMasterEncryptionKey as HsmDestroyMasterEncryptionKey, Error as HsmError,
+    ExportMasterEncryptionKey as HsmExportMasterEncryptionKey,
+    ImportMasterEncryptionKey as HsmImportMasterEncryptionKey, MakeRandom as HsmMakeRandom,
+    ManageMasterEncryptionKeySlots as HsmManageMasterEncryptionKeySlots,
 };
 
+/// Number of master encryption key slots of the HSM library, see
+/// `EDGELET_ENC_KEY_SLOT_COUNT`.
+const MASTER_ENCRYPTION_KEY_SLOT_COUNT: usize = 2;
+
 /// The TPM Key Store.
 /// Activate a private key, and then you can use that key to sign data.
 #[derive(Clone)]
@@ -65,6 +75,136 @@ impl CoreMasterEncryptionKey for Crypto {
     }
 }
 
+impl CoreRotateMasterEncryptionKey for Crypto {
+    fn rotate_key(&self, data: &[CoreEncryptedData]) -> Result<Vec<CoreEncryptedData>, CoreError> {
+        // The lock is held throughout, so that nothing is encrypted with the
+        // old key after its data has been decrypted.
+        let crypto = self.crypto.lock().expect("Lock on crypto structure failed");
+
+        let plaintexts = data
+            .iter()
+            .map(|item| {
+                crypto.decrypt(
+                    item.client_id(),
+                    item.ciphertext(),
+                    item.initialization_vector(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))?;
+
+        let old_slot = crypto
+            .get_active_master_encryption_key_slot()
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))?;
+        let new_slot = (old_slot + 1) % MASTER_ENCRYPTION_KEY_SLOT_COUNT;
+
+        // The new key is created next to the old one, so that the old key,
+        // and the data the caller still holds encrypted with it, survives
+        // until everything is encrypted with the new key.
+        crypto
+            .create_master_encryption_key_in_slot(new_slot)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))?;
+
+        crypto
+            .set_active_master_encryption_key_slot(new_slot)
+            .and_then(|_| {
+                data.iter()
+                    .zip(plaintexts)
+                    .map(|(item, plaintext)| -> Result<_, HsmError> {
+                        let ciphertext = crypto.encrypt(
+                            item.client_id(),
+                            plaintext.as_ref(),
+                            item.initialization_vector(),
+                        )?;
+                        Ok(CoreEncryptedData::new(
+                            item.client_id().to_vec(),
+                            ciphertext.as_ref().to_vec(),
+                            item.initialization_vector().to_vec(),
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .and_then(|rotated| {
+                crypto
+                    .destroy_master_encryption_key_in_slot(old_slot)
+                    .map(|_| rotated)
+            })
+            .or_else(|err| {
+                // Go back to the old key, which still decrypts everything the
+                // caller holds. Errors are ignored since the original error
+                // is the one worth reporting.
+                let _ = crypto
+                    .set_active_master_encryption_key_slot(old_slot)
+                    .and_then(|_| crypto.destroy_master_encryption_key_in_slot(new_slot));
+                Err(err)
+            })
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+}
+
+impl CoreBackupMasterEncryptionKey for Crypto {
+    fn export_key(&self, passphrase: &str) -> Result<Vec<u8>, CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .export_master_encryption_key(passphrase)
+            .map(|wrapped_key| wrapped_key.as_ref().to_vec())
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn import_key(&self, passphrase: &str, backup: &[u8]) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .import_master_encryption_key(passphrase, backup)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+}
+
+impl CoreMasterEncryptionKeySlots for Crypto {
+    fn create_key_in_slot(&self, slot: usize) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .create_master_encryption_key_in_slot(slot)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn destroy_key_in_slot(&self, slot: usize) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .destroy_master_encryption_key_in_slot(slot)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn set_active_key_slot(&self, slot: usize) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .set_active_master_encryption_key_slot(slot)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn active_key_slot(&self) -> Result<usize, CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .get_active_master_encryption_key_slot()
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+}
+
 impl CoreCreateCertificate for Crypto {
     type Certificate = Certificate;
 
@@ -143,6 +283,20 @@ impl CoreGetTrustBundle for Crypto {
     }
 }
 
+impl CoreCheckHsmHealth for Crypto {
+    fn check_hsm_health(&self) -> Result<(), CoreError> {
+        // Asking for a single random byte is about the cheapest operation
+        // that still has to go through to the HSM.
+        let mut buffer = [0_u8; 1];
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .get_random_bytes(&mut buffer)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::HsmHealthCheck)))
+    }
+}
+
 #[derive(Debug)]
 pub struct Certificate(HsmCertificate);
 
@@ -1,20 +1,59 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 use bytes::Bytes;
 use failure::Fail;
 
 use edgelet_core::crypto::{
-    Activate, KeyIdentity, KeyStore as CoreKeyStore, Sign, SignatureAlgorithm,
+    derivation_data, Activate, KeyIdentity, KeyStore as CoreKeyStore, Sign, SignatureAlgorithm,
 };
 use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
-use hsm::{ManageTpmKeys, SignWithTpm, Tpm, TpmDigest};
+use hsm::{
+    Error as HsmError, ErrorKind as HsmErrorKind, ManageTpmKeys, SignWithTpm, Tpm, TpmDigest,
+};
 
 pub use error::{Error, ErrorKind};
 
 const ROOT_KEY_NAME: &str = "primary";
 
+/// This is the number of times a TPM operation is attempted before its
+/// failure is returned.
+const TPM_ATTEMPTS: u32 = 3;
+
+/// This is the time to wait before the first retry of a TPM operation, which
+/// doubles with every further retry.
+const TPM_RETRY_DELAY_MS: u64 = 100;
+
+/// Runs a TPM operation, retrying it when the TPM was too busy to run it.
+/// Other failures are returned right away. The TPM is only locked while the
+/// operation runs, so other callers can use it while a retry is pending.
+fn with_retries<H, T, F>(hsm: &Mutex<H>, mut op: F) -> Result<T, HsmError>
+where
+    F: FnMut(&H) -> Result<T, HsmError>,
+{
+    let mut attempt = 1;
+    loop {
+        let result = {
+            let hsm = hsm.lock().expect("Lock on KeyStore TPM failed");
+            op(&hsm)
+        };
+        match result {
+            Err(ref err) if attempt < TPM_ATTEMPTS && is_transient(err) => {
+                thread::sleep(Duration::from_millis(TPM_RETRY_DELAY_MS << (attempt - 1)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient(err: &HsmError) -> bool {
+    err.kind() == HsmErrorKind::TpmRetry
+}
+
 /// Represents a key which can sign data.
 #[derive(Clone, Debug)]
 pub struct TpmKey {
@@ -44,10 +83,7 @@ impl TpmKeyStore {
 
     /// Activate and store a private key in the TPM.
     pub fn activate_key(&self, key_value: &Bytes) -> Result<(), Error> {
-        self.tpm
-            .lock()
-            .expect("Lock on KeyStore TPM failed")
-            .activate_identity_key(key_value)?;
+        with_retries(&self.tpm, |tpm| tpm.activate_identity_key(key_value))?;
         Ok(())
     }
 
@@ -111,37 +147,128 @@ impl Sign for TpmKey {
     /// Sign data with this key.
     /// If an identity was given, we will derive a new key from the identity and sign the data.
     /// If an identity was not given, we will sign the data with the stored key.
+    /// The TPM only signs with HMAC-SHA256.
     fn sign(
         &self,
-        _signature_algorithm: SignatureAlgorithm,
+        signature_algorithm: SignatureAlgorithm,
         data: &[u8],
     ) -> Result<Self::Signature, CoreError> {
+        if signature_algorithm != SignatureAlgorithm::HMACSHA256 {
+            return Err(CoreError::from(CoreErrorKind::SignUnsupportedAlgorithm(
+                signature_algorithm,
+            )));
+        }
+
         match self.identity {
-            KeyIdentity::Device => self
-                .tpm
-                .lock()
-                .expect("Lock failed")
-                .sign_with_identity(data)
+            KeyIdentity::Device => with_retries(&self.tpm, |tpm| tpm.sign_with_identity(data))
                 .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
                 .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore))),
-            KeyIdentity::Module(ref _m) => self
-                .tpm
-                .lock()
-                .expect("Lock failed")
-                .derive_and_sign_with_identity(
-                    data,
-                    format!(
-                        "{}{}",
-                        match self.identity {
-                            KeyIdentity::Device => "",
-                            KeyIdentity::Module(ref m) => m,
-                        },
-                        self.key_name
-                    )
-                    .as_bytes(),
-                )
+            KeyIdentity::Module(_) => {
+                let identity = derivation_data(&self.identity, &self.key_name);
+                with_retries(&self.tpm, |tpm| {
+                    tpm.derive_and_sign_with_identity(data, identity.as_bytes())
+                })
                 .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
-                .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore))),
+                .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::mpsc;
+
+    use super::*;
+
+    // Fails with the given errors before it succeeds, like a busy TPM device.
+    struct FlakyTpm {
+        failures: Vec<HsmErrorKind>,
+        calls: Cell<usize>,
+    }
+
+    impl FlakyTpm {
+        fn new(failures: Vec<HsmErrorKind>) -> Self {
+            FlakyTpm {
+                failures,
+                calls: Cell::new(0),
+            }
         }
+
+        fn sign(&self) -> Result<Vec<u8>, HsmError> {
+            let call = self.calls.get();
+            self.calls.set(call + 1);
+            match self.failures.get(call) {
+                Some(kind) => Err(HsmError::from(*kind)),
+                None => Ok(b"digest".to_vec()),
+            }
+        }
+    }
+
+    #[test]
+    fn transient_failure_is_retried() {
+        let tpm = Mutex::new(FlakyTpm::new(vec![HsmErrorKind::TpmRetry]));
+
+        let digest = with_retries(&tpm, FlakyTpm::sign).unwrap();
+
+        assert_eq!(b"digest".to_vec(), digest);
+        assert_eq!(2, tpm.lock().unwrap().calls.get());
+    }
+
+    #[test]
+    fn retries_are_bounded() {
+        let tpm = Mutex::new(FlakyTpm::new(vec![HsmErrorKind::TpmRetry; 5]));
+
+        let err = with_retries(&tpm, FlakyTpm::sign).unwrap_err();
+
+        assert_eq!(HsmErrorKind::TpmRetry, err.kind());
+        assert_eq!(TPM_ATTEMPTS as usize, tpm.lock().unwrap().calls.get());
+    }
+
+    #[test]
+    fn permanent_failure_is_not_retried() {
+        let tpm = Mutex::new(FlakyTpm::new(vec![HsmErrorKind::NoneFn]));
+
+        let err = with_retries(&tpm, FlakyTpm::sign).unwrap_err();
+
+        assert_eq!(HsmErrorKind::NoneFn, err.kind());
+        assert_eq!(1, tpm.lock().unwrap().calls.get());
+    }
+
+    #[test]
+    fn api_failure_is_not_retried() {
+        let tpm = Mutex::new(FlakyTpm::new(vec![HsmErrorKind::Api(1)]));
+
+        let err = with_retries(&tpm, FlakyTpm::sign).unwrap_err();
+
+        assert_eq!(HsmErrorKind::Api(1), err.kind());
+        assert_eq!(1, tpm.lock().unwrap().calls.get());
+    }
+
+    #[test]
+    fn tpm_is_unlocked_before_retry() {
+        let tpm = Arc::new(Mutex::new(FlakyTpm::new(vec![HsmErrorKind::TpmRetry])));
+        let (tx, rx) = mpsc::channel();
+
+        let other = Arc::clone(&tpm);
+        let digest = with_retries(&tpm, |flaky| {
+            if flaky.calls.get() == 0 {
+                // another caller waiting for the TPM while the first attempt runs
+                let other = Arc::clone(&other);
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    let _tpm = other.lock().unwrap();
+                    tx.send(()).unwrap();
+                });
+            } else {
+                rx.recv_timeout(Duration::from_secs(5))
+                    .expect("TPM stayed locked during the retry delay");
+            }
+            flaky.sign()
+        })
+        .unwrap();
+
+        assert_eq!(b"digest".to_vec(), digest);
     }
 }
@@ -0,0 +1,68 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::crypto::{BackupMasterEncryptionKey, Decrypt, Encrypt, MasterEncryptionKey};
+use edgelet_hsm::Crypto;
+
+/// Backup and restore master encryption key tests
+#[test]
+fn crypto_backup_restore_master_key_success() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+
+    let ciphertext = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+
+    // act
+    let backup = crypto
+        .export_key("correct horse battery staple")
+        .expect("Export master key function returned error");
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+    crypto
+        .import_key("wrong passphrase", &backup)
+        .expect_err("Import master key function returned unexpected success");
+    crypto
+        .import_key("correct horse battery staple", &backup)
+        .expect("Import master key function returned error");
+
+    // assert
+    let plaintext_result = crypto
+        .decrypt(client_id, ciphertext.as_ref(), iv)
+        .expect("Decrypt function returned error");
+    assert_eq!(
+        plaintext,
+        plaintext_result.as_ref(),
+        "Failure plaintext after restore did not match {:?} and {:?}",
+        plaintext,
+        plaintext_result.as_ref()
+    );
+
+    // cleanup
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+}
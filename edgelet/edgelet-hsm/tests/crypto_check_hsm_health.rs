@@ -0,0 +1,27 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::CheckHsmHealth;
+use edgelet_hsm::Crypto;
+
+#[test]
+fn crypto_check_hsm_health_success() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    // act
+    let result = crypto.check_hsm_health();
+
+    // assert
+    assert!(result.is_ok());
+}
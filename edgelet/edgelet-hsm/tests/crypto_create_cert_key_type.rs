@@ -0,0 +1,88 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate base64;
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use std::env;
+
+use edgelet_core::{
+    Certificate, CertificateIssuer, CertificateProperties, CertificateType, CreateCertificate,
+    KeyBytes, PrivateKey, Signature, IOTEDGED_CA_ALIAS,
+};
+use edgelet_hsm::Crypto;
+
+const CERT_KEY_TYPE_KEY: &str = "IOTEDGE_CERT_KEY_TYPE";
+
+/// DER encoding of the object identifier of the prime256v1 (P-256) curve.
+const PRIME256V1_OID: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+
+fn pem_to_der(pem: &str) -> Vec<u8> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    base64::decode(&body).unwrap()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[test]
+fn crypto_create_cert_ec_p256_key_type_success() {
+    // arrange
+    env::set_var(CERT_KEY_TYPE_KEY, "ec-p256");
+    let crypto = Crypto::new().unwrap();
+
+    let edgelet_ca_props = CertificateProperties::new(
+        3600,
+        "test-iotedge-cn".to_string(),
+        CertificateType::Ca,
+        IOTEDGED_CA_ALIAS.to_string(),
+    )
+    .with_issuer(CertificateIssuer::DeviceCa);
+    crypto.create_certificate(&edgelet_ca_props).unwrap();
+
+    let props = CertificateProperties::new(
+        3600,
+        "Common Name".to_string(),
+        CertificateType::Server,
+        "ec-server-alias".to_string(),
+    );
+
+    // act
+    let cert_info = crypto.create_certificate(&props).unwrap();
+
+    // assert
+    let pk = match cert_info.get_private_key().unwrap() {
+        Some(pk) => pk,
+        None => panic!("Expected to find a key"),
+    };
+    match pk {
+        PrivateKey::Ref(_) => panic!("did not expect reference private key"),
+        PrivateKey::Key(KeyBytes::Pem(k)) => {
+            let der = pem_to_der(std::str::from_utf8(k.as_bytes()).unwrap());
+            assert!(contains(&der, PRIME256V1_OID), "Expected a P-256 key");
+        }
+    }
+
+    // cleanup
+    crypto
+        .destroy_certificate("ec-server-alias".to_string())
+        .unwrap();
+    crypto
+        .destroy_certificate(IOTEDGED_CA_ALIAS.to_string())
+        .unwrap();
+    env::remove_var(CERT_KEY_TYPE_KEY);
+}
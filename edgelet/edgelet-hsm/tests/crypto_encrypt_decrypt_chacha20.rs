@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use std::env;
+
+use edgelet_core::crypto::{Decrypt, Encrypt, MasterEncryptionKey};
+use edgelet_hsm::Crypto;
+
+const ENCRYPTION_ALGORITHM_KEY: &str = "IOTEDGE_ENCRYPTION_ALGORITHM";
+
+/// Ciphertext encrypted with ChaCha20-Poly1305 starts with this version.
+const CHACHA20_POLY1305_VERSION: u8 = 2;
+
+/// Encrypt/Decrypt tests with a non-default algorithm
+#[test]
+fn crypto_encrypt_decypt_chacha20_poly1305_success() {
+    // arrange
+    env::set_var(ENCRYPTION_ALGORITHM_KEY, "chacha20-poly1305");
+    let crypto = Crypto::new().unwrap();
+
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    // longer than the 12 byte nonce ChaCha20-Poly1305 takes
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+
+    //act
+    let ciphertext = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    assert_eq!(CHACHA20_POLY1305_VERSION, ciphertext.as_ref()[0]);
+
+    //act
+    let plaintext_result = crypto
+        .decrypt(client_id, ciphertext.as_ref(), iv)
+        .expect("Decrypt function returned error");
+    assert_eq!(
+        plaintext,
+        plaintext_result.as_ref(),
+        "Failure plaintext after decrypt did not match {:?} and {:?}",
+        plaintext,
+        plaintext_result.as_ref()
+    );
+
+    let bad_client_id = b"module2";
+    crypto
+        .decrypt(bad_client_id, ciphertext.as_ref(), iv)
+        .expect_err("Decrypt function returned unexpected success");
+
+    // only differs after the first 12 bytes
+    let bad_iv = b"initialization vectoR";
+    crypto
+        .decrypt(client_id, ciphertext.as_ref(), bad_iv)
+        .expect_err("Decrypt function returned unexpected success");
+
+    // cleanup
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+    env::remove_var(ENCRYPTION_ALGORITHM_KEY);
+}
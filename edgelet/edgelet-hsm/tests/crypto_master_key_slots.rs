@@ -0,0 +1,90 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::crypto::{Decrypt, Encrypt, MasterEncryptionKey, MasterEncryptionKeySlots};
+use edgelet_hsm::Crypto;
+
+/// Master encryption key slot tests
+#[test]
+fn crypto_decrypt_after_slot_rotation_success() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key_in_slot(0)
+        .expect("Create master key in slot function returned error");
+    crypto
+        .set_active_key_slot(0)
+        .expect("Set active key slot function returned error");
+
+    let ciphertext_a = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+
+    // act
+    crypto
+        .create_key_in_slot(1)
+        .expect("Create master key in slot function returned error");
+    crypto
+        .set_active_key_slot(1)
+        .expect("Set active key slot function returned error");
+
+    let ciphertext_b = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+
+    // assert
+    assert_ne!(ciphertext_a.as_ref(), ciphertext_b.as_ref());
+    for ciphertext in &[ciphertext_a.as_ref(), ciphertext_b.as_ref()] {
+        let plaintext_result = crypto
+            .decrypt(client_id, ciphertext, iv)
+            .expect("Decrypt function returned error");
+        assert_eq!(
+            plaintext,
+            plaintext_result.as_ref(),
+            "Failure plaintext after rotation did not match {:?} and {:?}",
+            plaintext,
+            plaintext_result.as_ref()
+        );
+    }
+
+    crypto
+        .destroy_key_in_slot(0)
+        .expect("Destroy master key in slot function returned error");
+    crypto
+        .decrypt(client_id, ciphertext_a.as_ref(), iv)
+        .expect_err("Decrypt function returned unexpected success");
+
+    // cleanup
+    crypto
+        .destroy_key_in_slot(1)
+        .expect("Destroy master key in slot function returned error");
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+}
+
+#[test]
+fn crypto_set_active_key_slot_out_of_range_fails() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    // act, assert
+    crypto
+        .set_active_key_slot(2)
+        .expect_err("Set active key slot function returned unexpected success");
+}
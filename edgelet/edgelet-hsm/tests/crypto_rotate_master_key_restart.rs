@@ -0,0 +1,98 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::crypto::{
+    Decrypt, Encrypt, EncryptedData, MasterEncryptionKey, MasterEncryptionKeySlots,
+    RotateMasterEncryptionKey,
+};
+use edgelet_hsm::Crypto;
+
+/// Rotate master encryption key across a restart tests
+#[test]
+fn crypto_rotated_master_key_survives_restart() {
+    // arrange
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    let crypto = Crypto::new().unwrap();
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+    let ciphertext = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    let data = EncryptedData::new(
+        client_id.to_vec(),
+        ciphertext.as_ref().to_vec(),
+        iv.to_vec(),
+    );
+    let slot = crypto
+        .active_key_slot()
+        .expect("Active key slot function returned error");
+    let rotated = crypto
+        .rotate_key(&[data])
+        .expect("Rotate master key function returned error");
+    let rotated_slot = crypto
+        .active_key_slot()
+        .expect("Active key slot function returned error");
+    assert_ne!(slot, rotated_slot);
+
+    // act
+    drop(crypto);
+    let crypto = Crypto::new().unwrap();
+
+    // assert
+    assert_eq!(
+        rotated_slot,
+        crypto
+            .active_key_slot()
+            .expect("Active key slot function returned error")
+    );
+
+    let plaintext_result = crypto
+        .decrypt(
+            rotated[0].client_id(),
+            rotated[0].ciphertext(),
+            rotated[0].initialization_vector(),
+        )
+        .expect("Decrypt function returned error");
+    assert_eq!(plaintext, plaintext_result.as_ref());
+
+    let ciphertext_result = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    assert_eq!(rotated[0].ciphertext(), ciphertext_result.as_ref());
+
+    crypto
+        .decrypt(client_id, ciphertext.as_ref(), iv)
+        .expect_err("Decrypt with the old key returned unexpected success");
+
+    // cleanup
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+    assert_eq!(
+        0,
+        crypto
+            .active_key_slot()
+            .expect("Active key slot function returned error")
+    );
+    crypto
+        .decrypt(
+            rotated[0].client_id(),
+            rotated[0].ciphertext(),
+            rotated[0].initialization_vector(),
+        )
+        .expect_err("Decrypt after destroying every slot returned unexpected success");
+}
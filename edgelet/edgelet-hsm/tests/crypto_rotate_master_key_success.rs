@@ -0,0 +1,77 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::crypto::{
+    Decrypt, Encrypt, EncryptedData, MasterEncryptionKey, RotateMasterEncryptionKey,
+};
+use edgelet_hsm::Crypto;
+
+/// Rotate master encryption key tests
+#[test]
+fn crypto_rotate_master_key_success() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+
+    let ciphertext = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    let data = EncryptedData::new(
+        client_id.to_vec(),
+        ciphertext.as_ref().to_vec(),
+        iv.to_vec(),
+    );
+
+    // act
+    let rotated = crypto
+        .rotate_key(&[data])
+        .expect("Rotate master key function returned error");
+
+    // assert
+    assert_eq!(1, rotated.len());
+    let plaintext_result = crypto
+        .decrypt(
+            rotated[0].client_id(),
+            rotated[0].ciphertext(),
+            rotated[0].initialization_vector(),
+        )
+        .expect("Decrypt function returned error");
+    assert_eq!(
+        plaintext,
+        plaintext_result.as_ref(),
+        "Failure plaintext after rotation did not match {:?} and {:?}",
+        plaintext,
+        plaintext_result.as_ref()
+    );
+
+    let bad_data = EncryptedData::new(
+        client_id.to_vec(),
+        ciphertext.as_ref().to_vec(),
+        b"inconsistent_iv".to_vec(),
+    );
+    crypto
+        .rotate_key(&[bad_data])
+        .expect_err("Rotate master key function returned unexpected success");
+
+    // cleanup
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+}
@@ -0,0 +1,69 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate base64;
+extern crate bytes;
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use std::str;
+
+use bytes::Bytes;
+use edgelet_core::crypto::{DerivedKeyStore, MemoryKey, Sign, Signature, SignatureAlgorithm};
+use edgelet_core::KeyIdentity;
+use edgelet_core::KeyStore;
+use edgelet_hsm::TpmKeyStore;
+
+const TEST_KEY_BASE64: &str = "D7PuplFy7vIr0349blOugqCxyfMscyVZDoV9Ii0EFnA=";
+
+// This tests the following:
+//  1) A well known identity key K can be installed in the TPM
+//  2) The TPM derives the keys of module identities from the same data as
+//     the derived key store, so both sign a payload DATA with the same key.
+//  3) Module IDs and key names which concatenate to the same string, like
+//     "mod1" + "key" and "mod" + "1key", yield different digests.
+#[test]
+fn tpm_identity_derivation_matches_derived_key_store() {
+    // arrange
+    let key_store = TpmKeyStore::new().unwrap();
+
+    let decoded_key = base64::decode(TEST_KEY_BASE64).unwrap();
+    let decoded_key_str = unsafe { str::from_utf8_unchecked(&decoded_key) };
+    let derived_key_store = DerivedKeyStore::new(MemoryKey::new(&decoded_key));
+    let module1_identity = KeyIdentity::Module("mod1".to_string());
+    let module2_identity = KeyIdentity::Module("mod".to_string());
+
+    key_store
+        .activate_key(&Bytes::from(decoded_key_str))
+        .unwrap();
+
+    let data_to_be_signed = b"I am the very model of a modern major general";
+
+    // act
+    let digest1 = key_store
+        .get(&module1_identity, "key")
+        .unwrap()
+        .sign(SignatureAlgorithm::HMACSHA256, data_to_be_signed)
+        .unwrap();
+    let digest2 = key_store
+        .get(&module2_identity, "1key")
+        .unwrap()
+        .sign(SignatureAlgorithm::HMACSHA256, data_to_be_signed)
+        .unwrap();
+    let expected = derived_key_store
+        .get(&module1_identity, "key")
+        .unwrap()
+        .sign(SignatureAlgorithm::HMACSHA256, data_to_be_signed)
+        .unwrap();
+
+    // assert
+    assert_eq!(expected.as_bytes(), digest1.as_bytes());
+    assert_ne!(digest1.as_bytes(), digest2.as_bytes());
+}
@@ -131,6 +131,7 @@ impl ModuleRegistry for ModuleClient {
     type Error = Error;
     type PullFuture = FutureResult<(), Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type PruneFuture = FutureResult<(), Self::Error>;
     type Config = ModuleConfig;
 
     fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
@@ -140,6 +141,10 @@ impl ModuleRegistry for ModuleClient {
     fn remove(&self, _name: &str) -> Self::RemoveFuture {
         future::ok(())
     }
+
+    fn prune(&self) -> Self::PruneFuture {
+        future::ok(())
+    }
 }
 
 impl ModuleRuntime for ModuleClient {
@@ -162,6 +167,7 @@ impl ModuleRuntime for ModuleClient {
     type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error> + Send>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type PruneVolumesFuture = FutureResult<(), Self::Error>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
         unimplemented!()
@@ -330,6 +336,10 @@ impl ModuleRuntime for ModuleClient {
             future::join_all(n).map(|_| ())
         }))
     }
+
+    fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+        future::ok(())
+    }
 }
 
 pub struct Logs(String, Body);
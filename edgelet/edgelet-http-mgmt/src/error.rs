@@ -26,15 +26,27 @@ pub enum ErrorKind {
     #[fail(display = "Client error")]
     Client(MgmtError<serde_json::Value>),
 
+    #[fail(display = "Could not rotate the master encryption key")]
+    EncryptionKey,
+
+    #[fail(display = "Could not export the master encryption key")]
+    ExportEncryptionKey,
+
     #[fail(display = "{}", _0)]
     IdentityOperation(IdentityOperation),
 
+    #[fail(display = "Could not import the master encryption key")]
+    ImportEncryptionKey,
+
     #[fail(display = "Could not initialize module client")]
     InitializeModuleClient,
 
     #[fail(display = "Invalid API version {:?}", _0)]
     InvalidApiVersion(String),
 
+    #[fail(display = "The passphrase must be at least {} characters long", _0)]
+    InvalidPassphrase(usize),
+
     #[fail(display = "A request to Azure IoT Hub failed")]
     IotHub,
 
@@ -61,6 +73,9 @@ pub enum ErrorKind {
 
     #[fail(display = "Could not update module")]
     UpdateModule(String),
+
+    #[fail(display = "Could not query watchdog state")]
+    Watchdog,
 }
 
 impl Fail for Error {
@@ -129,6 +144,7 @@ impl IntoResponse for Error {
             } else {
                 match self.kind() {
                     ErrorKind::InvalidApiVersion(_)
+                    | ErrorKind::InvalidPassphrase(_)
                     | ErrorKind::MalformedRequestBody
                     | ErrorKind::MalformedRequestParameter(_)
                     | ErrorKind::MissingRequiredParameter(_) => StatusCode::BAD_REQUEST,
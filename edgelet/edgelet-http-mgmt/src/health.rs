@@ -0,0 +1,260 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use chrono::Utc;
+use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use log::Level;
+use serde_json;
+
+use edgelet_core::watchdog::WatchdogState;
+use edgelet_core::CheckHsmHealth;
+use edgelet_utils::log_failure;
+use management::models::Health;
+
+const HEALTH_PATH: &str = "/health";
+
+/// Serves the daemon's health on `GET /health` and passes every other request
+/// through to the upstream service. Health probes are not versioned, so this
+/// is meant to wrap the `ApiVersionService`.
+///
+/// The daemon is only reported healthy if the watchdog is and the HSM answers
+/// its health probe.
+#[derive(Clone)]
+pub struct HealthService<T, H> {
+    watchdog: WatchdogState,
+    hsm: H,
+    upstream: T,
+}
+
+impl<T, H> HealthService<T, H> {
+    pub fn new(watchdog: WatchdogState, hsm: H, upstream: T) -> Self {
+        HealthService {
+            watchdog,
+            hsm,
+            upstream,
+        }
+    }
+}
+
+fn status(healthy: bool) -> &'static str {
+    if healthy {
+        "healthy"
+    } else {
+        "unhealthy"
+    }
+}
+
+fn health_response<H: CheckHsmHealth>(watchdog: &WatchdogState, hsm: &H) -> Response<Body> {
+    let hsm_healthy = match hsm.check_hsm_health() {
+        Ok(()) => true,
+        Err(err) => {
+            log_failure(Level::Warn, &err);
+            false
+        }
+    };
+    let healthy = watchdog.is_healthy(Utc::now()) && hsm_healthy;
+    let status_code = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let mut health = Health::new(status(healthy).to_string());
+    if let Some(last_check) = watchdog.last_check() {
+        health.set_last_watchdog_check(last_check.to_rfc3339());
+    }
+    health.set_hsm(status(hsm_healthy).to_string());
+
+    let body = serde_json::to_string(&health).expect("serialization of Health failed.");
+    Response::builder()
+        .status(status_code)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, body.len().to_string().as_str())
+        .body(body.into())
+        .expect("response builder failure")
+}
+
+impl<T, H> Service for HealthService<T, H>
+where
+    T: Service<ResBody = Body>,
+    H: CheckHsmHealth,
+    <T as Service>::Future: Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        if req.method() == Method::GET && req.uri().path() == HEALTH_PATH {
+            Box::new(future::ok(health_response(&self.watchdog, &self.hsm)))
+        } else {
+            Box::new(self.upstream.call(req))
+        }
+    }
+}
+
+impl<T, H> NewService for HealthService<T, H>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    H: 'static + Clone + Send,
+    HealthService<<T as NewService>::Service, H>: Service,
+{
+    type ReqBody = <HealthService<<T as NewService>::Service, H> as Service>::ReqBody;
+    type ResBody = <HealthService<<T as NewService>::Service, H> as Service>::ResBody;
+    type Error = <HealthService<<T as NewService>::Service, H> as Service>::Error;
+    type Service = HealthService<<T as NewService>::Service, H>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        let watchdog = self.watchdog.clone();
+        let hsm = self.hsm.clone();
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(move |upstream| HealthService {
+                    watchdog,
+                    hsm,
+                    upstream,
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::FutureResult;
+    use futures::Stream;
+    use hyper::Error as HyperError;
+
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestHsm {
+        healthy: bool,
+    }
+
+    impl CheckHsmHealth for TestHsm {
+        fn check_hsm_health(&self) -> Result<(), CoreError> {
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(CoreError::from(CoreErrorKind::HsmHealthCheck))
+            }
+        }
+    }
+
+    const HEALTHY_HSM: TestHsm = TestHsm { healthy: true };
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = HyperError;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(
+                Response::builder()
+                    .status(StatusCode::IM_A_TEAPOT)
+                    .body(Body::default())
+                    .unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn healthy_daemon_returns_ok() {
+        let watchdog = WatchdogState::new();
+        watchdog.set_last_check(Utc::now());
+        let mut service = HealthService::new(watchdog, HEALTHY_HSM, TestService);
+        let req = Request::get("http://localhost/health")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let health: Health = serde_json::from_slice(&b).unwrap();
+                assert_eq!("healthy", health.status());
+                assert!(health.last_watchdog_check().is_some());
+                assert_eq!(Some("healthy"), health.hsm());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn uninitialized_daemon_returns_service_unavailable() {
+        let mut service = HealthService::new(WatchdogState::new(), HEALTHY_HSM, TestService);
+        let req = Request::get("http://localhost/health")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let health: Health = serde_json::from_slice(&b).unwrap();
+                assert_eq!("unhealthy", health.status());
+                assert_eq!(None, health.last_watchdog_check());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn unhealthy_hsm_returns_service_unavailable() {
+        let watchdog = WatchdogState::new();
+        watchdog.set_last_check(Utc::now());
+        let mut service = HealthService::new(watchdog, TestHsm { healthy: false }, TestService);
+        let req = Request::get("http://localhost/health")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let health: Health = serde_json::from_slice(&b).unwrap();
+                assert_eq!("unhealthy", health.status());
+                assert_eq!(Some("unhealthy"), health.hsm());
+                assert!(health.last_watchdog_check().is_some());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn other_requests_are_passed_through() {
+        let mut service = HealthService::new(WatchdogState::new(), HEALTHY_HSM, TestService);
+        let req = Request::get("http://localhost/modules")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+    }
+}
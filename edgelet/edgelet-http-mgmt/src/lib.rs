@@ -9,7 +9,7 @@
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 #![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
 
-#[cfg(test)]
+extern crate base64;
 extern crate chrono;
 extern crate edgelet_core;
 extern crate edgelet_docker;
@@ -18,6 +18,7 @@ extern crate edgelet_http;
 extern crate edgelet_iothub;
 #[cfg(test)]
 extern crate edgelet_test_utils;
+extern crate edgelet_utils;
 extern crate failure;
 extern crate futures;
 extern crate hyper;
@@ -38,12 +39,16 @@ use hyper::{Body, Response};
 
 mod client;
 mod error;
+mod health;
 mod server;
+mod version;
 
 pub use client::ModuleClient;
 pub use error::{Error, ErrorKind};
+pub use health::HealthService;
 pub use server::ListModules;
 pub use server::ManagementService;
+pub use version::VersionService;
 
 pub trait IntoResponse {
     fn into_response(self) -> Response<Body>;
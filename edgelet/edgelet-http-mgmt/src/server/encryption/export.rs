@@ -0,0 +1,168 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use base64;
+use failure::ResultExt;
+use futures::{Future, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::BackupMasterEncryptionKey;
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{EncryptionKeyBackup, ExportEncryptionKeyRequest};
+
+use super::check_passphrase;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ExportEncryptionKey<C> {
+    crypto: C,
+}
+
+impl<C> ExportEncryptionKey<C> {
+    pub fn new(crypto: C) -> Self {
+        ExportEncryptionKey { crypto }
+    }
+}
+
+impl<C> Handler<Parameters> for ExportEncryptionKey<C>
+where
+    C: 'static + BackupMasterEncryptionKey + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        info!("Export master encryption key");
+
+        let crypto = self.crypto.clone();
+        let response = req
+            .into_body()
+            .concat2()
+            .then(move |b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let request = serde_json::from_slice::<ExportEncryptionKeyRequest>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+                check_passphrase(request.passphrase())?;
+
+                let key = crypto
+                    .export_key(request.passphrase())
+                    .context(ErrorKind::ExportEncryptionKey)?;
+
+                let b = serde_json::to_string(&EncryptionKeyBackup::new(base64::encode(&key)))
+                    .context(ErrorKind::ExportEncryptionKey)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::ExportEncryptionKey)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    // "Wraps" the key by prefixing it with the passphrase.
+    #[derive(Clone)]
+    struct TestCrypto;
+
+    impl BackupMasterEncryptionKey for TestCrypto {
+        fn export_key(&self, passphrase: &str) -> Result<Vec<u8>, CoreError> {
+            Ok(format!("{}:key", passphrase).into_bytes())
+        }
+
+        fn import_key(&self, _passphrase: &str, _backup: &[u8]) -> Result<(), CoreError> {
+            Err(CoreError::from(CoreErrorKind::KeyStore))
+        }
+    }
+
+    fn export_request(passphrase: &str) -> Request<Body> {
+        let request = ExportEncryptionKeyRequest::new(passphrase.to_string());
+        Request::post("http://localhost/encryptionkey/export")
+            .body(serde_json::to_string(&request).unwrap().into())
+            .unwrap()
+    }
+
+    #[test]
+    fn export_returns_wrapped_key() {
+        // arrange
+        let handler = ExportEncryptionKey::new(TestCrypto);
+
+        // act
+        let response = handler
+            .handle(export_request("long enough passphrase"), Parameters::new())
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let backup: EncryptionKeyBackup = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    b"long enough passphrase:key".to_vec(),
+                    base64::decode(backup.key()).unwrap()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn export_rejects_short_passphrase() {
+        // arrange
+        let handler = ExportEncryptionKey::new(TestCrypto);
+
+        // act
+        let response = handler
+            .handle(export_request("short"), Parameters::new())
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "The passphrase must be at least 12 characters long",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn export_fails_for_malformed_request() {
+        // arrange
+        let handler = ExportEncryptionKey::new(TestCrypto);
+        let request = Request::post("http://localhost/encryptionkey/export")
+            .body("{}".into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use base64;
+use failure::ResultExt;
+use futures::{Future, Stream};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::BackupMasterEncryptionKey;
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::ImportEncryptionKeyRequest;
+
+use super::check_passphrase;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ImportEncryptionKey<C> {
+    crypto: C,
+}
+
+impl<C> ImportEncryptionKey<C> {
+    pub fn new(crypto: C) -> Self {
+        ImportEncryptionKey { crypto }
+    }
+}
+
+impl<C> Handler<Parameters> for ImportEncryptionKey<C>
+where
+    C: 'static + BackupMasterEncryptionKey + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        info!("Import master encryption key");
+
+        let crypto = self.crypto.clone();
+        let response = req
+            .into_body()
+            .concat2()
+            .then(move |b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let request = serde_json::from_slice::<ImportEncryptionKeyRequest>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+                check_passphrase(request.passphrase())?;
+                let key = base64::decode(request.key()).context(ErrorKind::MalformedRequestBody)?;
+
+                crypto
+                    .import_key(request.passphrase(), &key)
+                    .context(ErrorKind::ImportEncryptionKey)?;
+
+                let response = Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::ImportEncryptionKey)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    // "Wraps" the key by prefixing it with the passphrase, and only accepts
+    // backups prefixed with the passphrase they are imported with.
+    #[derive(Clone)]
+    struct TestCrypto {
+        key: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl TestCrypto {
+        fn new(key: &[u8]) -> Self {
+            TestCrypto {
+                key: Arc::new(Mutex::new(key.to_vec())),
+            }
+        }
+
+        fn key(&self) -> Vec<u8> {
+            self.key.lock().unwrap().clone()
+        }
+    }
+
+    impl BackupMasterEncryptionKey for TestCrypto {
+        fn export_key(&self, passphrase: &str) -> Result<Vec<u8>, CoreError> {
+            let mut backup = format!("{}:", passphrase).into_bytes();
+            backup.extend_from_slice(&self.key());
+            Ok(backup)
+        }
+
+        fn import_key(&self, passphrase: &str, backup: &[u8]) -> Result<(), CoreError> {
+            let prefix = format!("{}:", passphrase).into_bytes();
+            if !backup.starts_with(&prefix) {
+                return Err(CoreError::from(CoreErrorKind::KeyStore));
+            }
+            *self.key.lock().unwrap() = backup[prefix.len()..].to_vec();
+            Ok(())
+        }
+    }
+
+    fn import_request(passphrase: &str, key: &[u8]) -> Request<Body> {
+        let request = ImportEncryptionKeyRequest::new(passphrase.to_string(), base64::encode(key));
+        Request::post("http://localhost/encryptionkey/import")
+            .body(serde_json::to_string(&request).unwrap().into())
+            .unwrap()
+    }
+
+    #[test]
+    fn import_restores_exported_key() {
+        // arrange
+        let crypto = TestCrypto::new(b"old key");
+        let backup = TestCrypto::new(b"new key")
+            .export_key("long enough passphrase")
+            .unwrap();
+        let handler = ImportEncryptionKey::new(crypto.clone());
+
+        // act
+        let response = handler
+            .handle(
+                import_request("long enough passphrase", &backup),
+                Parameters::new(),
+            )
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(b"new key".to_vec(), crypto.key());
+    }
+
+    #[test]
+    fn import_rejects_wrong_passphrase() {
+        // arrange
+        let crypto = TestCrypto::new(b"old key");
+        let backup = TestCrypto::new(b"new key")
+            .export_key("long enough passphrase")
+            .unwrap();
+        let handler = ImportEncryptionKey::new(crypto.clone());
+
+        // act
+        let response = handler
+            .handle(
+                import_request("some other passphrase", &backup),
+                Parameters::new(),
+            )
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        assert_eq!(b"old key".to_vec(), crypto.key());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not import the master encryption key\n\tcaused by: A error occurred in the key store.",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn import_rejects_short_passphrase() {
+        // arrange
+        let crypto = TestCrypto::new(b"old key");
+        let backup = TestCrypto::new(b"new key").export_key("short").unwrap();
+        let handler = ImportEncryptionKey::new(crypto.clone());
+
+        // act
+        let response = handler
+            .handle(import_request("short", &backup), Parameters::new())
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(b"old key".to_vec(), crypto.key());
+    }
+
+    #[test]
+    fn import_fails_for_malformed_key() {
+        // arrange
+        let crypto = TestCrypto::new(b"old key");
+        let handler = ImportEncryptionKey::new(crypto.clone());
+        let request = Request::post("http://localhost/encryptionkey/import")
+            .body(
+                json!({ "passphrase": "long enough passphrase", "key": "!@#$%" })
+                    .to_string()
+                    .into(),
+            )
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(b"old key".to_vec(), crypto.key());
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Microsoft. All rights reserved.
+mod export;
+mod import;
+mod rotate;
+
+pub use self::export::ExportEncryptionKey;
+pub use self::import::ImportEncryptionKey;
+pub use self::rotate::RotateEncryptionKey;
+
+use error::{Error, ErrorKind};
+
+/// Exported keys are only as safe as the passphrase they are wrapped with,
+/// so short ones are rejected.
+const MIN_PASSPHRASE_LEN: usize = 12;
+
+fn check_passphrase(passphrase: &str) -> Result<(), Error> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        Err(Error::from(ErrorKind::InvalidPassphrase(
+            MIN_PASSPHRASE_LEN,
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use base64;
+use failure::ResultExt;
+use futures::{Future, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{EncryptedData, RotateMasterEncryptionKey};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{EncryptedItem, EncryptedItemList};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct RotateEncryptionKey<C> {
+    crypto: C,
+}
+
+impl<C> RotateEncryptionKey<C> {
+    pub fn new(crypto: C) -> Self {
+        RotateEncryptionKey { crypto }
+    }
+}
+
+impl<C> Handler<Parameters> for RotateEncryptionKey<C>
+where
+    C: 'static + RotateMasterEncryptionKey + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        info!("Rotate master encryption key");
+
+        let crypto = self.crypto.clone();
+        let response = read_request(req)
+            .and_then(move |items| -> Result<_, Error> {
+                let data = items
+                    .iter()
+                    .map(|item| -> Result<_, Error> {
+                        let ciphertext = base64::decode(item.ciphertext())
+                            .context(ErrorKind::MalformedRequestBody)?;
+                        let initialization_vector = base64::decode(item.initialization_vector())
+                            .context(ErrorKind::MalformedRequestBody)?;
+                        Ok(EncryptedData::new(
+                            client_id(item),
+                            ciphertext,
+                            initialization_vector,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let rotated = crypto.rotate_key(&data).context(ErrorKind::EncryptionKey)?;
+                let items = items
+                    .iter()
+                    .zip(rotated)
+                    .map(|(item, data)| {
+                        item.clone()
+                            .with_ciphertext(base64::encode(data.ciphertext()))
+                    })
+                    .collect();
+
+                let b = serde_json::to_string(&EncryptedItemList::new(items))
+                    .context(ErrorKind::EncryptionKey)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::EncryptionKey)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+// The workload API encrypts data for a module with its name and generation ID
+// as the client ID.
+fn client_id(item: &EncryptedItem) -> Vec<u8> {
+    format!("{}{}", item.module_id(), item.generation_id()).into_bytes()
+}
+
+fn read_request(req: Request<Body>) -> impl Future<Item = Vec<EncryptedItem>, Error = Error> {
+    req.into_body().concat2().then(|b| {
+        let b = b.context(ErrorKind::MalformedRequestBody)?;
+        let list = serde_json::from_slice::<EncryptedItemList>(&b)
+            .context(ErrorKind::MalformedRequestBody)?;
+        Ok(list.items().to_vec())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    // "Encrypts" by XOR-ing with a single byte key, which rotating increments.
+    #[derive(Clone)]
+    struct TestCrypto {
+        key: Arc<Mutex<u8>>,
+    }
+
+    impl TestCrypto {
+        fn new(key: u8) -> Self {
+            TestCrypto {
+                key: Arc::new(Mutex::new(key)),
+            }
+        }
+
+        fn key(&self) -> u8 {
+            *self.key.lock().unwrap()
+        }
+    }
+
+    fn xor(data: &[u8], key: u8) -> Vec<u8> {
+        data.iter().map(|b| b ^ key).collect()
+    }
+
+    impl RotateMasterEncryptionKey for TestCrypto {
+        fn rotate_key(&self, data: &[EncryptedData]) -> Result<Vec<EncryptedData>, CoreError> {
+            let mut key = self.key.lock().unwrap();
+            if data.iter().any(|item| item.client_id() != b"m1gen1") {
+                return Err(CoreError::from(CoreErrorKind::KeyStore));
+            }
+            let old_key = *key;
+            *key += 1;
+            Ok(data
+                .iter()
+                .map(|item| {
+                    EncryptedData::new(
+                        item.client_id().to_vec(),
+                        xor(&xor(item.ciphertext(), old_key), *key),
+                        item.initialization_vector().to_vec(),
+                    )
+                })
+                .collect())
+        }
+    }
+
+    fn rotate_request(module_id: &str, ciphertext: &[u8]) -> Request<Body> {
+        let items = EncryptedItemList::new(vec![EncryptedItem::new(
+            module_id.to_string(),
+            "gen1".to_string(),
+            base64::encode(ciphertext),
+            base64::encode("iv"),
+        )]);
+        Request::post("http://localhost/encryptionkey/rotate")
+            .body(serde_json::to_string(&items).unwrap().into())
+            .unwrap()
+    }
+
+    #[test]
+    fn rotate_reencrypts_items_with_new_key() {
+        // arrange
+        let crypto = TestCrypto::new(1);
+        let handler = RotateEncryptionKey::new(crypto.clone());
+        let ciphertext = xor(b"plaintext", crypto.key());
+
+        // act
+        let response = handler
+            .handle(rotate_request("m1", &ciphertext), Parameters::new())
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(2, crypto.key());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: EncryptedItemList = serde_json::from_slice(&b).unwrap();
+                assert_eq!(1, list.items().len());
+                let item = &list.items()[0];
+                assert_eq!("m1", item.module_id());
+                assert_eq!("gen1", item.generation_id());
+                assert_eq!(&base64::encode("iv"), item.initialization_vector());
+                let ciphertext = base64::decode(item.ciphertext()).unwrap();
+                assert_eq!(b"plaintext".to_vec(), xor(&ciphertext, crypto.key()));
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn rotate_fails_for_undecryptable_item() {
+        // arrange
+        let crypto = TestCrypto::new(1);
+        let handler = RotateEncryptionKey::new(crypto.clone());
+
+        // act
+        let response = handler
+            .handle(rotate_request("m2", b"ciphertext"), Parameters::new())
+            .wait()
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        assert_eq!(1, crypto.key());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not rotate the master encryption key\n\tcaused by: A error occurred in the key store.",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn rotate_fails_for_malformed_ciphertext() {
+        // arrange
+        let handler = RotateEncryptionKey::new(TestCrypto::new(1));
+        let items = json!({
+            "items": [{
+                "moduleId": "m1",
+                "generationId": "gen1",
+                "ciphertext": "!@#$%",
+                "initializationVector": "aXY=",
+            }]
+        });
+        let request = Request::post("http://localhost/encryptionkey/rotate")
+            .body(serde_json::to_string(&items).unwrap().into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
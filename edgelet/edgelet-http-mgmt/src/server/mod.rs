@@ -1,10 +1,16 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+mod encryption;
 mod identity;
 mod module;
 mod system_info;
+mod watchdog;
 
-use edgelet_core::{IdentityManager, Module, ModuleRuntime, Policy};
+use edgelet_core::watchdog::WatchdogState;
+use edgelet_core::{
+    BackupMasterEncryptionKey, IdentityManager, Module, ModuleRuntime, Policy,
+    RotateMasterEncryptionKey,
+};
 use edgelet_http::authorization::Authorization;
 use edgelet_http::route::*;
 use failure::{Compat, ResultExt};
@@ -14,9 +20,11 @@ use hyper::{Body, Request};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use self::encryption::*;
 use self::identity::*;
 pub use self::module::*;
 use self::system_info::*;
+use self::watchdog::*;
 use error::{Error, ErrorKind};
 
 lazy_static! {
@@ -31,13 +39,19 @@ pub struct ManagementService {
 impl ManagementService {
     // clippy bug: https://github.com/rust-lang-nursery/rust-clippy/issues/3220
     #[cfg_attr(feature = "cargo-clippy", allow(new_ret_no_self))]
-    pub fn new<M, I>(runtime: &M, identity: &I) -> impl Future<Item = Self, Error = Error>
+    pub fn new<M, I, C>(
+        runtime: &M,
+        identity: &I,
+        watchdog: &WatchdogState,
+        crypto: &C,
+    ) -> impl Future<Item = Self, Error = Error>
     where
         M: 'static + ModuleRuntime + Clone + Send + Sync,
         <M::Module as Module>::Config: DeserializeOwned + Serialize,
         M::Logs: Into<Body>,
         I: 'static + IdentityManager + Clone + Send + Sync,
         I::Identity: Serialize,
+        C: 'static + RotateMasterEncryptionKey + BackupMasterEncryptionKey + Clone + Send + Sync,
     {
         let router = router!(
             get    "/modules"                         => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
@@ -56,6 +70,15 @@ impl ManagementService {
             delete "/identities/(?P<name>[^/]+)"      => Authorization::new(DeleteIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
 
             get    "/systeminfo"                      => Authorization::new(GetSystemInfo::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+
+            get    "/watchdog"                        => Authorization::new(GetWatchdog::new(watchdog.clone()), Policy::Anonymous, runtime.clone()),
+            get    "/watchdog/metrics"                => Authorization::new(GetWatchdogMetrics::new(watchdog.clone()), Policy::Anonymous, runtime.clone()),
+            post   "/watchdog/pause"                  => Authorization::new(PauseWatchdog::new(watchdog.clone()), Policy::Anonymous, runtime.clone()),
+            post   "/watchdog/resume"                 => Authorization::new(ResumeWatchdog::new(watchdog.clone()), Policy::Anonymous, runtime.clone()),
+
+            post   "/encryptionkey/rotate"            => Authorization::new(RotateEncryptionKey::new(crypto.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            post   "/encryptionkey/export"            => Authorization::new(ExportEncryptionKey::new(crypto.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            post   "/encryptionkey/import"            => Authorization::new(ImportEncryptionKey::new(crypto.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
         );
 
         router.new_service().then(|inner| {
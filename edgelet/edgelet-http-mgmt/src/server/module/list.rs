@@ -76,6 +76,9 @@ where
     if let Some(description) = state.status_description() {
         runtime_status.set_description(description.to_string());
     }
+    if let Some(health) = state.health() {
+        runtime_status.set_health(health.to_string());
+    }
     let mut status = Status::new(runtime_status);
     if let Some(started_at) = state.started_at() {
         status.set_start_time(started_at.to_rfc3339());
@@ -98,7 +101,7 @@ where
 #[cfg(test)]
 mod tests {
     use chrono::prelude::*;
-    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_core::{HealthStatus, ModuleRuntimeState, ModuleStatus};
     use edgelet_http::route::Parameters;
     use edgelet_test_utils::module::*;
     use futures::Stream;
@@ -116,7 +119,8 @@ mod tests {
             .with_status_description(Some("description".to_string()))
             .with_started_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(14, 20, 0, 1)))
             .with_finished_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(15, 20, 0, 1)))
-            .with_image_id(Some("image-id".to_string()));
+            .with_image_id(Some("image-id".to_string()))
+            .with_health(Some(HealthStatus::Unhealthy));
         let config = TestConfig::new("microsoft/test-image".to_string());
         let module: TestModule<Error> =
             TestModule::new("test-module".to_string(), config, Ok(state));
@@ -160,6 +164,10 @@ mod tests {
                     "description",
                     module.status().runtime_status().description().unwrap()
                 );
+                assert_eq!(
+                    "unhealthy",
+                    module.status().runtime_status().health().unwrap()
+                );
                 Ok(())
             })
             .wait()
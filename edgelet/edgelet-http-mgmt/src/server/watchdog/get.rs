@@ -0,0 +1,137 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::ResultExt;
+use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::watchdog::WatchdogState;
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::*;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct GetWatchdog {
+    state: WatchdogState,
+}
+
+impl GetWatchdog {
+    pub fn new(state: WatchdogState) -> Self {
+        GetWatchdog { state }
+    }
+}
+
+impl Handler<Parameters> for GetWatchdog {
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        debug!("Get Watchdog Details");
+
+        let mut body = WatchdogDetails::new(
+            self.state.status().to_string(),
+            i64::from(self.state.restart_count()),
+            self.state.is_paused(),
+        );
+        if let Some(event) = self.state.last_restart() {
+            let mut restart = RestartEvent::new(
+                i64::from(event.count()),
+                event.reason().to_string(),
+                event.timestamp().to_rfc3339(),
+            );
+            if let Some(exit_code) = event.exit_code() {
+                restart.set_exit_code(exit_code);
+            }
+            body.set_last_restart(restart);
+        }
+        if let Some(last_error) = self.state.last_error() {
+            body.set_last_error(last_error);
+        }
+
+        let response = serde_json::to_string(&body)
+            .context(ErrorKind::Watchdog)
+            .and_then(|b| {
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::Watchdog)
+            })
+            .unwrap_or_else(|e| Error::from(e).into_response());
+
+        Box::new(future::ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+    use edgelet_http::route::Parameters;
+    use futures::Stream;
+    use management::models::WatchdogDetails;
+
+    use super::*;
+
+    #[test]
+    fn watchdog_details_success() {
+        // arrange
+        let handler = GetWatchdog::new(WatchdogState::new());
+        let request = Request::get("http://localhost/watchdog")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let details: WatchdogDetails = serde_json::from_slice(&b).unwrap();
+                assert_eq!("unknown", details.status());
+                assert_eq!(0, *details.restart_count());
+                assert!(!*details.paused());
+                assert!(details.last_restart().is_none());
+                assert!(details.last_error().is_none());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn watchdog_details_include_last_error() {
+        // arrange
+        let state = WatchdogState::new();
+        state.set_last_error(&CoreError::from(CoreErrorKind::ModuleRuntime));
+        let handler = GetWatchdog::new(state);
+        let request = Request::get("http://localhost/watchdog")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let details: WatchdogDetails = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    Some("A module runtime error occurred."),
+                    details.last_error()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use failure::ResultExt;
+use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+
+use edgelet_core::watchdog::{WatchdogMetrics, WatchdogState};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// Content type of the Prometheus text exposition format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+pub struct GetWatchdogMetrics {
+    state: WatchdogState,
+}
+
+impl GetWatchdogMetrics {
+    pub fn new(state: WatchdogState) -> Self {
+        GetWatchdogMetrics { state }
+    }
+}
+
+impl Handler<Parameters> for GetWatchdogMetrics {
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        debug!("Get Watchdog Metrics");
+
+        let body = format_metrics(&self.state.metrics());
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, METRICS_CONTENT_TYPE)
+            .header(CONTENT_LENGTH, body.len().to_string().as_str())
+            .body(body.into())
+            .context(ErrorKind::Watchdog)
+            .unwrap_or_else(|e| Error::from(e).into_response());
+
+        Box::new(future::ok(response))
+    }
+}
+
+const ITERATIONS: &str = "edgelet_watchdog_iterations_total";
+const RESTARTS: &str = "edgelet_watchdog_restarts_total";
+const ITERATION_DURATION: &str = "edgelet_watchdog_iteration_duration_seconds";
+
+// Writing to a String cannot fail, so the results of write! are ignored below
+fn format_metrics(metrics: &WatchdogMetrics) -> String {
+    let mut body = String::new();
+
+    write_header(
+        &mut body,
+        ITERATIONS,
+        "counter",
+        "Number of times the watchdog checked the edge runtime module.",
+    );
+    let _ = writeln!(body, "{} {}", ITERATIONS, metrics.iterations());
+
+    write_header(
+        &mut body,
+        RESTARTS,
+        "counter",
+        "Number of times the watchdog started the edge runtime module.",
+    );
+    let _ = writeln!(body, "{} {}", RESTARTS, metrics.restarts());
+
+    write_header(
+        &mut body,
+        ITERATION_DURATION,
+        "histogram",
+        "Time taken to check the edge runtime module.",
+    );
+    for (bound, count) in metrics.iteration_duration_buckets() {
+        let _ = writeln!(
+            body,
+            "{}_bucket{{le=\"{}\"}} {}",
+            ITERATION_DURATION,
+            secs(bound),
+            count
+        );
+    }
+    let _ = writeln!(
+        body,
+        "{}_bucket{{le=\"+Inf\"}} {}",
+        ITERATION_DURATION,
+        metrics.iterations()
+    );
+    let _ = writeln!(
+        body,
+        "{}_sum {}",
+        ITERATION_DURATION,
+        secs(metrics.iteration_duration_sum())
+    );
+    let _ = writeln!(
+        body,
+        "{}_count {}",
+        ITERATION_DURATION,
+        metrics.iterations()
+    );
+
+    body
+}
+
+fn write_header(body: &mut String, name: &str, type_: &str, help: &str) {
+    let _ = writeln!(body, "# HELP {} {}", name, help);
+    let _ = writeln!(body, "# TYPE {} {}", name, type_);
+}
+
+fn secs(duration: Duration) -> String {
+    format!("{}.{:03}", duration.as_secs(), duration.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_http::route::Parameters;
+    use futures::Stream;
+
+    use super::*;
+
+    #[test]
+    fn watchdog_metrics_success() {
+        // arrange
+        let handler = GetWatchdogMetrics::new(WatchdogState::new());
+        let request = Request::get("http://localhost/watchdog/metrics")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            METRICS_CONTENT_TYPE,
+            response.headers().get(CONTENT_TYPE).unwrap()
+        );
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let body = String::from_utf8(b.to_vec()).unwrap();
+                assert!(body.contains("edgelet_watchdog_iterations_total 0\n"));
+                assert!(body.contains("edgelet_watchdog_restarts_total 0\n"));
+                assert!(body.contains(
+                    "edgelet_watchdog_iteration_duration_seconds_bucket{le=\"0.050\"} 0\n"
+                ));
+                assert!(body.contains("edgelet_watchdog_iteration_duration_seconds_sum 0.000\n"));
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright (c) Microsoft. All rights reserved.
+mod get;
+mod metrics;
+mod pause;
+mod resume;
+
+pub use self::get::GetWatchdog;
+pub use self::metrics::GetWatchdogMetrics;
+pub use self::pause::PauseWatchdog;
+pub use self::resume::ResumeWatchdog;
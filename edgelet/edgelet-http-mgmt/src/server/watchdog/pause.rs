@@ -0,0 +1,65 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::ResultExt;
+use futures::{future, Future};
+use hyper::{Body, Request, Response, StatusCode};
+
+use edgelet_core::watchdog::WatchdogState;
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct PauseWatchdog {
+    state: WatchdogState,
+}
+
+impl PauseWatchdog {
+    pub fn new(state: WatchdogState) -> Self {
+        PauseWatchdog { state }
+    }
+}
+
+impl Handler<Parameters> for PauseWatchdog {
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        info!("Pause watchdog");
+        self.state.pause();
+
+        let response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::default())
+            .context(ErrorKind::Watchdog)
+            .unwrap_or_else(|e| Error::from(e).into_response());
+
+        Box::new(future::ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_http::route::Parameters;
+
+    use super::*;
+
+    #[test]
+    fn pause_success() {
+        // arrange
+        let state = WatchdogState::new();
+        let handler = PauseWatchdog::new(state.clone());
+        let request = Request::post("http://localhost/watchdog/pause")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert!(state.is_paused());
+    }
+}
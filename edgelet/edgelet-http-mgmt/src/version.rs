@@ -0,0 +1,159 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{build_version, source_version};
+use edgelet_http::SUPPORTED_API_VERSIONS;
+use management::models::DaemonVersion;
+
+const VERSION_PATH: &str = "/version";
+
+/// Serves the version of the daemon and the API versions it supports on
+/// `GET /version` and passes every other request through to the upstream
+/// service. Like health probes, this is neither versioned nor authorized,
+/// so tooling can ask for it before it knows which api-version to use.
+#[derive(Clone)]
+pub struct VersionService<T> {
+    upstream: T,
+}
+
+impl<T> VersionService<T> {
+    pub fn new(upstream: T) -> Self {
+        VersionService { upstream }
+    }
+}
+
+fn daemon_version() -> DaemonVersion {
+    let api_versions = SUPPORTED_API_VERSIONS
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    let version = DaemonVersion::new(build_version().to_string(), api_versions);
+    match source_version() {
+        Some(commit) => version.with_commit(commit.to_string()),
+        None => version,
+    }
+}
+
+fn version_response() -> Response<Body> {
+    let body =
+        serde_json::to_string(&daemon_version()).expect("serialization of DaemonVersion failed.");
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, body.len().to_string().as_str())
+        .body(body.into())
+        .expect("response builder failure")
+}
+
+impl<T> Service for VersionService<T>
+where
+    T: Service<ResBody = Body>,
+    <T as Service>::Future: Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        if req.method() == Method::GET && req.uri().path() == VERSION_PATH {
+            Box::new(future::ok(version_response()))
+        } else {
+            Box::new(self.upstream.call(req))
+        }
+    }
+}
+
+impl<T> NewService for VersionService<T>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    VersionService<<T as NewService>::Service>: Service,
+{
+    type ReqBody = <VersionService<<T as NewService>::Service> as Service>::ReqBody;
+    type ResBody = <VersionService<<T as NewService>::Service> as Service>::ResBody;
+    type Error = <VersionService<<T as NewService>::Service> as Service>::Error;
+    type Service = VersionService<<T as NewService>::Service>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(|upstream| VersionService { upstream }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::FutureResult;
+    use futures::Stream;
+    use hyper::Error as HyperError;
+
+    use edgelet_http::API_VERSION;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = HyperError;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(
+                Response::builder()
+                    .status(StatusCode::IM_A_TEAPOT)
+                    .body(Body::default())
+                    .unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn version_returns_daemon_and_api_versions() {
+        let mut service = VersionService::new(TestService);
+        let req = Request::get("http://localhost/version")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let version: DaemonVersion = serde_json::from_slice(&b).unwrap();
+                assert_eq!(build_version(), version.version());
+                assert_eq!(source_version(), version.commit());
+                assert!(version.api_versions().contains(&API_VERSION.to_string()));
+                assert_eq!(SUPPORTED_API_VERSIONS.len(), version.api_versions().len());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn other_requests_are_passed_through() {
+        let mut service = VersionService::new(TestService);
+        let req = Request::get("http://localhost/modules")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use log::Level;
+use serde_json;
+
+use edgelet_core::{CheckHsmHealth, KeyIdentity, KeyStore};
+use edgelet_utils::log_failure;
+use workload::models::Health;
+
+const HEALTH_PATH: &str = "/health";
+
+// The device key the key store is probed with. Keys of the daemon's key store
+// are derived from the device identity key, so this needs no provisioning.
+const HEALTH_KEY_NAME: &str = "health";
+
+/// Serves the readiness of the workload API on `GET /health` and passes every
+/// other request through to the upstream service. Health probes are neither
+/// versioned nor authorized, so this is meant to wrap the `ApiVersionService`.
+///
+/// The workload API is only reported ready if the HSM answers its health
+/// probe and the key store can produce a device key.
+#[derive(Clone)]
+pub struct HealthService<T, K, H> {
+    key_store: K,
+    hsm: H,
+    upstream: T,
+}
+
+impl<T, K, H> HealthService<T, K, H> {
+    pub fn new(key_store: K, hsm: H, upstream: T) -> Self {
+        HealthService {
+            key_store,
+            hsm,
+            upstream,
+        }
+    }
+}
+
+fn status(healthy: bool) -> &'static str {
+    if healthy {
+        "healthy"
+    } else {
+        "unhealthy"
+    }
+}
+
+fn health_response<K, H>(key_store: &K, hsm: &H) -> Response<Body>
+where
+    K: KeyStore,
+    H: CheckHsmHealth,
+{
+    let hsm_healthy = match hsm.check_hsm_health() {
+        Ok(()) => true,
+        Err(err) => {
+            log_failure(Level::Warn, &err);
+            false
+        }
+    };
+    let key_store_healthy = match key_store.get(&KeyIdentity::Device, HEALTH_KEY_NAME) {
+        Ok(_) => true,
+        Err(err) => {
+            log_failure(Level::Warn, &err);
+            false
+        }
+    };
+    let healthy = hsm_healthy && key_store_healthy;
+    let status_code = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let health = Health::new(status(healthy).to_string())
+        .with_hsm(status(hsm_healthy).to_string())
+        .with_key_store(status(key_store_healthy).to_string());
+
+    let body = serde_json::to_string(&health).expect("serialization of Health failed.");
+    Response::builder()
+        .status(status_code)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, body.len().to_string().as_str())
+        .body(body.into())
+        .expect("response builder failure")
+}
+
+impl<T, K, H> Service for HealthService<T, K, H>
+where
+    T: Service<ResBody = Body>,
+    K: KeyStore,
+    H: CheckHsmHealth,
+    <T as Service>::Future: Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        if req.method() == Method::GET && req.uri().path() == HEALTH_PATH {
+            Box::new(future::ok(health_response(&self.key_store, &self.hsm)))
+        } else {
+            Box::new(self.upstream.call(req))
+        }
+    }
+}
+
+impl<T, K, H> NewService for HealthService<T, K, H>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    K: 'static + Clone + Send,
+    H: 'static + Clone + Send,
+    HealthService<<T as NewService>::Service, K, H>: Service,
+{
+    type ReqBody = <HealthService<<T as NewService>::Service, K, H> as Service>::ReqBody;
+    type ResBody = <HealthService<<T as NewService>::Service, K, H> as Service>::ResBody;
+    type Error = <HealthService<<T as NewService>::Service, K, H> as Service>::Error;
+    type Service = HealthService<<T as NewService>::Service, K, H>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        let key_store = self.key_store.clone();
+        let hsm = self.hsm.clone();
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(move |upstream| HealthService {
+                    key_store,
+                    hsm,
+                    upstream,
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::FutureResult;
+    use futures::Stream;
+    use hyper::Error as HyperError;
+
+    use edgelet_core::crypto::{MemoryKey, MemoryKeyStore};
+    use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestHsm {
+        healthy: bool,
+    }
+
+    impl CheckHsmHealth for TestHsm {
+        fn check_hsm_health(&self) -> Result<(), CoreError> {
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(CoreError::from(CoreErrorKind::HsmHealthCheck))
+            }
+        }
+    }
+
+    const HEALTHY_HSM: TestHsm = TestHsm { healthy: true };
+
+    fn initialized_key_store() -> MemoryKeyStore {
+        let mut key_store = MemoryKeyStore::new();
+        key_store.insert(&KeyIdentity::Device, HEALTH_KEY_NAME, MemoryKey::new("key"));
+        key_store
+    }
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = HyperError;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(
+                Response::builder()
+                    .status(StatusCode::IM_A_TEAPOT)
+                    .body(Body::default())
+                    .unwrap(),
+            )
+        }
+    }
+
+    fn health<T: Service<ReqBody = Body, ResBody = Body>>(service: &mut T) -> (StatusCode, Health) {
+        let req = Request::get("http://localhost/health")
+            .body(Body::default())
+            .unwrap();
+        let response = Service::call(service, req).map_err(|_| ()).wait().unwrap();
+        let status = response.status();
+        let body = response.into_body().concat2().wait().unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn initialized_workload_api_returns_ok() {
+        let mut service = HealthService::new(initialized_key_store(), HEALTHY_HSM, TestService);
+
+        let (status, health) = health(&mut service);
+
+        assert_eq!(StatusCode::OK, status);
+        assert_eq!("healthy", health.status());
+        assert_eq!(Some("healthy"), health.hsm());
+        assert_eq!(Some("healthy"), health.key_store());
+    }
+
+    #[test]
+    fn uninitialized_key_store_returns_service_unavailable() {
+        let mut service = HealthService::new(MemoryKeyStore::new(), HEALTHY_HSM, TestService);
+
+        let (status, health) = health(&mut service);
+
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+        assert_eq!("unhealthy", health.status());
+        assert_eq!(Some("healthy"), health.hsm());
+        assert_eq!(Some("unhealthy"), health.key_store());
+    }
+
+    #[test]
+    fn unhealthy_hsm_returns_service_unavailable() {
+        let mut service = HealthService::new(
+            initialized_key_store(),
+            TestHsm { healthy: false },
+            TestService,
+        );
+
+        let (status, health) = health(&mut service);
+
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+        assert_eq!("unhealthy", health.status());
+        assert_eq!(Some("unhealthy"), health.hsm());
+        assert_eq!(Some("healthy"), health.key_store());
+    }
+
+    #[test]
+    fn other_requests_are_passed_through() {
+        let mut service = HealthService::new(MemoryKeyStore::new(), HEALTHY_HSM, TestService);
+        let req = Request::get("http://localhost/trust-bundle")
+            .body(Body::default())
+            .unwrap();
+
+        let response = Service::call(&mut service, req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+    }
+}
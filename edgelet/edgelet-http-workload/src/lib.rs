@@ -30,8 +30,10 @@ extern crate workload;
 use hyper::{Body, Response};
 
 mod error;
+mod health;
 mod server;
 
+pub use health::HealthService;
 pub use server::WorkloadService;
 
 pub trait IntoResponse {
@@ -6,6 +6,7 @@ use futures::{future, Future, IntoFuture, Stream};
 use hyper::{Body, Request, Response};
 use serde_json;
 
+use edgelet_core::certificate_renewal::IssuedCertificates;
 use edgelet_core::{
     Certificate, CertificateProperties, CertificateType, CreateCertificate, WorkloadConfig,
 };
@@ -20,11 +21,23 @@ use IntoResponse;
 pub struct ServerCertHandler<T: CreateCertificate, W: WorkloadConfig> {
     hsm: T,
     config: W,
+    issued: IssuedCertificates,
 }
 
 impl<T: CreateCertificate, W: WorkloadConfig> ServerCertHandler<T, W> {
     pub fn new(hsm: T, config: W) -> Self {
-        ServerCertHandler { hsm, config }
+        ServerCertHandler {
+            hsm,
+            config,
+            issued: IssuedCertificates::new(),
+        }
+    }
+
+    /// Records the issued certificates in `issued`, so that they can be
+    /// renewed before they expire.
+    pub fn with_issued_certificates(mut self, issued: IssuedCertificates) -> Self {
+        self.issued = issued;
+        self
     }
 }
 impl<T, W> Handler<Parameters> for ServerCertHandler<T, W>
@@ -39,6 +52,7 @@ where
         params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
         let hsm = self.hsm.clone();
+        let issued = self.issued.clone();
         let cfg = self.config.clone();
         let max_duration = cfg.get_cert_max_duration(CertificateType::Server);
 
@@ -94,6 +108,7 @@ where
                     &props,
                     ErrorKind::CertOperation(CertOperation::GetServerCert),
                 )?;
+                issued.insert(props);
                 Ok(body)
             })
             .or_else(|e| future::ok(e.into_response()));
@@ -575,6 +590,38 @@ mod tests {
         assert_eq!(Some("Betelgeuse"), cert_resp.private_key().bytes());
     }
 
+    #[test]
+    fn succeeds_records_issued_certificate() {
+        let issued = IssuedCertificates::new();
+        let handler = ServerCertHandler::new(
+            TestHsm::default().with_on_create(|_| {
+                Ok(TestCert::default()
+                    .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
+            }),
+            TestWorkloadData::default(),
+        )
+        .with_issued_certificates(issued.clone());
+
+        let cert_req = ServerCertificateRequest::new(
+            "marvin".to_string(),
+            (Utc::now() + Duration::hours(1)).to_rfc3339(),
+        );
+
+        let request =
+            Request::get("http://localhost/modules/beeblebrox/genid/I/certificate/server")
+                .body(serde_json::to_string(&cert_req).unwrap().into())
+                .unwrap();
+
+        let params = Parameters::with_captures(vec![
+            (Some("name".to_string()), "beeblebrox".to_string()),
+            (Some("genid".to_string()), "I".to_string()),
+        ]);
+        let response = handler.handle(request, params).wait().unwrap();
+
+        assert_eq!(StatusCode::CREATED, response.status());
+        assert_eq!(1, issued.len());
+    }
+
     #[test]
     fn succeeds_ref() {
         let handler = ServerCertHandler::new(
@@ -85,15 +85,16 @@ where
 
 #[cfg(test)]
 mod tests {
-    use edgelet_core::Decrypt;
     use edgelet_core::Error as CoreError;
+    use edgelet_core::{Decrypt, Encrypt, ErrorKind as CoreErrorKind};
     use edgelet_http::route::Parameters;
     use futures::Future;
     use hyper::{Request, StatusCode};
-    use workload::models::DecryptResponse;
     use workload::models::ErrorResponse;
+    use workload::models::{DecryptResponse, EncryptRequest, EncryptResponse};
 
     use super::*;
+    use server::encrypt::EncryptHandler;
 
     #[derive(Clone, Debug, Default)]
     struct TestHsm {}
@@ -115,6 +116,86 @@ mod tests {
         }
     }
 
+    // "Encrypts" by prefixing the plaintext with the client ID, and only
+    // "decrypts" ciphertext that was encrypted for the same client ID, like
+    // the HSM does with the master encryption key.
+    #[derive(Clone, Debug, Default)]
+    struct ScopedHsm {}
+
+    impl Encrypt for ScopedHsm {
+        type Buffer = Vec<u8>;
+
+        fn encrypt(
+            &self,
+            client_id: &[u8],
+            plaintext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, CoreError> {
+            let mut ciphertext = client_id.to_vec();
+            ciphertext.extend_from_slice(plaintext);
+            Ok(ciphertext)
+        }
+    }
+
+    impl Decrypt for ScopedHsm {
+        type Buffer = Vec<u8>;
+
+        fn decrypt(
+            &self,
+            client_id: &[u8],
+            ciphertext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, CoreError> {
+            if ciphertext.starts_with(client_id) {
+                Ok(ciphertext[client_id.len()..].to_vec())
+            } else {
+                Err(CoreError::from(CoreErrorKind::KeyStore))
+            }
+        }
+    }
+
+    fn module_params(name: &str) -> Parameters {
+        Parameters::with_captures(vec![
+            (Some("name".to_string()), name.to_string()),
+            (Some("genid".to_string()), "I".to_string()),
+        ])
+    }
+
+    fn encrypt_for_module(name: &str, plaintext: &str) -> String {
+        let request = EncryptRequest::new(base64::encode(plaintext), b64_text!());
+        let request = Request::builder()
+            .body(serde_json::to_string(&request).unwrap().into())
+            .unwrap();
+        let response = EncryptHandler::new(ScopedHsm::default())
+            .handle(request, module_params(name))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        response
+            .into_body()
+            .concat2()
+            .map(|b| {
+                serde_json::from_slice::<EncryptResponse>(&b)
+                    .unwrap()
+                    .ciphertext()
+                    .to_string()
+            })
+            .wait()
+            .unwrap()
+    }
+
+    fn decrypt_for_module(name: &str, ciphertext: String) -> Response<Body> {
+        let request = DecryptRequest::new(ciphertext, b64_text!());
+        let request = Request::builder()
+            .body(serde_json::to_string(&request).unwrap().into())
+            .unwrap();
+        DecryptHandler::new(ScopedHsm::default())
+            .handle(request, module_params(name))
+            .wait()
+            .unwrap()
+    }
+
     fn create_args(
         request: Option<&DecryptRequest>,
         params: Option<Vec<(Option<String>, String)>>,
@@ -301,4 +382,33 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn module_decrypts_its_own_ciphertext() {
+        let ciphertext = encrypt_for_module("module1", "secret");
+
+        let response = decrypt_for_module("module1", ciphertext);
+
+        assert_eq!(StatusCode::OK, response.status());
+        let body = response
+            .into_body()
+            .concat2()
+            .map(move |b| serde_json::from_slice::<DecryptResponse>(&b).unwrap())
+            .wait()
+            .unwrap();
+        assert_eq!(base64::encode("secret"), body.plaintext().to_string());
+    }
+
+    #[test]
+    fn module_cannot_decrypt_ciphertext_of_another_module() {
+        let ciphertext = encrypt_for_module("module1", "secret");
+
+        let response = decrypt_for_module("module2", ciphertext);
+
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        assert_response_message_eq(
+            "Could not decrypt\n\tcaused by: A error occurred in the key store.",
+            response,
+        );
+    }
 }
@@ -6,6 +6,7 @@ mod encrypt;
 mod sign;
 mod trust_bundle;
 
+use edgelet_core::certificate_renewal::IssuedCertificates;
 use edgelet_core::{
     CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyStore, Module, ModuleRuntime, Policy,
     WorkloadConfig,
@@ -39,6 +40,7 @@ impl WorkloadService {
         hsm: H,
         runtime: &M,
         config: W,
+        issued_certs: IssuedCertificates,
     ) -> impl Future<Item = Self, Error = Error>
     where
         K: KeyStore + Clone + Send + Sync + 'static,
@@ -54,7 +56,7 @@ impl WorkloadService {
             post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/decrypt" => Authorization::new(DecryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
             post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/encrypt" => Authorization::new(EncryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
             post   "/modules/(?P<name>[^/]+)/certificate/identity" => Authorization::new(IdentityCertHandler::new(hsm.clone(), config.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone(), config), Policy::Caller, runtime.clone()),
+            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone(), config).with_issued_certificates(issued_certs), Policy::Caller, runtime.clone()),
 
             get    "/trust-bundle" => Authorization::new(TrustBundleHandler::new(hsm), Policy::Anonymous, runtime.clone()),
         );
@@ -40,8 +40,14 @@ pub fn sign<K: KeyStore>(
         .get(&KeyIdentity::Module(id.clone()), request.key_id())
         .context(ErrorKind::ModuleNotFound(id))?;
     let data: Vec<u8> = base64::decode(request.data()).context(ErrorKind::MalformedRequestBody)?;
+    // The algorithm used to be ignored, so requests that don't name one of the
+    // supported algorithms keep being signed with the default.
+    let signature_algorithm = request
+        .algo()
+        .parse::<SignatureAlgorithm>()
+        .unwrap_or_default();
     let signature = k
-        .sign(SignatureAlgorithm::HMACSHA256, &data)
+        .sign(signature_algorithm, &data)
         .context(ErrorKind::EncryptionOperation(EncryptionOperation::Sign))?;
     let encoded = base64::encode(signature.as_bytes());
     Ok(SignResponse::new(encoded))
@@ -219,6 +225,46 @@ mod tests {
         assert_eq!(state.last_key_name, "primaryg1");
     }
 
+    #[test]
+    fn success_with_signature_algorithm() {
+        // arrange
+        let key = MemoryKey::new("key");
+        let store = TestKeyStore::new(key);
+        let handler = SignHandler::new(store);
+
+        let sign_request = SignRequest::new(
+            "primary".to_string(),
+            "HMACSHA384".to_string(),
+            base64::encode("The quick brown fox jumps over the lazy dog"),
+        );
+        let body = serde_json::to_string(&sign_request).unwrap();
+
+        let parameters = Parameters::with_captures(vec![
+            (Some("name".to_string()), "test".to_string()),
+            (Some("genid".to_string()), "g1".to_string()),
+        ]);
+        let request = Request::post("http://localhost/modules/name/sign")
+            .body(body.into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        let expected = "1/RyfiwLOa4PHkDMlvYCQtW3gBhBzqb8WSxdPhrlBwBYKpbPNeHlVJlf5OAzgcI3";
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let sign_response: SignResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(expected, sign_response.digest());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
     #[test]
     fn not_found() {
         // arrange
@@ -4,7 +4,7 @@ use std::str;
 
 use failure::ResultExt;
 use futures::{Future, IntoFuture};
-use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE};
 use hyper::{Body, Request, Response, StatusCode};
 use serde_json;
 
@@ -16,6 +16,10 @@ use workload::models::TrustBundleResponse;
 use error::{EncryptionOperation, Error, ErrorKind};
 use IntoResponse;
 
+/// Media type of the trust bundle when it is requested as a bare PEM file
+/// rather than wrapped in a JSON response.
+const PEM_CONTENT_TYPE: &str = "application/x-pem-file";
+
 pub struct TrustBundleHandler<T: GetTrustBundle> {
     hsm: T,
 }
@@ -35,9 +39,22 @@ where
 {
     fn handle(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
         _params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let pem_requested = req
+            .headers()
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|media_type| {
+                media_type
+                    .split(';')
+                    .next()
+                    .map_or(false, |media_type| media_type.trim() == PEM_CONTENT_TYPE)
+            });
+
         let response = self
             .hsm
             .get_trust_bundle()
@@ -54,12 +71,17 @@ where
                         EncryptionOperation::GetTrustBundle,
                     ))?
                     .to_string();
-                let body = serde_json::to_string(&TrustBundleResponse::new(cert)).context(
-                    ErrorKind::EncryptionOperation(EncryptionOperation::GetTrustBundle),
-                )?;
+                let (content_type, body) = if pem_requested {
+                    (PEM_CONTENT_TYPE, cert)
+                } else {
+                    let body = serde_json::to_string(&TrustBundleResponse::new(cert)).context(
+                        ErrorKind::EncryptionOperation(EncryptionOperation::GetTrustBundle),
+                    )?;
+                    ("application/json", body)
+                };
                 let response = Response::builder()
                     .status(StatusCode::OK)
-                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_TYPE, content_type)
                     .header(CONTENT_LENGTH, body.len().to_string().as_str())
                     .body(body.into())
                     .context(ErrorKind::EncryptionOperation(
@@ -79,11 +101,59 @@ mod tests {
     use futures::Future;
     use futures::Stream;
 
+    use base64;
     use edgelet_core::{Error as CoreError, ErrorKind as CoreErrorKind};
     use edgelet_test_utils::cert::TestCert;
 
     use super::*;
 
+    // A trust bundle made of an owner CA and the device CA it issued.
+    const OWNER_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBkjCCATegAwIBAgIUcQlla3jqqWR3Z7fcUSXSvYkVdQMwCgYIKoZIzj0EAwIw
+HTEbMBkGA1UEAwwSVGVzdCBFZGdlIE93bmVyIENBMCAXDTI2MTAxNDA2MzI0MloY
+DzIxMjYwOTIwMDYzMjQyWjAdMRswGQYDVQQDDBJUZXN0IEVkZ2UgT3duZXIgQ0Ew
+WTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARvmw3JxBCIDphP5MPN/tOs6ZH40q0/
+NP5ZMakDqdYC+BfcjyFsrdLsgj3VKDSmVFBbq2KPLFbcnDu5wZpaBqdYo1MwUTAd
+BgNVHQ4EFgQUxKx6HpjC7/k7HCV0n/KzMf+r3WIwHwYDVR0jBBgwFoAUxKx6HpjC
+7/k7HCV0n/KzMf+r3WIwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBG
+AiEAvDnwUw+FP93I//02OUHGY3g9ts5i8/3+fH4JXf7g3YICIQDKKqu+OIxkiZzo
+YDjN1sFqsqCIw1WofH+kG8osGUgEhQ==
+-----END CERTIFICATE-----
+";
+
+    const DEVICE_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBkzCCATigAwIBAgIUbzUyXVCkfCvvDh8YCoXjx0qw6AEwCgYIKoZIzj0EAwIw
+HTEbMBkGA1UEAwwSVGVzdCBFZGdlIE93bmVyIENBMCAXDTI2MTAxNDA2MzI0MloY
+DzIxMjYwOTIwMDYzMjQyWjAeMRwwGgYDVQQDDBNUZXN0IEVkZ2UgRGV2aWNlIENB
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE+TpwVhphaMEyldvROxU4KfLxek3C
+NRDj68OsaDYNT9oXq/asJgILQ+JGj8DWxscmsrzCj3O27gXmKIQ9/xVd76NTMFEw
+DwYDVR0TAQH/BAUwAwEB/zAdBgNVHQ4EFgQUBAbTLe/z3bk6lb1oLYVfoBCyvU4w
+HwYDVR0jBBgwFoAUxKx6HpjC7/k7HCV0n/KzMf+r3WIwCgYIKoZIzj0EAwIDSQAw
+RgIhANoBj8Pqq37zry/wj9O6q087HdWcEMYr/PvS50vbXRm6AiEA/4QjaPUKbFoL
+DCjkRcpWZSQLeBYJZysQGWfmSQJczOM=
+-----END CERTIFICATE-----
+";
+
+    // Splits a PEM bundle into the DER of its certificates, failing the test
+    // if it isn't made of well-formed certificate blocks only.
+    fn parse_pem_bundle(bundle: &str) -> Vec<Vec<u8>> {
+        let mut certs = vec![];
+        let mut lines = bundle.lines().filter(|line| !line.trim().is_empty());
+        while let Some(begin) = lines.next() {
+            assert_eq!("-----BEGIN CERTIFICATE-----", begin);
+            let encoded: String = lines
+                .by_ref()
+                .take_while(|line| *line != "-----END CERTIFICATE-----")
+                .collect();
+            certs.push(base64::decode(&encoded).unwrap());
+        }
+        certs
+    }
+
+    fn trust_bundle() -> String {
+        format!("{}{}", OWNER_CA, DEVICE_CA)
+    }
+
     #[derive(Clone, Default, Debug)]
     struct TestHsm {
         fail_call: bool,
@@ -183,4 +253,83 @@ mod tests {
             .wait()
             .unwrap();
     }
+
+    #[test]
+    fn success_pem() {
+        let handler = TrustBundleHandler::new(
+            TestHsm::default()
+                .with_cert(TestCert::default().with_cert(trust_bundle().into_bytes())),
+        );
+        let request = Request::get("http://localhost/trust-bundle")
+            .header(ACCEPT, "application/x-pem-file")
+            .body("".into())
+            .unwrap();
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content_length = {
+            let headers = response.headers();
+            assert_eq!(
+                headers.get(CONTENT_TYPE).unwrap(),
+                &"application/x-pem-file"
+            );
+
+            headers
+                .get(CONTENT_LENGTH)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                assert_eq!(content_length, b.len().to_string());
+                let bundle = str::from_utf8(&b).unwrap();
+                let certs = parse_pem_bundle(bundle);
+                assert_eq!(2, certs.len());
+                assert!(certs.contains(&parse_pem_bundle(DEVICE_CA).remove(0)));
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn success_json_contains_device_ca() {
+        let handler = TrustBundleHandler::new(
+            TestHsm::default()
+                .with_cert(TestCert::default().with_cert(trust_bundle().into_bytes())),
+        );
+        let request = Request::get("http://localhost/trust-bundle")
+            .header(ACCEPT, "application/json")
+            .body("".into())
+            .unwrap();
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            &"application/json"
+        );
+
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let trust_bundle: TrustBundleResponse = serde_json::from_slice(&b).unwrap();
+                let certs = parse_pem_bundle(trust_bundle.certificate());
+                assert_eq!(
+                    vec![
+                        parse_pem_bundle(OWNER_CA).remove(0),
+                        parse_pem_bundle(DEVICE_CA).remove(0),
+                    ],
+                    certs
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
 }
@@ -270,6 +270,7 @@ mod tests {
         type Error = Error;
         type PullFuture = FutureResult<(), Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type PruneFuture = FutureResult<(), Self::Error>;
 
         fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
             notimpl_error!()
@@ -277,6 +278,9 @@ mod tests {
         fn remove(&self, _name: &str) -> Self::RemoveFuture {
             notimpl_error!()
         }
+        fn prune(&self) -> Self::PruneFuture {
+            notimpl_error!()
+        }
     }
 
     impl ModuleRuntime for TestModuleList {
@@ -298,6 +302,7 @@ mod tests {
         type StopFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type PruneVolumesFuture = FutureResult<(), Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             notimpl_error!()
@@ -351,5 +356,9 @@ mod tests {
         fn remove_all(&self) -> Self::RemoveAllFuture {
             notimpl_error!()
         }
+
+        fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+            notimpl_error!()
+        }
     }
 }
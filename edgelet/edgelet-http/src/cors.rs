@@ -0,0 +1,287 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::Arc;
+
+use futures::{future, Future};
+use hyper::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+    ORIGIN, VARY,
+};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Method, Request, Response, StatusCode};
+
+const ANY_ORIGIN: &str = "*";
+
+// The origins and methods cross-origin requests are allowed for.
+#[derive(Debug)]
+struct CorsPolicy {
+    origins: Vec<String>,
+    methods: Vec<Method>,
+}
+
+impl CorsPolicy {
+    // The value of the `Access-Control-Allow-Origin` header for a request
+    // from `origin`, if it is allowed.
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if self.origins.iter().any(|o| o == ANY_ORIGIN) {
+            Some(HeaderValue::from_static(ANY_ORIGIN))
+        } else {
+            let origin_str = origin.to_str().ok()?;
+            if self.origins.iter().any(|o| o == origin_str) {
+                Some(origin.clone())
+            } else {
+                None
+            }
+        }
+    }
+
+    fn preflight(
+        &self,
+        allow_origin: HeaderValue,
+        method: &HeaderValue,
+        headers: Option<&HeaderValue>,
+    ) -> Response<Body> {
+        let allowed = Method::from_bytes(method.as_bytes())
+            .map(|method| self.methods.contains(&method))
+            .unwrap_or(false);
+        if !allowed {
+            return Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Body::empty())
+                .expect("response builder failure");
+        }
+
+        let methods = self
+            .methods
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut response = Response::builder();
+        response
+            .status(StatusCode::NO_CONTENT)
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin)
+            .header(ACCESS_CONTROL_ALLOW_METHODS, methods.as_str())
+            .header(VARY, "Origin");
+        if let Some(headers) = headers {
+            response.header(ACCESS_CONTROL_ALLOW_HEADERS, headers.clone());
+        }
+        response
+            .body(Body::empty())
+            .expect("response builder failure")
+    }
+}
+
+/// Lets browsers call the upstream service from other origins. Preflight
+/// requests from the allowed origins are answered here, and the responses to
+/// their other requests get the CORS headers. Requests from other origins
+/// are passed through untouched, as are all requests if no policy is set.
+#[derive(Clone)]
+pub struct CorsService<T> {
+    upstream: T,
+    policy: Option<Arc<CorsPolicy>>,
+}
+
+impl<T> CorsService<T> {
+    pub fn new(upstream: T) -> Self {
+        CorsService {
+            upstream,
+            policy: None,
+        }
+    }
+
+    /// Allows requests with any of `methods` from any of `origins`, which are
+    /// compared to the `Origin` header as is. An origin of `*` allows all.
+    pub fn with_policy(mut self, origins: Vec<String>, methods: Vec<Method>) -> Self {
+        self.policy = Some(Arc::new(CorsPolicy { origins, methods }));
+        self
+    }
+}
+
+impl<T> Service for CorsService<T>
+where
+    T: Service<ResBody = Body>,
+    <T as Service>::Future: Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        let allow_origin = self.policy.as_ref().and_then(|policy| {
+            req.headers()
+                .get(ORIGIN)
+                .and_then(|origin| policy.allow_origin(origin))
+                .map(|allow_origin| (policy.clone(), allow_origin))
+        });
+        let (policy, allow_origin) = match allow_origin {
+            Some(allowed) => allowed,
+            None => return Box::new(self.upstream.call(req)),
+        };
+
+        if req.method() == Method::OPTIONS {
+            if let Some(method) = req.headers().get(ACCESS_CONTROL_REQUEST_METHOD) {
+                return Box::new(future::ok(policy.preflight(
+                    allow_origin,
+                    method,
+                    req.headers().get(ACCESS_CONTROL_REQUEST_HEADERS),
+                )));
+            }
+        }
+
+        Box::new(self.upstream.call(req).map(move |mut response| {
+            response
+                .headers_mut()
+                .insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+            response
+                .headers_mut()
+                .append(VARY, HeaderValue::from_static("Origin"));
+            response
+        }))
+    }
+}
+
+impl<T> NewService for CorsService<T>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    CorsService<<T as NewService>::Service>: Service,
+{
+    type ReqBody = <CorsService<<T as NewService>::Service> as Service>::ReqBody;
+    type ResBody = <CorsService<<T as NewService>::Service> as Service>::ResBody;
+    type Error = <CorsService<<T as NewService>::Service> as Service>::Error;
+    type Service = CorsService<<T as NewService>::Service>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        let policy = self.policy.clone();
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(move |upstream| CorsService { upstream, policy }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::FutureResult;
+    use hyper::Error as HyperError;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = HyperError;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(
+                Response::builder()
+                    .status(StatusCode::IM_A_TEAPOT)
+                    .body(Body::default())
+                    .unwrap(),
+            )
+        }
+    }
+
+    fn service() -> CorsService<TestService> {
+        CorsService::new(TestService).with_policy(
+            vec!["https://dashboard.example.com".to_string()],
+            vec![Method::GET, Method::POST],
+        )
+    }
+
+    fn preflight(origin: &str, method: &str) -> Request<Body> {
+        Request::options("http://localhost/modules")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, method)
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+            .body(Body::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn preflight_from_allowed_origin_succeeds() {
+        let req = preflight("https://dashboard.example.com", "POST");
+
+        let response = service().call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        let headers = response.headers();
+        assert_eq!(
+            "https://dashboard.example.com",
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN]
+        );
+        assert_eq!("GET, POST", headers[ACCESS_CONTROL_ALLOW_METHODS]);
+        assert_eq!("content-type", headers[ACCESS_CONTROL_ALLOW_HEADERS]);
+        assert_eq!("Origin", headers[VARY]);
+    }
+
+    #[test]
+    fn preflight_for_disallowed_method_is_forbidden() {
+        let req = preflight("https://dashboard.example.com", "DELETE");
+
+        let response = service().call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[test]
+    fn requests_from_other_origins_are_passed_through() {
+        let req = preflight("https://evil.example.com", "GET");
+
+        let response = service().call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[test]
+    fn responses_to_allowed_origin_have_cors_headers() {
+        let req = Request::get("http://localhost/modules")
+            .header(ORIGIN, "https://dashboard.example.com")
+            .body(Body::default())
+            .unwrap();
+
+        let response = service().call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+        assert_eq!(
+            "https://dashboard.example.com",
+            response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN]
+        );
+        assert_eq!("Origin", response.headers()[VARY]);
+    }
+
+    #[test]
+    fn any_origin_is_allowed_with_wildcard() {
+        let mut service = CorsService::new(TestService)
+            .with_policy(vec![ANY_ORIGIN.to_string()], vec![Method::GET]);
+        let req = preflight("https://other.example.com", "GET");
+
+        let response = service.call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!("*", response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN]);
+    }
+
+    #[test]
+    fn requests_are_passed_through_without_policy() {
+        let mut service = CorsService::new(TestService);
+        let req = preflight("https://dashboard.example.com", "GET");
+
+        let response = service.call(req).wait().unwrap();
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
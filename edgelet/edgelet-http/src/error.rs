@@ -37,6 +37,12 @@ pub enum ErrorKind {
     #[fail(display = "Invalid API version {:?}", _0)]
     InvalidApiVersion(String),
 
+    #[fail(display = "Invalid CA certificate")]
+    InvalidCaCertificate,
+
+    #[fail(display = "Invalid server certificate")]
+    InvalidServerCertificate,
+
     #[fail(display = "Invalid URL {:?}", _0)]
     InvalidUrl(String),
 
@@ -68,6 +74,12 @@ pub enum ErrorKind {
     #[fail(display = "Token source error")]
     TokenSource,
 
+    #[fail(display = "Too many requests")]
+    TooManyRequests,
+
+    #[fail(display = "The request does not carry a valid bearer token")]
+    Unauthorized,
+
     #[fail(
         display = "Could not form well-formed URL by joining {:?} with {:?}",
         _0, _1
@@ -135,6 +147,8 @@ impl IntoResponse for Error {
         let status_code = match *self.kind() {
             ErrorKind::Authorization | ErrorKind::ModuleNotFound(_) => StatusCode::NOT_FOUND,
             ErrorKind::InvalidApiVersion(_) => StatusCode::BAD_REQUEST,
+            ErrorKind::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -179,6 +193,7 @@ pub enum InvalidUrlReason {
     FileNotFound,
     InvalidScheme,
     InvalidCredentials,
+    InvalidPipePath,
     NoAddress,
     NoHost,
     UnrecognizedSocket,
@@ -195,6 +210,10 @@ impl Display for InvalidUrlReason {
             InvalidUrlReason::InvalidCredentials => {
                 write!(f, "Username or password could not be parsed from URL")
             }
+            InvalidUrlReason::InvalidPipePath => write!(
+                f,
+                "URL does not name a pipe, it must be of the form npipe://<host>/pipe/<name>"
+            ),
             InvalidUrlReason::NoAddress => write!(f, "URL has no address"),
             InvalidUrlReason::NoHost => write!(f, "URL has no host"),
             InvalidUrlReason::UnrecognizedSocket => {
@@ -0,0 +1,120 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io;
+#[cfg(target_os = "linux")]
+use std::mem;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use libc;
+use tokio::net::TcpStream;
+
+/// TCP keepalive probing of the connections accepted by a server. Probes
+/// are sent once a connection has been idle for `idle`, and then every
+/// `interval` until the peer answers. The interval of the system is used
+/// if it is not set, and on platforms other than Linux.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TcpKeepalive {
+    idle: Duration,
+    interval: Option<Duration>,
+}
+
+impl TcpKeepalive {
+    pub fn new(idle: Duration, interval: Option<Duration>) -> Self {
+        TcpKeepalive { idle, interval }
+    }
+
+    pub(crate) fn apply(&self, stream: &TcpStream) -> io::Result<()> {
+        stream.set_keepalive(Some(self.idle))?;
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(interval) = self.interval {
+                set_interval(stream, interval)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(
+    feature = "cargo-clippy",
+    allow(cast_possible_truncation, cast_possible_wrap)
+)]
+fn set_interval(stream: &TcpStream, interval: Duration) -> io::Result<()> {
+    let secs = interval.as_secs().min(libc::c_int::max_value() as u64) as libc::c_int;
+    let ret = unsafe {
+        libc::setsockopt(
+            stream.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_KEEPINTVL,
+            &secs as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener as StdTcpListener, TcpStream as StdTcpStream};
+
+    use super::*;
+    use util::StreamSelector;
+
+    fn accepted_stream() -> (StdTcpStream, TcpStream) {
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let client = StdTcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        (
+            client,
+            TcpStream::from_std(stream, &Default::default()).unwrap(),
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn interval(stream: &TcpStream) -> libc::c_int {
+        let mut secs: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::IPPROTO_TCP,
+                libc::TCP_KEEPINTVL,
+                &mut secs as *mut libc::c_int as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        assert_eq!(0, ret);
+        secs
+    }
+
+    #[test]
+    fn keepalive_is_enabled_on_accepted_connection() {
+        let (_client, stream) = accepted_stream();
+        assert_eq!(None, stream.keepalive().unwrap());
+
+        let socket = StreamSelector::Tcp(stream);
+        socket
+            .set_tcp_keepalive(&TcpKeepalive::new(
+                Duration::from_secs(30),
+                Some(Duration::from_secs(5)),
+            ))
+            .unwrap();
+
+        if let StreamSelector::Tcp(ref stream) = socket {
+            assert_eq!(Some(Duration::from_secs(30)), stream.keepalive().unwrap());
+            #[cfg(target_os = "linux")]
+            assert_eq!(5, interval(stream));
+        } else {
+            panic!("not a TCP stream");
+        }
+    }
+}
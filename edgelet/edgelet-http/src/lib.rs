@@ -33,8 +33,11 @@ extern crate libc;
 extern crate log;
 #[cfg(windows)]
 extern crate mio_uds_windows;
+extern crate native_tls;
+extern crate net2;
 #[cfg(unix)]
 extern crate nix;
+extern crate openssl;
 extern crate percent_encoding;
 extern crate regex;
 #[cfg(unix)]
@@ -52,6 +55,8 @@ extern crate tempfile;
 extern crate tokio;
 #[cfg(windows)]
 extern crate tokio_named_pipe;
+extern crate tokio_threadpool;
+extern crate tokio_tls;
 #[cfg(unix)]
 extern crate tokio_uds;
 #[cfg(windows)]
@@ -63,23 +68,33 @@ extern crate winapi;
 
 extern crate edgelet_utils;
 
+use std::io;
 #[cfg(unix)]
 use std::net;
-use std::net::ToSocketAddrs;
+use std::net::{SocketAddr, ToSocketAddrs};
 #[cfg(unix)]
 use std::os::unix::io::FromRawFd;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use failure::{Fail, ResultExt};
+use futures::future::Either;
 use futures::{future, Future, Poll, Stream};
 use hyper::server::conn::Http;
 use hyper::service::{NewService, Service};
 use hyper::{Body, Response};
 use log::Level;
+use net2::TcpBuilder;
+#[cfg(unix)]
+use nix::sys::socket::{self, SockAddr};
 #[cfg(unix)]
 use systemd::Socket;
 use tokio::net::TcpListener;
+#[cfg(windows)]
+use tokio_named_pipe::PipeListener;
+use tokio_threadpool::Builder as ThreadPoolBuilder;
+use tokio_tls::TlsAcceptor;
 #[cfg(unix)]
 use tokio_uds::UnixListener;
 use url::Url;
@@ -88,27 +103,41 @@ use edgelet_utils::log_failure;
 
 pub mod authorization;
 pub mod client;
+mod cors;
 pub mod error;
+mod keepalive;
 pub mod logging;
 mod pid;
+mod rate_limit;
 pub mod route;
+mod tls;
+mod token_auth;
 mod unix;
 mod util;
 mod version;
 
+pub use self::cors::CorsService;
 pub use self::error::{BindListenerType, Error, ErrorKind, InvalidUrlReason};
+pub use self::keepalive::TcpKeepalive;
+pub use self::rate_limit::RateLimitService;
+pub use self::tls::tls_acceptor;
+pub use self::token_auth::TokenAuthService;
 pub use self::util::proxy::MaybeProxyClient;
 pub use self::util::UrlConnector;
-pub use self::version::{ApiVersionService, API_VERSION};
+pub use self::version::{ApiVersionService, API_VERSION, SUPPORTED_API_VERSIONS};
 
 use self::pid::PidService;
 use self::util::incoming::Incoming;
+use self::util::IncomingSocketAddr;
 
 const HTTP_SCHEME: &str = "http";
 const TCP_SCHEME: &str = "tcp";
 const UNIX_SCHEME: &str = "unix";
 #[cfg(unix)]
 const FD_SCHEME: &str = "fd";
+#[cfg(windows)]
+const PIPE_SCHEME: &str = "npipe";
+const WORKER_THREAD_NAME_PREFIX: &str = "http-worker-";
 
 pub trait IntoResponse {
     fn into_response(self) -> Response<Body>;
@@ -135,6 +164,74 @@ pub struct Server<S> {
     protocol: Http,
     new_service: S,
     incoming: Incoming,
+    worker_threads: usize,
+    tls_acceptor: Option<TlsAcceptor>,
+    backlog: Option<i32>,
+    tcp_keepalive: Option<TcpKeepalive>,
+    max_connections: Option<usize>,
+}
+
+impl<S> Server<S> {
+    /// Serves connections on a dedicated pool of this many threads when it is
+    /// more than one. Otherwise they are served by the executor the server
+    /// runs on.
+    pub fn with_worker_threads(mut self, worker_threads: usize) -> Self {
+        self.worker_threads = worker_threads;
+        self
+    }
+
+    pub fn worker_threads(&self) -> usize {
+        self.worker_threads
+    }
+
+    /// The accept backlog the server listens with, if it was bound with one
+    /// that could be applied to its listener.
+    pub fn backlog(&self) -> Option<i32> {
+        self.backlog
+    }
+
+    /// Serves connections over TLS with the given server identity. Clients
+    /// that don't complete a TLS handshake are disconnected.
+    pub fn with_tls_acceptor(mut self, tls_acceptor: native_tls::TlsAcceptor) -> Self {
+        self.tls_acceptor = Some(tls_acceptor.into());
+        self
+    }
+
+    pub fn is_tls(&self) -> bool {
+        self.tls_acceptor.is_some()
+    }
+
+    /// Enables TCP keepalive on the connections accepted when serving on a
+    /// TCP address, so that connections to peers that went away are
+    /// eventually closed.
+    pub fn with_tcp_keepalive(mut self, keepalive: TcpKeepalive) -> Self {
+        self.tcp_keepalive = Some(keepalive);
+        self
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<&TcpKeepalive> {
+        self.tcp_keepalive.as_ref()
+    }
+
+    /// Serves at most this many connections at a time. Connections accepted
+    /// beyond it are closed right away, until some of the served ones close.
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    pub fn max_connections(&self) -> Option<usize> {
+        self.max_connections
+    }
+
+    /// Sets the permissions of the socket file when serving on a unix socket,
+    /// for example `0o660` to give a group access to it.
+    pub fn with_socket_mode(self, mode: u32) -> Result<Self, Error> {
+        if let Incoming::Unix(ref listener) = self.incoming {
+            unix::set_mode(listener, mode)?;
+        }
+        Ok(self)
+    }
 }
 
 impl<S> Server<S>
@@ -158,14 +255,51 @@ where
             protocol,
             new_service,
             incoming,
+            worker_threads,
+            tls_acceptor,
+            tcp_keepalive,
+            max_connections,
+            ..
         } = self;
 
         let protocol = Arc::new(protocol);
+        let pool = if worker_threads > 1 {
+            Some(
+                ThreadPoolBuilder::new()
+                    .pool_size(worker_threads)
+                    .name_prefix(WORKER_THREAD_NAME_PREFIX)
+                    .build(),
+            )
+        } else {
+            None
+        };
+
+        let connections = Arc::new(AtomicUsize::new(0));
 
         let srv = incoming.for_each(move |(socket, addr)| {
             let protocol = protocol.clone();
+            let tls_acceptor = tls_acceptor.clone();
 
             debug!("accepted new connection ({})", addr);
+            let permit = match max_connections {
+                Some(max) => match ConnectionPermit::acquire(&connections, max) {
+                    Some(permit) => Some(permit),
+                    None => {
+                        warn!(
+                            "refusing connection ({}), {} connections are already being served",
+                            addr, max
+                        );
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            if let Some(ref keepalive) = tcp_keepalive {
+                if let Err(err) = socket.set_tcp_keepalive(keepalive) {
+                    warn!("could not enable TCP keepalive: ({})", addr);
+                    log_failure(Level::Warn, &err);
+                }
+            }
             let pid = socket.pid()?;
             let fut = new_service
                 .new_service()
@@ -179,18 +313,39 @@ where
                 })
                 .and_then(move |(srv, addr)| {
                     let service = PidService::new(pid, srv);
-                    protocol
-                        .serve_connection(socket, service)
-                        .then(move |result| match result {
-                            Ok(_) => Ok(()),
-                            Err(err) => {
-                                error!("server connection error: ({})", addr);
-                                log_failure(Level::Error, &err);
-                                Err(())
-                            }
-                        })
+                    match tls_acceptor {
+                        Some(tls_acceptor) => Either::A(
+                            tls_acceptor
+                                .accept(socket)
+                                .then(move |result| match result {
+                                    Ok(stream) => Ok(protocol
+                                        .serve_connection(stream, service)
+                                        .then(move |result| connection_result(result, &addr))),
+                                    Err(err) => {
+                                        error!("TLS handshake error: ({})", addr);
+                                        log_failure(Level::Error, &err);
+                                        Err(())
+                                    }
+                                })
+                                .flatten(),
+                        ),
+                        None => Either::B(
+                            protocol
+                                .serve_connection(socket, service)
+                                .then(move |result| connection_result(result, &addr)),
+                        ),
+                    }
+                })
+                .then(move |result| {
+                    drop(permit);
+                    result
                 });
-            tokio::spawn(fut);
+            match pool {
+                Some(ref pool) => pool.spawn(fut),
+                None => {
+                    tokio::spawn(fut);
+                }
+            }
             Ok(())
         });
 
@@ -211,10 +366,55 @@ where
     }
 }
 
+// Counts a connection against the limit of its server until it is dropped.
+struct ConnectionPermit(Arc<AtomicUsize>);
+
+impl ConnectionPermit {
+    // Only the accept loop of the server acquires permits, so the count can't
+    // grow between the check and the increment.
+    fn acquire(connections: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        if connections.load(Ordering::SeqCst) < max {
+            connections.fetch_add(1, Ordering::SeqCst);
+            Some(ConnectionPermit(connections.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn connection_result(
+    result: Result<(), hyper::Error>,
+    addr: &IncomingSocketAddr,
+) -> Result<(), ()> {
+    result.map_err(|err| {
+        error!("server connection error: ({})", addr);
+        log_failure(Level::Error, &err);
+    })
+}
+
 pub trait HyperExt {
     fn bind_url<S>(&self, url: Url, new_service: S) -> Result<Server<S>, Error>
     where
         S: NewService<ReqBody = Body>;
+
+    /// Like `bind_url`, but listens with the given accept backlog instead of
+    /// the default one of the listener. It has no effect on sockets passed in
+    /// by systemd, whose backlog is set by their socket unit, nor on unix
+    /// sockets on Windows.
+    fn bind_url_with_backlog<S>(
+        &self,
+        url: Url,
+        new_service: S,
+        backlog: i32,
+    ) -> Result<Server<S>, Error>
+    where
+        S: NewService<ReqBody = Body>;
 }
 
 impl HyperExt for Http {
@@ -222,92 +422,187 @@ impl HyperExt for Http {
     where
         S: NewService<ReqBody = Body>,
     {
-        let incoming = match url.scheme() {
-            HTTP_SCHEME | TCP_SCHEME => {
-                let addr = url
-                    .to_socket_addrs()
-                    .context(ErrorKind::InvalidUrl(url.to_string()))?
-                    .next()
-                    .ok_or_else(|| {
-                        ErrorKind::InvalidUrlWithReason(
-                            url.to_string(),
-                            InvalidUrlReason::NoAddress,
-                        )
-                    })?;
-
-                let listener = TcpListener::bind(&addr)
-                    .with_context(|_| ErrorKind::BindListener(BindListenerType::Address(addr)))?;
-                Incoming::Tcp(listener)
-            }
-            UNIX_SCHEME => {
-                let path = url.to_uds_file_path()?;
-                unix::listener(path)?
+        bind_url(self, url, new_service, None)
+    }
+
+    fn bind_url_with_backlog<S>(
+        &self,
+        url: Url,
+        new_service: S,
+        backlog: i32,
+    ) -> Result<Server<S>, Error>
+    where
+        S: NewService<ReqBody = Body>,
+    {
+        bind_url(self, url, new_service, Some(backlog))
+    }
+}
+
+fn bind_url<S>(
+    protocol: &Http,
+    url: Url,
+    new_service: S,
+    backlog: Option<i32>,
+) -> Result<Server<S>, Error>
+where
+    S: NewService<ReqBody = Body>,
+{
+    let incoming = match url.scheme() {
+        HTTP_SCHEME | TCP_SCHEME => {
+            let addr = url
+                .to_socket_addrs()
+                .context(ErrorKind::InvalidUrl(url.to_string()))?
+                .next()
+                .ok_or_else(|| {
+                    ErrorKind::InvalidUrlWithReason(url.to_string(), InvalidUrlReason::NoAddress)
+                })?;
+
+            let listener = match backlog {
+                Some(backlog) => tcp_listener(&addr, backlog),
+                None => TcpListener::bind(&addr),
             }
-            #[cfg(unix)]
-            FD_SCHEME => {
-                let host = match url.host_str() {
-                    Some(host) => host,
-                    None => {
-                        return Err(ErrorKind::InvalidUrlWithReason(
-                            url.to_string(),
-                            InvalidUrlReason::NoHost,
-                        )
-                        .into())
-                    }
-                };
-
-                // Try to parse the host as an FD number, then as an FD name
-                let socket = host
-                    .parse()
-                    .map_err(|_| ())
-                    .and_then(|num| systemd::listener(num).map_err(|_| ()))
-                    .or_else(|_| systemd::listener_name(host))
-                    .with_context(|_| {
-                        ErrorKind::InvalidUrlWithReason(
-                            url.to_string(),
-                            InvalidUrlReason::FdNeitherNumberNorName,
-                        )
-                    })?;
-
-                match socket {
-                    Socket::Inet(fd, _addr) => {
-                        let l = unsafe { net::TcpListener::from_raw_fd(fd) };
-                        Incoming::Tcp(
-                            TcpListener::from_std(l, &Default::default()).with_context(|_| {
-                                ErrorKind::BindListener(BindListenerType::Fd(fd))
-                            })?,
-                        )
-                    }
-                    Socket::Unix(fd) => {
-                        let l = unsafe { ::std::os::unix::net::UnixListener::from_raw_fd(fd) };
-                        Incoming::Unix(
-                            UnixListener::from_std(l, &Default::default()).with_context(|_| {
-                                ErrorKind::BindListener(BindListenerType::Fd(fd))
-                            })?,
-                        )
-                    }
-                    Socket::Unknown => Err(ErrorKind::InvalidUrlWithReason(
+            .with_context(|_| ErrorKind::BindListener(BindListenerType::Address(addr)))?;
+            Incoming::Tcp(listener)
+        }
+        UNIX_SCHEME => {
+            let path = url.to_uds_file_path()?;
+            unix::listener(path, backlog)?
+        }
+        #[cfg(windows)]
+        PIPE_SCHEME => {
+            let path = url.to_pipe_path()?;
+            let listener = PipeListener::bind(&path)
+                .with_context(|_| ErrorKind::Path(path.display().to_string()))?;
+            Incoming::Pipe(listener)
+        }
+        #[cfg(unix)]
+        FD_SCHEME => {
+            let host = match url.host_str() {
+                Some(host) => host,
+                None => {
+                    return Err(ErrorKind::InvalidUrlWithReason(
+                        url.to_string(),
+                        InvalidUrlReason::NoHost,
+                    )
+                    .into())
+                }
+            };
+
+            // Try to parse the host as an FD number, then as an FD name
+            let socket = host
+                .parse()
+                .map_err(|_| ())
+                .and_then(|num| systemd::listener(num).map_err(|_| ()))
+                .or_else(|_| systemd::listener_name(host))
+                .with_context(|_| {
+                    ErrorKind::InvalidUrlWithReason(
                         url.to_string(),
-                        InvalidUrlReason::UnrecognizedSocket,
-                    ))?,
+                        InvalidUrlReason::FdNeitherNumberNorName,
+                    )
+                })?;
+
+            match socket {
+                Socket::Inet(fd, _addr) => {
+                    let l = unsafe { net::TcpListener::from_raw_fd(fd) };
+                    Incoming::Tcp(
+                        TcpListener::from_std(l, &Default::default())
+                            .with_context(|_| ErrorKind::BindListener(BindListenerType::Fd(fd)))?,
+                    )
+                }
+                Socket::Unix(fd) => {
+                    let l = unsafe { ::std::os::unix::net::UnixListener::from_raw_fd(fd) };
+                    Incoming::Unix(
+                        UnixListener::from_std(l, &Default::default())
+                            .with_context(|_| ErrorKind::BindListener(BindListenerType::Fd(fd)))?,
+                    )
                 }
+                Socket::Unknown => Err(ErrorKind::InvalidUrlWithReason(
+                    url.to_string(),
+                    InvalidUrlReason::UnrecognizedSocket,
+                ))?,
             }
-            _ => Err(Error::from(ErrorKind::InvalidUrlWithReason(
-                url.to_string(),
-                InvalidUrlReason::InvalidScheme,
-            )))?,
-        };
+        }
+        _ => Err(Error::from(ErrorKind::InvalidUrlWithReason(
+            url.to_string(),
+            InvalidUrlReason::InvalidScheme,
+        )))?,
+    };
+
+    // Sockets passed in by systemd keep the backlog of their socket unit, and
+    // neither unix sockets on Windows nor named pipes can be given one.
+    let backlog_applied = match url.scheme() {
+        HTTP_SCHEME | TCP_SCHEME => true,
+        UNIX_SCHEME => cfg!(unix),
+        _ => false,
+    };
+
+    Ok(Server {
+        protocol: protocol.clone(),
+        new_service,
+        incoming,
+        worker_threads: 1,
+        tls_acceptor: None,
+        backlog: if backlog_applied { backlog } else { None },
+        tcp_keepalive: None,
+        max_connections: None,
+    })
+}
 
-        Ok(Server {
-            protocol: self.clone(),
-            new_service,
-            incoming,
+fn tcp_listener(addr: &SocketAddr, backlog: i32) -> io::Result<TcpListener> {
+    let builder = if addr.is_ipv4() {
+        TcpBuilder::new_v4()?
+    } else {
+        TcpBuilder::new_v6()?
+    };
+    // as done by TcpListener::bind
+    #[cfg(unix)]
+    builder.reuse_address(true)?;
+    let listener = builder.bind(addr)?.listen(backlog)?;
+    TcpListener::from_std(listener, &Default::default())
+}
+
+/// Returns the `fd://` URL of the socket passed by systemd that listens on
+/// `url`, if there is one. Binding a server to it serves the activated socket
+/// instead of binding a new one.
+#[cfg(unix)]
+pub fn activated_url(url: &Url) -> Option<Url> {
+    let sockets = systemd::listeners().ok()?;
+    sockets
+        .iter()
+        .position(|socket| match (*socket, url.scheme()) {
+            (Socket::Inet(_, addr), HTTP_SCHEME) | (Socket::Inet(_, addr), TCP_SCHEME) => url
+                .to_socket_addrs()
+                .map(|mut addrs| addrs.any(|a| a == addr))
+                .unwrap_or(false),
+            (Socket::Unix(fd), UNIX_SCHEME) => match (url.to_uds_file_path(), unix_path(fd)) {
+                (Ok(path), Some(fd_path)) => path == fd_path,
+                _ => false,
+            },
+            _ => false,
         })
+        .and_then(|num| Url::parse(&format!("{}://{}", FD_SCHEME, num)).ok())
+}
+
+/// Sockets can't be passed by systemd on Windows.
+#[cfg(windows)]
+pub fn activated_url(_url: &Url) -> Option<Url> {
+    None
+}
+
+#[cfg(unix)]
+fn unix_path(fd: systemd::Fd) -> Option<PathBuf> {
+    match socket::getsockname(fd) {
+        Ok(SockAddr::Unix(addr)) => addr.path().map(Path::to_path_buf),
+        _ => None,
     }
 }
 
 pub trait UrlExt {
     fn to_uds_file_path(&self) -> Result<PathBuf, Error>;
+    /// The path of the named pipe of an `npipe://` URL, for example
+    /// `\\.\pipe\name` for `npipe://./pipe/name`.
+    #[cfg(windows)]
+    fn to_pipe_path(&self) -> Result<PathBuf, Error>;
     fn to_base_path(&self) -> Result<PathBuf, Error>;
 }
 
@@ -337,6 +632,30 @@ impl UrlExt for Url {
         }
     }
 
+    #[cfg(windows)]
+    fn to_pipe_path(&self) -> Result<PathBuf, Error> {
+        debug_assert_eq!(self.scheme(), PIPE_SCHEME);
+
+        let host = match self.host_str() {
+            Some(host) if !host.trim().is_empty() => host,
+            _ => {
+                return Err(ErrorKind::InvalidUrlWithReason(
+                    self.to_string(),
+                    InvalidUrlReason::NoHost,
+                )
+                .into())
+            }
+        };
+        if !self.path().starts_with("/pipe/") || self.path().len() == "/pipe/".len() {
+            return Err(ErrorKind::InvalidUrlWithReason(
+                self.to_string(),
+                InvalidUrlReason::InvalidPipePath,
+            )
+            .into());
+        }
+        Ok(format!(r"\\{}{}", host, self.path().replace("/", "\\")).into())
+    }
+
     fn to_base_path(&self) -> Result<PathBuf, Error> {
         match self.scheme() {
             "unix" => Ok(self.to_uds_file_path()?),
@@ -344,3 +663,154 @@ impl UrlExt for Url {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::net::SocketAddr;
+    #[cfg(target_os = "linux")]
+    use std::net::TcpStream;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    #[cfg(target_os = "linux")]
+    use std::time::Duration;
+
+    use futures::sync::oneshot;
+    use futures::Future;
+    use hyper::server::conn::Http;
+    use hyper::service::service_fn_ok;
+    use hyper::{Body, Client, Request, Response, StatusCode};
+    use tokio::runtime::Runtime;
+    use url::Url;
+
+    #[cfg(windows)]
+    use super::UrlExt;
+    use super::{HyperExt, Incoming, Server, WORKER_THREAD_NAME_PREFIX};
+
+    fn local_addr<S>(server: &Server<S>) -> SocketAddr {
+        match server.incoming {
+            Incoming::Tcp(ref listener) => listener.local_addr().unwrap(),
+            _ => panic!("expected a TCP listener"),
+        }
+    }
+
+    // Serves a few requests, each on its own connection, and returns the
+    // names of the threads that handled them.
+    fn handler_thread_names(worker_threads: usize) -> HashSet<String> {
+        let names = Arc::new(Mutex::new(HashSet::new()));
+        let handler_names = names.clone();
+        let url = Url::parse("http://127.0.0.1:0").unwrap();
+        let server = Http::new()
+            .bind_url(url, move || {
+                let names = handler_names.clone();
+                service_fn_ok(move |_: Request<Body>| {
+                    let name = thread::current().name().unwrap_or_default().to_string();
+                    names.lock().unwrap().insert(name);
+                    Response::new(Body::empty())
+                })
+            })
+            .unwrap()
+            .with_worker_threads(worker_threads);
+        let uri = format!("http://{}/", local_addr(&server));
+
+        let (tx, rx) = oneshot::channel();
+        let mut runtime = Runtime::new().unwrap();
+        runtime.spawn(server.run_until(rx.map_err(|_| ())).map_err(|_| ()));
+
+        let client = Client::builder().keep_alive(false).build_http::<Body>();
+        for _ in 0..8 {
+            let response = runtime.block_on(client.get(uri.parse().unwrap())).unwrap();
+            assert_eq!(StatusCode::OK, response.status());
+        }
+
+        tx.send(()).unwrap();
+        let names = names.lock().unwrap();
+        names.clone()
+    }
+
+    #[test]
+    fn server_uses_configured_worker_threads() {
+        let names = handler_thread_names(1);
+        assert!(!names.is_empty());
+        assert!(names
+            .iter()
+            .all(|name| !name.starts_with(WORKER_THREAD_NAME_PREFIX)));
+
+        let names = handler_thread_names(4);
+        assert!(!names.is_empty() && names.len() <= 4);
+        assert!(names
+            .iter()
+            .all(|name| name.starts_with(WORKER_THREAD_NAME_PREFIX)));
+    }
+
+    // Connects to a server that accepts none of the connections, and returns
+    // which of them were queued.
+    #[cfg(target_os = "linux")]
+    fn queue_connections<S>(server: &Server<S>, count: usize) -> Vec<bool> {
+        let addr = local_addr(server);
+        let mut streams = vec![];
+        let queued = (0..count)
+            .map(
+                |_| match TcpStream::connect_timeout(&addr, Duration::from_millis(250)) {
+                    Ok(stream) => {
+                        streams.push(stream);
+                        true
+                    }
+                    Err(_) => false,
+                },
+            )
+            .collect();
+        drop(streams);
+        queued
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn server_is_bound_with_configured_backlog() {
+        let url = Url::parse("http://127.0.0.1:0").unwrap();
+        let new_service = || service_fn_ok(|_: Request<Body>| Response::new(Body::empty()));
+
+        let server = Http::new().bind_url(url.clone(), new_service).unwrap();
+        assert_eq!(None, server.backlog());
+        assert!(queue_connections(&server, 4).iter().all(|queued| *queued));
+
+        let server = Http::new()
+            .bind_url_with_backlog(url, new_service, 1)
+            .unwrap();
+        assert_eq!(Some(1), server.backlog());
+        let queued = queue_connections(&server, 4);
+        assert!(queued[0]);
+        assert!(queued.iter().any(|queued| !*queued));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn pipe_url_is_converted_to_pipe_path() {
+        let url = Url::parse("npipe://./pipe/iotedge_mgmt").unwrap();
+        assert_eq!(
+            r"\\.\pipe\iotedge_mgmt",
+            url.to_pipe_path().unwrap().to_str().unwrap()
+        );
+
+        assert!(Url::parse("npipe://./iotedge_mgmt")
+            .unwrap()
+            .to_pipe_path()
+            .is_err());
+        assert!(Url::parse("npipe://./pipe/")
+            .unwrap()
+            .to_pipe_path()
+            .is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn server_is_bound_to_named_pipe() {
+        let url = Url::parse("npipe://./pipe/edgelet-http-bind-test").unwrap();
+        let new_service = || service_fn_ok(|_: Request<Body>| Response::new(Body::empty()));
+
+        let _server = Http::new().bind_url(url.clone(), new_service).unwrap();
+
+        // the pipe is taken until the server is dropped
+        assert!(Http::new().bind_url(url, new_service).is_err());
+    }
+}
@@ -6,17 +6,160 @@ use edgelet_core::pid::Pid;
 use futures::prelude::*;
 use hyper::header::{CONTENT_LENGTH, USER_AGENT};
 use hyper::service::{NewService, Service};
-use hyper::Request;
+use hyper::{Request, Response};
+use serde_json::{Map, Value};
+
+/// A field of the JSON request log lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogField {
+    Label,
+    Time,
+    Request,
+    Status,
+    BodyLength,
+    UserAgent,
+    Pid,
+}
+
+impl LogField {
+    pub fn all() -> Vec<LogField> {
+        vec![
+            LogField::Label,
+            LogField::Time,
+            LogField::Request,
+            LogField::Status,
+            LogField::BodyLength,
+            LogField::UserAgent,
+            LogField::Pid,
+        ]
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            LogField::Label => "label",
+            LogField::Time => "time",
+            LogField::Request => "request",
+            LogField::Status => "status",
+            LogField::BodyLength => "bodyLength",
+            LogField::UserAgent => "userAgent",
+            LogField::Pid => "pid",
+        }
+    }
+}
+
+/// The format of the line logged for each request. `Plain` is the
+/// access log style line the daemon has always logged, `Json` logs an
+/// object with the given fields.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogFormat {
+    Plain,
+    Json(Vec<LogField>),
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Plain
+    }
+}
+
+// What is logged about a request, collected before it is passed on.
+struct RequestRecord {
+    label: String,
+    request: String,
+    user_agent: Option<String>,
+    pid: Option<String>,
+}
+
+impl RequestRecord {
+    fn new<B>(label: &str, req: &Request<B>) -> Self {
+        let uri = req.uri().query().map_or_else(
+            || req.uri().path().to_string(),
+            |q| format!("{}?{}", req.uri().path(), q),
+        );
+        let request = format!("{} {} {:?}", req.method(), uri, req.version());
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|ua| ua.to_str().ok())
+            .map(|ua| ua.to_string());
+        let pid = req.extensions().get::<Pid>().map(|p| p.to_string());
+
+        RequestRecord {
+            label: label.to_string(),
+            request,
+            user_agent,
+            pid,
+        }
+    }
+
+    fn log_line<B>(
+        &self,
+        format: &LogFormat,
+        time: DateTime<Utc>,
+        response: &Response<B>,
+    ) -> String {
+        let body_length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|l| l.to_str().ok().map(|l| l.to_string()));
+
+        match format {
+            LogFormat::Plain => format!(
+                "[{}] - - - [{}] \"{}\" {} {} \"-\" \"{}\" pid({})",
+                self.label,
+                time,
+                self.request,
+                response.status(),
+                body_length.as_ref().map_or("-", |l| l.as_str()),
+                self.user_agent.as_ref().map_or("-", |ua| ua.as_str()),
+                self.pid.as_ref().map_or("-", |p| p.as_str()),
+            ),
+            LogFormat::Json(fields) => {
+                let optional = |value: &Option<String>| {
+                    value
+                        .as_ref()
+                        .map_or(Value::Null, |v| Value::from(v.as_str()))
+                };
+                let line: Map<String, Value> = fields
+                    .iter()
+                    .map(|field| {
+                        let value = match field {
+                            LogField::Label => Value::from(self.label.as_str()),
+                            LogField::Time => Value::from(time.to_rfc3339()),
+                            LogField::Request => Value::from(self.request.as_str()),
+                            LogField::Status => Value::from(response.status().as_u16()),
+                            LogField::BodyLength => optional(&body_length),
+                            LogField::UserAgent => optional(&self.user_agent),
+                            LogField::Pid => optional(&self.pid),
+                        };
+                        (field.key().to_string(), value)
+                    })
+                    .collect();
+                Value::Object(line).to_string()
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct LoggingService<T> {
     label: String,
+    format: LogFormat,
     inner: T,
 }
 
 impl<T> LoggingService<T> {
     pub fn new(label: String, inner: T) -> Self {
-        LoggingService { label, inner }
+        LoggingService {
+            label,
+            format: LogFormat::default(),
+            inner,
+        }
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
     }
 }
 
@@ -36,43 +179,13 @@ where
     >;
 
     fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
-        let label = self.label.clone();
-        let uri = req.uri().query().map_or_else(
-            || req.uri().path().to_string(),
-            |q| format!("{}?{}", req.uri().path(), q),
-        );
-        let request = format!("{} {} {:?}", req.method(), uri, req.version());
-        let user_agent = req
-            .headers()
-            .get(USER_AGENT)
-            .and_then(|ua| ua.to_str().ok())
-            .unwrap_or_else(|| "-")
-            .to_string();
-        let pid = req
-            .extensions()
-            .get::<Pid>()
-            .map_or_else(|| "-".to_string(), |p| p.to_string());
+        let record = RequestRecord::new(&self.label, &req);
+        let format = self.format.clone();
 
         let inner = self.inner.call(req);
 
         Box::new(inner.map(move |response| {
-            let body_length = response
-                .headers()
-                .get(CONTENT_LENGTH)
-                .and_then(|l| l.to_str().ok().map(|l| l.to_string()))
-                .unwrap_or_else(|| "-".to_string());
-
-            info!(
-                "[{}] - - - [{}] \"{}\" {} {} \"-\" \"{}\" pid({})",
-                label,
-                Utc::now(),
-                request,
-                response.status(),
-                body_length,
-                user_agent,
-                pid,
-            );
-
+            info!("{}", record.log_line(&format, Utc::now(), &response));
             response
         }))
     }
@@ -94,10 +207,113 @@ where
 
     fn new_service(&self) -> Self::Future {
         let label = self.label.clone();
-        Box::new(
-            self.inner
-                .new_service()
-                .map(|inner| LoggingService { label, inner }),
-        )
+        let format = self.format.clone();
+        Box::new(self.inner.new_service().map(|inner| LoggingService {
+            label,
+            format,
+            inner,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Body, StatusCode};
+    use serde_json;
+
+    use super::*;
+
+    fn request() -> Request<Body> {
+        let mut req = Request::get("http://localhost/modules?api-version=2018-06-28")
+            .header(USER_AGENT, "edgeAgent")
+            .body(Body::default())
+            .unwrap();
+        req.extensions_mut().insert(Pid::Value(42));
+        req
+    }
+
+    fn response() -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_LENGTH, "2")
+            .body("[]".into())
+            .unwrap()
+    }
+
+    fn time() -> DateTime<Utc> {
+        Utc.ymd(2018, 10, 1).and_hms(12, 30, 0)
+    }
+
+    #[test]
+    fn plain_format_logs_access_log_line() {
+        let record = RequestRecord::new("mgmt", &request());
+
+        let line = record.log_line(&LogFormat::Plain, time(), &response());
+
+        assert_eq!(
+            "[mgmt] - - - [2018-10-01 12:30:00 UTC] \"GET /modules?api-version=2018-06-28 HTTP/1.1\" 200 OK 2 \"-\" \"edgeAgent\" pid(42)",
+            line
+        );
+    }
+
+    #[test]
+    fn json_format_logs_selected_fields() {
+        let record = RequestRecord::new("mgmt", &request());
+
+        let line = record.log_line(&LogFormat::Json(LogField::all()), time(), &response());
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(
+            json!({
+                "label": "mgmt",
+                "time": "2018-10-01T12:30:00+00:00",
+                "request": "GET /modules?api-version=2018-06-28 HTTP/1.1",
+                "status": 200,
+                "bodyLength": "2",
+                "userAgent": "edgeAgent",
+                "pid": "42",
+            }),
+            line
+        );
+
+        let line = record.log_line(
+            &LogFormat::Json(vec![LogField::Request, LogField::Status]),
+            time(),
+            &response(),
+        );
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(
+            json!({
+                "request": "GET /modules?api-version=2018-06-28 HTTP/1.1",
+                "status": 200,
+            }),
+            line
+        );
+    }
+
+    #[test]
+    fn json_format_logs_missing_values_as_null() {
+        let req = Request::get("http://localhost/modules")
+            .body(Body::default())
+            .unwrap();
+        let record = RequestRecord::new("work", &req);
+        let response = Response::new(Body::default());
+
+        let line = record.log_line(
+            &LogFormat::Json(vec![
+                LogField::BodyLength,
+                LogField::UserAgent,
+                LogField::Pid,
+            ]),
+            time(),
+            &response,
+        );
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(
+            json!({ "bodyLength": null, "userAgent": null, "pid": null }),
+            line
+        );
     }
 }
@@ -0,0 +1,186 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::{future, Future};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Request, Response};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+// Counts the requests of the current window of `period`, so that at most
+// `requests` of them are let through.
+#[derive(Debug)]
+struct Window {
+    requests: u32,
+    period: Duration,
+    start: Instant,
+    count: u32,
+}
+
+impl Window {
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.start) >= self.period {
+            self.start = now;
+            self.count = 0;
+        }
+        if self.count < self.requests {
+            self.count += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Rejects requests with `429 Too Many Requests` once more than the
+/// configured number of them arrive within a period. The limit is shared by
+/// all connections of the server it is created for.
+#[derive(Clone)]
+pub struct RateLimitService<T> {
+    upstream: T,
+    window: Option<Arc<Mutex<Window>>>,
+}
+
+impl<T> RateLimitService<T> {
+    pub fn new(upstream: T) -> Self {
+        RateLimitService {
+            upstream,
+            window: None,
+        }
+    }
+
+    pub fn with_limit(mut self, requests: u32, period: Duration) -> Self {
+        self.window = Some(Arc::new(Mutex::new(Window {
+            requests,
+            period,
+            start: Instant::now(),
+            count: 0,
+        })));
+        self
+    }
+}
+
+impl<T> Service for RateLimitService<T>
+where
+    T: Service<ResBody = Body>,
+    <T as Service>::Future: Send + 'static,
+    <T as Service>::Error: IntoResponse + Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        let allowed = self.window.as_ref().map_or(true, |window| {
+            window
+                .lock()
+                .expect("rate limit lock poisoned")
+                .try_acquire(Instant::now())
+        });
+
+        if allowed {
+            Box::new(
+                self.upstream
+                    .call(req)
+                    .or_else(|e| future::ok(e.into_response())),
+            )
+        } else {
+            Box::new(future::ok(
+                Error::from(ErrorKind::TooManyRequests).into_response(),
+            ))
+        }
+    }
+}
+
+impl<T> NewService for RateLimitService<T>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    RateLimitService<<T as NewService>::Service>: Service,
+{
+    type ReqBody = <RateLimitService<<T as NewService>::Service> as Service>::ReqBody;
+    type ResBody = <RateLimitService<<T as NewService>::Service> as Service>::ResBody;
+    type Error = <RateLimitService<<T as NewService>::Service> as Service>::Error;
+    type Service = RateLimitService<<T as NewService>::Service>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        let window = self.window.clone();
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(move |upstream| RateLimitService { upstream, window }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use failure::Compat;
+    use futures::future::FutureResult;
+    use hyper::StatusCode;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = Compat<Error>;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(Response::new(Body::default()))
+        }
+    }
+
+    fn status(service: &mut RateLimitService<TestService>) -> StatusCode {
+        let req = Request::get("http://localhost")
+            .body(Body::default())
+            .unwrap();
+        Service::call(service, req).wait().unwrap().status()
+    }
+
+    #[test]
+    fn requests_beyond_limit_are_throttled() {
+        let mut service =
+            RateLimitService::new(TestService).with_limit(2, Duration::from_secs(3600));
+        assert_eq!(StatusCode::OK, status(&mut service));
+        assert_eq!(StatusCode::OK, status(&mut service));
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, status(&mut service));
+
+        // the limit is shared with the services of other connections
+        let mut other = service.clone();
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, status(&mut other));
+    }
+
+    #[test]
+    fn requests_are_unlimited_by_default() {
+        let mut service = RateLimitService::new(TestService);
+        for _ in 0..100 {
+            assert_eq!(StatusCode::OK, status(&mut service));
+        }
+    }
+
+    #[test]
+    fn window_resets_after_period() {
+        let start = Instant::now();
+        let mut window = Window {
+            requests: 1,
+            period: Duration::from_secs(1),
+            start,
+            count: 0,
+        };
+        assert!(window.try_acquire(start));
+        assert!(!window.try_acquire(start + Duration::from_millis(500)));
+        assert!(window.try_acquire(start + Duration::from_secs(1)));
+        assert!(!window.try_acquire(start + Duration::from_millis(1500)));
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::ResultExt;
+use native_tls::{Identity, TlsAcceptor};
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::PKey;
+use openssl::stack::Stack;
+use openssl::x509::X509;
+
+use error::{Error, ErrorKind};
+
+/// Builds a TLS acceptor from a PEM server certificate, optionally followed by
+/// its issuer chain, and its PEM private key.
+pub fn tls_acceptor(cert_pem: &[u8], key_pem: &[u8]) -> Result<TlsAcceptor, Error> {
+    let mut certs = X509::stack_from_pem(cert_pem)
+        .context(ErrorKind::InvalidServerCertificate)?
+        .into_iter();
+    let cert = certs
+        .next()
+        .ok_or_else(|| Error::from(ErrorKind::InvalidServerCertificate))?;
+    let mut chain = Stack::new().context(ErrorKind::InvalidServerCertificate)?;
+    for issuer in certs {
+        chain
+            .push(issuer)
+            .context(ErrorKind::InvalidServerCertificate)?;
+    }
+    let key = PKey::private_key_from_pem(key_pem).context(ErrorKind::InvalidServerCertificate)?;
+
+    // native-tls only loads server identities from PKCS#12 archives
+    let mut builder = Pkcs12::builder();
+    builder.ca(chain);
+    let identity = builder
+        .build("", "", &key, &cert)
+        .and_then(|pkcs12| pkcs12.to_der())
+        .context(ErrorKind::InvalidServerCertificate)?;
+    let identity =
+        Identity::from_pkcs12(&identity, "").context(ErrorKind::InvalidServerCertificate)?;
+    let acceptor = TlsAcceptor::new(identity).context(ErrorKind::InvalidServerCertificate)?;
+    Ok(acceptor)
+}
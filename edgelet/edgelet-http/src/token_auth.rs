@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::Arc;
+
+use futures::{future, Future};
+use hyper::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use hyper::service::{NewService, Service};
+use hyper::{Body, Request, Response};
+use openssl::memcmp;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Rejects requests with `401 Unauthorized` unless their `Authorization`
+/// header carries the configured bearer token. All requests are passed
+/// through if no token is set.
+#[derive(Clone)]
+pub struct TokenAuthService<T> {
+    upstream: T,
+    token: Option<Arc<String>>,
+}
+
+impl<T> TokenAuthService<T> {
+    pub fn new(upstream: T) -> Self {
+        TokenAuthService {
+            upstream,
+            token: None,
+        }
+    }
+
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(Arc::new(token));
+        self
+    }
+}
+
+// Compares in constant time, so the token cannot be guessed byte by byte
+// from how long the comparison takes.
+fn is_authorized<B>(req: &Request<B>, token: &str) -> bool {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            if value.starts_with(BEARER_PREFIX) {
+                Some(&value[BEARER_PREFIX.len()..])
+            } else {
+                None
+            }
+        })
+        .map_or(false, |bearer| {
+            bearer.len() == token.len() && memcmp::eq(bearer.as_bytes(), token.as_bytes())
+        })
+}
+
+impl<T> Service for TokenAuthService<T>
+where
+    T: Service<ResBody = Body>,
+    <T as Service>::Future: Send + 'static,
+    <T as Service>::Error: IntoResponse + Send + 'static,
+{
+    type ReqBody = T::ReqBody;
+    type ResBody = T::ResBody;
+    type Error = T::Error;
+    type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
+
+    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+        let authorized = self
+            .token
+            .as_ref()
+            .map_or(true, |token| is_authorized(&req, token));
+
+        if authorized {
+            Box::new(
+                self.upstream
+                    .call(req)
+                    .or_else(|e| future::ok(e.into_response())),
+            )
+        } else {
+            let mut response = Error::from(ErrorKind::Unauthorized).into_response();
+            response
+                .headers_mut()
+                .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            Box::new(future::ok(response))
+        }
+    }
+}
+
+impl<T> NewService for TokenAuthService<T>
+where
+    T: NewService,
+    <T as NewService>::Future: Send + 'static,
+    TokenAuthService<<T as NewService>::Service>: Service,
+{
+    type ReqBody = <TokenAuthService<<T as NewService>::Service> as Service>::ReqBody;
+    type ResBody = <TokenAuthService<<T as NewService>::Service> as Service>::ResBody;
+    type Error = <TokenAuthService<<T as NewService>::Service> as Service>::Error;
+    type Service = TokenAuthService<<T as NewService>::Service>;
+    type Future = Box<Future<Item = Self::Service, Error = Self::InitError> + Send>;
+    type InitError = <T as NewService>::InitError;
+
+    fn new_service(&self) -> Self::Future {
+        let token = self.token.clone();
+        Box::new(
+            self.upstream
+                .new_service()
+                .map(move |upstream| TokenAuthService { upstream, token }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use failure::Compat;
+    use futures::future::FutureResult;
+    use hyper::StatusCode;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct TestService;
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = Compat<Error>;
+        type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
+
+        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+            future::ok(Response::new(Body::default()))
+        }
+    }
+
+    fn request(authorization: Option<&str>) -> Request<Body> {
+        let mut req = Request::get("http://localhost/modules");
+        if let Some(authorization) = authorization {
+            req.header(AUTHORIZATION, authorization);
+        }
+        req.body(Body::default()).unwrap()
+    }
+
+    fn service() -> TokenAuthService<TestService> {
+        TokenAuthService::new(TestService).with_token("s3cr3t".to_string())
+    }
+
+    #[test]
+    fn valid_token_is_passed_through() {
+        let response = service()
+            .call(request(Some("Bearer s3cr3t")))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[test]
+    fn missing_token_is_unauthorized() {
+        let response = service().call(request(None)).wait().unwrap();
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        assert_eq!("Bearer", response.headers()[WWW_AUTHENTICATE]);
+    }
+
+    #[test]
+    fn invalid_token_is_unauthorized() {
+        for authorization in &["Bearer wrong", "Bearer s3cr3", "Bearer s3cr3t2", "s3cr3t"] {
+            let response = service()
+                .call(request(Some(*authorization)))
+                .wait()
+                .unwrap();
+
+            assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        }
+    }
+
+    #[test]
+    fn requests_are_passed_through_without_token() {
+        let mut service = TokenAuthService::new(TestService);
+
+        let response = service.call(request(None)).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+    }
+}
@@ -2,11 +2,17 @@
 
 use std::fs;
 #[cfg(unix)]
-use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::io::FromRawFd;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener as StdUnixListener;
 use std::path::Path;
 
 use failure::ResultExt;
 #[cfg(unix)]
+use nix::sys::socket::{self, AddressFamily, SockAddr, SockFlag, SockType, UnixAddr};
+#[cfg(unix)]
 use nix::sys::stat::{umask, Mode};
 #[cfg(unix)]
 use tokio_uds::UnixListener;
@@ -16,7 +22,7 @@ use tokio_uds_windows::UnixListener;
 use error::{Error, ErrorKind};
 use util::{incoming::Incoming, socket_file_exists};
 
-pub fn listener<P: AsRef<Path>>(path: P) -> Result<Incoming, Error> {
+pub fn listener<P: AsRef<Path>>(path: P, backlog: Option<i32>) -> Result<Incoming, Error> {
     let listener = if socket_file_exists(path.as_ref()) {
         // get the previous file's metadata
         #[cfg(unix)]
@@ -33,20 +39,73 @@ pub fn listener<P: AsRef<Path>>(path: P) -> Result<Incoming, Error> {
         defer! {{ umask(prev); }}
 
         debug!("binding {}...", path.as_ref().display());
-        let listener = UnixListener::bind(&path)
-            .with_context(|_| ErrorKind::Path(path.as_ref().display().to_string()))?;
+        let listener = bind(path.as_ref(), backlog)?;
         debug!("bound {}", path.as_ref().display());
 
         Incoming::Unix(listener)
     } else {
-        let listener = UnixListener::bind(&path)
-            .with_context(|_| ErrorKind::Path(path.as_ref().display().to_string()))?;
+        let listener = bind(path.as_ref(), backlog)?;
         Incoming::Unix(listener)
     };
 
     Ok(listener)
 }
 
+#[cfg(unix)]
+#[cfg_attr(feature = "cargo-clippy", allow(cast_sign_loss))]
+fn bind(path: &Path, backlog: Option<i32>) -> Result<UnixListener, Error> {
+    let context = || ErrorKind::Path(path.display().to_string());
+    match backlog {
+        Some(backlog) => {
+            let fd = socket::socket(
+                AddressFamily::Unix,
+                SockType::Stream,
+                SockFlag::SOCK_CLOEXEC,
+                None,
+            )
+            .with_context(|_| context())?;
+            // owned by the listener from here on so that it is closed on errors
+            let listener = unsafe { StdUnixListener::from_raw_fd(fd) };
+            let addr = UnixAddr::new(path).with_context(|_| context())?;
+            socket::bind(fd, &SockAddr::Unix(addr)).with_context(|_| context())?;
+            socket::listen(fd, backlog.max(0) as usize).with_context(|_| context())?;
+            let listener = UnixListener::from_std(listener, &Default::default())
+                .with_context(|_| context())?;
+            Ok(listener)
+        }
+        None => {
+            let listener = UnixListener::bind(path).with_context(|_| context())?;
+            Ok(listener)
+        }
+    }
+}
+
+// The backlog of unix sockets can't be configured on Windows.
+#[cfg(windows)]
+fn bind(path: &Path, _backlog: Option<i32>) -> Result<UnixListener, Error> {
+    let listener =
+        UnixListener::bind(path).with_context(|_| ErrorKind::Path(path.display().to_string()))?;
+    Ok(listener)
+}
+
+/// Sets the permissions of the socket file of a listener. Socket files on
+/// Windows have no such permissions, so this does nothing there.
+#[cfg(unix)]
+pub fn set_mode(listener: &UnixListener, mode: u32) -> Result<(), Error> {
+    let addr = listener.local_addr().context(ErrorKind::Initialization)?;
+    if let Some(path) = addr.as_pathname() {
+        debug!("setting permissions {:#o} for {}...", mode, path.display());
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+            .with_context(|_| ErrorKind::Path(path.display().to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn set_mode(_listener: &UnixListener, _mode: u32) -> Result<(), Error> {
+    Ok(())
+}
+
 #[cfg(unix)]
 fn get_metadata(path: &Path) -> Result<fs::Metadata, Error> {
     let metadata =
@@ -73,6 +132,7 @@ mod tests {
 
     use std::fs::OpenOptions;
     use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::net::UnixStream;
 
     use futures::Stream;
     use nix::sys::stat::stat;
@@ -93,7 +153,7 @@ mod tests {
         assert_eq!(0o600, file.metadata().unwrap().mode() & 0o7777);
         drop(file);
 
-        let listener = listener(&path).unwrap();
+        let listener = listener(&path, None).unwrap();
         let _srv = listener.for_each(move |(_socket, _addr)| Ok(()));
 
         let file_stat = stat(&path).unwrap();
@@ -101,4 +161,66 @@ mod tests {
 
         dir.close().unwrap();
     }
+
+    #[test]
+    fn test_set_mode() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mode.sock");
+
+        let listener = match listener(&path, None).unwrap() {
+            Incoming::Unix(listener) => listener,
+            Incoming::Tcp(_) => panic!("expected a unix listener"),
+        };
+        set_mode(&listener, 0o660).unwrap();
+
+        let file_stat = stat(&path).unwrap();
+        assert_eq!(0o660, file_stat.st_mode & 0o777);
+
+        set_mode(&listener, 0o600).unwrap();
+
+        let file_stat = stat(&path).unwrap();
+        assert_eq!(0o600, file_stat.st_mode & 0o777);
+
+        dir.close().unwrap();
+    }
+
+    // Makes non-blocking connections to a listener that accepts none of them,
+    // and returns which of them were queued.
+    #[cfg(target_os = "linux")]
+    fn queue_connections(path: &Path, count: usize) -> Vec<bool> {
+        let mut streams = vec![];
+        let queued = (0..count)
+            .map(|_| {
+                let fd = socket::socket(
+                    AddressFamily::Unix,
+                    SockType::Stream,
+                    SockFlag::SOCK_NONBLOCK | SockFlag::SOCK_CLOEXEC,
+                    None,
+                )
+                .unwrap();
+                streams.push(unsafe { UnixStream::from_raw_fd(fd) });
+                socket::connect(fd, &SockAddr::Unix(UnixAddr::new(path).unwrap())).is_ok()
+            })
+            .collect();
+        drop(streams);
+        queued
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_backlog() {
+        let dir = tempdir().unwrap();
+
+        let path = dir.path().join("default.sock");
+        let _default = listener(&path, None).unwrap();
+        assert!(queue_connections(&path, 4).iter().all(|queued| *queued));
+
+        let path = dir.path().join("backlog.sock");
+        let _backlog = listener(&path, Some(1)).unwrap();
+        let queued = queue_connections(&path, 4);
+        assert!(queued[0]);
+        assert!(queued.iter().any(|queued| !*queued));
+
+        dir.close().unwrap();
+    }
 }
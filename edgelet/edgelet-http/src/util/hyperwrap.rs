@@ -6,6 +6,7 @@ use hyper::client::HttpConnector;
 use hyper::{Body, Client as HyperClient, Error as HyperError, Request, Response, StatusCode, Uri};
 use hyper_proxy::{Intercept, Proxy, ProxyConnector};
 use hyper_tls::HttpsConnector;
+use native_tls::{Certificate, TlsConnector};
 use typed_headers::Credentials;
 use url::percent_encoding::percent_decode;
 use url::Url;
@@ -15,10 +16,14 @@ use error::{Error, ErrorKind, InvalidUrlReason};
 
 const DNS_WORKER_THREADS: usize = 4;
 
+const PEM_CERTIFICATE_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
 #[derive(Clone, Debug)]
 pub struct Config {
     proxy_uri: Option<Uri>,
     null: bool,
+    ca_certificates: Vec<Vec<u8>>,
 }
 
 impl Config {
@@ -32,13 +37,23 @@ impl Config {
         self
     }
 
+    /// Trusts the CA certificates in the PEM `bundle` for HTTPS connections,
+    /// in addition to the system's trusted roots.
+    pub fn ca_bundle(&mut self, bundle: &[u8]) -> &mut Config {
+        self.ca_certificates.extend(pem_certificates(bundle));
+        self
+    }
+
     pub fn build(&self) -> Result<Client, Error> {
         if self.null {
             Ok(Client::Null)
         } else {
             let config = self.clone();
-            let https =
-                HttpsConnector::new(DNS_WORKER_THREADS).context(ErrorKind::Initialization)?;
+            let https = if config.ca_certificates.is_empty() {
+                HttpsConnector::new(DNS_WORKER_THREADS).context(ErrorKind::Initialization)?
+            } else {
+                https_connector(&config.ca_certificates)?
+            };
             match config.proxy_uri {
                 None => Ok(Client::NoProxy(HyperClient::builder().build(https))),
                 Some(uri) => {
@@ -53,6 +68,34 @@ impl Config {
     }
 }
 
+// Splits a PEM bundle into its certificates, since native-tls only parses one
+// certificate at a time.
+fn pem_certificates(bundle: &[u8]) -> Vec<Vec<u8>> {
+    String::from_utf8_lossy(bundle)
+        .split_terminator(PEM_CERTIFICATE_END)
+        .filter_map(|block| {
+            block
+                .find(PEM_CERTIFICATE_BEGIN)
+                .map(|begin| format!("{}{}\n", &block[begin..], PEM_CERTIFICATE_END).into_bytes())
+        })
+        .collect()
+}
+
+fn https_connector(ca_certificates: &[Vec<u8>]) -> Result<HttpsConnector<HttpConnector>, Error> {
+    let mut tls = TlsConnector::builder();
+    for pem in ca_certificates {
+        let certificate = Certificate::from_pem(pem)
+            .context(ErrorKind::InvalidCaCertificate)
+            .context(ErrorKind::Initialization)?;
+        tls.add_root_certificate(certificate);
+    }
+    let tls = tls.build().context(ErrorKind::Initialization)?;
+
+    let mut http = HttpConnector::new(DNS_WORKER_THREADS);
+    http.enforce_http(false);
+    Ok(HttpsConnector::from((http, tls)))
+}
+
 fn uri_to_proxy(uri: Uri) -> Result<Proxy, Error> {
     let url = Url::parse(&uri.to_string()).with_context(|_| ErrorKind::Proxy(uri.clone()))?;
     let mut proxy = Proxy::new(Intercept::All, uri.clone());
@@ -119,6 +162,7 @@ impl Client {
         Config {
             proxy_uri: None,
             null: false,
+            ca_certificates: vec![],
         }
     }
 
@@ -163,6 +207,8 @@ mod tests {
     use super::*;
     use hyper::Uri;
 
+    const CA_BUNDLE: &[u8] = include_bytes!("../../test/ca-bundle.pem");
+
     // test that the client builder (Config) is wired up correctly to create the
     // right enum variants
 
@@ -192,6 +238,29 @@ mod tests {
         assert!(client.has_proxy());
     }
 
+    #[test]
+    fn can_create_client_with_ca_bundle() {
+        let mut config = Client::configure();
+        config.ca_bundle(CA_BUNDLE);
+        assert_eq!(2, config.ca_certificates.len());
+
+        let client = config.build().unwrap();
+        assert!(!client.has_proxy() && !client.is_null());
+    }
+
+    #[test]
+    fn invalid_ca_bundle_fails() {
+        let bundle = format!(
+            "{}\nnot base64\n{}\n",
+            PEM_CERTIFICATE_BEGIN, PEM_CERTIFICATE_END
+        );
+        let err = Client::configure()
+            .ca_bundle(bundle.as_bytes())
+            .build()
+            .unwrap_err();
+        assert_eq!(&ErrorKind::Initialization, err.kind());
+    }
+
     #[test]
     fn proxy_no_username() {
         let uri = "http://example.com".parse().unwrap();
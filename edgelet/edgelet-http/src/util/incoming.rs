@@ -4,6 +4,8 @@ use std::io;
 
 use futures::{Poll, Stream};
 use tokio::net::TcpListener;
+#[cfg(windows)]
+use tokio_named_pipe::PipeListener;
 #[cfg(unix)]
 use tokio_uds::UnixListener;
 #[cfg(windows)]
@@ -13,6 +15,8 @@ use util::{IncomingSocketAddr, StreamSelector};
 
 pub enum Incoming {
     Tcp(TcpListener),
+    #[cfg(windows)]
+    Pipe(PipeListener),
     Unix(UnixListener),
 }
 
@@ -34,6 +38,13 @@ impl Stream for Incoming {
                     Some((StreamSelector::Tcp(stream), IncomingSocketAddr::Tcp(addr)))
                 })
             }
+            #[cfg(windows)]
+            Incoming::Pipe(ref mut listener) => {
+                let path = listener.path().to_path_buf();
+                listener.poll_accept()?.map(|stream| {
+                    Some((StreamSelector::Pipe(stream), IncomingSocketAddr::Pipe(path)))
+                })
+            }
             Incoming::Unix(ref mut listener) => {
                 let accept = match listener.poll_accept() {
                     Ok(accept) => accept,
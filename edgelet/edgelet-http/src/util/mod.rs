@@ -6,6 +6,8 @@ use std::net::SocketAddr;
 #[cfg(unix)]
 use std::os::unix::net::SocketAddr as UnixSocketAddr;
 use std::path::Path;
+#[cfg(windows)]
+use std::path::PathBuf;
 
 use bytes::{Buf, BufMut};
 use edgelet_core::pid::Pid;
@@ -21,6 +23,7 @@ use tokio_uds::UnixStream;
 #[cfg(windows)]
 use tokio_uds_windows::UnixStream;
 
+use keepalive::TcpKeepalive;
 use pid::UnixStreamExt;
 
 pub mod connector;
@@ -39,15 +42,28 @@ pub enum StreamSelector {
 }
 
 impl StreamSelector {
-    #[cfg_attr(feature = "cargo-clippy", allow(match_same_arms))]
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap, match_same_arms))]
     pub fn pid(&self) -> io::Result<Pid> {
         match *self {
             StreamSelector::Tcp(_) => Ok(Pid::Any),
             #[cfg(windows)]
-            StreamSelector::Pipe(_) => Ok(Pid::Any),
+            StreamSelector::Pipe(ref stream) => {
+                stream.client_pid().map(|pid| Pid::Value(pid as i32))
+            }
             StreamSelector::Unix(ref stream) => stream.pid(),
         }
     }
+
+    /// Enables TCP keepalive on TCP streams. Other streams are left as they
+    /// are.
+    pub fn set_tcp_keepalive(&self, keepalive: &TcpKeepalive) -> io::Result<()> {
+        match *self {
+            StreamSelector::Tcp(ref stream) => keepalive.apply(stream),
+            #[cfg(windows)]
+            StreamSelector::Pipe(_) => Ok(()),
+            StreamSelector::Unix(_) => Ok(()),
+        }
+    }
 }
 
 impl Read for StreamSelector {
@@ -126,6 +142,8 @@ impl AsyncWrite for StreamSelector {
 
 pub enum IncomingSocketAddr {
     Tcp(SocketAddr),
+    #[cfg(windows)]
+    Pipe(PathBuf),
     Unix(UnixSocketAddr),
 }
 
@@ -133,6 +151,8 @@ impl fmt::Display for IncomingSocketAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             IncomingSocketAddr::Tcp(ref socket) => socket.fmt(f),
+            #[cfg(windows)]
+            IncomingSocketAddr::Pipe(ref path) => write!(f, "{}", path.display()),
             IncomingSocketAddr::Unix(ref socket) => {
                 if let Some(path) = socket.as_pathname() {
                     write!(f, "{}", path.display())
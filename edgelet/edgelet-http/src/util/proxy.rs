@@ -12,10 +12,16 @@ pub struct MaybeProxyClient {
 
 impl MaybeProxyClient {
     pub fn new(proxy_uri: Option<Uri>) -> Result<Self, Error> {
-        MaybeProxyClient::create(false, proxy_uri)
+        MaybeProxyClient::create(false, proxy_uri, None)
     }
 
-    fn create(null: bool, proxy_uri: Option<Uri>) -> Result<Self, Error> {
+    /// Like `new`, but the CA certificates in the PEM `ca_bundle` are trusted
+    /// for HTTPS connections as well.
+    pub fn new_with_ca_bundle(proxy_uri: Option<Uri>, ca_bundle: &[u8]) -> Result<Self, Error> {
+        MaybeProxyClient::create(false, proxy_uri, Some(ca_bundle))
+    }
+
+    fn create(null: bool, proxy_uri: Option<Uri>, ca_bundle: Option<&[u8]>) -> Result<Self, Error> {
         let mut config = Client::configure();
         if null {
             config.null();
@@ -23,6 +29,9 @@ impl MaybeProxyClient {
         if let Some(uri) = proxy_uri {
             config.proxy(uri);
         }
+        if let Some(ca_bundle) = ca_bundle {
+            config.ca_bundle(ca_bundle);
+        }
         Ok(MaybeProxyClient {
             client: config.build()?,
         })
@@ -30,7 +39,7 @@ impl MaybeProxyClient {
 
     #[cfg(test)]
     pub fn new_null() -> Result<Self, Error> {
-        MaybeProxyClient::create(true, None)
+        MaybeProxyClient::create(true, None, None)
     }
 
     #[cfg(test)]
@@ -10,6 +10,9 @@ use IntoResponse;
 
 pub const API_VERSION: &str = "2018-06-28";
 
+/// The API versions requests are accepted for.
+pub const SUPPORTED_API_VERSIONS: &[&str] = &[API_VERSION];
+
 #[derive(Clone)]
 pub struct ApiVersionService<T> {
     upstream: T,
@@ -42,7 +45,9 @@ where
             });
 
             match api_version {
-                Some(ref api_version) if api_version == API_VERSION => Ok(()),
+                Some(ref api_version) if SUPPORTED_API_VERSIONS.contains(&api_version.as_ref()) => {
+                    Ok(())
+                }
                 Some(api_version) => Err(ErrorKind::InvalidApiVersion(api_version.into_owned())),
                 None => Err(ErrorKind::InvalidApiVersion(String::new())),
             }
@@ -0,0 +1,95 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_http;
+extern crate futures;
+extern crate hyper;
+extern crate tokio;
+extern crate url;
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use edgelet_http::HyperExt;
+use futures::Future;
+use hyper::server::conn::Http;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Request, Response};
+use tokio::runtime::Runtime;
+use url::Url;
+
+const REQUEST: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+fn unused_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// Sends a request on a new connection, which is kept open once the response
+// is read. Returns `None` if the server closed the connection instead.
+fn connect(port: u16) -> Option<TcpStream> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    if stream.write_all(REQUEST).is_err() {
+        return None;
+    }
+    let mut buf = [0; 1024];
+    match stream.read(&mut buf) {
+        Ok(0) => None,
+        Ok(n) => {
+            assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK"));
+            Some(stream)
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::ConnectionReset => None,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[test]
+fn connections_beyond_max_are_refused_until_others_close() {
+    let port = unused_port();
+    let url = Url::parse(&format!("http://127.0.0.1:{}", port)).unwrap();
+    let mut runtime = Runtime::new().unwrap();
+
+    let server = Http::new()
+        .bind_url(url, || {
+            Ok::<_, io::Error>(service_fn_ok(|_: Request<Body>| {
+                Response::new(Body::empty())
+            }))
+        })
+        .unwrap()
+        .with_max_connections(2);
+    assert_eq!(Some(2), server.max_connections());
+    runtime.spawn(server.run().map_err(|_| ()));
+
+    let first = connect(port).expect("first connection was refused");
+    let _second = connect(port).expect("second connection was refused");
+    assert!(connect(port).is_none());
+
+    // the server notices the closed connection asynchronously
+    drop(first);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let third = loop {
+        if let Some(stream) = connect(port) {
+            break stream;
+        }
+        assert!(Instant::now() < deadline, "connection was still refused");
+        thread::sleep(Duration::from_millis(50));
+    };
+    assert!(connect(port).is_none());
+    drop(third);
+}
@@ -23,10 +23,12 @@ extern crate nix;
 extern crate systemd;
 extern crate url;
 
+use std::net::TcpListener;
+use std::os::unix::io::AsRawFd;
 use std::sync::{Mutex, MutexGuard};
 use std::{env, io};
 
-use edgelet_http::HyperExt;
+use edgelet_http::{activated_url, HyperExt};
 use futures::{future, Future};
 use hyper::server::conn::Http;
 use hyper::service::Service;
@@ -48,6 +50,10 @@ const LISTEN_FDS_START: Fd = 5;
 #[cfg(not(test))]
 const LISTEN_FDS_START: Fd = 3;
 
+// The systemd crate isn't built for tests here, so it expects passed sockets
+// from fd 3 regardless of the above.
+const SYSTEMD_LISTEN_FDS_START: Fd = 3;
+
 const ENV_FDS: &str = "LISTEN_FDS";
 const ENV_PID: &str = "LISTEN_PID";
 
@@ -138,3 +144,54 @@ fn test_fd_err() {
     unistd::close(fd).unwrap();
     assert!(run.is_err());
 }
+
+#[test]
+fn test_activated_url_uses_passed_socket() {
+    let _l = lock_env();
+    set_current_pid();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+
+    // pass the listening socket the way systemd does, keeping whatever the
+    // test harness has open at that fd
+    let saved = unistd::dup(SYSTEMD_LISTEN_FDS_START).ok();
+    unistd::dup2(listener.as_raw_fd(), SYSTEMD_LISTEN_FDS_START).unwrap();
+    env::set_var(ENV_FDS, "1");
+
+    let activated = activated_url(&url);
+    // binding the address itself would fail since the passed socket is
+    // listening on it
+    let run = Http::new().bind_url(activated.clone().unwrap_or(url), move || {
+        let service = TestService {
+            status_code: StatusCode::OK,
+            error: false,
+        };
+        Ok::<_, io::Error>(service)
+    });
+    let bound = run.is_ok();
+    drop(run);
+
+    match saved {
+        Some(saved) => {
+            unistd::dup2(saved, SYSTEMD_LISTEN_FDS_START).unwrap();
+            unistd::close(saved).unwrap();
+        }
+        None => {
+            unistd::close(SYSTEMD_LISTEN_FDS_START).ok();
+        }
+    }
+    env::remove_var(ENV_FDS);
+
+    assert_eq!(Some(Url::parse("fd://0").unwrap()), activated);
+    assert!(bound);
+}
+
+#[test]
+fn test_activated_url_without_passed_socket() {
+    let _l = lock_env();
+    set_current_pid();
+    env::remove_var(ENV_FDS);
+
+    let url = Url::parse("http://127.0.0.1:8080").unwrap();
+    assert_eq!(None, activated_url(&url));
+}
@@ -0,0 +1,84 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_http;
+extern crate futures;
+extern crate hyper;
+extern crate hyper_tls;
+extern crate native_tls;
+extern crate tokio;
+extern crate url;
+
+use std::io;
+use std::net::TcpListener;
+
+use edgelet_http::{tls_acceptor, HyperExt};
+use futures::Future;
+use hyper::client::HttpConnector;
+use hyper::server::conn::Http;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Client, Request, Response, StatusCode};
+use hyper_tls::HttpsConnector;
+use native_tls::TlsConnector;
+use tokio::runtime::Runtime;
+use url::Url;
+
+// A self-signed certificate for "localhost"
+const CERT: &[u8] = include_bytes!("certs/cert.pem");
+const KEY: &[u8] = include_bytes!("certs/key.pem");
+
+fn unused_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+#[test]
+fn tls_server_serves_https_and_rejects_plaintext() {
+    let port = unused_port();
+    let url = Url::parse(&format!("http://127.0.0.1:{}", port)).unwrap();
+    let mut runtime = Runtime::new().unwrap();
+
+    let server = Http::new()
+        .bind_url(url, || {
+            Ok::<_, io::Error>(service_fn_ok(|_: Request<Body>| {
+                Response::new(Body::empty())
+            }))
+        })
+        .unwrap()
+        .with_tls_acceptor(tls_acceptor(CERT, KEY).unwrap());
+    assert!(server.is_tls());
+    runtime.spawn(server.run().map_err(|_| ()));
+
+    let mut http = HttpConnector::new(1);
+    http.enforce_http(false);
+    let tls = TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let https_client = Client::builder().build::<_, Body>(HttpsConnector::from((http, tls)));
+    let response = runtime
+        .block_on(https_client.get(format!("https://localhost:{}", port).parse().unwrap()))
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+
+    let http_client = Client::new();
+    let result =
+        runtime.block_on(http_client.get(format!("http://127.0.0.1:{}", port).parse().unwrap()));
+    assert!(result.is_err());
+}
+
+#[test]
+fn tls_acceptor_rejects_invalid_certificate() {
+    assert!(tls_acceptor(b"not a certificate", KEY).is_err());
+    assert!(tls_acceptor(CERT, b"not a key").is_err());
+}
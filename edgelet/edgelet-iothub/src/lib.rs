@@ -125,6 +125,7 @@ where
     hub_id: String,
     device_id: String,
     key: K,
+    signature_algorithm: SignatureAlgorithm,
 }
 
 impl<K> SasTokenSource<K>
@@ -136,8 +137,15 @@ where
             hub_id,
             device_id,
             key,
+            signature_algorithm: SignatureAlgorithm::default(),
         }
     }
+
+    /// Signs tokens with the given algorithm instead of HMAC-SHA256.
+    pub fn with_signature_algorithm(mut self, signature_algorithm: SignatureAlgorithm) -> Self {
+        self.signature_algorithm = signature_algorithm;
+        self
+    }
 }
 
 impl<K> TokenSource for SasTokenSource<K>
@@ -156,7 +164,7 @@ where
 
         let signature = self
             .key
-            .sign(SignatureAlgorithm::HMACSHA256, sig_data.as_bytes())
+            .sign(self.signature_algorithm, sig_data.as_bytes())
             .map(|s| base64::encode(s.as_bytes()))
             .context(ErrorKind::GetToken)?;
 
@@ -177,6 +185,7 @@ where
             hub_id: self.hub_id.clone(),
             device_id: self.device_id.clone(),
             key: self.key.clone(),
+            signature_algorithm: self.signature_algorithm,
         }
     }
 }
@@ -432,6 +441,33 @@ mod tests {
         assert_eq!(m1.generation_id(), "");
     }
 
+    #[test]
+    fn sas_token_is_signed_with_signature_algorithm() {
+        let expiry = Utc.ymd(2019, 1, 1).and_hms(0, 0, 0);
+        let signature = |token: &str| -> Vec<u8> {
+            let sig = ::url::form_urlencoded::parse(token.as_bytes())
+                .find(|(name, _)| name == "sig")
+                .map(|(_, value)| value.into_owned())
+                .unwrap();
+            ::base64::decode(&sig).unwrap()
+        };
+        let token_source = SasTokenSource::new(
+            "hub".to_string(),
+            "device".to_string(),
+            MemoryKey::new("device"),
+        );
+
+        let sha256_token = token_source.get(&expiry).unwrap();
+        let sha384_token = token_source
+            .with_signature_algorithm(SignatureAlgorithm::HMACSHA384)
+            .get(&expiry)
+            .unwrap();
+
+        assert_eq!(32, signature(&sha256_token).len());
+        assert_eq!(48, signature(&sha384_token).len());
+        assert_ne!(sha256_token, sha384_token);
+    }
+
     #[test]
     fn get_key_pair_succeeds() {
         let mut key_store = MemoryKeyStore::new();
@@ -1,6 +1,8 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use edgelet_core::*;
@@ -13,15 +15,21 @@ use hyper::Body;
 
 #[derive(Clone, Debug)]
 pub struct NullRegistry<E: Fail> {
+    prune_called: Arc<AtomicUsize>,
     phantom: PhantomData<E>,
 }
 
 impl<E: Fail> NullRegistry<E> {
     pub fn new() -> Self {
         NullRegistry {
+            prune_called: Arc::new(AtomicUsize::new(0)),
             phantom: PhantomData,
         }
     }
+
+    pub fn prune_called(&self) -> usize {
+        self.prune_called.load(Ordering::SeqCst)
+    }
 }
 
 impl<E: Fail> Default for NullRegistry<E> {
@@ -34,6 +42,7 @@ impl<E: Fail> ModuleRegistry for NullRegistry<E> {
     type Error = E;
     type PullFuture = FutureResult<(), Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type PruneFuture = FutureResult<(), Self::Error>;
     type Config = TestConfig;
 
     fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
@@ -43,6 +52,11 @@ impl<E: Fail> ModuleRegistry for NullRegistry<E> {
     fn remove(&self, _name: &str) -> Self::RemoveFuture {
         future::ok(())
     }
+
+    fn prune(&self) -> Self::PruneFuture {
+        self.prune_called.fetch_add(1, Ordering::SeqCst);
+        future::ok(())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -103,6 +117,7 @@ impl<E: Clone + Fail> Module for TestModule<E> {
 pub struct TestRuntime<E: Fail> {
     module: Result<TestModule<E>, E>,
     registry: NullRegistry<E>,
+    prune_volumes_called: Arc<AtomicUsize>,
 }
 
 impl<E: Fail> TestRuntime<E> {
@@ -110,8 +125,13 @@ impl<E: Fail> TestRuntime<E> {
         TestRuntime {
             module,
             registry: NullRegistry::new(),
+            prune_volumes_called: Arc::new(AtomicUsize::new(0)),
         }
     }
+
+    pub fn prune_volumes_called(&self) -> usize {
+        self.prune_volumes_called.load(Ordering::SeqCst)
+    }
 }
 
 pub struct EmptyBody<E> {
@@ -167,6 +187,7 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
     type StopFuture = FutureResult<(), Self::Error>;
     type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
     type RemoveAllFuture = FutureResult<(), Self::Error>;
+    type PruneVolumesFuture = FutureResult<(), Self::Error>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
         match self.module {
@@ -251,4 +272,9 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
     fn remove_all(&self) -> Self::RemoveAllFuture {
         future::ok(())
     }
+
+    fn prune_volumes(&self) -> Self::PruneVolumesFuture {
+        self.prune_volumes_called.fetch_add(1, Ordering::SeqCst);
+        future::ok(())
+    }
 }
@@ -67,6 +67,15 @@ where
         .context(ErrorKind::SerdeClone)?)
 }
 
+/// For `skip_serializing_if`, so that a setting left at its default doesn't
+/// show up in the serialized form.
+pub fn is_default<T>(value: &T) -> bool
+where
+    T: Default + PartialEq,
+{
+    *value == T::default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +160,30 @@ mod tests {
         assert_eq!(c1.name, c2.name);
         assert_eq!(c1.age, c2.age);
     }
+
+    #[test]
+    fn is_default_skips_default_values() {
+        #[derive(Serialize)]
+        struct Settings {
+            #[serde(skip_serializing_if = "is_default")]
+            enabled: bool,
+            #[serde(skip_serializing_if = "is_default")]
+            retries: u32,
+        }
+
+        let settings = Settings {
+            enabled: false,
+            retries: 0,
+        };
+        assert_eq!("{}", serde_json::to_string(&settings).unwrap());
+
+        let settings = Settings {
+            enabled: true,
+            retries: 3,
+        };
+        assert_eq!(
+            r#"{"enabled":true,"retries":3}"#,
+            serde_json::to_string(&settings).unwrap()
+        );
+    }
 }
@@ -27,6 +27,9 @@ pub enum CertificateType {
 /// - [`MakeRandom`]
 /// - [`CreateMasterEncryptionKey`]
 /// - [`DestroyMasterEncryptionKey`]
+/// - [`ExportMasterEncryptionKey`]
+/// - [`ImportMasterEncryptionKey`]
+/// - [`ManageMasterEncryptionKeySlots`]
 /// - [`CreateCertificate`]
 /// - [`Encrypt`]
 /// - [`Decrypt`]
@@ -133,6 +136,101 @@ impl DestroyMasterEncryptionKey for Crypto {
     }
 }
 
+impl ExportMasterEncryptionKey for Crypto {
+    fn export_master_encryption_key(&self, passphrase: &str) -> Result<Buffer, Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_export_master_encryption_key
+            .ok_or(ErrorKind::NoneFn)?;
+
+        let c_passphrase = CString::new(passphrase).map_err(|_| ErrorKind::ToCStr)?;
+        let mut wrapped_key = SIZED_BUFFER {
+            buffer: std::ptr::null_mut() as *mut c_uchar,
+            size: 0,
+        };
+        let result = unsafe { if_fn(self.handle, c_passphrase.as_ptr(), &mut wrapped_key) };
+        match result {
+            0 => Ok(Buffer::new(self.interface, wrapped_key)),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+}
+
+impl ImportMasterEncryptionKey for Crypto {
+    fn import_master_encryption_key(
+        &self,
+        passphrase: &str,
+        wrapped_key: &[u8],
+    ) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_import_master_encryption_key
+            .ok_or(ErrorKind::NoneFn)?;
+
+        let c_passphrase = CString::new(passphrase).map_err(|_| ErrorKind::ToCStr)?;
+        let c_wrapped_key = SIZED_BUFFER {
+            buffer: wrapped_key.as_ptr() as *mut c_uchar,
+            size: wrapped_key.len(),
+        };
+        let result = unsafe { if_fn(self.handle, c_passphrase.as_ptr(), &c_wrapped_key) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+}
+
+impl ManageMasterEncryptionKeySlots for Crypto {
+    fn create_master_encryption_key_in_slot(&self, slot: usize) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_create_master_encryption_key_in_slot
+            .ok_or(ErrorKind::NoneFn)?;
+        let result = unsafe { if_fn(self.handle, slot) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+
+    fn destroy_master_encryption_key_in_slot(&self, slot: usize) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_destroy_master_encryption_key_in_slot
+            .ok_or(ErrorKind::NoneFn)?;
+        let result = unsafe { if_fn(self.handle, slot) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+
+    fn set_active_master_encryption_key_slot(&self, slot: usize) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_set_active_master_encryption_key_slot
+            .ok_or(ErrorKind::NoneFn)?;
+        let result = unsafe { if_fn(self.handle, slot) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+
+    fn get_active_master_encryption_key_slot(&self) -> Result<usize, Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_get_active_master_encryption_key_slot
+            .ok_or(ErrorKind::NoneFn)?;
+        let mut slot: usize = 0;
+        let result = unsafe { if_fn(self.handle, &mut slot) };
+        match result {
+            0 => Ok(slot),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+}
+
 fn make_certification_props(props: &CertificateProperties) -> Result<CERT_PROPS_HANDLE, Error> {
     let handle = unsafe { cert_properties_create() };
     if handle.is_null() {
@@ -627,7 +725,8 @@ mod tests {
 
     use super::super::{
         CreateCertificate, CreateMasterEncryptionKey, Decrypt, DestroyMasterEncryptionKey, Encrypt,
-        GetTrustBundle, MakeRandom,
+        ExportMasterEncryptionKey, GetTrustBundle, ImportMasterEncryptionKey, MakeRandom,
+        ManageMasterEncryptionKeySlots,
     };
     use super::{Buffer, CertificateProperties, Crypto};
     use hsm_sys::*;
@@ -890,6 +989,52 @@ mod tests {
             1
         }
     }
+    unsafe extern "C" fn fake_export_master(
+        handle: HSM_CLIENT_HANDLE,
+        _passphrase: *const c_char,
+        wrapped_key: *mut SIZED_BUFFER,
+    ) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            (*wrapped_key).buffer = malloc(DEFAULT_BUF_LEN) as *mut c_uchar;
+            (*wrapped_key).size = DEFAULT_BUF_LEN;
+            0
+        } else {
+            1
+        }
+    }
+    unsafe extern "C" fn fake_import_master(
+        handle: HSM_CLIENT_HANDLE,
+        _passphrase: *const c_char,
+        _wrapped_key: *const SIZED_BUFFER,
+    ) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            0
+        } else {
+            1
+        }
+    }
+    unsafe extern "C" fn fake_master_key_slot(handle: HSM_CLIENT_HANDLE, _slot: usize) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            0
+        } else {
+            1
+        }
+    }
+    unsafe extern "C" fn fake_get_active_master_key_slot(
+        handle: HSM_CLIENT_HANDLE,
+        slot: *mut usize,
+    ) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            *slot = 1;
+            0
+        } else {
+            1
+        }
+    }
     unsafe extern "C" fn fake_encrypt(
         handle: HSM_CLIENT_HANDLE,
         _client_id: *const SIZED_BUFFER,
@@ -986,6 +1131,14 @@ mod tests {
         println!("You should never see this print");
     }
 
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_create_master_key_in_slot_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        hsm_crypto.create_master_encryption_key_in_slot(1).unwrap();
+        println!("You should never see this print");
+    }
+
     #[test]
     #[should_panic(expected = "HSM API Not Implemented")]
     fn no_destroy_master_key_api_fail() {
@@ -994,6 +1147,26 @@ mod tests {
         println!("You should never see this print");
     }
 
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_export_master_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        let result = hsm_crypto
+            .export_master_encryption_key("passphrase")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_import_master_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        hsm_crypto
+            .import_master_encryption_key("passphrase", b"wrapped_key")
+            .unwrap();
+        println!("You should never see this print");
+    }
+
     #[test]
     #[should_panic(expected = "HSM API Not Implemented")]
     fn no_create_certificate_api_fail() {
@@ -1051,6 +1224,14 @@ mod tests {
                 hsm_client_decrypt_data: Some(fake_decrypt),
                 hsm_client_get_trust_bundle: Some(fake_trust_bundle),
                 hsm_client_free_buffer: Some(real_buffer_destroy),
+                hsm_client_export_master_encryption_key: Some(fake_export_master),
+                hsm_client_import_master_encryption_key: Some(fake_import_master),
+                hsm_client_create_master_encryption_key_in_slot: Some(fake_master_key_slot),
+                hsm_client_destroy_master_encryption_key_in_slot: Some(fake_master_key_slot),
+                hsm_client_set_active_master_encryption_key_slot: Some(fake_master_key_slot),
+                hsm_client_get_active_master_encryption_key_slot: Some(
+                    fake_get_active_master_key_slot,
+                ),
             },
         }
     }
@@ -1080,6 +1261,42 @@ mod tests {
         println!("You should never see this print");
     }
 
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_export_master_encryption_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        let result = hsm_crypto
+            .export_master_encryption_key("passphrase")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_import_master_encryption_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        hsm_crypto
+            .import_master_encryption_key("passphrase", b"wrapped_key")
+            .unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_set_active_master_encryption_key_slot_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        hsm_crypto.set_active_master_encryption_key_slot(1).unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_get_active_master_encryption_key_slot_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        let result = hsm_crypto.get_active_master_encryption_key_slot().unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
     #[test]
     #[should_panic(expected = "HSM API returned an invalid null response")]
     fn hsm_create_certificate_errors() {
@@ -1132,6 +1349,14 @@ mod tests {
                 hsm_client_decrypt_data: Some(fake_decrypt),
                 hsm_client_get_trust_bundle: Some(fake_trust_bundle),
                 hsm_client_free_buffer: Some(real_buffer_destroy),
+                hsm_client_export_master_encryption_key: Some(fake_export_master),
+                hsm_client_import_master_encryption_key: Some(fake_import_master),
+                hsm_client_create_master_encryption_key_in_slot: Some(fake_master_key_slot),
+                hsm_client_destroy_master_encryption_key_in_slot: Some(fake_master_key_slot),
+                hsm_client_set_active_master_encryption_key_slot: Some(fake_master_key_slot),
+                hsm_client_get_active_master_encryption_key_slot: Some(
+                    fake_get_active_master_key_slot,
+                ),
             },
         }
     }
@@ -1148,6 +1373,23 @@ mod tests {
 
         let _destroy_key: () = hsm_crypto.destroy_master_encryption_key().unwrap();
 
+        let wrapped_key = hsm_crypto
+            .export_master_encryption_key("passphrase")
+            .unwrap();
+        assert_eq!(wrapped_key.len(), DEFAULT_BUF_LEN);
+
+        let _import_key: () = hsm_crypto
+            .import_master_encryption_key("passphrase", &wrapped_key)
+            .unwrap();
+
+        let _slot_key: () = hsm_crypto.create_master_encryption_key_in_slot(1).unwrap();
+        let _active_slot: () = hsm_crypto.set_active_master_encryption_key_slot(1).unwrap();
+        assert_eq!(
+            1,
+            hsm_crypto.get_active_master_encryption_key_slot().unwrap()
+        );
+        let _destroy_slot_key: () = hsm_crypto.destroy_master_encryption_key_in_slot(0).unwrap();
+
         let props = CertificateProperties::default();
         let _new_cert = hsm_crypto.create_certificate(&props).unwrap();
 
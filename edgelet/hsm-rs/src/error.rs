@@ -7,6 +7,7 @@ use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 
 use failure::{Backtrace, Context, Fail};
+use hsm_sys::HSM_TPM_RETRY;
 
 #[derive(Debug)]
 pub struct Error {
@@ -19,6 +20,8 @@ pub enum ErrorKind {
     Init(isize),
     #[fail(display = "HSM API failure occurred: {}", _0)]
     Api(c_int),
+    #[fail(display = "HSM API failure occurred because the TPM was busy")]
+    TpmRetry,
     #[fail(display = "HSM API Not Implemented")]
     NoneFn,
     #[fail(display = "HSM API failed to create Certificate properties")]
@@ -77,8 +80,13 @@ impl From<Context<ErrorKind>> for Error {
 
 impl From<c_int> for Error {
     fn from(result: c_int) -> Self {
+        let kind = if result == HSM_TPM_RETRY {
+            ErrorKind::TpmRetry
+        } else {
+            ErrorKind::Api(result)
+        };
         Error {
-            inner: Context::new(ErrorKind::Api(result)),
+            inner: Context::new(kind),
         }
     }
 }
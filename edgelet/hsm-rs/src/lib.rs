@@ -71,6 +71,25 @@ pub trait DestroyMasterEncryptionKey {
     fn destroy_master_encryption_key(&self) -> Result<(), Error>;
 }
 
+pub trait ExportMasterEncryptionKey {
+    fn export_master_encryption_key(&self, passphrase: &str) -> Result<Buffer, Error>;
+}
+
+pub trait ImportMasterEncryptionKey {
+    fn import_master_encryption_key(
+        &self,
+        passphrase: &str,
+        wrapped_key: &[u8],
+    ) -> Result<(), Error>;
+}
+
+pub trait ManageMasterEncryptionKeySlots {
+    fn create_master_encryption_key_in_slot(&self, slot: usize) -> Result<(), Error>;
+    fn destroy_master_encryption_key_in_slot(&self, slot: usize) -> Result<(), Error>;
+    fn set_active_master_encryption_key_slot(&self, slot: usize) -> Result<(), Error>;
+    fn get_active_master_encryption_key_slot(&self) -> Result<usize, Error>;
+}
+
 pub trait CreateCertificate {
     fn create_certificate(
         &self,
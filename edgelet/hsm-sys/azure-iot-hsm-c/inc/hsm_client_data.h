@@ -20,6 +20,12 @@ extern "C" {
 
 #define AZURE_IOT_HSM_VERSION "1.0.1"
 
+/**
+ * Result of a TPM function when the TPM could not run one of its commands at
+ * the time, e.g. because it was busy with another one. The call can be retried.
+ */
+#define HSM_TPM_RETRY (-2)
+
 typedef void* HSM_CLIENT_HANDLE;
 
 /**
@@ -169,8 +175,9 @@ typedef char* (*HSM_CLIENT_GET_COMMON_NAME)(HSM_CLIENT_HANDLE handle);
 typedef int (*HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY)(HSM_CLIENT_HANDLE handle);
 
 /**
-* @brief        Removes the master encryption key from the HSM. This is expected
-*               to be called once during de-provisioning of the device.
+* @brief        Removes the master encryption keys of every key slot from the HSM
+*               and makes slot 0 the active slot again. This is expected to be
+*               called once during de-provisioning of the device.
 *
 * @note         Once this is erased, all encrypted data is lost.
 *
@@ -259,6 +266,85 @@ typedef int (*HSM_CLIENT_DECRYPT_DATA)(HSM_CLIENT_HANDLE handle, const SIZED_BUF
 */
 typedef CERT_INFO_HANDLE (*HSM_CLIENT_GET_TRUST_BUNDLE)(HSM_CLIENT_HANDLE handle);
 
+/**
+* @brief    Exports the master encryption key wrapped with a key derived from
+*           the supplied passphrase, so that it can be restored on this or
+*           another device with ::HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY.
+*
+* @param handle             A valid HSM client handle
+* @param passphrase         Passphrase used to protect the exported key
+* @param[out] wrapped_key   The wrapped key. This function allocates memory for a buffer
+*                           which must be freed by a call to ::HSM_CLIENT_FREE_BUFFER.
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY)(HSM_CLIENT_HANDLE handle, const char* passphrase, SIZED_BUFFER* wrapped_key);
+
+/**
+* @brief    Replaces the master encryption key with one exported by
+*           ::HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY.
+*
+* @note     Data encrypted with the replaced key can no longer be decrypted.
+*
+* @param handle         A valid HSM client handle
+* @param passphrase     Passphrase the key was exported with
+* @param wrapped_key    The wrapped key
+*
+* @return   Zero on success, nonzero otherwise. Fails if the passphrase
+*           does not match the one the key was exported with.
+*/
+typedef int (*HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY)(HSM_CLIENT_HANDLE handle, const char* passphrase, const SIZED_BUFFER* wrapped_key);
+
+/**
+* @brief    Provisions a master encryption key in the given key slot. Having
+*           keys in more than one slot allows rotating the key used for
+*           encryption while data encrypted with the previous key can still
+*           be decrypted.
+*
+* @param handle     A valid HSM client handle
+* @param slot       The key slot, slot 0 holds the key of
+*                   ::HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY after initialization
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT)(HSM_CLIENT_HANDLE handle, size_t slot);
+
+/**
+* @brief    Removes the master encryption key in the given key slot.
+*
+* @note     Once this is erased, data encrypted with the key of the slot is lost.
+*
+* @param handle     A valid HSM client handle
+* @param slot       The key slot
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT)(HSM_CLIENT_HANDLE handle, size_t slot);
+
+/**
+* @brief    Selects the key slot whose master encryption key encrypts data
+*           from now on. Decryption tries the active slot first and then the
+*           keys of the other slots.
+*
+* @note     The active slot is persisted in the HSM store and survives a restart.
+*
+* @param handle     A valid HSM client handle
+* @param slot       The key slot, which must hold a key
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT)(HSM_CLIENT_HANDLE handle, size_t slot);
+
+/**
+* @brief    Returns the key slot whose master encryption key encrypts data.
+*
+* @param handle     A valid HSM client handle
+* @param[out] slot  The active key slot, slot 0 until another slot is selected
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT)(HSM_CLIENT_HANDLE handle, size_t* slot);
+
 typedef struct HSM_CLIENT_TPM_INTERFACE_TAG
 {
     HSM_CLIENT_CREATE hsm_client_tpm_create;
@@ -297,6 +383,12 @@ typedef struct HSM_CLIENT_CRYPTO_INTERFACE_TAG
     HSM_CLIENT_DECRYPT_DATA hsm_client_decrypt_data;
     HSM_CLIENT_GET_TRUST_BUNDLE hsm_client_get_trust_bundle;
     HSM_CLIENT_FREE_BUFFER hsm_client_free_buffer;
+    HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY hsm_client_export_master_encryption_key;
+    HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY hsm_client_import_master_encryption_key;
+    HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_create_master_encryption_key_in_slot;
+    HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_destroy_master_encryption_key_in_slot;
+    HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_set_active_master_encryption_key_slot;
+    HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_get_active_master_encryption_key_slot;
 } HSM_CLIENT_CRYPTO_INTERFACE;
 
 extern const HSM_CLIENT_TPM_INTERFACE* hsm_client_tpm_interface();
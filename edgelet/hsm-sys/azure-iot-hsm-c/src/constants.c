@@ -7,6 +7,10 @@ const char* const ENV_DEVICE_CA_PATH = "IOTEDGE_DEVICE_CA_CERT";
 const char* const ENV_DEVICE_PK_PATH = "IOTEDGE_DEVICE_CA_PK";
 const char* const ENV_TRUSTED_CA_CERTS_PATH = "IOTEDGE_TRUSTED_CA_CERTS";
 const char* const ENV_TPM_SELECT = "IOTEDGE_USE_TPM_DEVICE";
+const char* const ENV_ENCRYPTION_ALGORITHM = "IOTEDGE_ENCRYPTION_ALGORITHM";
+const char* const ENV_CERT_KEY_TYPE = "IOTEDGE_CERT_KEY_TYPE";
+const char* const ENV_TPM_DEVICE_PATH = "IOTEDGE_TPM_DEVICE_PATH";
+const char* const ENV_CRL_PATH = "IOTEDGE_CRL_PATH";
 
 /* HSM directory name under IOTEDGE_HOMEDIR */
 const char* const DEFAULT_EDGE_HOME_DIR_UNIX = "/var/lib/iotedge"; // note MacOS is included
@@ -20,6 +24,8 @@ const char* const HSM_CRYPTO_PK_DIR = "private";
 const char* const EDGE_STORE_NAME = "edgelet";
 const char* const EDGELET_IDENTITY_SAS_KEY_NAME = "edgelet-identity";
 const char* const EDGELET_ENC_KEY_NAME = "edgelet-master";
+/* the first slot holds the master encryption key created before slots existed */
+const char* const EDGELET_ENC_KEY_SLOT_NAMES[EDGELET_ENC_KEY_SLOT_COUNT] = { "edgelet-master", "edgelet-master-1" };
 const char* const DEFAULT_TRUSTED_CA_ALIAS = "edgelet-trusted-ca";
 const char* const OWNER_CA_ALIAS = "edge_owner_ca";
 const char* const OWNER_CA_COMMON_NAME = "Test Edge Owner CA";
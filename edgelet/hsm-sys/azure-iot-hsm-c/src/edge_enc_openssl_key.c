@@ -1,10 +1,12 @@
 #include <stdlib.h>
+#include <string.h>
 
 #include <openssl/evp.h>
 #include <openssl/rand.h>
 
 #include "azure_c_shared_utility/gballoc.h"
 #include "hsm_client_store.h"
+#include "hsm_key.h"
 #include "hsm_log.h"
 #include "edge_openssl_common.h"
 
@@ -21,18 +23,48 @@
 //   |                    |
 //   |       ...          |
 //   +--------------------+
+//
+//   V2 ciphertext layout is the same as V1, with ChaCha20-Poly1305 instead
+//   of AES-256-GCM as the cipher. ChaCha20-Poly1305 only takes 12 byte
+//   nonces, so the nonce is the start of the SHA-256 digest of the
+//   initialization vector, which may then have any length like it does in V1.
 
 #define CIPHER_VERSION_SIZE 1
 #define ENCRYPTION_KEY_SIZE_IN_BYTES_V1 32
 #define CIPHER_TAG_SIZE_V1 16
 #define CIPHER_VERSION_V1 1
 #define CIPHER_HEADER_SIZE_V1 ((CIPHER_VERSION_SIZE) + (CIPHER_TAG_SIZE_V1))
+#define CIPHER_VERSION_V2 2
+#define CIPHER_NONCE_SIZE_V2 12
+
+#if OPENSSL_VERSION_NUMBER >= 0x10100000L
+#define CHACHA20_POLY1305_SUPPORTED 1
+#endif
+
+//   Wrapped key layout
+//   0      1          17        29   OFFSET
+//   +--------------------------------+
+//   | VER |   SALT    |     IV       |  HEADER
+//   +--------------------------------+
+//   |  V1 CIPHERTEXT OF THE KEY      |  PAYLOAD
+//   +--------------------------------+
+//
+//   The key is encrypted with AES-256-GCM under a key derived from the
+//   passphrase and the salt with PBKDF2-HMAC-SHA256. The header is the AAD.
+
+#define WRAP_VERSION_V1 1
+#define WRAP_SALT_SIZE 16
+#define WRAP_IV_SIZE 12
+#define WRAP_HEADER_SIZE ((CIPHER_VERSION_SIZE) + (WRAP_SALT_SIZE) + (WRAP_IV_SIZE))
+#define WRAP_KDF_ITERATIONS 100000
+#define WRAPPED_KEY_SIZE_V1 ((WRAP_HEADER_SIZE) + (CIPHER_HEADER_SIZE_V1) + (ENCRYPTION_KEY_SIZE_IN_BYTES_V1))
 
 struct ENC_KEY_TAG
 {
     HSM_CLIENT_KEY_INTERFACE intf;
     unsigned char *key;
     size_t key_size;
+    unsigned char version;
 };
 typedef struct ENC_KEY_TAG ENC_KEY;
 
@@ -93,8 +125,86 @@ int enc_key_derive_and_sign
     return __FAILURE__;
 }
 
+static const EVP_CIPHER* get_cipher(unsigned char version)
+{
+    const EVP_CIPHER *result;
+
+    if (version == CIPHER_VERSION_V1)
+    {
+        result = EVP_aes_256_gcm();
+    }
+#if defined(CHACHA20_POLY1305_SUPPORTED)
+    else if (version == CIPHER_VERSION_V2)
+    {
+        result = EVP_chacha20_poly1305();
+    }
+#endif
+    else
+    {
+        LOG_ERROR("Unsupported cipher version %d", version);
+        result = NULL;
+    }
+
+    return result;
+}
+
+static bool is_supported_version(unsigned char version)
+{
+    bool result;
+
+#if defined(CHACHA20_POLY1305_SUPPORTED)
+    result = ((version == CIPHER_VERSION_V1) || (version == CIPHER_VERSION_V2));
+#else
+    result = (version == CIPHER_VERSION_V1);
+#endif
+
+    return result;
+}
+
+static int get_cipher_iv
+(
+    unsigned char cipher_version,
+    const SIZED_BUFFER *initialization_vector,
+    unsigned char *nonce_buffer,
+    const unsigned char **iv,
+    int *iv_len
+)
+{
+    int result;
+
+    if (cipher_version == CIPHER_VERSION_V2)
+    {
+        unsigned char digest[EVP_MAX_MD_SIZE];
+        unsigned int digest_len = 0;
+
+        if ((EVP_Digest(initialization_vector->buffer, initialization_vector->size,
+                        digest, &digest_len, EVP_sha256(), NULL) != 1) ||
+            (digest_len < CIPHER_NONCE_SIZE_V2))
+        {
+            LOG_ERROR("Could not derive nonce from the initialization vector");
+            result = __FAILURE__;
+        }
+        else
+        {
+            memcpy(nonce_buffer, digest, CIPHER_NONCE_SIZE_V2);
+            *iv = nonce_buffer;
+            *iv_len = CIPHER_NONCE_SIZE_V2;
+            result = 0;
+        }
+    }
+    else
+    {
+        *iv = initialization_vector->buffer;
+        *iv_len = (int)initialization_vector->size;
+        result = 0;
+    }
+
+    return result;
+}
+
 static int encrypt_v1
 (
+    unsigned char cipher_version,
     const unsigned char *plaintext,
     int plaintext_len,
     const unsigned char *aad,
@@ -131,8 +241,8 @@ static int encrypt_v1
         unsigned char *ciphertext = tag + CIPHER_TAG_SIZE_V1;
 
         memset(ciphertext_buffer, 0, ciphertext_size);
-        *version = CIPHER_VERSION_V1;
-        if (EVP_EncryptInit_ex(ctx, EVP_aes_256_gcm(), NULL, NULL, NULL) != 1)
+        *version = cipher_version;
+        if (EVP_EncryptInit_ex(ctx, get_cipher(cipher_version), NULL, NULL, NULL) != 1)
         {
             LOG_ERROR("Could not initialize encrypt operation");
             result = __FAILURE__;
@@ -223,9 +333,12 @@ static int encrypt
 )
 {
     int result;
+    unsigned char nonce[CIPHER_NONCE_SIZE_V2];
+    const unsigned char *iv;
+    int iv_len;
 
     initialize_openssl();
-    if (version == CIPHER_VERSION_V1)
+    if (is_supported_version(version))
     {
         if (!validate_key_v1(key, key_size))
         {
@@ -237,16 +350,22 @@ static int encrypt
             LOG_ERROR("Plaintext buffer size too large %zu", plaintext->size);
             result = __FAILURE__;
         }
+        else if (get_cipher_iv(version, initialization_vector, nonce, &iv, &iv_len) != 0)
+        {
+            LOG_ERROR("Could not prepare the initialization vector");
+            result = __FAILURE__;
+        }
         else
         {
-            // default encryption implementation
-            result = encrypt_v1(plaintext->buffer,
+            // the V2 layout only differs from V1 in the cipher and its nonce
+            result = encrypt_v1(version,
+                                plaintext->buffer,
                                 (int)plaintext->size,
                                 identity->buffer,
                                 (int)identity->size,
                                 key,
-                                initialization_vector->buffer,
-                                (int)initialization_vector->size,
+                                iv,
+                                iv_len,
                                 &ciphertext->buffer,
                                 &ciphertext->size);
         }
@@ -262,6 +381,7 @@ static int encrypt
 
 static int decrypt_v1
 (
+    unsigned char cipher_version,
     const unsigned char *ciphertext_buffer,
     int ciphertext_buffer_size,
     const unsigned char *aad,
@@ -300,7 +420,7 @@ static int decrypt_v1
 
         memset(plaintext_buffer, 0, plaintext_buffer_size);
         memcpy(tag, tag_start, CIPHER_TAG_SIZE_V1);
-        if (EVP_DecryptInit_ex(ctx, EVP_aes_256_gcm(), NULL, NULL, NULL) != 1)
+        if (EVP_DecryptInit_ex(ctx, get_cipher(cipher_version), NULL, NULL, NULL) != 1)
         {
             LOG_ERROR("Could not initialize decrypt operation");
             result = __FAILURE__;
@@ -373,8 +493,12 @@ static int decrypt
 )
 {
     int result;
+    unsigned char nonce[CIPHER_NONCE_SIZE_V2];
+    const unsigned char *iv;
+    int iv_len;
+
     initialize_openssl();
-    if (version == CIPHER_VERSION_V1)
+    if (is_supported_version(version))
     {
         if (!validate_key_v1(key, key_size))
         {
@@ -386,16 +510,22 @@ static int decrypt
             LOG_ERROR("Ciphertext buffer incorrect size %zu", ciphertext->size);
             result = __FAILURE__;
         }
+        else if (get_cipher_iv(version, initialization_vector, nonce, &iv, &iv_len) != 0)
+        {
+            LOG_ERROR("Could not prepare the initialization vector");
+            result = __FAILURE__;
+        }
         else
         {
-            // default decrypt version
-            result = decrypt_v1(ciphertext->buffer,
+            // the V2 layout only differs from V1 in the cipher and its nonce
+            result = decrypt_v1(version,
+                                ciphertext->buffer,
                                 (int)ciphertext->size,
                                 identity->buffer,
                                 (int)identity->size,
                                 key,
-                                initialization_vector->buffer,
-                                (int)initialization_vector->size,
+                                iv,
+                                iv_len,
                                 &plaintext->buffer,
                                 &plaintext->size);
         }
@@ -457,8 +587,7 @@ static int enc_key_encrypt
         else
         {
             ENC_KEY *enc_key = (ENC_KEY*)key_handle;
-            // default encryption impl version 1
-            result = encrypt(CIPHER_VERSION_V1,
+            result = encrypt(enc_key->version,
                              enc_key->key,
                              enc_key->key_size,
                              identity,
@@ -485,7 +614,7 @@ static bool validate_input_ciphertext_buffer(const SIZED_BUFFER *sb, unsigned ch
         LOG_ERROR("Ciphertext has invalid size %zu", sb->size);
         result = false;
     }
-    else if (sb->buffer[0] != CIPHER_VERSION_V1)
+    else if (!is_supported_version(sb->buffer[0]))
     {
         LOG_ERROR("Unsupported encryption version %c", sb->buffer[0]);
         result = false;
@@ -558,14 +687,40 @@ static void enc_key_destroy(KEY_HANDLE key_handle)
 }
 
 KEY_HANDLE create_encryption_key(const unsigned char *key, size_t key_size)
+{
+    // default encryption impl version 1
+    return create_encryption_key_with_algorithm(key, key_size, HSM_ENC_ALGORITHM_AES_256_GCM);
+}
+
+KEY_HANDLE create_encryption_key_with_algorithm
+(
+    const unsigned char *key,
+    size_t key_size,
+    HSM_ENC_ALGORITHM_T algorithm
+)
 {
     ENC_KEY* enc_key;
+    unsigned char version;
+
+    if (algorithm == HSM_ENC_ALGORITHM_CHACHA20_POLY1305)
+    {
+        version = CIPHER_VERSION_V2;
+    }
+    else
+    {
+        version = CIPHER_VERSION_V1;
+    }
 
     if ((key == NULL) || (key_size != ENCRYPTION_KEY_SIZE_IN_BYTES_V1))
     {
         LOG_ERROR("Invalid encryption key create parameters");
         enc_key = NULL;
     }
+    else if (!is_supported_version(version))
+    {
+        LOG_ERROR("Encryption algorithm %d is not supported by this OpenSSL version", (int)algorithm);
+        enc_key = NULL;
+    }
     else
     {
         enc_key = (ENC_KEY*)malloc(sizeof(ENC_KEY));
@@ -588,6 +743,7 @@ KEY_HANDLE create_encryption_key(const unsigned char *key, size_t key_size)
             enc_key->intf.hsm_client_key_destroy = enc_key_destroy;
             memcpy(enc_key->key, key, key_size);
             enc_key->key_size = key_size;
+            enc_key->version = version;
         }
     }
 
@@ -645,3 +801,179 @@ int generate_encryption_key(unsigned char **key, size_t *key_size)
 
     return result;
 }
+
+static int derive_wrapping_key
+(
+    const char *passphrase,
+    const unsigned char *salt,
+    unsigned char *wrapping_key
+)
+{
+    int result;
+
+    if (PKCS5_PBKDF2_HMAC(passphrase, (int)strlen(passphrase),
+                          salt, WRAP_SALT_SIZE,
+                          WRAP_KDF_ITERATIONS, EVP_sha256(),
+                          ENCRYPTION_KEY_SIZE_IN_BYTES_V1, wrapping_key) != 1)
+    {
+        LOG_ERROR("Could not derive wrapping key from passphrase");
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
+int wrap_encryption_key
+(
+    const unsigned char *key,
+    size_t key_size,
+    const char *passphrase,
+    unsigned char **wrapped_key,
+    size_t *wrapped_key_size
+)
+{
+    int result;
+
+    initialize_openssl();
+    if ((wrapped_key == NULL) || (wrapped_key_size == NULL))
+    {
+        LOG_ERROR("Invalid wrapped key output parameters");
+        result = __FAILURE__;
+    }
+    else if ((key == NULL) || (key_size != ENCRYPTION_KEY_SIZE_IN_BYTES_V1))
+    {
+        LOG_ERROR("Invalid encryption key to wrap");
+        *wrapped_key = NULL;
+        *wrapped_key_size = 0;
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase");
+        *wrapped_key = NULL;
+        *wrapped_key_size = 0;
+        result = __FAILURE__;
+    }
+    else
+    {
+        unsigned char header[WRAP_HEADER_SIZE];
+        unsigned char wrapping_key[ENCRYPTION_KEY_SIZE_IN_BYTES_V1];
+        unsigned char *salt = header + CIPHER_VERSION_SIZE;
+        unsigned char *iv = salt + WRAP_SALT_SIZE;
+        unsigned char *ciphertext = NULL;
+        size_t ciphertext_size = 0;
+
+        *wrapped_key = NULL;
+        *wrapped_key_size = 0;
+        header[0] = WRAP_VERSION_V1;
+        if ((RAND_bytes(salt, WRAP_SALT_SIZE) != 1) || (RAND_bytes(iv, WRAP_IV_SIZE) != 1))
+        {
+            LOG_ERROR("Could not generate salt and IV to wrap key");
+            result = __FAILURE__;
+        }
+        else if (derive_wrapping_key(passphrase, salt, wrapping_key) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else if (encrypt_v1(CIPHER_VERSION_V1,
+                            key, (int)key_size,
+                            header, WRAP_HEADER_SIZE,
+                            wrapping_key,
+                            iv, WRAP_IV_SIZE,
+                            &ciphertext, &ciphertext_size) != 0)
+        {
+            LOG_ERROR("Could not wrap encryption key");
+            result = __FAILURE__;
+        }
+        else if ((*wrapped_key = (unsigned char*)malloc(WRAP_HEADER_SIZE + ciphertext_size)) == NULL)
+        {
+            LOG_ERROR("Could not allocate memory for wrapped key");
+            result = __FAILURE__;
+        }
+        else
+        {
+            memcpy(*wrapped_key, header, WRAP_HEADER_SIZE);
+            memcpy(*wrapped_key + WRAP_HEADER_SIZE, ciphertext, ciphertext_size);
+            *wrapped_key_size = WRAP_HEADER_SIZE + ciphertext_size;
+            result = 0;
+        }
+
+        if (ciphertext != NULL)
+        {
+            free(ciphertext);
+        }
+        OPENSSL_cleanse(wrapping_key, sizeof(wrapping_key));
+    }
+
+    return result;
+}
+
+int unwrap_encryption_key
+(
+    const unsigned char *wrapped_key,
+    size_t wrapped_key_size,
+    const char *passphrase,
+    unsigned char **key,
+    size_t *key_size
+)
+{
+    int result;
+
+    initialize_openssl();
+    if ((key == NULL) || (key_size == NULL))
+    {
+        LOG_ERROR("Invalid key output parameters");
+        result = __FAILURE__;
+    }
+    else if ((wrapped_key == NULL) || (wrapped_key_size != WRAPPED_KEY_SIZE_V1) ||
+             (wrapped_key[0] != WRAP_VERSION_V1))
+    {
+        LOG_ERROR("Invalid wrapped key");
+        *key = NULL;
+        *key_size = 0;
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase");
+        *key = NULL;
+        *key_size = 0;
+        result = __FAILURE__;
+    }
+    else
+    {
+        unsigned char wrapping_key[ENCRYPTION_KEY_SIZE_IN_BYTES_V1];
+        const unsigned char *salt = wrapped_key + CIPHER_VERSION_SIZE;
+        const unsigned char *iv = salt + WRAP_SALT_SIZE;
+
+        *key = NULL;
+        *key_size = 0;
+        if (derive_wrapping_key(passphrase, salt, wrapping_key) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else if (decrypt_v1(CIPHER_VERSION_V1,
+                            wrapped_key + WRAP_HEADER_SIZE,
+                            (int)(wrapped_key_size - WRAP_HEADER_SIZE),
+                            wrapped_key, WRAP_HEADER_SIZE,
+                            wrapping_key,
+                            iv, WRAP_IV_SIZE,
+                            key, key_size) != 0)
+        {
+            // a wrong passphrase fails the verification of the tag
+            LOG_ERROR("Could not unwrap encryption key");
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
+        OPENSSL_cleanse(wrapping_key, sizeof(wrapping_key));
+    }
+
+    return result;
+}
@@ -169,7 +169,31 @@ static int edge_hsm_client_get_random_bytes(HSM_CLIENT_HANDLE handle, unsigned c
     return result;
 }
 
-static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle)
+static int get_active_enc_key_slot(EDGE_CRYPTO *edge_crypto, size_t *slot)
+{
+    int result;
+
+    // the active slot is kept in the store so that it survives a restart
+    if (g_hsm_store_if->hsm_client_store_get_active_encryption_key_slot(edge_crypto->hsm_store_handle,
+                                                                        slot) != 0)
+    {
+        LOG_ERROR("Could not read the active master encryption key slot");
+        result = __FAILURE__;
+    }
+    else if (*slot >= EDGELET_ENC_KEY_SLOT_COUNT)
+    {
+        LOG_ERROR("Invalid active master encryption key slot %zu in the store", *slot);
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_create_master_encryption_key_in_slot(HSM_CLIENT_HANDLE handle, size_t slot)
 {
     int result;
 
@@ -183,13 +207,18 @@ static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle
         LOG_ERROR("Invalid handle value specified");
         result = __FAILURE__;
     }
+    else if (slot >= EDGELET_ENC_KEY_SLOT_COUNT)
+    {
+        LOG_ERROR("Invalid master encryption key slot %zu specified", slot);
+        result = __FAILURE__;
+    }
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
         if (g_hsm_store_if->hsm_client_store_insert_encryption_key(edge_crypto->hsm_store_handle,
-                                                                   EDGELET_ENC_KEY_NAME) != 0)
+                                                                   EDGELET_ENC_KEY_SLOT_NAMES[slot]) != 0)
         {
-            LOG_ERROR("Could not insert encryption key %s", EDGELET_ENC_KEY_NAME);
+            LOG_ERROR("Could not insert encryption key %s", EDGELET_ENC_KEY_SLOT_NAMES[slot]);
             result = __FAILURE__;
         }
         else
@@ -201,7 +230,7 @@ static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle
     return result;
 }
 
-static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handle)
+static int edge_hsm_client_destroy_master_encryption_key_in_slot(HSM_CLIENT_HANDLE handle, size_t slot)
 {
     int result;
 
@@ -215,14 +244,19 @@ static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handl
         LOG_ERROR("Invalid handle value specified");
         result = __FAILURE__;
     }
+    else if (slot >= EDGELET_ENC_KEY_SLOT_COUNT)
+    {
+        LOG_ERROR("Invalid master encryption key slot %zu specified", slot);
+        result = __FAILURE__;
+    }
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
         if (g_hsm_store_if->hsm_client_store_remove_key(edge_crypto->hsm_store_handle,
                                                         HSM_KEY_ENCRYPTION,
-                                                        EDGELET_ENC_KEY_NAME) != 0)
+                                                        EDGELET_ENC_KEY_SLOT_NAMES[slot]) != 0)
         {
-            LOG_ERROR("Could not remove encryption key %s", EDGELET_ENC_KEY_NAME);
+            LOG_ERROR("Could not remove encryption key %s", EDGELET_ENC_KEY_SLOT_NAMES[slot]);
             result = __FAILURE__;
         }
         else
@@ -234,6 +268,155 @@ static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handl
     return result;
 }
 
+static int edge_hsm_client_set_active_master_encryption_key_slot(HSM_CLIENT_HANDLE handle, size_t slot)
+{
+    int result;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else if (slot >= EDGELET_ENC_KEY_SLOT_COUNT)
+    {
+        LOG_ERROR("Invalid master encryption key slot %zu specified", slot);
+        result = __FAILURE__;
+    }
+    else
+    {
+        KEY_HANDLE key_handle;
+        EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
+        // only a slot that holds a key can be used to encrypt data
+        key_handle = g_hsm_store_if->hsm_client_store_open_key(edge_crypto->hsm_store_handle,
+                                                               HSM_KEY_ENCRYPTION,
+                                                               EDGELET_ENC_KEY_SLOT_NAMES[slot]);
+        if (key_handle == NULL)
+        {
+            LOG_ERROR("Master encryption key slot %zu has no key", slot);
+            result = __FAILURE__;
+        }
+        else
+        {
+            int status = g_hsm_store_if->hsm_client_store_close_key(edge_crypto->hsm_store_handle, key_handle);
+            if (status != 0)
+            {
+                LOG_ERROR("Error closing key handle. Error code %d", status);
+                result = __FAILURE__;
+            }
+            else if (g_hsm_store_if->hsm_client_store_set_active_encryption_key_slot(edge_crypto->hsm_store_handle,
+                                                                                     slot) != 0)
+            {
+                LOG_ERROR("Could not persist the active master encryption key slot %zu", slot);
+                result = __FAILURE__;
+            }
+            else
+            {
+                result = 0;
+            }
+        }
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_get_active_master_encryption_key_slot(HSM_CLIENT_HANDLE handle, size_t* slot)
+{
+    int result;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else if (slot == NULL)
+    {
+        LOG_ERROR("Invalid slot output parameter specified");
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = get_active_enc_key_slot((EDGE_CRYPTO*)handle, slot);
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle)
+{
+    int result;
+    size_t slot;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else if (get_active_enc_key_slot((EDGE_CRYPTO*)handle, &slot) != 0)
+    {
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = edge_hsm_client_create_master_encryption_key_in_slot(handle, slot);
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handle)
+{
+    int result;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else
+    {
+        size_t slot;
+        EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
+        // a key left behind in any slot would still decrypt data after
+        // de-provisioning, so remove the keys of every slot
+        result = 0;
+        for (slot = 0; slot < EDGELET_ENC_KEY_SLOT_COUNT; slot++)
+        {
+            if (edge_hsm_client_destroy_master_encryption_key_in_slot(handle, slot) != 0)
+            {
+                result = __FAILURE__;
+            }
+        }
+        if (g_hsm_store_if->hsm_client_store_set_active_encryption_key_slot(edge_crypto->hsm_store_handle,
+                                                                            0) != 0)
+        {
+            LOG_ERROR("Could not reset the active master encryption key slot");
+            result = __FAILURE__;
+        }
+    }
+
+    return result;
+}
+
 static CERT_INFO_HANDLE edge_hsm_client_create_certificate(HSM_CLIENT_HANDLE handle, CERT_PROPS_HANDLE certificate_props)
 {
     CERT_INFO_HANDLE result;
@@ -345,6 +528,7 @@ static bool validate_sized_buffer(const SIZED_BUFFER *sized_buffer)
 static int encrypt_data
 (
     EDGE_CRYPTO *edge_crypto,
+    const char *key_name,
     const SIZED_BUFFER *id,
     const SIZED_BUFFER *pt,
     const SIZED_BUFFER *iv,
@@ -357,10 +541,10 @@ static int encrypt_data
     const HSM_CLIENT_KEY_INTERFACE *key_if = g_hsm_key_if;
     key_handle = store_if->hsm_client_store_open_key(edge_crypto->hsm_store_handle,
                                                      HSM_KEY_ENCRYPTION,
-                                                     EDGELET_ENC_KEY_NAME);
+                                                     key_name);
     if (key_handle == NULL)
     {
-        LOG_ERROR("Could not get encryption key by name '%s'", EDGELET_ENC_KEY_NAME);
+        LOG_ERROR("Could not get encryption key by name '%s'", key_name);
         result = __FAILURE__;
     }
     else
@@ -390,6 +574,7 @@ static int encrypt_data
 static int decrypt_data
 (
     EDGE_CRYPTO *edge_crypto,
+    const char *key_name,
     const SIZED_BUFFER *id,
     const SIZED_BUFFER *ct,
     const SIZED_BUFFER *iv,
@@ -402,10 +587,10 @@ static int decrypt_data
     const HSM_CLIENT_KEY_INTERFACE *key_if = g_hsm_key_if;
     key_handle = store_if->hsm_client_store_open_key(edge_crypto->hsm_store_handle,
                                                      HSM_KEY_ENCRYPTION,
-                                                     EDGELET_ENC_KEY_NAME);
+                                                     key_name);
     if (key_handle == NULL)
     {
-        LOG_ERROR("Could not get encryption key by name '%s'", EDGELET_ENC_KEY_NAME);
+        LOG_ERROR("Could not get encryption key by name '%s'", key_name);
         result = __FAILURE__;
     }
     else
@@ -470,8 +655,21 @@ static int edge_hsm_client_encrypt_data
     }
     else
     {
+        size_t slot;
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
-        result = encrypt_data(edge_crypto, identity, plaintext, initialization_vector, ciphertext);
+        if (get_active_enc_key_slot(edge_crypto, &slot) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = encrypt_data(edge_crypto,
+                                  EDGELET_ENC_KEY_SLOT_NAMES[slot],
+                                  identity,
+                                  plaintext,
+                                  initialization_vector,
+                                  ciphertext);
+        }
     }
 
     return result;
@@ -515,8 +713,140 @@ static int edge_hsm_client_decrypt_data
     }
     else
     {
+        size_t index, active_slot;
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
-        result = decrypt_data(edge_crypto, identity, ciphertext, initialization_vector, plaintext);
+        if (get_active_enc_key_slot(edge_crypto, &active_slot) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else
+        {
+            // data encrypted before the last rotation was encrypted with the key
+            // of another slot, so try the active slot first and then the others
+            result = __FAILURE__;
+            for (index = 0; (index < EDGELET_ENC_KEY_SLOT_COUNT) && (result != 0); index++)
+            {
+                size_t slot = (active_slot + index) % EDGELET_ENC_KEY_SLOT_COUNT;
+                result = decrypt_data(edge_crypto,
+                                      EDGELET_ENC_KEY_SLOT_NAMES[slot],
+                                      identity,
+                                      ciphertext,
+                                      initialization_vector,
+                                      plaintext);
+            }
+        }
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_export_master_encryption_key
+(
+    HSM_CLIENT_HANDLE handle,
+    const char* passphrase,
+    SIZED_BUFFER* wrapped_key
+)
+{
+    int result;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase value specified");
+        result = __FAILURE__;
+    }
+    else if (wrapped_key == NULL)
+    {
+        LOG_ERROR("Invalid wrapped key buffer specified");
+        result = __FAILURE__;
+    }
+    else
+    {
+        size_t slot;
+        EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
+        wrapped_key->buffer = NULL;
+        wrapped_key->size = 0;
+        if (get_active_enc_key_slot(edge_crypto, &slot) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else if (g_hsm_store_if->hsm_client_store_export_encryption_key(edge_crypto->hsm_store_handle,
+                                                                        EDGELET_ENC_KEY_SLOT_NAMES[slot],
+                                                                        passphrase,
+                                                                        &wrapped_key->buffer,
+                                                                        &wrapped_key->size) != 0)
+        {
+            LOG_ERROR("Could not export encryption key %s", EDGELET_ENC_KEY_SLOT_NAMES[slot]);
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_import_master_encryption_key
+(
+    HSM_CLIENT_HANDLE handle,
+    const char* passphrase,
+    const SIZED_BUFFER* wrapped_key
+)
+{
+    int result;
+
+    if (!g_is_crypto_initialized)
+    {
+        LOG_ERROR("hsm_client_crypto_init not called");
+        result = __FAILURE__;
+    }
+    else if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value specified");
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase value specified");
+        result = __FAILURE__;
+    }
+    else if (!validate_sized_buffer(wrapped_key))
+    {
+        LOG_ERROR("Invalid wrapped key buffer specified");
+        result = __FAILURE__;
+    }
+    else
+    {
+        size_t slot;
+        EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
+        if (get_active_enc_key_slot(edge_crypto, &slot) != 0)
+        {
+            result = __FAILURE__;
+        }
+        else if (g_hsm_store_if->hsm_client_store_import_encryption_key(edge_crypto->hsm_store_handle,
+                                                                        EDGELET_ENC_KEY_SLOT_NAMES[slot],
+                                                                        passphrase,
+                                                                        wrapped_key->buffer,
+                                                                        wrapped_key->size) != 0)
+        {
+            LOG_ERROR("Could not import encryption key %s", EDGELET_ENC_KEY_SLOT_NAMES[slot]);
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
     }
 
     return result;
@@ -534,7 +864,13 @@ static const HSM_CLIENT_CRYPTO_INTERFACE edge_hsm_crypto_interface =
     edge_hsm_client_encrypt_data,
     edge_hsm_client_decrypt_data,
     edge_hsm_client_get_trust_bundle,
-    edge_hsm_crypto_free_buffer
+    edge_hsm_crypto_free_buffer,
+    edge_hsm_client_export_master_encryption_key,
+    edge_hsm_client_import_master_encryption_key,
+    edge_hsm_client_create_master_encryption_key_in_slot,
+    edge_hsm_client_destroy_master_encryption_key_in_slot,
+    edge_hsm_client_set_active_master_encryption_key_slot,
+    edge_hsm_client_get_active_master_encryption_key_slot
 };
 
 const HSM_CLIENT_CRYPTO_INTERFACE* hsm_client_crypto_interface(void)
@@ -1,4 +1,5 @@
 #include <limits.h>
+#include <stdio.h>
 #include <stdlib.h>
 
 #include "azure_c_shared_utility/gballoc.h"
@@ -88,6 +89,7 @@ static const char *ENC_KEYS_DIR     = "enc_keys";
 static const char *CERT_FILE_EXT    = ".cert.pem";
 static const char *PK_FILE_EXT      = ".key.pem";
 static const char *ENC_KEY_FILE_EXT = ".enc.key";
+static const char *ENC_KEY_SLOT_FILE = "active_slot";
 
 static HSM_STATE_T g_hsm_state = HSM_STATE_UNPROVISIONED;
 
@@ -792,6 +794,259 @@ static int save_encryption_key_to_file(const char *key_name, unsigned char *key,
     return result;
 }
 
+static int build_active_enc_key_slot_file_path(STRING_HANDLE slot_file)
+{
+    int result;
+    const char *base_dir_path = get_base_dir();
+
+    if ((STRING_concat(slot_file, base_dir_path) != 0) ||
+        (STRING_concat(slot_file, SLASH)  != 0) ||
+        (STRING_concat(slot_file, ENC_KEYS_DIR)  != 0) ||
+        (STRING_concat(slot_file, SLASH)  != 0) ||
+        (STRING_concat(slot_file, ENC_KEY_SLOT_FILE) != 0))
+    {
+        LOG_ERROR("Could not construct path to the active encryption key slot");
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
+static int read_active_enc_key_slot_from_file(size_t *slot)
+{
+    int result;
+    STRING_HANDLE slot_file_handle;
+
+    if ((slot_file_handle = STRING_new()) == NULL)
+    {
+        LOG_ERROR("Could not create string handle");
+        result = __FAILURE__;
+    }
+    else
+    {
+        const char *slot_file;
+        char *contents = NULL;
+        size_t contents_size = 0;
+
+        if (build_active_enc_key_slot_file_path(slot_file_handle) != 0)
+        {
+            LOG_ERROR("Could not construct path to the active slot file");
+            result = __FAILURE__;
+        }
+        else if ((slot_file = STRING_c_str(slot_file_handle)) == NULL)
+        {
+            LOG_ERROR("Active slot file path NULL");
+            result = __FAILURE__;
+        }
+        else if (!is_file_valid(slot_file))
+        {
+            // no slot was ever activated so the first slot is in use
+            *slot = 0;
+            result = 0;
+        }
+        else if ((contents = read_file_into_cstring(slot_file, &contents_size)) == NULL)
+        {
+            LOG_ERROR("Could not read the active encryption key slot file");
+            result = __FAILURE__;
+        }
+        else
+        {
+            char *end = NULL;
+            unsigned long value = strtoul(contents, &end, 10);
+            if ((end == contents) || ((*end != '\0') && (*end != '\n')))
+            {
+                LOG_ERROR("Invalid active encryption key slot '%s'", contents);
+                result = __FAILURE__;
+            }
+            else
+            {
+                *slot = (size_t)value;
+                result = 0;
+            }
+        }
+
+        if (contents != NULL)
+        {
+            free(contents);
+        }
+        STRING_delete(slot_file_handle);
+    }
+
+    return result;
+}
+
+static int write_active_enc_key_slot_to_file(size_t slot)
+{
+    int result;
+    STRING_HANDLE slot_file_handle;
+
+    if ((slot_file_handle = STRING_new()) == NULL)
+    {
+        LOG_ERROR("Could not create string handle");
+        result = __FAILURE__;
+    }
+    else
+    {
+        const char *slot_file;
+        char contents[32];
+        int contents_len = snprintf(contents, sizeof(contents), "%zu", slot);
+
+        if ((contents_len <= 0) || ((size_t)contents_len >= sizeof(contents)))
+        {
+            LOG_ERROR("Could not format active encryption key slot %zu", slot);
+            result = __FAILURE__;
+        }
+        else if (build_active_enc_key_slot_file_path(slot_file_handle) != 0)
+        {
+            LOG_ERROR("Could not construct path to the active slot file");
+            result = __FAILURE__;
+        }
+        else if ((slot_file = STRING_c_str(slot_file_handle)) == NULL)
+        {
+            LOG_ERROR("Active slot file path NULL");
+            result = __FAILURE__;
+        }
+        else if (write_buffer_to_file(slot_file, (const unsigned char*)contents, (size_t)contents_len, true) != 0)
+        {
+            LOG_ERROR("Could not write the active encryption key slot file");
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
+        STRING_delete(slot_file_handle);
+    }
+
+    return result;
+}
+
+static int get_cert_key_props(PKI_KEY_PROPS *key_props, bool *use_key_props)
+{
+    int result;
+    char* env_key_type = NULL;
+
+    if (hsm_get_env(ENV_CERT_KEY_TYPE, &env_key_type) != 0)
+    {
+        LOG_ERROR("Could not lookup env variable %s", ENV_CERT_KEY_TYPE);
+        result = __FAILURE__;
+    }
+    else if ((env_key_type == NULL) || (strlen(env_key_type) == 0))
+    {
+        // keys are of the same type as the key of the issuer
+        *use_key_props = false;
+        result = 0;
+    }
+    else if (strcmp(env_key_type, "rsa") == 0)
+    {
+        key_props->key_type = HSM_PKI_KEY_RSA;
+        key_props->ec_curve_name = NULL;
+        *use_key_props = true;
+        result = 0;
+    }
+    else if (strcmp(env_key_type, "ec-p256") == 0)
+    {
+        key_props->key_type = HSM_PKI_KEY_EC;
+        key_props->ec_curve_name = "prime256v1";
+        *use_key_props = true;
+        result = 0;
+    }
+    else if (strcmp(env_key_type, "ec-p384") == 0)
+    {
+        key_props->key_type = HSM_PKI_KEY_EC;
+        key_props->ec_curve_name = "secp384r1";
+        *use_key_props = true;
+        result = 0;
+    }
+    else
+    {
+        LOG_ERROR("Unknown certificate key type %s in env variable %s",
+                  env_key_type, ENV_CERT_KEY_TYPE);
+        result = __FAILURE__;
+    }
+
+    if (env_key_type != NULL)
+    {
+        free(env_key_type);
+    }
+
+    return result;
+}
+
+static int check_issuer_revocation(const char *issuer_cert_path, bool *is_revoked)
+{
+    int result;
+    char* env_crl_path = NULL;
+
+    *is_revoked = false;
+    if (hsm_get_env(ENV_CRL_PATH, &env_crl_path) != 0)
+    {
+        LOG_ERROR("Could not lookup env variable %s", ENV_CRL_PATH);
+        result = __FAILURE__;
+    }
+    else if ((env_crl_path == NULL) || (strlen(env_crl_path) == 0))
+    {
+        // revocation checking is disabled
+        result = 0;
+    }
+    else if (check_certificate_revocation(issuer_cert_path, env_crl_path, is_revoked) != 0)
+    {
+        LOG_ERROR("Could not check revocation status of issuer certificate %s", issuer_cert_path);
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    if (env_crl_path != NULL)
+    {
+        free(env_crl_path);
+    }
+
+    return result;
+}
+
+static int get_encryption_algorithm(HSM_ENC_ALGORITHM_T *algorithm)
+{
+    int result;
+    char* env_algorithm = NULL;
+
+    if (hsm_get_env(ENV_ENCRYPTION_ALGORITHM, &env_algorithm) != 0)
+    {
+        LOG_ERROR("Could not lookup env variable %s", ENV_ENCRYPTION_ALGORITHM);
+        result = __FAILURE__;
+    }
+    else if ((env_algorithm == NULL) || (strlen(env_algorithm) == 0) ||
+             (strcmp(env_algorithm, "aes-256-gcm") == 0))
+    {
+        *algorithm = HSM_ENC_ALGORITHM_AES_256_GCM;
+        result = 0;
+    }
+    else if (strcmp(env_algorithm, "chacha20-poly1305") == 0)
+    {
+        *algorithm = HSM_ENC_ALGORITHM_CHACHA20_POLY1305;
+        result = 0;
+    }
+    else
+    {
+        LOG_ERROR("Unknown encryption algorithm %s in env variable %s",
+                  env_algorithm, ENV_ENCRYPTION_ALGORITHM);
+        result = __FAILURE__;
+    }
+
+    if (env_algorithm != NULL)
+    {
+        free(env_algorithm);
+    }
+
+    return result;
+}
+
 static int load_encryption_key_from_file(CRYPTO_STORE* store, const char *key_name)
 {
     int result;
@@ -1715,6 +1970,30 @@ static int get_tg_env_vars(char **trusted_certs_path, char **device_ca_path, cha
     return result;
 }
 
+static int verify_device_ca(const char *device_ca_path, const char *device_pk_path)
+{
+    int result;
+    bool verify_status = false;
+
+    if (verify_ca_certificate_and_key(device_ca_path, device_pk_path, &verify_status) != 0)
+    {
+        LOG_ERROR("Failure when verifying device CA certificate %s", device_ca_path);
+        result = __FAILURE__;
+    }
+    else if (!verify_status)
+    {
+        LOG_ERROR("Device CA certificate %s is not a CA certificate or does not match "
+                  "private key %s", device_ca_path, device_pk_path);
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
 static int hsm_provision_edge_certificates(void)
 {
     int result;
@@ -1804,6 +2083,11 @@ static int hsm_provision_edge_certificates(void)
             LOG_ERROR("Failure generating required HSM certificates");
             result = __FAILURE__;
         }
+        else if (env_set && (verify_device_ca(device_ca_path, device_pk_path) != 0))
+        {
+            LOG_ERROR("Failure validating the device CA certificate and private key");
+            result = __FAILURE__;
+        }
         else if (env_set && (edge_hsm_client_store_insert_pki_cert(g_crypto_store,
                                                                 hsm_get_device_ca_alias(),
                                                                 hsm_get_device_ca_alias(), // since we don't know the issuer, we treat this certificate as the issuer
@@ -2172,7 +2456,16 @@ static KEY_HANDLE edge_hsm_client_open_key
             {
                 if (key_type == HSM_KEY_ENCRYPTION)
                 {
-                    result = create_encryption_key(buffer_ptr, buffer_size);
+                    HSM_ENC_ALGORITHM_T algorithm;
+                    if (get_encryption_algorithm(&algorithm) != 0)
+                    {
+                        LOG_ERROR("Could not determine encryption algorithm for %s", key_name);
+                        result = NULL;
+                    }
+                    else
+                    {
+                        result = create_encryption_key_with_algorithm(buffer_ptr, buffer_size, algorithm);
+                    }
                 }
                 else
                 {
@@ -2319,6 +2612,7 @@ static int verify_certificate_helper
         STRING_HANDLE issuer_cert_path_handle = NULL;
         CRYPTO_STORE *store = (CRYPTO_STORE*)handle;
         STORE_ENTRY_PKI_CERT *cert_entry;
+        bool issuer_revoked = false;
 
         const char *issuer_cert_path = NULL;
         if ((cert_entry = get_pki_cert(store, issuer_alias)) != NULL)
@@ -2352,8 +2646,18 @@ static int verify_certificate_helper
             LOG_ERROR("Error trying to verify certificate %s for alias %s", cert_file_path, alias);
             result = __FAILURE__;
         }
+        else if (*cert_verified && (check_issuer_revocation(issuer_cert_path, &issuer_revoked) != 0))
+        {
+            LOG_ERROR("Error trying to check revocation of issuer %s for alias %s", issuer_alias, alias);
+            result = __FAILURE__;
+        }
         else
         {
+            if (issuer_revoked)
+            {
+                LOG_ERROR("Issuer %s of certificate alias %s has been revoked", issuer_alias, alias);
+                *cert_verified = false;
+            }
             result = 0;
         }
 
@@ -2429,6 +2733,9 @@ static int edge_hsm_client_store_create_pki_cert_internal
             const char *issuer_cert_path = NULL;
             const char *alias_pk_path = STRING_c_str(alias_pk_handle);
             const char *alias_cert_path = STRING_c_str(alias_cert_handle);
+            PKI_KEY_PROPS key_props;
+            bool use_key_props = false;
+            bool issuer_revoked = false;
             result = 0;
             if (strcmp(alias, issuer_alias) != 0)
             {
@@ -2448,9 +2755,38 @@ static int edge_hsm_client_store_create_pki_cert_internal
                         LOG_ERROR("Unexpected NULL file paths found for issuer %s", issuer_alias);
                         result = __FAILURE__;
                     }
+                    else if (check_issuer_revocation(issuer_cert_path, &issuer_revoked) != 0)
+                    {
+                        LOG_ERROR("Could not check revocation of issuer %s", issuer_alias);
+                        result = __FAILURE__;
+                    }
+                    else if (issuer_revoked)
+                    {
+                        LOG_ERROR("Issuer %s has been revoked and cannot issue certificate %s",
+                                  issuer_alias, alias);
+                        result = __FAILURE__;
+                    }
                 }
             }
-            if (result == 0)
+            if ((result == 0) && (get_cert_key_props(&key_props, &use_key_props) != 0))
+            {
+                LOG_ERROR("Could not determine the key type for certificate %s", alias);
+                result = __FAILURE__;
+            }
+            if ((result == 0) && use_key_props)
+            {
+                // @note this will overwrite the older the certificate and private key
+                // files for the requested alias
+                result = generate_pki_cert_and_key_with_issuer_props(cert_props_handle,
+                                                                     rand(),
+                                                                     ca_path_len,
+                                                                     alias_pk_path,
+                                                                     alias_cert_path,
+                                                                     issuer_pk_path,
+                                                                     issuer_cert_path,
+                                                                     &key_props);
+            }
+            else if (result == 0)
             {
                 // @note this will overwrite the older the certificate and private key
                 // files for the requested alias
@@ -2703,6 +3039,219 @@ static int edge_hsm_client_store_insert_encryption_key
     return result;
 }
 
+static int edge_hsm_client_store_export_encryption_key
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    const char* key_name,
+    const char* passphrase,
+    unsigned char** wrapped_key,
+    size_t* wrapped_key_size
+)
+{
+    int result;
+
+    if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value");
+        result = __FAILURE__;
+    }
+    else if ((key_name == NULL) || (strlen(key_name) == 0))
+    {
+        LOG_ERROR("Invalid handle alias value");
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase value");
+        result = __FAILURE__;
+    }
+    else if ((wrapped_key == NULL) || (wrapped_key_size == NULL))
+    {
+        LOG_ERROR("Invalid wrapped key output parameters");
+        result = __FAILURE__;
+    }
+    else if (g_hsm_state != HSM_STATE_PROVISIONED)
+    {
+        LOG_ERROR("HSM store has not been provisioned");
+        result = __FAILURE__;
+    }
+    else
+    {
+        CRYPTO_STORE *store = (CRYPTO_STORE*)handle;
+        STORE_ENTRY_KEY* key_entry;
+        size_t buffer_size = 0;
+        const unsigned char *buffer_ptr = NULL;
+
+        *wrapped_key = NULL;
+        *wrapped_key_size = 0;
+        if (!key_exists(store, HSM_KEY_ENCRYPTION, key_name) &&
+            (load_encryption_key_from_file(store, key_name) != 0))
+        {
+            LOG_ERROR("HSM store could not load encryption key %s", key_name);
+            result = __FAILURE__;
+        }
+        else if ((key_entry = get_key(store, HSM_KEY_ENCRYPTION, key_name)) == NULL)
+        {
+            LOG_ERROR("Could not find key name %s", key_name);
+            result = __FAILURE__;
+        }
+        else if (((buffer_ptr = BUFFER_u_char(key_entry->key)) == NULL) ||
+                 (BUFFER_size(key_entry->key, &buffer_size) != 0) ||
+                 (buffer_size == 0))
+        {
+            LOG_ERROR("Invalid key buffer for %s", key_name);
+            result = __FAILURE__;
+        }
+        else if (wrap_encryption_key(buffer_ptr, buffer_size, passphrase,
+                                     wrapped_key, wrapped_key_size) != 0)
+        {
+            LOG_ERROR("Could not export encryption key %s", key_name);
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_store_import_encryption_key
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    const char* key_name,
+    const char* passphrase,
+    const unsigned char* wrapped_key,
+    size_t wrapped_key_size
+)
+{
+    int result;
+
+    if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value");
+        result = __FAILURE__;
+    }
+    else if ((key_name == NULL) || (strlen(key_name) == 0))
+    {
+        LOG_ERROR("Invalid handle alias value");
+        result = __FAILURE__;
+    }
+    else if ((passphrase == NULL) || (strlen(passphrase) == 0))
+    {
+        LOG_ERROR("Invalid passphrase value");
+        result = __FAILURE__;
+    }
+    else if ((wrapped_key == NULL) || (wrapped_key_size == 0))
+    {
+        LOG_ERROR("Invalid wrapped key value");
+        result = __FAILURE__;
+    }
+    else if (g_hsm_state != HSM_STATE_PROVISIONED)
+    {
+        LOG_ERROR("HSM store has not been provisioned");
+        result = __FAILURE__;
+    }
+    else
+    {
+        size_t key_size = 0;
+        unsigned char *key = NULL;
+        if (unwrap_encryption_key(wrapped_key, wrapped_key_size, passphrase, &key, &key_size) != 0)
+        {
+            LOG_ERROR("Could not import encryption key for %s", key_name);
+            result = __FAILURE__;
+        }
+        else
+        {
+            if (save_encryption_key_to_file(key_name, key, key_size) != 0)
+            {
+                LOG_ERROR("Could not persist encryption key %s to file", key_name);
+                result = __FAILURE__;
+            }
+            else if (put_key((CRYPTO_STORE*)handle, HSM_KEY_ENCRYPTION, key_name, key, key_size) != 0)
+            {
+                LOG_ERROR("Could not replace encryption key %s in the key store", key_name);
+                result = __FAILURE__;
+            }
+            else
+            {
+                result = 0;
+            }
+            free(key);
+        }
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_store_get_active_encryption_key_slot
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    size_t* slot
+)
+{
+    int result;
+
+    if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value");
+        result = __FAILURE__;
+    }
+    else if (slot == NULL)
+    {
+        LOG_ERROR("Invalid slot output parameter");
+        result = __FAILURE__;
+    }
+    else if (g_hsm_state != HSM_STATE_PROVISIONED)
+    {
+        LOG_ERROR("HSM store has not been provisioned");
+        result = __FAILURE__;
+    }
+    else if (read_active_enc_key_slot_from_file(slot) != 0)
+    {
+        LOG_ERROR("Could not read the active encryption key slot");
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
+static int edge_hsm_client_store_set_active_encryption_key_slot
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    size_t slot
+)
+{
+    int result;
+
+    if (handle == NULL)
+    {
+        LOG_ERROR("Invalid handle value");
+        result = __FAILURE__;
+    }
+    else if (g_hsm_state != HSM_STATE_PROVISIONED)
+    {
+        LOG_ERROR("HSM store has not been provisioned");
+        result = __FAILURE__;
+    }
+    else if (write_active_enc_key_slot_to_file(slot) != 0)
+    {
+        LOG_ERROR("Could not persist the active encryption key slot %zu", slot);
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = 0;
+    }
+
+    return result;
+}
+
 static const HSM_CLIENT_STORE_INTERFACE edge_hsm_client_store_interface =
 {
     edge_hsm_client_store_create,
@@ -2719,7 +3268,11 @@ static const HSM_CLIENT_STORE_INTERFACE edge_hsm_client_store_interface =
     edge_hsm_client_store_remove_pki_cert,
     edge_hsm_client_store_insert_pki_trusted_cert,
     edge_hsm_client_store_get_pki_trusted_certs,
-    edge_hsm_client_store_remove_pki_trusted_cert
+    edge_hsm_client_store_remove_pki_trusted_cert,
+    edge_hsm_client_store_export_encryption_key,
+    edge_hsm_client_store_import_encryption_key,
+    edge_hsm_client_store_get_active_encryption_key_slot,
+    edge_hsm_client_store_set_active_encryption_key_slot
 };
 
 const HSM_CLIENT_STORE_INTERFACE* hsm_client_store_interface(void)
@@ -315,19 +315,17 @@ static EVP_PKEY* generate_evp_key
 {
     EVP_PKEY *evp_key;
 
-    if (issuer_cert == NULL)
+    if ((key_props != NULL) && (key_props->key_type == HSM_PKI_KEY_EC))
     {
-        if ((key_props != NULL) && (key_props->key_type == HSM_PKI_KEY_EC))
-        {
-            const char *curve = (key_props->ec_curve_name != NULL) ? key_props->ec_curve_name :
-                                                                     DEFAULT_EC_CURVE_NAME;
-            evp_key = generate_ecc_key(curve);
-        }
-        else
-        {
-            // by default use RSA keys if no issuer cert or key properties was provided
-            evp_key = generate_rsa_key(cert_type);
-        }
+        // key properties take precedence over the key type of the issuer
+        const char *curve = (key_props->ec_curve_name != NULL) ? key_props->ec_curve_name :
+                                                                 DEFAULT_EC_CURVE_NAME;
+        evp_key = generate_ecc_key(curve);
+    }
+    else if ((issuer_cert == NULL) || (key_props != NULL))
+    {
+        // by default use RSA keys if no issuer cert or key properties was provided
+        evp_key = generate_rsa_key(cert_type);
     }
     else
     {
@@ -1469,6 +1467,42 @@ int generate_pki_cert_and_key_with_props
     return result;
 }
 
+int generate_pki_cert_and_key_with_issuer_props
+(
+    CERT_PROPS_HANDLE cert_props_handle,
+    int serial_number,
+    int ca_path_len,
+    const char* key_file_name,
+    const char* cert_file_name,
+    const char* issuer_key_file,
+    const char* issuer_certificate_file,
+    const PKI_KEY_PROPS *key_props
+)
+{
+    int result;
+
+    if ((key_props == NULL) ||
+        ((key_props->key_type != HSM_PKI_KEY_EC) &&
+         (key_props->key_type != HSM_PKI_KEY_RSA)))
+    {
+        LOG_ERROR("Invalid PKI key properties");
+        result = __FAILURE__;
+    }
+    else
+    {
+        result = generate_pki_cert_and_key_helper(cert_props_handle,
+                                                  serial_number,
+                                                  ca_path_len,
+                                                  key_file_name,
+                                                  cert_file_name,
+                                                  issuer_key_file,
+                                                  issuer_certificate_file,
+                                                  key_props);
+    }
+
+    return result;
+}
+
 int generate_pki_cert_and_key
 (
     CERT_PROPS_HANDLE cert_props_handle,
@@ -1728,6 +1762,78 @@ static int verify_certificate_internal
     return result;
 }
 
+int verify_ca_certificate_and_key
+(
+    const char *certificate_file_path,
+    const char *key_file_path,
+    bool *verify_status
+)
+{
+    int result;
+
+    if (verify_status == NULL)
+    {
+        LOG_ERROR("Invalid verify_status parameter");
+        result = __FAILURE__;
+    }
+    else
+    {
+        *verify_status = false;
+        if ((certificate_file_path == NULL) || (key_file_path == NULL))
+        {
+            LOG_ERROR("Invalid parameters");
+            result = __FAILURE__;
+        }
+        else
+        {
+            X509* x509_cert = NULL;
+            EVP_PKEY* evp_key = NULL;
+
+            initialize_openssl();
+            if ((x509_cert = load_certificate_file(certificate_file_path)) == NULL)
+            {
+                LOG_ERROR("Could not load certificate file %s", certificate_file_path);
+                result = __FAILURE__;
+            }
+            else if ((evp_key = load_private_key_file(key_file_path)) == NULL)
+            {
+                LOG_ERROR("Could not load private key file %s", key_file_path);
+                result = __FAILURE__;
+            }
+            else
+            {
+                // X509_check_ca returns 1 only for certificates with the basic
+                // constraints extension set to CA:TRUE
+                if (X509_check_ca(x509_cert) != 1)
+                {
+                    LOG_ERROR("Certificate %s is not a CA certificate", certificate_file_path);
+                }
+                else if (X509_check_private_key(x509_cert, evp_key) != 1)
+                {
+                    LOG_ERROR("Private key %s does not match certificate %s",
+                              key_file_path, certificate_file_path);
+                }
+                else
+                {
+                    *verify_status = true;
+                }
+                result = 0;
+            }
+
+            if (evp_key != NULL)
+            {
+                destroy_evp_key(evp_key);
+            }
+            if (x509_cert != NULL)
+            {
+                X509_free(x509_cert);
+            }
+        }
+    }
+
+    return result;
+}
+
 int verify_certificate
 (
     const char *certificate_file_path,
@@ -1763,3 +1869,223 @@ int verify_certificate
 
     return result;
 }
+
+static STACK_OF(X509)* load_certificate_chain_file(const char* cert_file_name)
+{
+    STACK_OF(X509)* chain;
+    BIO* cert_file = BIO_new_file(cert_file_name, "r");
+    if (cert_file == NULL)
+    {
+        LOG_ERROR("Failure to open certificate file %s", cert_file_name);
+        chain = NULL;
+    }
+    else
+    {
+        if ((chain = sk_X509_new_null()) == NULL)
+        {
+            LOG_ERROR("Could not allocate certificate chain");
+        }
+        else
+        {
+            X509* x509_cert;
+            while ((x509_cert = PEM_read_bio_X509(cert_file, NULL, NULL, NULL)) != NULL)
+            {
+                if (sk_X509_push(chain, x509_cert) == 0)
+                {
+                    LOG_ERROR("Could not add certificate to chain");
+                    X509_free(x509_cert);
+                    break;
+                }
+            }
+            // reaching the end of the file leaves a PEM_R_NO_START_LINE error on the queue
+            ERR_clear_error();
+            if (sk_X509_num(chain) == 0)
+            {
+                LOG_ERROR("Failure PEM_read_bio_X509 for cert %s", cert_file_name);
+                sk_X509_pop_free(chain, X509_free);
+                chain = NULL;
+            }
+        }
+        BIO_free_all(cert_file);
+    }
+
+    return chain;
+}
+
+static X509* find_certificate_by_subject(STACK_OF(X509)* chain, X509_NAME* subject_name)
+{
+    X509* result = NULL;
+    int index;
+
+    for (index = 0; (result == NULL) && (index < sk_X509_num(chain)); index++)
+    {
+        X509* x509_cert = sk_X509_value(chain, index);
+        if (X509_NAME_cmp(X509_get_subject_name(x509_cert), subject_name) == 0)
+        {
+            result = x509_cert;
+        }
+    }
+
+    return result;
+}
+
+static int validate_crl(X509_CRL* crl, X509* crl_issuer_cert)
+{
+    int result;
+    EVP_PKEY* issuer_pub_key;
+    ASN1_TIME* next_update = X509_CRL_get_nextUpdate(crl);
+
+    if ((issuer_pub_key = X509_get_pubkey(crl_issuer_cert)) == NULL)
+    {
+        LOG_ERROR("Error getting public key from CRL issuer certificate");
+        result = __FAILURE__;
+    }
+    else
+    {
+        if (X509_CRL_verify(crl, issuer_pub_key) != 1)
+        {
+            LOG_ERROR("CRL signature could not be verified with its issuer certificate");
+            result = __FAILURE__;
+        }
+        else if (X509_cmp_current_time(X509_CRL_get_lastUpdate(crl)) >= 0)
+        {
+            LOG_ERROR("CRL is not yet valid");
+            result = __FAILURE__;
+        }
+        else if ((next_update == NULL) || (X509_cmp_current_time(next_update) <= 0))
+        {
+            // an outdated CRL could be missing recent revocations
+            LOG_ERROR("CRL has expired");
+            result = __FAILURE__;
+        }
+        else
+        {
+            result = 0;
+        }
+        EVP_PKEY_free(issuer_pub_key);
+    }
+
+    return result;
+}
+
+static int is_serial_in_crl_file
+(
+    STACK_OF(X509)* chain,
+    const char* crl_file_path,
+    bool* is_revoked
+)
+{
+    int result;
+    BIO* crl_file = BIO_new_file(crl_file_path, "r");
+    if (crl_file == NULL)
+    {
+        LOG_ERROR("Failure to open CRL file %s", crl_file_path);
+        result = __FAILURE__;
+    }
+    else
+    {
+        X509* x509_cert = sk_X509_value(chain, 0);
+        X509_NAME* issuer_name = X509_get_issuer_name(x509_cert);
+        ASN1_INTEGER* serial = X509_get_serialNumber(x509_cert);
+        // the CRLs of the certificate's issuer are signed with the key of the
+        // issuer, whose certificate is part of the chain or is the certificate
+        // itself if it is self signed
+        X509* crl_issuer_cert = find_certificate_by_subject(chain, issuer_name);
+        X509_CRL* crl;
+        int crl_count = 0;
+
+        result = 0;
+        *is_revoked = false;
+        while ((result == 0) && (!*is_revoked) &&
+               ((crl = PEM_read_bio_X509_CRL(crl_file, NULL, NULL, NULL)) != NULL))
+        {
+            X509_REVOKED* revoked = NULL;
+            crl_count++;
+            // only CRLs published by the certificate's issuer can revoke it
+            if (X509_NAME_cmp(X509_CRL_get_issuer(crl), issuer_name) == 0)
+            {
+                if (crl_issuer_cert == NULL)
+                {
+                    LOG_ERROR("Certificate of the CRL issuer is not part of the certificate chain");
+                    result = __FAILURE__;
+                }
+                else if (validate_crl(crl, crl_issuer_cert) != 0)
+                {
+                    LOG_ERROR("Invalid CRL in file %s", crl_file_path);
+                    result = __FAILURE__;
+                }
+                else if (X509_CRL_get0_by_serial(crl, &revoked, serial) == 1)
+                {
+                    *is_revoked = true;
+                }
+            }
+            X509_CRL_free(crl);
+        }
+        // reaching the end of the file leaves a PEM_R_NO_START_LINE error on the queue
+        ERR_clear_error();
+        if ((result == 0) && (crl_count == 0))
+        {
+            LOG_ERROR("No CRLs could be read from file %s", crl_file_path);
+            result = __FAILURE__;
+        }
+        BIO_free_all(crl_file);
+    }
+
+    return result;
+}
+
+int check_certificate_revocation
+(
+    const char *certificate_file_path,
+    const char *crl_file_path,
+    bool *is_revoked
+)
+{
+    int result;
+
+    if (is_revoked == NULL)
+    {
+        LOG_ERROR("Invalid is_revoked parameter");
+        result = __FAILURE__;
+    }
+    else
+    {
+        *is_revoked = false;
+        if ((certificate_file_path == NULL) || (crl_file_path == NULL))
+        {
+            LOG_ERROR("Invalid parameters");
+            result = __FAILURE__;
+        }
+        else
+        {
+            STACK_OF(X509)* chain;
+
+            initialize_openssl();
+            if ((chain = load_certificate_chain_file(certificate_file_path)) == NULL)
+            {
+                LOG_ERROR("Could not load certificate file %s", certificate_file_path);
+                result = __FAILURE__;
+            }
+            else
+            {
+                if (is_serial_in_crl_file(chain, crl_file_path, is_revoked) != 0)
+                {
+                    LOG_ERROR("Could not check certificate %s against CRL file %s",
+                              certificate_file_path, crl_file_path);
+                    result = __FAILURE__;
+                }
+                else
+                {
+                    if (*is_revoked)
+                    {
+                        LOG_ERROR("Certificate %s has been revoked", certificate_file_path);
+                    }
+                    result = 0;
+                }
+                sk_X509_pop_free(chain, X509_free);
+            }
+        }
+    }
+
+    return result;
+}
@@ -65,6 +65,36 @@ typedef int (*HSM_CLIENT_STORE_REMOVE_PKI_TRUSTED_CERT)
     const char* alias
 );
 
+typedef int (*HSM_CLIENT_STORE_EXPORT_ENCRYPTION_KEY)
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    const char* key_name,
+    const char* passphrase,
+    unsigned char** wrapped_key,
+    size_t* wrapped_key_size
+);
+
+typedef int (*HSM_CLIENT_STORE_IMPORT_ENCRYPTION_KEY)
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    const char* key_name,
+    const char* passphrase,
+    const unsigned char* wrapped_key,
+    size_t wrapped_key_size
+);
+
+typedef int (*HSM_CLIENT_STORE_GET_ACTIVE_ENCRYPTION_KEY_SLOT)
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    size_t* slot
+);
+
+typedef int (*HSM_CLIENT_STORE_SET_ACTIVE_ENCRYPTION_KEY_SLOT)
+(
+    HSM_CLIENT_STORE_HANDLE handle,
+    size_t slot
+);
+
 struct HSM_CLIENT_STORE_INTERFACE_TAG {
     HSM_CLIENT_STORE_CREATE hsm_client_store_create;
     HSM_CLIENT_STORE_DESTROY hsm_client_store_destroy;
@@ -81,6 +111,10 @@ struct HSM_CLIENT_STORE_INTERFACE_TAG {
     HSM_CLIENT_STORE_INSERT_PKI_TRUSTED_CERT hsm_client_store_insert_pki_trusted_cert;
     HSM_CLIENT_STORE_GET_PKI_TRUSTED_CERTS hsm_client_store_get_pki_trusted_certs;
     HSM_CLIENT_STORE_REMOVE_PKI_TRUSTED_CERT hsm_client_store_remove_pki_trusted_cert;
+    HSM_CLIENT_STORE_EXPORT_ENCRYPTION_KEY hsm_client_store_export_encryption_key;
+    HSM_CLIENT_STORE_IMPORT_ENCRYPTION_KEY hsm_client_store_import_encryption_key;
+    HSM_CLIENT_STORE_GET_ACTIVE_ENCRYPTION_KEY_SLOT hsm_client_store_get_active_encryption_key_slot;
+    HSM_CLIENT_STORE_SET_ACTIVE_ENCRYPTION_KEY_SLOT hsm_client_store_set_active_encryption_key_slot;
 };
 typedef struct HSM_CLIENT_STORE_INTERFACE_TAG HSM_CLIENT_STORE_INTERFACE;
 const HSM_CLIENT_STORE_INTERFACE* hsm_client_store_interface(void);
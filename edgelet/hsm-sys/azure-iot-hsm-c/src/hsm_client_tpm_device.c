@@ -13,6 +13,8 @@
 #include "azure_c_shared_utility/crt_abstractions.h"
 
 #include "hsm_client_data.h"
+#include "hsm_constants.h"
+#include "hsm_utils.h"
 #include "edge_sas_perform_sign_with_key.h"
 #include "azure_utpm_c/tpm_comm.h"
 #include "azure_utpm_c/tpm_codec.h"
@@ -29,6 +31,9 @@ static const UINT32 TPM_20_SRK_HANDLE = HR_PERSISTENT | 0x00000001;
 static const UINT32 TPM_20_EK_HANDLE = HR_PERSISTENT | 0x00010001;
 static const UINT32 DPS_ID_KEY_HANDLE = HR_PERSISTENT | 0x00000100;
 
+// TPM device configured with ENV_TPM_DEVICE_PATH, or NULL for the default device
+static char* g_tpm_device_path = NULL;
+
 typedef struct HSM_CLIENT_INFO_TAG
 {
     TSS_DEVICE tpm_device;
@@ -144,6 +149,23 @@ static int create_tpm_session
     return result;
 }
 
+static int get_tpm_failure(TPM_RC rc)
+{
+    int result;
+
+    // these warnings mean the TPM did not run the command, so it can be sent again
+    if ((rc == TPM_RC_RETRY) || (rc == TPM_RC_YIELDED) || (rc == TPM_RC_TESTING))
+    {
+        result = HSM_TPM_RETRY;
+    }
+    else
+    {
+        result = __FAILURE__;
+    }
+
+    return result;
+}
+
 static int insert_key_in_tpm
 (
     HSM_CLIENT_INFO* sec_info,
@@ -171,6 +193,7 @@ static int insert_key_in_tpm
         TPM2B_DIGEST inner_wrap_key = { .t = {0, {0}} };
         TPM2B_PRIVATE id_key_priv;
         TPM_HANDLE load_id_key = TPM_ALG_NULL;
+        TPM_RC rc;
 
         uint8_t* curr_pos = (uint8_t*)key;
         uint32_t act_size = (int32_t)key_len;
@@ -188,16 +211,16 @@ static int insert_key_in_tpm
         // original activation buffer (after updating byte order of the UINT16 counter)
         DPS_UNMARSHAL(UINT16, &enc_data_size);
 
-        if (TPM2_ActivateCredential(&sec_info->tpm_device, &NullPwSession, &ek_sess, TPM_20_SRK_HANDLE, TPM_20_EK_HANDLE,
-            &enc_key_blob, &tpm_enc_secret, &inner_wrap_key) != TPM_RC_SUCCESS)
+        if ((rc = TPM2_ActivateCredential(&sec_info->tpm_device, &NullPwSession, &ek_sess, TPM_20_SRK_HANDLE, TPM_20_EK_HANDLE,
+            &enc_key_blob, &tpm_enc_secret, &inner_wrap_key)) != TPM_RC_SUCCESS)
         {
             LOG_ERROR("Failure: TPM2_ActivateCredential");
-            result = __FAILURE__;
+            result = get_tpm_failure(rc);
         }
-        else if (TPM2_Import(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, (TPM2B_DATA*)&inner_wrap_key, &id_key_Public, &id_key_dup_blob, &encrypt_wrap_key, &Aes128SymDef, &id_key_priv) != TPM_RC_SUCCESS)
+        else if ((rc = TPM2_Import(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, (TPM2B_DATA*)&inner_wrap_key, &id_key_Public, &id_key_dup_blob, &encrypt_wrap_key, &Aes128SymDef, &id_key_priv)) != TPM_RC_SUCCESS)
         {
             LOG_ERROR("Failure: importing dps Id key");
-            result = __FAILURE__;
+            result = get_tpm_failure(rc);
         }
         else
         {
@@ -224,25 +247,25 @@ static int insert_key_in_tpm
 
             memset(&sym_pub, 0, sizeof(TPM2B_PUBLIC));
             memset(&sym_priv, 0, sizeof(TPM2B_PRIVATE));
-            if (TSS_Create(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, &sen_create, &symTemplate, &sym_priv, &sym_pub) != TPM_RC_SUCCESS)
+            if ((rc = TSS_Create(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, &sen_create, &symTemplate, &sym_priv, &sym_pub)) != TPM_RC_SUCCESS)
             {
                 LOG_ERROR("Failed to inject symmetric key data");
-                result = __FAILURE__;
+                result = get_tpm_failure(rc);
             }
-            else if (TPM2_Load(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, &id_key_priv, &id_key_Public, &load_id_key, NULL) != TPM_RC_SUCCESS)
+            else if ((rc = TPM2_Load(&sec_info->tpm_device, &NullPwSession, TPM_20_SRK_HANDLE, &id_key_priv, &id_key_Public, &load_id_key, NULL)) != TPM_RC_SUCCESS)
             {
                 LOG_ERROR("Failed Load Id key.");
-                result = __FAILURE__;
+                result = get_tpm_failure(rc);
             }
             else
             {
                 // Remove old Id key
                 (void)TPM2_EvictControl(&sec_info->tpm_device, &NullPwSession, TPM_RH_OWNER, DPS_ID_KEY_HANDLE, DPS_ID_KEY_HANDLE);
 
-                if (TPM2_EvictControl(&sec_info->tpm_device, &NullPwSession, TPM_RH_OWNER, load_id_key, DPS_ID_KEY_HANDLE) != TPM_RC_SUCCESS)
+                if ((rc = TPM2_EvictControl(&sec_info->tpm_device, &NullPwSession, TPM_RH_OWNER, load_id_key, DPS_ID_KEY_HANDLE)) != TPM_RC_SUCCESS)
                 {
                     LOG_ERROR("Failed Load Id key.");
-                    result = __FAILURE__;
+                    result = get_tpm_failure(rc);
                 }
                 else if (TPM2_FlushContext(&sec_info->tpm_device, load_id_key) != TPM_RC_SUCCESS)
                 {
@@ -262,7 +285,12 @@ static int insert_key_in_tpm
 static int initialize_tpm_device(HSM_CLIENT_INFO* tpm_info)
 {
     int result;
-    if (TSS_CreatePwAuthSession(&NullAuth, &NullPwSession) != TPM_RC_SUCCESS)
+    if ((g_tpm_device_path != NULL) && !is_file_valid(g_tpm_device_path))
+    {
+        LOG_ERROR("TPM device %s could not be opened", g_tpm_device_path);
+        result = __FAILURE__;
+    }
+    else if (TSS_CreatePwAuthSession(&NullAuth, &NullPwSession) != TPM_RC_SUCCESS)
     {
         LOG_ERROR("Failure calling TSS_CreatePwAuthSession");
         result = __FAILURE__;
@@ -336,7 +364,13 @@ static int hsm_client_tpm_activate_identity_key
     }
     else
     {
-        if (insert_key_in_tpm((HSM_CLIENT_INFO*)handle, key, key_len))
+        int insert_result = insert_key_in_tpm((HSM_CLIENT_INFO*)handle, key, key_len);
+        if (insert_result == HSM_TPM_RETRY)
+        {
+            LOG_ERROR("TPM was busy inserting key into tpm");
+            result = HSM_TPM_RETRY;
+        }
+        else if (insert_result != 0)
         {
             LOG_ERROR("Failure inserting key into tpm");
             result = __FAILURE__;
@@ -590,11 +624,32 @@ static void hsm_client_tpm_free_buffer(void* buffer)
 
 int hsm_client_tpm_device_init(void)
 {
-    return 0;
+    int result;
+
+    if (hsm_get_env(ENV_TPM_DEVICE_PATH, &g_tpm_device_path) != 0)
+    {
+        LOG_ERROR("Could not lookup env variable %s", ENV_TPM_DEVICE_PATH);
+        result = __FAILURE__;
+    }
+    else
+    {
+        if (g_tpm_device_path != NULL)
+        {
+            LOG_INFO("Using TPM device %s", g_tpm_device_path);
+        }
+        result = 0;
+    }
+
+    return result;
 }
 
 void hsm_client_tpm_device_deinit(void)
 {
+    if (g_tpm_device_path != NULL)
+    {
+        free(g_tpm_device_path);
+        g_tpm_device_path = NULL;
+    }
 }
 
 static const HSM_CLIENT_TPM_INTERFACE tpm_interface =
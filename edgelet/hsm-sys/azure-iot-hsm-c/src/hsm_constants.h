@@ -8,6 +8,10 @@ extern const char* const ENV_EDGE_HOME_DIR;
 extern const char* const ENV_DEVICE_CA_PATH;
 extern const char* const ENV_DEVICE_PK_PATH;
 extern const char* const ENV_TRUSTED_CA_CERTS_PATH;
+extern const char* const ENV_ENCRYPTION_ALGORITHM;
+extern const char* const ENV_CERT_KEY_TYPE;
+extern const char* const ENV_TPM_DEVICE_PATH;
+extern const char* const ENV_CRL_PATH;
 
 /* HSM directory name under IOTEDGE_HOMEDIR */
 extern const char* const DEFAULT_EDGE_HOME_DIR_UNIX;
@@ -21,6 +25,8 @@ extern const char* const HSM_CRYPTO_PK_DIR;
 extern const char* const EDGE_STORE_NAME;
 extern const char* const EDGELET_IDENTITY_SAS_KEY_NAME;
 extern const char* const EDGELET_ENC_KEY_NAME;
+#define EDGELET_ENC_KEY_SLOT_COUNT 2
+extern const char* const EDGELET_ENC_KEY_SLOT_NAMES[EDGELET_ENC_KEY_SLOT_COUNT];
 extern const char* const DEFAULT_TRUSTED_CA_ALIAS;
 extern const char* const OWNER_CA_ALIAS;
 extern const char* const OWNER_CA_COMMON_NAME;
@@ -27,8 +27,16 @@ struct PKI_KEY_PROPS_TAG
 };
 typedef struct PKI_KEY_PROPS_TAG PKI_KEY_PROPS;
 
+enum HSM_ENC_ALGORITHM_T_TAG
+{
+    HSM_ENC_ALGORITHM_AES_256_GCM,
+    HSM_ENC_ALGORITHM_CHACHA20_POLY1305
+};
+typedef enum HSM_ENC_ALGORITHM_T_TAG HSM_ENC_ALGORITHM_T;
+
 MOCKABLE_FUNCTION(, KEY_HANDLE, create_sas_key, const unsigned char*, key, size_t, key_len);
 MOCKABLE_FUNCTION(, KEY_HANDLE, create_encryption_key, const unsigned char*, key, size_t, key_len);
+MOCKABLE_FUNCTION(, KEY_HANDLE, create_encryption_key_with_algorithm, const unsigned char*, key, size_t, key_len, HSM_ENC_ALGORITHM_T, algorithm);
 MOCKABLE_FUNCTION(, KEY_HANDLE, create_cert_key, const char*, key_file_name);
 
 MOCKABLE_FUNCTION(, int, generate_pki_cert_and_key, CERT_PROPS_HANDLE, cert_props_handle,
@@ -39,8 +47,17 @@ MOCKABLE_FUNCTION(, int, generate_pki_cert_and_key_with_props, CERT_PROPS_HANDLE
                     int, serial_number, int, ca_path_len,
                     const char*, key_file_name, const char*, cert_file_name,
                     const PKI_KEY_PROPS*, key_props);
+MOCKABLE_FUNCTION(, int, generate_pki_cert_and_key_with_issuer_props, CERT_PROPS_HANDLE, cert_props_handle,
+                    int, serial_number, int, ca_path_len,
+                    const char*, key_file_name, const char*, cert_file_name,
+                    const char*, issuer_key_file, const char*, issuer_certificate_file,
+                    const PKI_KEY_PROPS*, key_props);
 MOCKABLE_FUNCTION(, int, generate_encryption_key, unsigned char**, key, size_t*, key_size);
+MOCKABLE_FUNCTION(, int, wrap_encryption_key, const unsigned char*, key, size_t, key_size, const char*, passphrase, unsigned char**, wrapped_key, size_t*, wrapped_key_size);
+MOCKABLE_FUNCTION(, int, unwrap_encryption_key, const unsigned char*, wrapped_key, size_t, wrapped_key_size, const char*, passphrase, unsigned char**, key, size_t*, key_size);
 MOCKABLE_FUNCTION(, int, verify_certificate, const char*, certificate, const char*, certificate_key, const char*, issuer_certificate, bool*, verify_status);
+MOCKABLE_FUNCTION(, int, verify_ca_certificate_and_key, const char*, certificate, const char*, certificate_key, bool*, verify_status);
+MOCKABLE_FUNCTION(, int, check_certificate_revocation, const char*, certificate, const char*, crl_file, bool*, is_revoked);
 
 #ifdef __cplusplus
 }
@@ -796,6 +796,158 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_int_tests)
         test_helper_crypto_deinit(hsm_handle);
     }
 
+    TEST_FUNCTION(hsm_client_decrypt_after_master_key_slot_rotation_success)
+    {
+        // arrange
+        int status;
+        HSM_CLIENT_HANDLE hsm_handle = test_helper_crypto_init();
+        const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+        SIZED_BUFFER id = {TEST_ID, TEST_ID_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {TEST_IV, TEST_IV_SIZE};
+        SIZED_BUFFER ciphertext_result_1 = { NULL, 0 };
+        SIZED_BUFFER ciphertext_result_2 = { NULL, 0 };
+        SIZED_BUFFER plaintext_result_1 = { NULL, 0 };
+        SIZED_BUFFER plaintext_result_2 = { NULL, 0 };
+
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 0);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data(hsm_handle, &id, &pt, &iv, &ciphertext_result_1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // act, assert
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data(hsm_handle, &id, &pt, &iv, &ciphertext_result_2);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, ciphertext_result_1.size, ciphertext_result_2.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(ciphertext_result_1.buffer, ciphertext_result_2.buffer, ciphertext_result_1.size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = interface->hsm_client_decrypt_data(hsm_handle, &id, &ciphertext_result_1, &iv, &plaintext_result_1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_PLAINTEXT_SIZE, plaintext_result_1.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_PLAINTEXT, plaintext_result_1.buffer, TEST_PLAINTEXT_SIZE);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = interface->hsm_client_decrypt_data(hsm_handle, &id, &ciphertext_result_2, &iv, &plaintext_result_2);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_PLAINTEXT_SIZE, plaintext_result_2.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_PLAINTEXT, plaintext_result_2.buffer, TEST_PLAINTEXT_SIZE);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 0);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_destroy_master_encryption_key_in_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_destroy_master_encryption_key_in_slot(hsm_handle, 0);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        free(plaintext_result_1.buffer);
+        free(plaintext_result_2.buffer);
+        free(ciphertext_result_1.buffer);
+        free(ciphertext_result_2.buffer);
+        test_helper_crypto_deinit(hsm_handle);
+    }
+
+    TEST_FUNCTION(hsm_client_active_master_key_slot_survives_restart_success)
+    {
+        // arrange
+        int status;
+        size_t slot = 0;
+        HSM_CLIENT_HANDLE hsm_handle = test_helper_crypto_init();
+        const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+        SIZED_BUFFER id = {TEST_ID, TEST_ID_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {TEST_IV, TEST_IV_SIZE};
+        SIZED_BUFFER ciphertext_result_1 = { NULL, 0 };
+        SIZED_BUFFER ciphertext_result_2 = { NULL, 0 };
+        SIZED_BUFFER plaintext_result = { NULL, 0 };
+
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 0);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data(hsm_handle, &id, &pt, &iv, &ciphertext_result_1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // act
+        test_helper_crypto_deinit(hsm_handle);
+        hsm_handle = test_helper_crypto_init();
+        interface = hsm_client_crypto_interface();
+
+        // assert
+        status = interface->hsm_client_get_active_master_encryption_key_slot(hsm_handle, &slot);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 1, slot, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data(hsm_handle, &id, &pt, &iv, &ciphertext_result_2);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, ciphertext_result_1.size, ciphertext_result_2.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(ciphertext_result_1.buffer, ciphertext_result_2.buffer, ciphertext_result_1.size);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_decrypt_data(hsm_handle, &id, &ciphertext_result_1, &iv, &plaintext_result);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_PLAINTEXT_SIZE, plaintext_result.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_PLAINTEXT, plaintext_result.buffer, TEST_PLAINTEXT_SIZE);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        status = interface->hsm_client_destroy_master_encryption_key(hsm_handle);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        free(plaintext_result.buffer);
+        free(ciphertext_result_1.buffer);
+        free(ciphertext_result_2.buffer);
+        test_helper_crypto_deinit(hsm_handle);
+    }
+
+    TEST_FUNCTION(hsm_client_destroy_master_key_removes_every_slot_success)
+    {
+        // arrange
+        int status;
+        size_t slot = 1;
+        HSM_CLIENT_HANDLE hsm_handle = test_helper_crypto_init();
+        const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+        SIZED_BUFFER id = {TEST_ID, TEST_ID_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {TEST_IV, TEST_IV_SIZE};
+        SIZED_BUFFER ciphertext_result = { NULL, 0 };
+        SIZED_BUFFER plaintext_result = { NULL, 0 };
+
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 0);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_create_master_encryption_key_in_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data(hsm_handle, &id, &pt, &iv, &ciphertext_result);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // act
+        status = interface->hsm_client_destroy_master_encryption_key(hsm_handle);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_get_active_master_encryption_key_slot(hsm_handle, &slot);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, slot, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 0);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_decrypt_data(hsm_handle, &id, &ciphertext_result, &iv, &plaintext_result);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(ciphertext_result.buffer);
+        test_helper_crypto_deinit(hsm_handle);
+    }
+
     TEST_FUNCTION(hsm_client_multiple_masterkey_create_idempotent_success)
     {
         // arrange
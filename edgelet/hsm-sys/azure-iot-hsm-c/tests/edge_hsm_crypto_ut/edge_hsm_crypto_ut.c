@@ -54,6 +54,10 @@ MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_close_key, HSM_CLIENT_STORE_HAN
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_remove_key, HSM_CLIENT_STORE_HANDLE, handle, HSM_KEY_T, key_type, const char*, key_name);
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_insert_sas_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const unsigned char*, key, size_t, key_len);
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_insert_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_export_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const char*, passphrase, unsigned char**, wrapped_key, size_t*, wrapped_key_size);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_import_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const char*, passphrase, const unsigned char*, wrapped_key, size_t, wrapped_key_size);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_get_active_encryption_key_slot, HSM_CLIENT_STORE_HANDLE, handle, size_t*, slot);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_set_active_encryption_key_slot, HSM_CLIENT_STORE_HANDLE, handle, size_t, slot);
 
 // store pki mocks
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_create_pki_cert, HSM_CLIENT_STORE_HANDLE, handle, CERT_PROPS_HANDLE, cert_props_handle);
@@ -87,6 +91,7 @@ MOCKABLE_FUNCTION(, const char*, get_issuer_alias, CERT_PROPS_HANDLE, handle);
 //#############################################################################
 
 #include "hsm_client_data.h"
+#include "hsm_constants.h"
 
 //#############################################################################
 // Test defines and data
@@ -99,6 +104,9 @@ MOCKABLE_FUNCTION(, const char*, get_issuer_alias, CERT_PROPS_HANDLE, handle);
 #define TEST_CERT_INFO_HANDLE (CERT_INFO_HANDLE)0x1003
 #define TEST_TRUST_BUNDLE_CERT_INFO_HANDLE (CERT_INFO_HANDLE)0x1004
 #define TEST_CERT_PROPS_HANDLE (CERT_PROPS_HANDLE)0x1005
+#define TEST_WRAPPED_KEY_BUFFER (unsigned char*)0x1006
+#define TEST_WRAPPED_KEY_SIZE 61
+#define TEST_PASSPHRASE "test passphrase"
 
 DEFINE_ENUM_STRINGS(UMOCK_C_ERROR_CODE, UMOCK_C_ERROR_CODE_VALUES)
 
@@ -108,6 +116,9 @@ static TEST_MUTEX_HANDLE g_dllByDll;
 const char* TEST_ALIAS_STRING = "test_alias";
 const char* TEST_ISSUER_ALIAS_STRING = "test_issuer_alias";
 
+// active encryption key slot persisted by the mocked store
+static size_t g_test_active_enc_key_slot = 0;
+
 static const HSM_CLIENT_STORE_INTERFACE mocked_hsm_client_store_interface =
 {
     mocked_hsm_client_store_create,
@@ -124,7 +135,11 @@ static const HSM_CLIENT_STORE_INTERFACE mocked_hsm_client_store_interface =
     mocked_hsm_client_store_remove_pki_cert,
     mocked_hsm_client_store_insert_pki_trusted_cert,
     mocked_hsm_client_store_get_pki_trusted_certs,
-    mocked_hsm_client_store_remove_pki_trusted_cert
+    mocked_hsm_client_store_remove_pki_trusted_cert,
+    mocked_hsm_client_store_export_encryption_key,
+    mocked_hsm_client_store_import_encryption_key,
+    mocked_hsm_client_store_get_active_encryption_key_slot,
+    mocked_hsm_client_store_set_active_encryption_key_slot
 };
 
 static const HSM_CLIENT_KEY_INTERFACE mocked_hsm_client_key_interface =
@@ -189,8 +204,7 @@ static KEY_HANDLE test_hook_hsm_client_store_open_key(HSM_CLIENT_STORE_HANDLE ha
     (void)handle;
     (void)key_type;
     (void)key_name;
-    ASSERT_FAIL("API not expected to be called");
-    return NULL;
+    return TEST_KEY_HANDLE;
 }
 
 static int test_hook_hsm_client_store_close_key(HSM_CLIENT_STORE_HANDLE handle,
@@ -198,8 +212,7 @@ static int test_hook_hsm_client_store_close_key(HSM_CLIENT_STORE_HANDLE handle,
 {
     (void)handle;
     (void)key_handle;
-    ASSERT_FAIL("API not expected to be called");
-    return __LINE__;
+    return 0;
 }
 
 static int test_hook_hsm_client_store_remove_key(HSM_CLIENT_STORE_HANDLE handle,
@@ -233,6 +246,50 @@ static int test_hook_hsm_client_store_insert_encryption_key(HSM_CLIENT_STORE_HAN
     return 0;
 }
 
+static int test_hook_hsm_client_store_export_encryption_key(HSM_CLIENT_STORE_HANDLE handle,
+                                                            const char* key_name,
+                                                            const char* passphrase,
+                                                            unsigned char** wrapped_key,
+                                                            size_t* wrapped_key_size)
+{
+    (void)handle;
+    (void)key_name;
+    (void)passphrase;
+    *wrapped_key = (unsigned char*)TEST_WRAPPED_KEY_BUFFER;
+    *wrapped_key_size = TEST_WRAPPED_KEY_SIZE;
+    return 0;
+}
+
+static int test_hook_hsm_client_store_import_encryption_key(HSM_CLIENT_STORE_HANDLE handle,
+                                                            const char* key_name,
+                                                            const char* passphrase,
+                                                            const unsigned char* wrapped_key,
+                                                            size_t wrapped_key_size)
+{
+    (void)handle;
+    (void)key_name;
+    (void)passphrase;
+    (void)wrapped_key;
+    (void)wrapped_key_size;
+    return 0;
+}
+
+static int test_hook_hsm_client_store_get_active_encryption_key_slot(HSM_CLIENT_STORE_HANDLE handle,
+                                                                   size_t* slot)
+{
+    (void)handle;
+    *slot = g_test_active_enc_key_slot;
+    return 0;
+}
+
+static int test_hook_hsm_client_store_set_active_encryption_key_slot(HSM_CLIENT_STORE_HANDLE handle,
+                                                                   size_t slot)
+{
+    (void)handle;
+    g_test_active_enc_key_slot = slot;
+    return 0;
+}
+
 static int test_hook_hsm_client_store_create_pki_cert(HSM_CLIENT_STORE_HANDLE handle,
                                                       CERT_PROPS_HANDLE cert_props_handle)
 {
@@ -456,6 +513,18 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_unittests)
             REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_insert_encryption_key, test_hook_hsm_client_store_insert_encryption_key);
             REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_insert_encryption_key, 1);
 
+            REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_export_encryption_key, test_hook_hsm_client_store_export_encryption_key);
+            REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_export_encryption_key, 1);
+
+            REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_import_encryption_key, test_hook_hsm_client_store_import_encryption_key);
+            REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_import_encryption_key, 1);
+
+            REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_get_active_encryption_key_slot, test_hook_hsm_client_store_get_active_encryption_key_slot);
+            REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_get_active_encryption_key_slot, 1);
+
+            REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_set_active_encryption_key_slot, test_hook_hsm_client_store_set_active_encryption_key_slot);
+            REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_set_active_encryption_key_slot, 1);
+
             REGISTER_GLOBAL_MOCK_HOOK(mocked_hsm_client_store_create_pki_cert, test_hook_hsm_client_store_create_pki_cert);
             REGISTER_GLOBAL_MOCK_FAIL_RETURN(mocked_hsm_client_store_create_pki_cert, 1);
 
@@ -513,6 +582,7 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_unittests)
                 ASSERT_FAIL("Mutex is ABANDONED. Failure in test framework.");
             }
 
+            g_test_active_enc_key_slot = 0;
             umock_c_reset_all_calls();
         }
 
@@ -672,6 +742,12 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_unittests)
             ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_decrypt_data, "Line:" TOSTRING(__LINE__));
             ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_get_trust_bundle, "Line:" TOSTRING(__LINE__));
             ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_free_buffer, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_export_master_encryption_key, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_import_master_encryption_key, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_create_master_encryption_key_in_slot, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_destroy_master_encryption_key_in_slot, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_set_active_master_encryption_key_slot, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL_WITH_MSG(result->hsm_client_get_active_master_encryption_key_slot, "Line:" TOSTRING(__LINE__));
 
             //cleanup
         }
@@ -1062,10 +1138,492 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_unittests)
             HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
             HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY hsm_client_destroy_master_encryption_key;
             hsm_client_destroy_master_encryption_key = interface->hsm_client_destroy_master_encryption_key;
+            g_test_active_enc_key_slot = 1;
+            umock_c_reset_all_calls();
 
-            // act, assert
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_remove_key(TEST_HSM_STORE_HANDLE, HSM_KEY_ENCRYPTION, EDGELET_ENC_KEY_SLOT_NAMES[0]));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_remove_key(TEST_HSM_STORE_HANDLE, HSM_KEY_ENCRYPTION, EDGELET_ENC_KEY_SLOT_NAMES[1]));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_set_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, 0));
+
+            // act
             status = hsm_client_destroy_master_encryption_key(hsm_handle);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, g_test_active_enc_key_slot, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_export_master_encryption_key
+        */
+        TEST_FUNCTION(edge_hsm_client_export_master_encryption_key_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY hsm_client_export_master_encryption_key;
+            hsm_client_export_master_encryption_key = interface->hsm_client_export_master_encryption_key;
+            SIZED_BUFFER wrapped_key = { NULL, 0 };
+
+            // act, assert
+            status = hsm_client_export_master_encryption_key(NULL, TEST_PASSPHRASE, &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_export_master_encryption_key(hsm_handle, NULL, &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_export_master_encryption_key(hsm_handle, "", &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_export_master_encryption_key(hsm_handle, TEST_PASSPHRASE, NULL);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_export_master_encryption_key
+        */
+        TEST_FUNCTION(edge_hsm_client_export_master_encryption_key_success)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY hsm_client_export_master_encryption_key;
+            hsm_client_export_master_encryption_key = interface->hsm_client_export_master_encryption_key;
+            SIZED_BUFFER wrapped_key = { NULL, 0 };
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_get_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, IGNORED_PTR_ARG));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_export_encryption_key(TEST_HSM_STORE_HANDLE, EDGELET_ENC_KEY_NAME, TEST_PASSPHRASE, IGNORED_PTR_ARG, IGNORED_PTR_ARG));
+
+            // act
+            status = hsm_client_export_master_encryption_key(hsm_handle, TEST_PASSPHRASE, &wrapped_key);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(void_ptr, TEST_WRAPPED_KEY_BUFFER, wrapped_key.buffer, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_WRAPPED_KEY_SIZE, wrapped_key.size, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_import_master_encryption_key
+        */
+        TEST_FUNCTION(edge_hsm_client_import_master_encryption_key_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY hsm_client_import_master_encryption_key;
+            hsm_client_import_master_encryption_key = interface->hsm_client_import_master_encryption_key;
+            SIZED_BUFFER wrapped_key = { TEST_WRAPPED_KEY_BUFFER, TEST_WRAPPED_KEY_SIZE };
+            SIZED_BUFFER empty_key = { TEST_WRAPPED_KEY_BUFFER, 0 };
+
+            // act, assert
+            status = hsm_client_import_master_encryption_key(NULL, TEST_PASSPHRASE, &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_import_master_encryption_key(hsm_handle, NULL, &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_import_master_encryption_key(hsm_handle, "", &wrapped_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_import_master_encryption_key(hsm_handle, TEST_PASSPHRASE, NULL);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_import_master_encryption_key(hsm_handle, TEST_PASSPHRASE, &empty_key);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_import_master_encryption_key
+        */
+        TEST_FUNCTION(edge_hsm_client_import_master_encryption_key_success)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY hsm_client_import_master_encryption_key;
+            hsm_client_import_master_encryption_key = interface->hsm_client_import_master_encryption_key;
+            SIZED_BUFFER wrapped_key = { TEST_WRAPPED_KEY_BUFFER, TEST_WRAPPED_KEY_SIZE };
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_get_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, IGNORED_PTR_ARG));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_import_encryption_key(TEST_HSM_STORE_HANDLE, EDGELET_ENC_KEY_NAME, TEST_PASSPHRASE, TEST_WRAPPED_KEY_BUFFER, TEST_WRAPPED_KEY_SIZE));
+
+            // act
+            status = hsm_client_import_master_encryption_key(hsm_handle, TEST_PASSPHRASE, &wrapped_key);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_create_master_encryption_key_in_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_create_master_encryption_key_in_slot_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_create_master_encryption_key_in_slot;
+            hsm_client_create_master_encryption_key_in_slot = interface->hsm_client_create_master_encryption_key_in_slot;
+            umock_c_reset_all_calls();
+
+            // act, assert
+            status = hsm_client_create_master_encryption_key_in_slot(NULL, 1);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_create_master_encryption_key_in_slot(hsm_handle, EDGELET_ENC_KEY_SLOT_COUNT);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_create_master_encryption_key_in_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_create_master_encryption_key_in_slot_success)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_create_master_encryption_key_in_slot;
+            hsm_client_create_master_encryption_key_in_slot = interface->hsm_client_create_master_encryption_key_in_slot;
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_insert_encryption_key(TEST_HSM_STORE_HANDLE, EDGELET_ENC_KEY_SLOT_NAMES[1]));
+
+            // act
+            status = hsm_client_create_master_encryption_key_in_slot(hsm_handle, 1);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_destroy_master_encryption_key_in_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_destroy_master_encryption_key_in_slot_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_destroy_master_encryption_key_in_slot;
+            hsm_client_destroy_master_encryption_key_in_slot = interface->hsm_client_destroy_master_encryption_key_in_slot;
+            umock_c_reset_all_calls();
+
+            // act, assert
+            status = hsm_client_destroy_master_encryption_key_in_slot(NULL, 1);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_destroy_master_encryption_key_in_slot(hsm_handle, EDGELET_ENC_KEY_SLOT_COUNT);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_destroy_master_encryption_key_in_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_destroy_master_encryption_key_in_slot_success)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
             ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT hsm_client_destroy_master_encryption_key_in_slot;
+            hsm_client_destroy_master_encryption_key_in_slot = interface->hsm_client_destroy_master_encryption_key_in_slot;
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_remove_key(TEST_HSM_STORE_HANDLE, HSM_KEY_ENCRYPTION, EDGELET_ENC_KEY_SLOT_NAMES[1]));
+
+            // act
+            status = hsm_client_destroy_master_encryption_key_in_slot(hsm_handle, 1);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_set_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_set_active_master_encryption_key_slot_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_set_active_master_encryption_key_slot;
+            hsm_client_set_active_master_encryption_key_slot = interface->hsm_client_set_active_master_encryption_key_slot;
+            umock_c_reset_all_calls();
+
+            // act, assert
+            status = hsm_client_set_active_master_encryption_key_slot(NULL, 1);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_set_active_master_encryption_key_slot(hsm_handle, EDGELET_ENC_KEY_SLOT_COUNT);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_set_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_set_active_master_encryption_key_slot_fails_for_empty_slot)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_set_active_master_encryption_key_slot;
+            hsm_client_set_active_master_encryption_key_slot = interface->hsm_client_set_active_master_encryption_key_slot;
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_open_key(TEST_HSM_STORE_HANDLE, HSM_KEY_ENCRYPTION, EDGELET_ENC_KEY_SLOT_NAMES[1]))
+                .SetReturn(NULL);
+
+            // act
+            status = hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+
+            // assert
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_set_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_set_active_master_encryption_key_slot_success)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_set_active_master_encryption_key_slot;
+            hsm_client_set_active_master_encryption_key_slot = interface->hsm_client_set_active_master_encryption_key_slot;
+            HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY hsm_client_export_master_encryption_key;
+            hsm_client_export_master_encryption_key = interface->hsm_client_export_master_encryption_key;
+            SIZED_BUFFER wrapped_key = { NULL, 0 };
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_open_key(TEST_HSM_STORE_HANDLE, HSM_KEY_ENCRYPTION, EDGELET_ENC_KEY_SLOT_NAMES[1]));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_close_key(TEST_HSM_STORE_HANDLE, TEST_KEY_HANDLE));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_set_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, 1));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_get_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, IGNORED_PTR_ARG));
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_export_encryption_key(TEST_HSM_STORE_HANDLE, EDGELET_ENC_KEY_SLOT_NAMES[1], TEST_PASSPHRASE, IGNORED_PTR_ARG, IGNORED_PTR_ARG));
+
+            // act
+            status = hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            status = hsm_client_export_master_encryption_key(hsm_handle, TEST_PASSPHRASE, &wrapped_key);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_get_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_get_active_master_encryption_key_slot_invalid_param_validation)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_get_active_master_encryption_key_slot;
+            hsm_client_get_active_master_encryption_key_slot = interface->hsm_client_get_active_master_encryption_key_slot;
+            size_t slot = 0;
+            umock_c_reset_all_calls();
+
+            // act, assert
+            status = hsm_client_get_active_master_encryption_key_slot(NULL, &slot);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+            status = hsm_client_get_active_master_encryption_key_slot(hsm_handle, NULL);
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_get_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_get_active_master_encryption_key_slot_fails_for_invalid_stored_slot)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_get_active_master_encryption_key_slot;
+            hsm_client_get_active_master_encryption_key_slot = interface->hsm_client_get_active_master_encryption_key_slot;
+            size_t slot = 0;
+            g_test_active_enc_key_slot = EDGELET_ENC_KEY_SLOT_COUNT;
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_get_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, IGNORED_PTR_ARG));
+
+            // act
+            status = hsm_client_get_active_master_encryption_key_slot(hsm_handle, &slot);
+
+            // assert
+            ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+            //cleanup
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+        }
+
+        /**
+         * Test function for API
+         *   hsm_client_get_active_master_encryption_key_slot
+        */
+        TEST_FUNCTION(edge_hsm_client_get_active_master_encryption_key_slot_survives_reinitialization)
+        {
+            //arrange
+            int status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+            HSM_CLIENT_CREATE hsm_client_crypto_create = interface->hsm_client_crypto_create;
+            HSM_CLIENT_DESTROY hsm_client_crypto_destroy = interface->hsm_client_crypto_destroy;
+            HSM_CLIENT_HANDLE hsm_handle = hsm_client_crypto_create();
+            HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_set_active_master_encryption_key_slot;
+            hsm_client_set_active_master_encryption_key_slot = interface->hsm_client_set_active_master_encryption_key_slot;
+            HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT hsm_client_get_active_master_encryption_key_slot;
+            hsm_client_get_active_master_encryption_key_slot = interface->hsm_client_get_active_master_encryption_key_slot;
+            size_t slot = 0;
+            status = hsm_client_set_active_master_encryption_key_slot(hsm_handle, 1);
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            hsm_client_crypto_destroy(hsm_handle);
+            hsm_client_crypto_deinit();
+            status = hsm_client_crypto_init();
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            hsm_handle = hsm_client_crypto_create();
+            umock_c_reset_all_calls();
+
+            STRICT_EXPECTED_CALL(mocked_hsm_client_store_get_active_encryption_key_slot(TEST_HSM_STORE_HANDLE, IGNORED_PTR_ARG));
+
+            // act
+            status = hsm_client_get_active_master_encryption_key_slot(hsm_handle, &slot);
+
+            // assert
+            ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(size_t, 1, slot, "Line:" TOSTRING(__LINE__));
+            ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
 
             //cleanup
             hsm_client_crypto_destroy(hsm_handle);
@@ -247,6 +247,7 @@ BEGIN_TEST_SUITE(edge_hsm_store_unittests)
         REGISTER_UMOCK_ALIAS_TYPE(HSM_CLIENT_STORE_HANDLE, void*);
         REGISTER_UMOCK_ALIAS_TYPE(HSM_CLIENT_KEY_INTERFACE, void*);
         REGISTER_UMOCK_ALIAS_TYPE(KEY_HANDLE, void*);
+        REGISTER_UMOCK_ALIAS_TYPE(HSM_ENC_ALGORITHM_T, int);
         REGISTER_UMOCK_ALIAS_TYPE(CERT_PROPS_HANDLE, void*);
         REGISTER_UMOCK_ALIAS_TYPE(SINGLYLINKEDLIST_HANDLE, void*);
         REGISTER_UMOCK_ALIAS_TYPE(LIST_ITEM_HANDLE, void*);
@@ -54,6 +54,10 @@ MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_close_key, HSM_CLIENT_STORE_HAN
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_remove_key, HSM_CLIENT_STORE_HANDLE, handle, HSM_KEY_T, key_type, const char*, key_name);
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_insert_sas_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const unsigned char*, key, size_t, key_len);
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_insert_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_export_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const char*, passphrase, unsigned char**, wrapped_key, size_t*, wrapped_key_size);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_import_encryption_key, HSM_CLIENT_STORE_HANDLE, handle, const char*, key_name, const char*, passphrase, const unsigned char*, wrapped_key, size_t, wrapped_key_size);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_get_active_encryption_key_slot, HSM_CLIENT_STORE_HANDLE, handle, size_t*, slot);
+MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_set_active_encryption_key_slot, HSM_CLIENT_STORE_HANDLE, handle, size_t, slot);
 
 // store pki mocks
 MOCKABLE_FUNCTION(, int, mocked_hsm_client_store_create_pki_cert, HSM_CLIENT_STORE_HANDLE, handle, CERT_PROPS_HANDLE, cert_props_handle);
@@ -116,7 +120,11 @@ static const HSM_CLIENT_STORE_INTERFACE mocked_hsm_client_store_interface =
     mocked_hsm_client_store_remove_pki_cert,
     mocked_hsm_client_store_insert_pki_trusted_cert,
     mocked_hsm_client_store_get_pki_trusted_certs,
-    mocked_hsm_client_store_remove_pki_trusted_cert
+    mocked_hsm_client_store_remove_pki_trusted_cert,
+    mocked_hsm_client_store_export_encryption_key,
+    mocked_hsm_client_store_import_encryption_key,
+    mocked_hsm_client_store_get_active_encryption_key_slot,
+    mocked_hsm_client_store_set_active_encryption_key_slot
 };
 
 static const HSM_CLIENT_KEY_INTERFACE mocked_hsm_client_key_interface =
@@ -477,4 +477,59 @@ BEGIN_TEST_SUITE(edge_openssl_enc_tests)
         free(key2);
     }
 
+    TEST_FUNCTION(test_wrap_and_unwrap_key_success)
+    {
+        // arrange
+        int status;
+        unsigned char *wrapped_key = NULL;
+        size_t wrapped_key_size = 0;
+        unsigned char *key = NULL;
+        size_t key_size = 0;
+
+        // act
+        status = wrap_encryption_key(TEST_KEY, TEST_KEY_SIZE, "correct horse battery staple",
+                                     &wrapped_key, &wrapped_key_size);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NOT_NULL_WITH_MSG(wrapped_key, "Line:" TOSTRING(__LINE__));
+        status = unwrap_encryption_key(wrapped_key, wrapped_key_size, "correct horse battery staple",
+                                       &key, &key_size);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_KEY_SIZE, key_size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_KEY, key, TEST_KEY_SIZE);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_KEY, wrapped_key + (wrapped_key_size - TEST_KEY_SIZE), TEST_KEY_SIZE);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(key);
+        free(wrapped_key);
+    }
+
+    TEST_FUNCTION(test_unwrap_key_with_wrong_passphrase_fails)
+    {
+        // arrange
+        int status;
+        unsigned char *wrapped_key = NULL;
+        size_t wrapped_key_size = 0;
+        unsigned char *key = (unsigned char*)0x1000;
+        size_t key_size = 1234;
+        status = wrap_encryption_key(TEST_KEY, TEST_KEY_SIZE, "correct horse battery staple",
+                                     &wrapped_key, &wrapped_key_size);
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // act
+        status = unwrap_encryption_key(wrapped_key, wrapped_key_size, "wrong passphrase",
+                                       &key, &key_size);
+
+        // assert
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NULL_WITH_MSG(key, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, key_size, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(wrapped_key);
+    }
+
 END_TEST_SUITE(edge_openssl_enc_tests)
@@ -61,6 +61,16 @@ MOCKABLE_FUNCTION(, int, EVP_DecryptUpdate, EVP_CIPHER_CTX*, ctx, unsigned char*
                     int*, outl, const unsigned char*, in, int, inl);
 MOCKABLE_FUNCTION(, int, EVP_DecryptFinal_ex, EVP_CIPHER_CTX*, ctx, unsigned char*, outm, int*, outl);
 MOCKABLE_FUNCTION(, const EVP_CIPHER*, EVP_aes_256_gcm);
+MOCKABLE_FUNCTION(, const EVP_MD*, EVP_sha256);
+MOCKABLE_FUNCTION(, int, EVP_Digest, const void*, data, size_t, count, unsigned char*, md,
+                    unsigned int*, size, const EVP_MD*, type, ENGINE*, impl);
+MOCKABLE_FUNCTION(, int, PKCS5_PBKDF2_HMAC, const char*, pass, int, passlen,
+                    const unsigned char*, salt, int, saltlen, int, iter,
+                    const EVP_MD*, digest, int, keylen, unsigned char*, out);
+MOCKABLE_FUNCTION(, void, OPENSSL_cleanse, void*, ptr, size_t, len);
+#if OPENSSL_VERSION_NUMBER >= 0x10100000L
+MOCKABLE_FUNCTION(, const EVP_CIPHER*, EVP_chacha20_poly1305);
+#endif
 
 #undef ENABLE_MOCKS
 
@@ -114,6 +124,8 @@ static size_t TEST_IDENTITY_SIZE = sizeof(TEST_IDENTITY);
 static unsigned char TEST_IV[] = "IV";
 static size_t TEST_IV_SIZE = sizeof(TEST_IV);
 static const EVP_CIPHER* TEST_EVP_CIPHER = (EVP_CIPHER*)(0x2000);
+#define TEST_DIGEST_SIZE 32
+#define TEST_NONCE_SIZE 12
 
 //#############################################################################
 // Mocked functions test hooks
@@ -218,6 +230,32 @@ static const EVP_CIPHER* test_hook_EVP_aes_256_gcm(void)
     return TEST_EVP_CIPHER;
 }
 
+#if OPENSSL_VERSION_NUMBER >= 0x10100000L
+static const EVP_CIPHER* test_hook_EVP_chacha20_poly1305(void)
+{
+    return TEST_EVP_CIPHER;
+}
+#endif
+
+static int test_hook_EVP_Digest
+(
+    const void *data,
+    size_t count,
+    unsigned char *md,
+    unsigned int *size,
+    const EVP_MD *type,
+    ENGINE *impl
+)
+{
+    (void)data;
+    (void)count;
+    (void)type;
+    (void)impl;
+    memset(md, 0xAB, TEST_DIGEST_SIZE);
+    *size = TEST_DIGEST_SIZE;
+    return 1;
+}
+
 static int test_hook_EVP_DecryptInit_ex
 (
     EVP_CIPHER_CTX *ctx,
@@ -383,6 +421,11 @@ BEGIN_TEST_SUITE(edge_openssl_encryption_unittests)
 
         REGISTER_GLOBAL_MOCK_HOOK(EVP_CIPHER_CTX_free, test_hook_EVP_CIPHER_CTX_free);
         REGISTER_GLOBAL_MOCK_HOOK(EVP_aes_256_gcm, test_hook_EVP_aes_256_gcm);
+#if OPENSSL_VERSION_NUMBER >= 0x10100000L
+        REGISTER_GLOBAL_MOCK_HOOK(EVP_chacha20_poly1305, test_hook_EVP_chacha20_poly1305);
+#endif
+        REGISTER_GLOBAL_MOCK_HOOK(EVP_Digest, test_hook_EVP_Digest);
+        REGISTER_GLOBAL_MOCK_FAIL_RETURN(EVP_Digest, 0);
 
         REGISTER_GLOBAL_MOCK_HOOK(EVP_DecryptInit_ex, test_hook_EVP_DecryptInit_ex);
         REGISTER_GLOBAL_MOCK_FAIL_RETURN(EVP_DecryptInit_ex, 0);
@@ -737,6 +780,86 @@ BEGIN_TEST_SUITE(edge_openssl_encryption_unittests)
         key_destroy(key_handle);
     }
 
+#if OPENSSL_VERSION_NUMBER >= 0x10100000L
+    /**
+     * Test function for API
+     *   create_encryption_key_with_algorithm
+     *   key_encrypt
+    */
+    TEST_FUNCTION(key_encrypt_chacha20_poly1305_success)
+    {
+        // arrange
+        KEY_HANDLE key_handle = create_encryption_key_with_algorithm(TEST_KEY, ENCRYPTION_KEY_SIZE, HSM_ENC_ALGORITHM_CHACHA20_POLY1305);
+        ASSERT_IS_NOT_NULL_WITH_MSG(key_handle, "Line:" TOSTRING(__LINE__));
+        SIZED_BUFFER id = {TEST_IDENTITY, TEST_IDENTITY_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {TEST_IV, TEST_IV_SIZE};
+        SIZED_BUFFER ct = {NULL, 0};
+        int status;
+        umock_c_reset_all_calls();
+
+        // act
+        status = key_encrypt(key_handle, &id, &pt, &iv, &ct);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, TEST_CIPHERTEXT_HEADER_SIZE+TEST_PLAINTEXT_SIZE, ct.size, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NOT_NULL_WITH_MSG(ct.buffer, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 2, (int)ct.buffer[0], "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(ct.buffer);
+        key_destroy(key_handle);
+    }
+
+    /**
+     * Test function for API
+     *   create_encryption_key_with_algorithm
+     *   key_encrypt
+    */
+    TEST_FUNCTION(key_encrypt_chacha20_poly1305_derives_nonce_from_long_iv)
+    {
+        // arrange
+        unsigned char long_iv[] = "initialization vector";
+        size_t long_iv_size = sizeof(long_iv) - 1;
+        KEY_HANDLE key_handle = create_encryption_key_with_algorithm(TEST_KEY, ENCRYPTION_KEY_SIZE, HSM_ENC_ALGORITHM_CHACHA20_POLY1305);
+        ASSERT_IS_NOT_NULL_WITH_MSG(key_handle, "Line:" TOSTRING(__LINE__));
+        SIZED_BUFFER id = {TEST_IDENTITY, TEST_IDENTITY_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {long_iv, long_iv_size};
+        SIZED_BUFFER ct = {NULL, 0};
+        int status;
+        umock_c_reset_all_calls();
+
+        EXPECTED_CALL(initialize_openssl());
+        STRICT_EXPECTED_CALL(EVP_sha256());
+        STRICT_EXPECTED_CALL(EVP_Digest(long_iv, long_iv_size, IGNORED_PTR_ARG, IGNORED_PTR_ARG, IGNORED_PTR_ARG, NULL));
+        STRICT_EXPECTED_CALL(gballoc_malloc(TEST_CIPHERTEXT_SIZE));
+        STRICT_EXPECTED_CALL(EVP_CIPHER_CTX_new());
+        STRICT_EXPECTED_CALL(EVP_chacha20_poly1305());
+        STRICT_EXPECTED_CALL(EVP_EncryptInit_ex(TEST_EVP_CIPHER_CTX, TEST_EVP_CIPHER, NULL, NULL, NULL));
+        STRICT_EXPECTED_CALL(EVP_CIPHER_CTX_ctrl(TEST_EVP_CIPHER_CTX, EVP_CTRL_GCM_SET_IVLEN, TEST_NONCE_SIZE, NULL));
+        STRICT_EXPECTED_CALL(EVP_EncryptInit_ex(TEST_EVP_CIPHER_CTX, NULL, NULL, IGNORED_PTR_ARG, IGNORED_PTR_ARG));
+        STRICT_EXPECTED_CALL(EVP_EncryptUpdate(TEST_EVP_CIPHER_CTX, NULL, IGNORED_PTR_ARG, TEST_IDENTITY, (int)TEST_IDENTITY_SIZE));
+        STRICT_EXPECTED_CALL(EVP_EncryptUpdate(TEST_EVP_CIPHER_CTX, IGNORED_PTR_ARG, IGNORED_PTR_ARG, TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE));
+        STRICT_EXPECTED_CALL(EVP_EncryptFinal_ex(TEST_EVP_CIPHER_CTX, IGNORED_PTR_ARG, IGNORED_PTR_ARG));
+        STRICT_EXPECTED_CALL(EVP_CIPHER_CTX_ctrl(TEST_EVP_CIPHER_CTX, EVP_CTRL_GCM_GET_TAG, TEST_TAG_SIZE, IGNORED_PTR_ARG));
+        STRICT_EXPECTED_CALL(EVP_CIPHER_CTX_free(TEST_EVP_CIPHER_CTX));
+
+        // act
+        status = key_encrypt(key_handle, &id, &pt, &iv, &ct);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 2, (int)ct.buffer[0], "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls(), "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(ct.buffer);
+        key_destroy(key_handle);
+    }
+#endif
+
     /**
      * Test function for API
      *   key_encrypt
@@ -1021,4 +1144,54 @@ BEGIN_TEST_SUITE(edge_openssl_encryption_unittests)
         key_destroy(key_handle);
     }
 
+    /**
+     * Test function for APIs
+     *   wrap_encryption_key
+     *   unwrap_encryption_key
+    */
+    TEST_FUNCTION(wrap_unwrap_encryption_key_invalid_params)
+    {
+        // arrange
+        int status;
+        unsigned char *output = (unsigned char*)0x1000;
+        size_t output_size = 1234;
+
+        // act, assert
+        status = wrap_encryption_key(NULL, ENCRYPTION_KEY_SIZE, "passphrase", &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NULL_WITH_MSG(output, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, output_size, "Line:" TOSTRING(__LINE__));
+
+        output = (unsigned char*)0x1000;
+        output_size = 1234;
+        status = wrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE - 1, "passphrase", &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NULL_WITH_MSG(output, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, output_size, "Line:" TOSTRING(__LINE__));
+
+        status = wrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE, "", &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = wrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE, NULL, &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = wrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE, "passphrase", NULL, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        output = (unsigned char*)0x1000;
+        output_size = 1234;
+        status = unwrap_encryption_key(NULL, 0, "passphrase", &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_NULL_WITH_MSG(output, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL_WITH_MSG(size_t, 0, output_size, "Line:" TOSTRING(__LINE__));
+
+        status = unwrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE, "passphrase", &output, &output_size);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = unwrap_encryption_key(TEST_KEY, ENCRYPTION_KEY_SIZE, "passphrase", &output, NULL);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+    }
+
 END_TEST_SUITE(edge_openssl_encryption_unittests)
@@ -102,6 +102,9 @@ static char *TEST_CLIENT_PK_ECC_FILE_1   = NULL;
 #define TEST_CHAIN_FILE_PATH_NAME        "chain_file.pem"
 static char *TEST_CHAIN_FILE_PATH        = NULL;
 
+#define TEST_CRL_FILE_PATH_NAME          "ca_crl.pem"
+static char *TEST_CRL_FILE_PATH          = NULL;
+
 #define TEST_X509_EXT_BASIC_CONSTRIANTS         "X509v3 Basic Constraints"
 #define TEST_X509_EXT_KEY_USAGE                 "X509v3 Key Usage"
 #define TEST_X509_EXT_KEY_EXT_USAGE             "X509v3 Extended Key Usage"
@@ -325,6 +328,90 @@ static X509* test_helper_load_certificate_file(const char* cert_file_name)
     return x509_cert;
 }
 
+static void test_helper_write_signed_crl
+(
+    const char *issuer_cert_file,
+    const char *signer_private_key_file,
+    long revoked_serial_num,
+    long last_update_offset_secs,
+    long next_update_offset_secs,
+    const char *crl_file
+)
+{
+    X509* issuer_cert = test_helper_load_certificate_file(issuer_cert_file);
+    BIO* key_file = BIO_new_file(signer_private_key_file, "r");
+    ASSERT_IS_NOT_NULL_WITH_MSG(key_file, "Line:" TOSTRING(__LINE__));
+    EVP_PKEY* issuer_key = PEM_read_bio_PrivateKey(key_file, NULL, NULL, NULL);
+    BIO_free_all(key_file);
+    ASSERT_IS_NOT_NULL_WITH_MSG(issuer_key, "Line:" TOSTRING(__LINE__));
+
+    X509_CRL* crl = X509_CRL_new();
+    ASSERT_IS_NOT_NULL_WITH_MSG(crl, "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_CRL_set_version(crl, 1), "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_CRL_set_issuer_name(crl, X509_get_subject_name(issuer_cert)), "Line:" TOSTRING(__LINE__));
+    ASN1_TIME* last_update = X509_gmtime_adj(NULL, last_update_offset_secs);
+    ASN1_TIME* next_update = X509_gmtime_adj(NULL, next_update_offset_secs);
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_CRL_set_lastUpdate(crl, last_update), "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_CRL_set_nextUpdate(crl, next_update), "Line:" TOSTRING(__LINE__));
+
+    X509_REVOKED* revoked = X509_REVOKED_new();
+    ASN1_INTEGER* serial = ASN1_INTEGER_new();
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, ASN1_INTEGER_set(serial, revoked_serial_num), "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_REVOKED_set_serialNumber(revoked, serial), "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_REVOKED_set_revocationDate(revoked, last_update), "Line:" TOSTRING(__LINE__));
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, X509_CRL_add0_revoked(crl, revoked), "Line:" TOSTRING(__LINE__));
+    X509_CRL_sort(crl);
+    ASSERT_IS_TRUE_WITH_MSG((X509_CRL_sign(crl, issuer_key, EVP_sha256()) > 0), "Line:" TOSTRING(__LINE__));
+
+    BIO* output = BIO_new_file(crl_file, "w");
+    ASSERT_IS_NOT_NULL_WITH_MSG(output, "Line:" TOSTRING(__LINE__));
+    int status = PEM_write_bio_X509_CRL(output, crl);
+    BIO_free_all(output);
+    ASSERT_ARE_EQUAL_WITH_MSG(int, 1, status, "Line:" TOSTRING(__LINE__));
+
+    ASN1_INTEGER_free(serial);
+    ASN1_TIME_free(next_update);
+    ASN1_TIME_free(last_update);
+    X509_CRL_free(crl);
+    EVP_PKEY_free(issuer_key);
+    X509_free(issuer_cert);
+}
+
+static void test_helper_write_crl
+(
+    const char *issuer_cert_file,
+    const char *issuer_private_key_file,
+    long revoked_serial_num,
+    const char *crl_file
+)
+{
+    test_helper_write_signed_crl(issuer_cert_file,
+                                 issuer_private_key_file,
+                                 revoked_serial_num,
+                                 0,
+                                 TEST_VALIDITY,
+                                 crl_file);
+}
+
+static void test_helper_generate_ca_chain(CERT_PROPS_HANDLE ca_root_handle, CERT_PROPS_HANDLE int_ca_root_handle)
+{
+    PKI_KEY_PROPS key_props = { HSM_PKI_KEY_RSA, NULL };
+    test_helper_generate_self_signed(ca_root_handle,
+                                     TEST_SERIAL_NUM + 1,
+                                     2,
+                                     TEST_CA_PK_RSA_FILE_1,
+                                     TEST_CA_CERT_RSA_FILE_1,
+                                     &key_props);
+
+    test_helper_generate_pki_certificate(int_ca_root_handle,
+                                         TEST_SERIAL_NUM + 2,
+                                         1,
+                                         TEST_CA_PK_RSA_FILE_2,
+                                         TEST_CA_CERT_RSA_FILE_2,
+                                         TEST_CA_PK_RSA_FILE_1,
+                                         TEST_CA_CERT_RSA_FILE_1);
+}
+
 // parts of the implementation taken from X509V3_extensions_print
 // https://github.com/openssl/openssl/blob/32f803d88ec3df7f95dfbf840c271f7438ce3357/crypto/x509v3/v3_prn.c#L138
 static void test_helper_validate_extension
@@ -640,6 +727,7 @@ BEGIN_TEST_SUITE(edge_openssl_int_tests)
         TEST_CLIENT_PK_ECC_FILE_1   = prepare_file_path(TEST_TEMP_DIR, TEST_CLIENT_PK_ECC_FILE_1_NAME);
 
         TEST_CHAIN_FILE_PATH = prepare_file_path(TEST_TEMP_DIR, TEST_CHAIN_FILE_PATH_NAME);
+        TEST_CRL_FILE_PATH = prepare_file_path(TEST_TEMP_DIR, TEST_CRL_FILE_PATH_NAME);
     }
 
     TEST_SUITE_CLEANUP(TestClassCleanup)
@@ -669,6 +757,7 @@ BEGIN_TEST_SUITE(edge_openssl_int_tests)
         free(TEST_CLIENT_PK_ECC_FILE_1); TEST_CLIENT_PK_ECC_FILE_1 = NULL;
 
         free(TEST_CHAIN_FILE_PATH); TEST_CHAIN_FILE_PATH = NULL;
+        free(TEST_CRL_FILE_PATH); TEST_CRL_FILE_PATH = NULL;
 
         test_helper_teardown_temp_dir(&TEST_TEMP_DIR, &TEST_TEMP_DIR_GUID);
         test_helper_teardown_temp_dir(&TEST_IOTEDGE_HOMEDIR, &TEST_IOTEDGE_HOMEDIR_GUID);
@@ -924,4 +1013,330 @@ BEGIN_TEST_SUITE(edge_openssl_int_tests)
         //cleanup
     }
 
+    TEST_FUNCTION(test_verify_ca_certificate_and_key_success)
+    {
+        // arrange
+        CERT_PROPS_HANDLE cert_props_handle;
+        cert_props_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                                 TEST_CA_ALIAS_1,
+                                                                 TEST_CA_ALIAS_1,
+                                                                 CERTIFICATE_TYPE_CA,
+                                                                 TEST_VALIDITY);
+        PKI_KEY_PROPS key_props = { HSM_PKI_KEY_RSA, NULL };
+        test_helper_generate_self_signed(cert_props_handle,
+                                         TEST_SERIAL_NUM,
+                                         2,
+                                         TEST_CA_PK_RSA_FILE_1,
+                                         TEST_CA_CERT_RSA_FILE_1,
+                                         &key_props);
+        bool verify_status = false;
+
+        // act
+        int result = verify_ca_certificate_and_key(TEST_CA_CERT_RSA_FILE_1,
+                                                   TEST_CA_PK_RSA_FILE_1,
+                                                   &verify_status);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_TRUE_WITH_MSG(verify_status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(cert_props_handle);
+    }
+
+    TEST_FUNCTION(test_verify_ca_certificate_and_key_mismatched_key_fails)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_1_props_handle;
+        CERT_PROPS_HANDLE ca_2_props_handle;
+        ca_1_props_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                                 TEST_CA_ALIAS_1,
+                                                                 TEST_CA_ALIAS_1,
+                                                                 CERTIFICATE_TYPE_CA,
+                                                                 TEST_VALIDITY);
+        ca_2_props_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                 TEST_CA_ALIAS_2,
+                                                                 TEST_CA_ALIAS_2,
+                                                                 CERTIFICATE_TYPE_CA,
+                                                                 TEST_VALIDITY);
+        PKI_KEY_PROPS key_props = { HSM_PKI_KEY_RSA, NULL };
+        test_helper_generate_self_signed(ca_1_props_handle,
+                                         TEST_SERIAL_NUM,
+                                         2,
+                                         TEST_CA_PK_RSA_FILE_1,
+                                         TEST_CA_CERT_RSA_FILE_1,
+                                         &key_props);
+        test_helper_generate_self_signed(ca_2_props_handle,
+                                         TEST_SERIAL_NUM + 1,
+                                         2,
+                                         TEST_CA_PK_RSA_FILE_2,
+                                         TEST_CA_CERT_RSA_FILE_2,
+                                         &key_props);
+        bool verify_status = true;
+
+        // act
+        int result = verify_ca_certificate_and_key(TEST_CA_CERT_RSA_FILE_1,
+                                                   TEST_CA_PK_RSA_FILE_2,
+                                                   &verify_status);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(verify_status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        cert_properties_destroy(ca_1_props_handle);
+        cert_properties_destroy(ca_2_props_handle);
+    }
+
+    TEST_FUNCTION(test_verify_ca_certificate_and_key_non_ca_fails)
+    {
+        // arrange
+        CERT_PROPS_HANDLE cert_props_handle;
+        cert_props_handle = test_helper_create_certificate_props(TEST_SERVER_CN_1,
+                                                                 TEST_SERVER_ALIAS_1,
+                                                                 TEST_SERVER_ALIAS_1,
+                                                                 CERTIFICATE_TYPE_SERVER,
+                                                                 TEST_VALIDITY);
+        PKI_KEY_PROPS key_props = { HSM_PKI_KEY_RSA, NULL };
+        test_helper_generate_self_signed(cert_props_handle,
+                                         TEST_SERIAL_NUM,
+                                         0,
+                                         TEST_SERVER_PK_RSA_FILE_1,
+                                         TEST_SERVER_CERT_RSA_FILE_1,
+                                         &key_props);
+        bool verify_status = true;
+
+        // act
+        int result = verify_ca_certificate_and_key(TEST_SERVER_CERT_RSA_FILE_1,
+                                                   TEST_SERVER_PK_RSA_FILE_1,
+                                                   &verify_status);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(verify_status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_SERVER_PK_RSA_FILE_1);
+        delete_file(TEST_SERVER_CERT_RSA_FILE_1);
+        cert_properties_destroy(cert_props_handle);
+    }
+
+    TEST_FUNCTION(test_check_certificate_revocation_valid_issuer_succeeds)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_root_handle;
+        CERT_PROPS_HANDLE int_ca_root_handle;
+        ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              CERTIFICATE_TYPE_CA,
+                                                              TEST_VALIDITY);
+        int_ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                  TEST_CA_ALIAS_2,
+                                                                  TEST_CA_ALIAS_1,
+                                                                  CERTIFICATE_TYPE_CA,
+                                                                  TEST_VALIDITY);
+        test_helper_generate_ca_chain(ca_root_handle, int_ca_root_handle);
+        test_helper_write_crl(TEST_CA_CERT_RSA_FILE_1,
+                              TEST_CA_PK_RSA_FILE_1,
+                              TEST_SERIAL_NUM + 10,
+                              TEST_CRL_FILE_PATH);
+        bool is_revoked = true;
+
+        // act
+        int result = check_certificate_revocation(TEST_CA_CERT_RSA_FILE_2,
+                                                  TEST_CRL_FILE_PATH,
+                                                  &is_revoked);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(is_revoked, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CRL_FILE_PATH);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(int_ca_root_handle);
+        cert_properties_destroy(ca_root_handle);
+    }
+
+    TEST_FUNCTION(test_check_certificate_revocation_revoked_issuer_detected)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_root_handle;
+        CERT_PROPS_HANDLE int_ca_root_handle;
+        ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              CERTIFICATE_TYPE_CA,
+                                                              TEST_VALIDITY);
+        int_ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                  TEST_CA_ALIAS_2,
+                                                                  TEST_CA_ALIAS_1,
+                                                                  CERTIFICATE_TYPE_CA,
+                                                                  TEST_VALIDITY);
+        test_helper_generate_ca_chain(ca_root_handle, int_ca_root_handle);
+        test_helper_write_crl(TEST_CA_CERT_RSA_FILE_1,
+                              TEST_CA_PK_RSA_FILE_1,
+                              TEST_SERIAL_NUM + 2,
+                              TEST_CRL_FILE_PATH);
+        bool is_revoked = false;
+
+        // act
+        int result = check_certificate_revocation(TEST_CA_CERT_RSA_FILE_2,
+                                                  TEST_CRL_FILE_PATH,
+                                                  &is_revoked);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_TRUE_WITH_MSG(is_revoked, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CRL_FILE_PATH);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(int_ca_root_handle);
+        cert_properties_destroy(ca_root_handle);
+    }
+
+    TEST_FUNCTION(test_check_certificate_revocation_crl_from_other_issuer_ignored)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_root_handle;
+        CERT_PROPS_HANDLE int_ca_root_handle;
+        ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              CERTIFICATE_TYPE_CA,
+                                                              TEST_VALIDITY);
+        int_ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                  TEST_CA_ALIAS_2,
+                                                                  TEST_CA_ALIAS_1,
+                                                                  CERTIFICATE_TYPE_CA,
+                                                                  TEST_VALIDITY);
+        test_helper_generate_ca_chain(ca_root_handle, int_ca_root_handle);
+        // the intermediate CA did not issue itself so its CRL cannot revoke it
+        test_helper_write_crl(TEST_CA_CERT_RSA_FILE_2,
+                              TEST_CA_PK_RSA_FILE_2,
+                              TEST_SERIAL_NUM + 2,
+                              TEST_CRL_FILE_PATH);
+        bool is_revoked = true;
+
+        // act
+        int result = check_certificate_revocation(TEST_CA_CERT_RSA_FILE_2,
+                                                  TEST_CRL_FILE_PATH,
+                                                  &is_revoked);
+
+        // assert
+        ASSERT_ARE_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(is_revoked, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CRL_FILE_PATH);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(int_ca_root_handle);
+        cert_properties_destroy(ca_root_handle);
+    }
+
+    TEST_FUNCTION(test_check_certificate_revocation_crl_with_invalid_signature_fails)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_root_handle;
+        CERT_PROPS_HANDLE int_ca_root_handle;
+        ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              CERTIFICATE_TYPE_CA,
+                                                              TEST_VALIDITY);
+        int_ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                  TEST_CA_ALIAS_2,
+                                                                  TEST_CA_ALIAS_1,
+                                                                  CERTIFICATE_TYPE_CA,
+                                                                  TEST_VALIDITY);
+        test_helper_generate_ca_chain(ca_root_handle, int_ca_root_handle);
+        // the CRL names the root CA as its issuer but is not signed by it
+        test_helper_write_signed_crl(TEST_CA_CERT_RSA_FILE_1,
+                                     TEST_CA_PK_RSA_FILE_2,
+                                     TEST_SERIAL_NUM + 10,
+                                     0,
+                                     TEST_VALIDITY,
+                                     TEST_CRL_FILE_PATH);
+        bool is_revoked = false;
+
+        // act
+        int result = check_certificate_revocation(TEST_CA_CERT_RSA_FILE_2,
+                                                  TEST_CRL_FILE_PATH,
+                                                  &is_revoked);
+
+        // assert
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(is_revoked, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CRL_FILE_PATH);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(int_ca_root_handle);
+        cert_properties_destroy(ca_root_handle);
+    }
+
+    TEST_FUNCTION(test_check_certificate_revocation_expired_crl_fails)
+    {
+        // arrange
+        CERT_PROPS_HANDLE ca_root_handle;
+        CERT_PROPS_HANDLE int_ca_root_handle;
+        ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              TEST_CA_ALIAS_1,
+                                                              CERTIFICATE_TYPE_CA,
+                                                              TEST_VALIDITY);
+        int_ca_root_handle = test_helper_create_certificate_props(TEST_CA_CN_2,
+                                                                  TEST_CA_ALIAS_2,
+                                                                  TEST_CA_ALIAS_1,
+                                                                  CERTIFICATE_TYPE_CA,
+                                                                  TEST_VALIDITY);
+        test_helper_generate_ca_chain(ca_root_handle, int_ca_root_handle);
+        // the CRL was superseded before now so it may be missing revocations
+        test_helper_write_signed_crl(TEST_CA_CERT_RSA_FILE_1,
+                                     TEST_CA_PK_RSA_FILE_1,
+                                     TEST_SERIAL_NUM + 10,
+                                     -2 * TEST_VALIDITY,
+                                     -TEST_VALIDITY,
+                                     TEST_CRL_FILE_PATH);
+        bool is_revoked = false;
+
+        // act
+        int result = check_certificate_revocation(TEST_CA_CERT_RSA_FILE_2,
+                                                  TEST_CRL_FILE_PATH,
+                                                  &is_revoked);
+
+        // assert
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, result, "Line:" TOSTRING(__LINE__));
+        ASSERT_IS_FALSE_WITH_MSG(is_revoked, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        delete_file(TEST_CRL_FILE_PATH);
+        delete_file(TEST_CA_PK_RSA_FILE_2);
+        delete_file(TEST_CA_CERT_RSA_FILE_2);
+        delete_file(TEST_CA_PK_RSA_FILE_1);
+        delete_file(TEST_CA_CERT_RSA_FILE_1);
+        cert_properties_destroy(int_ca_root_handle);
+        cert_properties_destroy(ca_root_handle);
+    }
+
 END_TEST_SUITE(edge_openssl_int_tests)
@@ -2736,6 +2736,35 @@ BEGIN_TEST_SUITE(edge_openssl_pki_unittests)
         // cleanup
     }
 
+    /**
+     * Test function for API
+     *   generate_pki_cert_and_key_with_issuer_props
+    */
+    TEST_FUNCTION(generate_pki_cert_and_key_with_issuer_props_invalid_params)
+    {
+        // arrange
+        int status;
+        PKI_KEY_PROPS INVALID_KEY_PROPS = { .key_type = -1, .ec_curve_name = NULL };
+
+        // act, assert
+        status = generate_pki_cert_and_key_with_issuer_props(NULL, TEST_SERIAL_NUMBER, TEST_PATH_LEN_NON_CA, TEST_KEY_FILE, TEST_CERT_FILE, TEST_ISSUER_KEY_FILE, TEST_ISSUER_CERT_FILE, &TEST_VALID_KEY_PROPS_ECC);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = generate_pki_cert_and_key_with_issuer_props(TEST_CERT_PROPS_HANDLE, TEST_SERIAL_NUMBER, TEST_PATH_LEN_NON_CA, NULL, TEST_CERT_FILE, TEST_ISSUER_KEY_FILE, TEST_ISSUER_CERT_FILE, &TEST_VALID_KEY_PROPS_ECC);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = generate_pki_cert_and_key_with_issuer_props(TEST_CERT_PROPS_HANDLE, TEST_SERIAL_NUMBER, TEST_PATH_LEN_NON_CA, TEST_KEY_FILE, NULL, TEST_ISSUER_KEY_FILE, TEST_ISSUER_CERT_FILE, &TEST_VALID_KEY_PROPS_ECC);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = generate_pki_cert_and_key_with_issuer_props(TEST_CERT_PROPS_HANDLE, TEST_SERIAL_NUMBER, TEST_PATH_LEN_NON_CA, TEST_KEY_FILE, TEST_CERT_FILE, TEST_ISSUER_KEY_FILE, TEST_ISSUER_CERT_FILE, NULL);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = generate_pki_cert_and_key_with_issuer_props(TEST_CERT_PROPS_HANDLE, TEST_SERIAL_NUMBER, TEST_PATH_LEN_NON_CA, TEST_KEY_FILE, TEST_CERT_FILE, TEST_ISSUER_KEY_FILE, TEST_ISSUER_CERT_FILE, &INVALID_KEY_PROPS);
+        ASSERT_ARE_NOT_EQUAL_WITH_MSG(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+    }
+
     /**
      * Test function for API
      *   generate_pki_cert_and_key_with_props
@@ -43,6 +43,7 @@ static void my_gballoc_free(void* ptr)
 #include "azure_utpm_c/Marshal_fp.h"
 
 #include "edge_sas_perform_sign_with_key.h"
+#include "hsm_utils.h"
 
 #include "azure_utpm_c/TpmTypes.h"
 #undef ENABLE_MOCKS
@@ -50,11 +51,16 @@ static void my_gballoc_free(void* ptr)
 #include "hsm_client_data.h"
 #include "hsm_log.h"
 #include "hsm_client_tpm_device.h"
+#include "hsm_constants.h"
 
 static const char* TEST_STRING_VALUE = "Test_String_Value";
 static const unsigned char TEST_IMPORT_KEY[] = { 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x10 };
 
 static const char* TEST_RSA_KEY = "1234567890";
+static const char* TEST_TPM_DEVICE_PATH = "/dev/tpm-test";
+
+// value of the TPM device path env variable, NULL if it is not set
+static const char* g_tpm_device_path_env = NULL;
 static unsigned char TEST_BUFFER[128];
 static unsigned char IDENTITY_BUFFER[128];
 
@@ -102,6 +108,18 @@ static int my_mallocAndStrcpy_s(char** destination, const char* source)
     return 0;
 }
 
+static int my_hsm_get_env(const char* key, char** output)
+{
+    (void)key;
+    *output = NULL;
+    if (g_tpm_device_path_env != NULL)
+    {
+        *output = (char*)my_gballoc_malloc(strlen(g_tpm_device_path_env) + 1);
+        strcpy(*output, g_tpm_device_path_env);
+    }
+    return 0;
+}
+
 static int my_perform_sign_with_key( const unsigned char* key, size_t key_len, 
                                      const unsigned char* data_to_be_signed, size_t data_to_be_signed_size,
                                      unsigned char** digest, size_t* digest_size)
@@ -233,6 +251,11 @@ BEGIN_TEST_SUITE(hsm_client_tpm_ut)
         REGISTER_GLOBAL_MOCK_HOOK(perform_sign_with_key, my_perform_sign_with_key);
         REGISTER_GLOBAL_MOCK_FAIL_RETURN(perform_sign_with_key, 1);
 
+        REGISTER_GLOBAL_MOCK_HOOK(hsm_get_env, my_hsm_get_env);
+        REGISTER_GLOBAL_MOCK_FAIL_RETURN(hsm_get_env, 1);
+        REGISTER_GLOBAL_MOCK_RETURN(is_file_valid, true);
+        REGISTER_GLOBAL_MOCK_FAIL_RETURN(is_file_valid, false);
+
         for (size_t index = 0; index < 10; index++)
         {
             TEST_BUFFER[index] = (unsigned char)(index+1);
@@ -276,10 +299,14 @@ BEGIN_TEST_SUITE(hsm_client_tpm_ut)
         return result;
     }
 
-    static void setup_hsm_client_tpm_create_mock()
+    static void setup_hsm_client_tpm_create_mock_with_device_path(const char* tpm_device_path)
     {
         OBJECT_ATTR tmp = FixedTPM;
         STRICT_EXPECTED_CALL(gballoc_malloc(IGNORED_NUM_ARG));
+        if (tpm_device_path != NULL)
+        {
+            STRICT_EXPECTED_CALL(is_file_valid(tpm_device_path));
+        }
         STRICT_EXPECTED_CALL(TSS_CreatePwAuthSession(IGNORED_PTR_ARG, IGNORED_PTR_ARG));
         STRICT_EXPECTED_CALL(Initialize_TPM_Codec(IGNORED_PTR_ARG));
         STRICT_EXPECTED_CALL(ToTpmaObject(tmp))
@@ -290,6 +317,11 @@ BEGIN_TEST_SUITE(hsm_client_tpm_ut)
         STRICT_EXPECTED_CALL(TSS_CreatePersistentKey(IGNORED_PTR_ARG, IGNORED_NUM_ARG, IGNORED_PTR_ARG, IGNORED_NUM_ARG, IGNORED_PTR_ARG, IGNORED_PTR_ARG));
     }
 
+    static void setup_hsm_client_tpm_create_mock()
+    {
+        setup_hsm_client_tpm_create_mock_with_device_path(NULL);
+    }
+
     static void setup_hsm_client_tpm_activate_key_mock()
     {
         OBJECT_ATTR tmp = FixedTPM;
@@ -409,6 +441,72 @@ BEGIN_TEST_SUITE(hsm_client_tpm_ut)
         umock_c_negative_tests_deinit();
     }
 
+    TEST_FUNCTION(hsm_client_tpm_create_with_device_path_succeed)
+    {
+        //arrange
+        g_tpm_device_path_env = TEST_TPM_DEVICE_PATH;
+        int status = hsm_client_tpm_device_init();
+        ASSERT_ARE_EQUAL(int, 0, status);
+        umock_c_reset_all_calls();
+
+        setup_hsm_client_tpm_create_mock_with_device_path(TEST_TPM_DEVICE_PATH);
+
+        //act
+        const HSM_CLIENT_TPM_INTERFACE* tpm_if = hsm_client_tpm_device_interface();
+        HSM_CLIENT_HANDLE sec_handle = tpm_if->hsm_client_tpm_create();
+
+        //assert
+        ASSERT_IS_NOT_NULL(sec_handle);
+        ASSERT_ARE_EQUAL(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls());
+
+        //cleanup
+        tpm_if->hsm_client_tpm_destroy(sec_handle);
+        hsm_client_tpm_device_deinit();
+        g_tpm_device_path_env = NULL;
+    }
+
+    TEST_FUNCTION(hsm_client_tpm_create_with_invalid_device_path_fail)
+    {
+        //arrange
+        g_tpm_device_path_env = TEST_TPM_DEVICE_PATH;
+        int status = hsm_client_tpm_device_init();
+        ASSERT_ARE_EQUAL(int, 0, status);
+        umock_c_reset_all_calls();
+
+        STRICT_EXPECTED_CALL(gballoc_malloc(IGNORED_NUM_ARG));
+        STRICT_EXPECTED_CALL(is_file_valid(TEST_TPM_DEVICE_PATH)).SetReturn(false);
+        STRICT_EXPECTED_CALL(gballoc_free(IGNORED_PTR_ARG));
+
+        //act
+        const HSM_CLIENT_TPM_INTERFACE* tpm_if = hsm_client_tpm_device_interface();
+        HSM_CLIENT_HANDLE sec_handle = tpm_if->hsm_client_tpm_create();
+
+        //assert
+        ASSERT_IS_NULL(sec_handle);
+        ASSERT_ARE_EQUAL(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls());
+
+        //cleanup
+        hsm_client_tpm_device_deinit();
+        g_tpm_device_path_env = NULL;
+    }
+
+    TEST_FUNCTION(hsm_client_tpm_device_init_env_fail)
+    {
+        //arrange
+        umock_c_reset_all_calls();
+        STRICT_EXPECTED_CALL(hsm_get_env(ENV_TPM_DEVICE_PATH, IGNORED_PTR_ARG)).SetReturn(1);
+
+        //act
+        int status = hsm_client_tpm_device_init();
+
+        //assert
+        ASSERT_ARE_NOT_EQUAL(int, 0, status);
+        ASSERT_ARE_EQUAL(char_ptr, umock_c_get_expected_calls(), umock_c_get_actual_calls());
+
+        //cleanup
+        hsm_client_tpm_device_deinit();
+    }
+
     TEST_FUNCTION(hsm_client_tpm_destroy_succeed)
     {
         //arrange
@@ -102,6 +102,8 @@ pub type HSM_CLIENT_DESTROY = Option<unsafe extern "C" fn(handle: HSM_CLIENT_HAN
 pub type HSM_CLIENT_FREE_BUFFER = Option<unsafe extern "C" fn(buffer: *mut c_void)>;
 // TPM
 
+pub const HSM_TPM_RETRY: c_int = -2;
+
 pub type HSM_CLIENT_ACTIVATE_IDENTITY_KEY = Option<
     unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, key: *const c_uchar, key_len: usize) -> c_int,
 >;
@@ -420,6 +422,89 @@ pub type HSM_CLIENT_DESTROY_CERTIFICATE =
 pub type HSM_CLIENT_GET_TRUST_BUNDLE =
     Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE) -> CERT_INFO_HANDLE>;
 
+/// Exports the master encryption key wrapped with a key derived from the
+/// passphrase.
+///
+/// handle[in]          -- A valid HSM client handle
+/// passphrase[in]      -- Passphrase used to protect the exported key
+/// wrapped_key[out]    -- The wrapped key
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY = Option<
+    unsafe extern "C" fn(
+        handle: HSM_CLIENT_HANDLE,
+        passphrase: *const c_char,
+        wrapped_key: *mut SIZED_BUFFER,
+    ) -> c_int,
+>;
+
+/// Replaces the master encryption key with an exported one.
+///
+/// handle[in]          -- A valid HSM client handle
+/// passphrase[in]      -- Passphrase the key was exported with
+/// wrapped_key[in]     -- The wrapped key
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY = Option<
+    unsafe extern "C" fn(
+        handle: HSM_CLIENT_HANDLE,
+        passphrase: *const c_char,
+        wrapped_key: *const SIZED_BUFFER,
+    ) -> c_int,
+>;
+
+/// Provisions a master encryption key in the given key slot, so that the key
+/// used for encryption can be rotated while data encrypted with the previous
+/// key can still be decrypted.
+///
+/// handle[in]          -- A valid HSM client handle
+/// slot[in]            -- The key slot
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, slot: usize) -> c_int>;
+
+/// Removes the master encryption key in the given key slot.
+///
+/// handle[in]          -- A valid HSM client handle
+/// slot[in]            -- The key slot
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, slot: usize) -> c_int>;
+
+/// Selects the key slot whose master encryption key encrypts data. Decryption
+/// tries the active slot first and then the other slots. The active slot is
+/// persisted in the HSM store.
+///
+/// handle[in]          -- A valid HSM client handle
+/// slot[in]            -- The key slot, which must hold a key
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, slot: usize) -> c_int>;
+
+/// Returns the key slot whose master encryption key encrypts data.
+///
+/// handle[in]          -- A valid HSM client handle
+/// slot[out]           -- The active key slot
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, slot: *mut usize) -> c_int>;
+
 extern "C" {
     /// Creates the certificate information object and initializes the values
     ///
@@ -778,6 +863,16 @@ pub struct HSM_CLIENT_CRYPTO_INTERFACE_TAG {
     pub hsm_client_decrypt_data: HSM_CLIENT_DECRYPT_DATA,
     pub hsm_client_get_trust_bundle: HSM_CLIENT_GET_TRUST_BUNDLE,
     pub hsm_client_free_buffer: HSM_CLIENT_FREE_BUFFER,
+    pub hsm_client_export_master_encryption_key: HSM_CLIENT_EXPORT_MASTER_ENCRYPTION_KEY,
+    pub hsm_client_import_master_encryption_key: HSM_CLIENT_IMPORT_MASTER_ENCRYPTION_KEY,
+    pub hsm_client_create_master_encryption_key_in_slot:
+        HSM_CLIENT_CREATE_MASTER_ENCRYPTION_KEY_IN_SLOT,
+    pub hsm_client_destroy_master_encryption_key_in_slot:
+        HSM_CLIENT_DESTROY_MASTER_ENCRYPTION_KEY_IN_SLOT,
+    pub hsm_client_set_active_master_encryption_key_slot:
+        HSM_CLIENT_SET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT,
+    pub hsm_client_get_active_master_encryption_key_slot:
+        HSM_CLIENT_GET_ACTIVE_MASTER_ENCRYPTION_KEY_SLOT,
 }
 pub type HSM_CLIENT_CRYPTO_INTERFACE = HSM_CLIENT_CRYPTO_INTERFACE_TAG;
 
@@ -795,6 +890,12 @@ impl Default for HSM_CLIENT_CRYPTO_INTERFACE_TAG {
             hsm_client_decrypt_data: None,
             hsm_client_get_trust_bundle: None,
             hsm_client_free_buffer: None,
+            hsm_client_export_master_encryption_key: None,
+            hsm_client_import_master_encryption_key: None,
+            hsm_client_create_master_encryption_key_in_slot: None,
+            hsm_client_destroy_master_encryption_key_in_slot: None,
+            hsm_client_set_active_master_encryption_key_slot: None,
+            hsm_client_get_active_master_encryption_key_slot: None,
         }
     }
 }
@@ -803,7 +904,7 @@ impl Default for HSM_CLIENT_CRYPTO_INTERFACE_TAG {
 fn bindgen_test_layout_HSM_CLIENT_CRYPTO_INTERFACE_TAG() {
     assert_eq!(
         ::std::mem::size_of::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>(),
-        11_usize * ::std::mem::size_of::<usize>(),
+        17_usize * ::std::mem::size_of::<usize>(),
         concat!("Size of: ", stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG))
     );
     assert_eq!(
@@ -954,6 +1055,84 @@ fn bindgen_test_layout_HSM_CLIENT_CRYPTO_INTERFACE_TAG() {
             stringify!(hsm_client_free_buffer)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_export_master_encryption_key as *const _ as usize
+        },
+        11_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_export_master_encryption_key)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_import_master_encryption_key as *const _ as usize
+        },
+        12_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_import_master_encryption_key)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_create_master_encryption_key_in_slot as *const _ as usize
+        },
+        13_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_create_master_encryption_key_in_slot)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_destroy_master_encryption_key_in_slot as *const _ as usize
+        },
+        14_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_destroy_master_encryption_key_in_slot)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_set_active_master_encryption_key_slot as *const _ as usize
+        },
+        15_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_set_active_master_encryption_key_slot)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_get_active_master_encryption_key_slot as *const _ as usize
+        },
+        16_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_get_active_master_encryption_key_slot)
+        )
+    );
 }
 
 extern "C" {
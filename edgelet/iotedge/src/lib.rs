@@ -9,17 +9,14 @@
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 #![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
 
-extern crate bytes;
 extern crate chrono;
 extern crate chrono_humanize;
 #[macro_use]
 extern crate clap;
 extern crate edgelet_core;
 extern crate failure;
-#[macro_use]
 extern crate futures;
 extern crate tabwriter;
-extern crate tokio;
 
 use futures::Future;
 
@@ -3,10 +3,11 @@
 use clap::{App, Arg, ArgMatches};
 use edgelet_core;
 use edgelet_docker::DockerConfig;
+use log::Level;
 
 use error::Error;
 use logging;
-use settings::Settings;
+use settings::{LogLevel, Settings};
 
 pub fn create_base_app<'a, 'b>() -> App<'a, 'b> {
     App::new(crate_name!())
@@ -46,51 +47,57 @@ pub fn log_banner() {
     info!("Version - {}", edgelet_core::version());
 }
 
-pub fn init_common<'a>() -> Result<(Settings<DockerConfig>, ArgMatches<'a>), Error> {
+// The settings are read before the logger is initialized, since they
+// configure its level. What is logged about them is only logged once the
+// logger is initialized with `init_logger`, which uses the default level if
+// the settings could not be read.
+pub fn init_common<'a>() -> (Result<Settings<DockerConfig>, Error>, ArgMatches<'a>) {
     let matches = create_app().get_matches();
-    let settings = {
-        let config_file = matches
-            .value_of("config-file")
-            .and_then(|name| {
-                info!("Using config file: {}", name);
-                Some(name)
-            })
-            .or_else(|| {
-                info!("Using default configuration");
-                None
-            });
+    let settings = Settings::<DockerConfig>::new(matches.value_of("config-file"));
+    (settings, matches)
+}
 
-        Settings::<DockerConfig>::new(config_file)?
-    };
+fn init_logger<F>(init: F, settings: &Result<Settings<DockerConfig>, Error>, matches: &ArgMatches)
+where
+    F: FnOnce(Option<Level>),
+{
+    let level = settings
+        .as_ref()
+        .ok()
+        .and_then(|settings| settings.log_level())
+        .map(LogLevel::level);
+    init(level);
 
-    Ok((settings, matches))
+    log_banner();
+    match matches.value_of("config-file") {
+        Some(name) => info!("Using config file: {}", name),
+        None => info!("Using default configuration"),
+    }
 }
 
 #[cfg(target_os = "windows")]
 pub fn init() -> Result<Settings<DockerConfig>, Error> {
-    let (settings, matches) = init_common()?;
+    let (settings, matches) = init_common();
 
     if matches.is_present("use-event-logger") {
-        logging::init_win_log();
+        init_logger(logging::init_win_log, &settings, &matches);
     } else {
-        logging::init();
+        init_logger(logging::init, &settings, &matches);
     }
 
-    log_banner();
-
-    Ok(settings)
+    settings
 }
 
 #[cfg(not(target_os = "windows"))]
 pub fn init() -> Result<Settings<DockerConfig>, Error> {
-    logging::init();
-    log_banner();
-    init_common().map(|(settings, _)| settings)
+    let (settings, matches) = init_common();
+    init_logger(logging::init, &settings, &matches);
+    settings
 }
 
 #[cfg(target_os = "windows")]
 pub fn init_win_svc() -> Result<Settings<DockerConfig>, Error> {
-    logging::init_win_log();
-    log_banner();
-    init_common().map(|(settings, _)| settings)
+    let (settings, matches) = init_common();
+    init_logger(logging::init_win_log, &settings, &matches);
+    settings
 }
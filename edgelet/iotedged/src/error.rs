@@ -5,6 +5,7 @@ use std::fmt::Display;
 #[cfg(windows)]
 use std::sync::Mutex;
 
+use edgelet_http::SUPPORTED_API_VERSIONS;
 use failure::{Backtrace, Context, Fail};
 #[cfg(windows)]
 use windows_service::Error as WindowsServiceError;
@@ -73,24 +74,42 @@ impl From<Context<ErrorKind>> for Error {
 pub enum InitializeErrorReason {
     CreateMasterEncryptionKey,
     CreateSettingsDirectory,
+    DestroyMasterEncryptionKey,
     DestroyWorkloadCa,
     DeviceClient,
     DpsProvisioningClient,
     EdgeRuntime,
     Hsm,
     HttpClient,
+    InvalidAgentApiVersion,
+    InvalidCertificateValidity,
+    InvalidConnectUri,
+    InvalidExternalManagementUri,
+    InvalidListenBacklog,
+    InvalidManagementAuth,
+    InvalidManagementCors,
+    InvalidMaxConnections,
     InvalidProxyUri,
+    InvalidRateLimit,
+    InvalidShutdownSignal,
+    InvalidSocketMode,
     InvalidSocketUri,
+    InvalidTcpKeepalive,
+    InvalidThreadCount,
     LoadSettings,
+    ManagementAuthToken,
     ManagementService,
     ManualProvisioningClient,
     ModuleRuntime,
     NotConfigured,
     PrepareWorkloadCa,
+    RefreshIdentityKeys,
     #[cfg(windows)]
     RegisterWindowsService,
+    RegistryCaBundle,
     RemoveExistingModules,
     SaveSettings,
+    SeccompProfile,
     #[cfg(windows)]
     StartWindowsService,
     Tokio,
@@ -108,6 +127,10 @@ impl fmt::Display for InitializeErrorReason {
                 write!(f, "Could not create settings directory")
             }
 
+            InitializeErrorReason::DestroyMasterEncryptionKey => {
+                write!(f, "Could not destroy master encryption key")
+            }
+
             InitializeErrorReason::DestroyWorkloadCa => {
                 write!(f, "Could not destroy workload CA certificate")
             }
@@ -124,12 +147,79 @@ impl fmt::Display for InitializeErrorReason {
 
             InitializeErrorReason::HttpClient => write!(f, "Could not initialize HTTP client"),
 
+            InitializeErrorReason::InvalidAgentApiVersion => write!(
+                f,
+                "Invalid edge agent API version, it must be one of {}",
+                SUPPORTED_API_VERSIONS.join(", ")
+            ),
+
+            InitializeErrorReason::InvalidCertificateValidity => write!(
+                f,
+                "Invalid certificate validity, it must be between 1 and 2147483647 seconds"
+            ),
+
+            InitializeErrorReason::InvalidConnectUri => write!(
+                f,
+                "Invalid connect URI, it must reach the socket of the corresponding listen URI"
+            ),
+
+            InitializeErrorReason::InvalidExternalManagementUri => write!(
+                f,
+                "Invalid external management URI, it must differ from the management listen URI"
+            ),
+
+            InitializeErrorReason::InvalidListenBacklog => {
+                write!(f, "Invalid listen backlog, it must be at least 1")
+            }
+
+            InitializeErrorReason::InvalidManagementAuth => write!(
+                f,
+                "Invalid management auth settings, exactly one of token and token_file must be set and the token must not be empty"
+            ),
+
+            InitializeErrorReason::InvalidManagementCors => write!(
+                f,
+                "Invalid management CORS settings, they need at least one origin such as https://dashboard.example.com or *, and valid HTTP methods"
+            ),
+
+            InitializeErrorReason::InvalidMaxConnections => {
+                write!(f, "Invalid max connections, it must be at least 1")
+            }
+
             InitializeErrorReason::InvalidProxyUri => write!(f, "Invalid proxy URI"),
 
+            InitializeErrorReason::InvalidRateLimit => write!(
+                f,
+                "Invalid rate limit, its requests and period_secs must be at least 1"
+            ),
+
+            InitializeErrorReason::InvalidShutdownSignal => write!(
+                f,
+                "Invalid shutdown signal, it must be a signal the daemon can handle such as SIGTERM"
+            ),
+
+            InitializeErrorReason::InvalidSocketMode => write!(
+                f,
+                "Invalid socket mode, it must be octal permissions such as 0660"
+            ),
+
             InitializeErrorReason::InvalidSocketUri => write!(f, "Invalid socket URI"),
 
+            InitializeErrorReason::InvalidTcpKeepalive => write!(
+                f,
+                "Invalid TCP keepalive, its idle_secs and interval_secs must be at least 1"
+            ),
+
+            InitializeErrorReason::InvalidThreadCount => {
+                write!(f, "Invalid thread count, it must be at least 1")
+            }
+
             InitializeErrorReason::LoadSettings => write!(f, "Could not load settings"),
 
+            InitializeErrorReason::ManagementAuthToken => {
+                write!(f, "Could not read the token of the management API")
+            }
+
             InitializeErrorReason::ManagementService => {
                 write!(f, "Could not start management service")
             }
@@ -158,17 +248,27 @@ impl fmt::Display for InitializeErrorReason {
                 write!(f, "Could not prepare workload CA certificate")
             }
 
+            InitializeErrorReason::RefreshIdentityKeys => {
+                write!(f, "Could not refresh the keys of module identities")
+            }
+
             #[cfg(windows)]
             InitializeErrorReason::RegisterWindowsService => {
                 write!(f, "Could not register Windows Service control handle")
             }
 
+            InitializeErrorReason::RegistryCaBundle => {
+                write!(f, "Could not read registry CA bundle")
+            }
+
             InitializeErrorReason::RemoveExistingModules => {
                 write!(f, "Could not remove existing modules")
             }
 
             InitializeErrorReason::SaveSettings => write!(f, "Could not save settings file"),
 
+            InitializeErrorReason::SeccompProfile => write!(f, "Could not read seccomp profile"),
+
             #[cfg(windows)]
             InitializeErrorReason::StartWindowsService => {
                 write!(f, "Could not start as Windows Service")
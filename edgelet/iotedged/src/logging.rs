@@ -4,8 +4,9 @@ use std::env;
 use std::io::Write;
 
 use edgelet_utils::log_failure;
-use env_logger;
-use log::{Level, LevelFilter};
+use env_logger::Builder;
+use log::{Level, LevelFilter, Record};
+use serde_json;
 #[cfg(target_os = "windows")]
 use win_logger::EventLogger;
 
@@ -14,10 +15,43 @@ use error::Error;
 #[cfg(target_os = "windows")]
 const IOTEDGED_SERVICE_NAME: &str = crate_name!();
 const ENV_LOG: &str = "IOTEDGE_LOG";
+const ENV_LOG_FORMAT: &str = "IOTEDGE_LOG_FORMAT";
+
+/// The format of the lines the daemon logs, selected with the
+/// `IOTEDGE_LOG_FORMAT` environment variable. `Plain` is the syslog style
+/// text the daemon has always logged, and `Json` logs one object per line
+/// for log aggregators.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Plain,
+    Json,
+}
+
+impl Format {
+    fn from_env() -> Self {
+        match env::var(ENV_LOG_FORMAT) {
+            Ok(ref format) if format.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Plain,
+        }
+    }
+}
+
+/// Logs to stdout at `level`, or at info level if it is not set. A filter in
+/// the `IOTEDGE_LOG` environment variable takes precedence.
+pub fn init(level: Option<Level>) {
+    builder(level, &env::var(ENV_LOG).unwrap_or_default()).init();
+}
+
+fn builder(level: Option<Level>, filter: &str) -> Builder {
+    let format = Format::from_env();
+    let mut builder = Builder::new();
+    builder
+        .format(move |fmt, record| {
+            if format == Format::Json {
+                let timestamp = fmt.timestamp().to_string();
+                return writeln!(fmt, "{}", json_line(&timestamp, record));
+            }
 
-pub fn init() {
-    env_logger::Builder::new()
-        .format(|fmt, record| {
             let level = match record.level() {
                 Level::Trace => "TRCE",
                 Level::Debug => "DBUG",
@@ -48,17 +82,15 @@ pub fn init() {
                 )
             }
         })
-        .filter_level(LevelFilter::Info)
-        .parse(&env::var(ENV_LOG).unwrap_or_default())
-        .init();
+        .filter_level(level.map_or(LevelFilter::Info, |level| level.to_level_filter()))
+        .parse(filter);
+    builder
 }
 
 #[cfg(target_os = "windows")]
-pub fn init_win_log() {
-    let mut min_log_level = "info".to_string();
-    if let Ok(log_level) = env::var(ENV_LOG) {
-        min_log_level = log_level;
-    }
+pub fn init_win_log(level: Option<Level>) {
+    let min_log_level = env::var(ENV_LOG)
+        .unwrap_or_else(|_| level.unwrap_or(Level::Info).to_string().to_lowercase());
 
     EventLogger::new(IOTEDGED_SERVICE_NAME.to_string(), &min_log_level)
         .expect("Could not create Windows EventLogger")
@@ -66,6 +98,16 @@ pub fn init_win_log() {
         .expect("Could not initialize Windows EventLogger");
 }
 
+fn json_line(timestamp: &str, record: &Record) -> String {
+    json!({
+        "timestamp": timestamp,
+        "level": record.level().to_string(),
+        "module": record.module_path().unwrap_or_else(|| record.target()),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
 fn syslog_level(level: Level) -> i8 {
     match level {
         Level::Error => 3,
@@ -78,3 +120,74 @@ fn syslog_level(level: Level) -> i8 {
 pub fn log_error(error: &Error) {
     log_failure(Level::Error, error);
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn level_defaults_to_info() {
+        assert_eq!(LevelFilter::Info, builder(None, "").build().filter());
+    }
+
+    #[test]
+    fn level_is_set_without_env_filter() {
+        assert_eq!(
+            LevelFilter::Debug,
+            builder(Some(Level::Debug), "").build().filter()
+        );
+    }
+
+    #[test]
+    fn env_filter_overrides_level() {
+        assert_eq!(
+            LevelFilter::Warn,
+            builder(Some(Level::Debug), "warn").build().filter()
+        );
+    }
+
+    #[test]
+    fn json_line_has_record_fields() {
+        let line = json_line(
+            "2018-10-01T12:30:00Z",
+            &Record::builder()
+                .args(format_args!(
+                    "Listening on {}",
+                    "unix:///var/run/iotedge/mgmt.sock"
+                ))
+                .level(Level::Warn)
+                .target("iotedged::signal")
+                .module_path(Some("iotedged::signal"))
+                .build(),
+        );
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(
+            json!({
+                "timestamp": "2018-10-01T12:30:00Z",
+                "level": "WARN",
+                "module": "iotedged::signal",
+                "message": "Listening on unix:///var/run/iotedge/mgmt.sock",
+            }),
+            line
+        );
+    }
+
+    #[test]
+    fn json_line_falls_back_to_target_for_module() {
+        let line = json_line(
+            "2018-10-01T12:30:00Z",
+            &Record::builder()
+                .args(format_args!("shutdown signaled"))
+                .level(Level::Debug)
+                .target("iotedged")
+                .build(),
+        );
+        let line: Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!("iotedged", line["module"]);
+        assert_eq!("DEBUG", line["level"]);
+    }
+}
@@ -1,28 +1,51 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs::{File as FsFile, OpenOptions};
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use base64;
 use config::{Config, Environment, File, FileFormat};
 use failure::{Fail, ResultExt};
+use hyper::Method;
 use log::Level;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
 use sha2::{Digest, Sha256};
-use url::Url;
+use url::{Host, Url};
 use url_serde;
 
+use docker::models::AuthConfig;
+use edgelet_core::certificate_expiry::CertificateExpirySettings;
+use edgelet_core::watchdog::WatchdogSettings;
 use edgelet_core::ModuleSpec;
-use edgelet_utils::log_failure;
+use edgelet_http::logging::{LogField, LogFormat};
+use edgelet_http::SUPPORTED_API_VERSIONS;
+use edgelet_utils::{is_default, log_failure};
 
 use error::{Error, ErrorKind, InitializeErrorReason};
+use signal::{self, ShutdownTrigger};
 
 /// This is the name of the network created by the iotedged
 const DEFAULT_NETWORKID: &str = "azure-iot-edge";
 
+/// This is the default identity of the edge runtime module
+const DEFAULT_AGENT_MODULE_ID: &str = "$edgeAgent";
+
+/// This is the default validity of the workload CA certificate (90 days)
+const DEFAULT_WORKLOAD_CA_VALIDITY_SECS: u64 = 7_776_000;
+
+/// These are the bounds the HSM accepts for the validity of a certificate.
+/// The HSM also never issues a certificate that outlives its issuer, so the
+/// workload CA certificate expires with the device CA certificate at the
+/// latest.
+const MIN_CERTIFICATE_VALIDITY_SECS: u64 = 1;
+const MAX_CERTIFICATE_VALIDITY_SECS: u64 = i32::max_value() as u64;
+
 /// This is the default connection string
 pub const DEFAULT_CONNECTION_STRING: &str = "<ADD DEVICE CONNECTION STRING HERE>";
 
@@ -99,6 +122,63 @@ pub struct Listen {
     workload_uri: Url,
     #[serde(with = "url_serde")]
     management_uri: Url,
+    /// Number of threads serving connections to the workload API.
+    #[serde(
+        default = "default_listen_threads",
+        skip_serializing_if = "is_default_listen_threads"
+    )]
+    workload_threads: usize,
+    /// Number of threads serving connections to the management API.
+    #[serde(
+        default = "default_listen_threads",
+        skip_serializing_if = "is_default_listen_threads"
+    )]
+    management_threads: usize,
+    /// Accept backlog of the listeners of both APIs. The default one of each
+    /// kind of listener is used if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backlog: Option<i32>,
+    /// TCP keepalive of the connections accepted by the listeners of both
+    /// APIs that are bound to TCP addresses. It is disabled if this is not
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tcp_keepalive: Option<TcpKeepalive>,
+    /// Number of connections each listener of both APIs serves at a time.
+    /// Connections beyond it are closed as soon as they are accepted. It is
+    /// unlimited if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_connections: Option<usize>,
+    /// Whether the management API is served over TLS, with a server
+    /// certificate for `hostname` issued by the workload CA.
+    #[serde(default, skip_serializing_if = "is_default")]
+    management_tls: bool,
+    /// Whether the workload API is served over TLS, with a server certificate
+    /// for `hostname` issued by the device CA.
+    #[serde(default, skip_serializing_if = "is_default")]
+    workload_tls: bool,
+    /// Octal permissions of the unix socket files of both APIs, for example
+    /// "0660". They are created with the daemon's umask if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    socket_mode: Option<String>,
+    /// Maximum rate of requests to the management API. Requests beyond it are
+    /// rejected with `429 Too Many Requests`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    management_rate_limit: Option<RateLimit>,
+    /// An additional address the management API is served on, for example to
+    /// reach it over TCP from remote tooling while the primary listener is a
+    /// unix socket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    management_external: Option<ExternalManagement>,
+    /// Origins browsers may call the management API from. This applies to
+    /// the external management listener too, but never to the workload API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    management_cors: Option<Cors>,
+    /// Bearer token requests to the management API must carry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    management_auth: Option<ManagementAuth>,
+    /// Format of the line logged for each request to either API.
+    #[serde(default, skip_serializing_if = "is_default")]
+    request_log: RequestLog,
 }
 
 impl Listen {
@@ -109,300 +189,2670 @@ impl Listen {
     pub fn management_uri(&self) -> &Url {
         &self.management_uri
     }
+
+    pub fn workload_threads(&self) -> usize {
+        self.workload_threads
+    }
+
+    pub fn management_threads(&self) -> usize {
+        self.management_threads
+    }
+
+    pub fn backlog(&self) -> Option<i32> {
+        self.backlog
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<&TcpKeepalive> {
+        self.tcp_keepalive.as_ref()
+    }
+
+    pub fn max_connections(&self) -> Option<usize> {
+        self.max_connections
+    }
+
+    pub fn management_tls(&self) -> bool {
+        self.management_tls
+    }
+
+    pub fn workload_tls(&self) -> bool {
+        self.workload_tls
+    }
+
+    pub fn socket_mode(&self) -> Option<u32> {
+        self.socket_mode
+            .as_ref()
+            .and_then(|mode| parse_socket_mode(mode))
+    }
+
+    pub fn management_rate_limit(&self) -> Option<&RateLimit> {
+        self.management_rate_limit.as_ref()
+    }
+
+    pub fn management_external(&self) -> Option<&ExternalManagement> {
+        self.management_external.as_ref()
+    }
+
+    pub fn management_cors(&self) -> Option<&Cors> {
+        self.management_cors.as_ref()
+    }
+
+    pub fn management_auth(&self) -> Option<&ManagementAuth> {
+        self.management_auth.as_ref()
+    }
+
+    pub fn request_log(&self) -> &RequestLog {
+        &self.request_log
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct MobyRuntime {
+/// How requests to the APIs are logged. JSON lines have all fields if
+/// `fields` is not set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RequestLog {
+    #[serde(default)]
+    format: RequestLogFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<RequestLogField>>,
+}
+
+impl RequestLog {
+    pub fn format(&self) -> LogFormat {
+        match self.format {
+            RequestLogFormat::Plain => LogFormat::Plain,
+            RequestLogFormat::Json => {
+                LogFormat::Json(self.fields.as_ref().map_or_else(LogField::all, |fields| {
+                    fields.iter().map(|field| field.log_field()).collect()
+                }))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestLogFormat {
+    Plain,
+    Json,
+}
+
+impl Default for RequestLogFormat {
+    fn default() -> Self {
+        RequestLogFormat::Plain
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestLogField {
+    Label,
+    Time,
+    Request,
+    Status,
+    BodyLength,
+    UserAgent,
+    Pid,
+}
+
+impl RequestLogField {
+    fn log_field(self) -> LogField {
+        match self {
+            RequestLogField::Label => LogField::Label,
+            RequestLogField::Time => LogField::Time,
+            RequestLogField::Request => LogField::Request,
+            RequestLogField::Status => LogField::Status,
+            RequestLogField::BodyLength => LogField::BodyLength,
+            RequestLogField::UserAgent => LogField::UserAgent,
+            RequestLogField::Pid => LogField::Pid,
+        }
+    }
+}
+
+/// A listener for the management API besides `listen.management_uri`. It has
+/// its own TLS and rate limit settings, which default to no TLS and no limit
+/// regardless of those of the primary listener.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExternalManagement {
     #[serde(with = "url_serde")]
     uri: Url,
-    network: String,
+    #[serde(default)]
+    tls: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rate_limit: Option<RateLimit>,
 }
 
-impl MobyRuntime {
+impl ExternalManagement {
     pub fn uri(&self) -> &Url {
         &self.uri
     }
 
-    pub fn network(&self) -> &str {
-        if self.network.is_empty() {
-            &DEFAULT_NETWORKID
+    pub fn tls(&self) -> bool {
+        self.tls
+    }
+
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+}
+
+/// The bearer token is either set as `token` or read from `token_file`,
+/// which keeps it out of the config file. Listeners on unix sockets are only
+/// exempt if `allow_unauthenticated_unix` is set, for clients such as the
+/// edge agent that reach the API through the socket file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ManagementAuth {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token_file: Option<PathBuf>,
+    #[serde(default)]
+    allow_unauthenticated_unix: bool,
+}
+
+impl ManagementAuth {
+    /// Reads the token from `token_file` if it was not set directly.
+    /// Surrounding whitespace, such as a trailing newline, is not part of it.
+    pub fn token(&self) -> Result<String, Error> {
+        let token = match (&self.token, &self.token_file) {
+            (Some(token), _) => token.clone(),
+            (None, Some(token_file)) => {
+                let mut token = String::new();
+                FsFile::open(token_file)
+                    .and_then(|mut file| file.read_to_string(&mut token))
+                    .context(ErrorKind::Initialize(
+                        InitializeErrorReason::ManagementAuthToken,
+                    ))?;
+                token
+            }
+            (None, None) => String::new(),
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            Err(Error::from(ErrorKind::Initialize(
+                InitializeErrorReason::InvalidManagementAuth,
+            )))
         } else {
-            &self.network
+            Ok(token.to_string())
         }
     }
+
+    pub fn allow_unauthenticated_unix(&self) -> bool {
+        self.allow_unauthenticated_unix
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Certificates {
-    device_ca_cert: PathBuf,
-    device_ca_pk: PathBuf,
-    trusted_ca_certs: PathBuf,
+/// Cross-origin requests are allowed from `origins`, which are compared to
+/// the `Origin` header of requests as is, or from anywhere if one of them is
+/// `*`. Only `GET` requests are allowed if `methods` is not set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Cors {
+    origins: Vec<String>,
+    #[serde(default = "default_cors_methods")]
+    methods: Vec<String>,
 }
 
-impl Certificates {
-    pub fn device_ca_cert(&self) -> &Path {
-        &self.device_ca_cert
+impl Cors {
+    pub fn origins(&self) -> &[String] {
+        &self.origins
     }
 
-    pub fn device_ca_pk(&self) -> &Path {
-        &self.device_ca_pk
+    pub fn methods(&self) -> Vec<Method> {
+        self.methods
+            .iter()
+            .filter_map(|method| parse_method(method))
+            .collect()
     }
+}
 
-    pub fn trusted_ca_certs(&self) -> &Path {
-        &self.trusted_ca_certs
+fn default_cors_methods() -> Vec<String> {
+    vec!["GET".to_string()]
+}
+
+fn parse_method(method: &str) -> Option<Method> {
+    Method::from_bytes(method.to_uppercase().as_bytes()).ok()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RateLimit {
+    requests: u32,
+    #[serde(default = "default_rate_limit_period_secs")]
+    period_secs: u64,
+}
+
+impl RateLimit {
+    pub fn requests(&self) -> u32 {
+        self.requests
+    }
+
+    pub fn period(&self) -> Duration {
+        Duration::from_secs(self.period_secs)
+    }
+}
+
+fn default_rate_limit_period_secs() -> u64 {
+    1
+}
+
+/// Connections are probed once they have been idle for `idle_secs`, and then
+/// every `interval_secs`. The system's interval is used if that is not set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TcpKeepalive {
+    #[serde(default = "default_tcp_keepalive_idle_secs")]
+    idle_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    interval_secs: Option<u64>,
+}
+
+impl TcpKeepalive {
+    pub fn idle(&self) -> Duration {
+        Duration::from_secs(self.idle_secs)
+    }
+
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval_secs.map(Duration::from_secs)
     }
 }
 
+fn default_tcp_keepalive_idle_secs() -> u64 {
+    60
+}
+
+fn parse_socket_mode(mode: &str) -> Option<u32> {
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+}
+
+fn default_listen_threads() -> usize {
+    1
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+fn is_default_listen_threads(threads: &usize) -> bool {
+    *threads == default_listen_threads()
+}
+
+/// Number of times to retry initializing the container runtime while waiting
+/// for the daemon and its network to become available.
+const DEFAULT_INIT_RETRIES: u32 = 10;
+
+/// Time to wait between attempts to initialize the container runtime.
+const DEFAULT_INIT_RETRY_INTERVAL_SECS: u64 = 3;
+
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Settings<T> {
-    provisioning: Provisioning,
-    agent: ModuleSpec<T>,
-    hostname: String,
-    connect: Connect,
-    listen: Listen,
-    homedir: PathBuf,
-    moby_runtime: MobyRuntime,
-    certificates: Option<Certificates>,
+pub struct MobyRuntime {
+    #[serde(with = "url_serde")]
+    uri: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    api_version: Option<String>,
+    network: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_driver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_ipv6_subnet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_subnet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_gateway: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additional_networks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_config: Option<LogConfig>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    default_resources: ResourceLimits,
+    #[serde(default, skip_serializing_if = "is_default")]
+    prune_images_on_reconfigure: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    prune_volumes_on_reconfigure: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    readonly_rootfs: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ulimits: Vec<Ulimit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    security_opt: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extra_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    content_trust: ContentTrust,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_pull_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    registry_ca_bundle: Option<PathBuf>,
+    #[serde(
+        default = "default_init_retries",
+        skip_serializing_if = "is_default_init_retries"
+    )]
+    init_retries: u32,
+    #[serde(
+        default = "default_init_retry_interval_secs",
+        skip_serializing_if = "is_default_init_retry_interval_secs"
+    )]
+    init_retry_interval_secs: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    registries: BTreeMap<String, AuthConfig>,
 }
 
-impl<T> Settings<T>
-where
-    T: DeserializeOwned + Serialize,
-{
-    pub fn new(filename: Option<&str>) -> Result<Self, Error> {
-        let mut config = Config::default();
-        config
-            .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
-            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
-        if let Some(file) = filename {
-            config
-                .merge(File::with_name(file).required(true))
-                .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
-        }
+fn default_init_retries() -> u32 {
+    DEFAULT_INIT_RETRIES
+}
 
-        config
-            .merge(Environment::with_prefix("iotedge"))
-            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+#[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+fn is_default_init_retries(retries: &u32) -> bool {
+    *retries == DEFAULT_INIT_RETRIES
+}
 
-        let settings: Self = config
-            .try_into()
-            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+fn default_init_retry_interval_secs() -> u64 {
+    DEFAULT_INIT_RETRY_INTERVAL_SECS
+}
 
-        Ok(settings)
+#[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+fn is_default_init_retry_interval_secs(secs: &u64) -> bool {
+    *secs == DEFAULT_INIT_RETRY_INTERVAL_SECS
+}
+
+impl MobyRuntime {
+    pub fn uri(&self) -> &Url {
+        &self.uri
     }
 
-    pub fn provisioning(&self) -> &Provisioning {
-        &self.provisioning
+    /// Docker Engine API version to use for all requests, e.g. "1.25". The
+    /// engine's latest version is used if this is not set.
+    pub fn api_version(&self) -> Option<&str> {
+        self.api_version.as_ref().map(AsRef::as_ref)
     }
 
-    pub fn agent(&self) -> &ModuleSpec<T> {
-        &self.agent
+    pub fn network(&self) -> &str {
+        if self.network.is_empty() {
+            &DEFAULT_NETWORKID
+        } else {
+            &self.network
+        }
     }
 
-    pub fn agent_mut(&mut self) -> &mut ModuleSpec<T> {
-        &mut self.agent
+    /// Driver for the network on which the containers are created. The
+    /// container runtime's default driver is used if this is not set.
+    pub fn network_driver(&self) -> Option<&str> {
+        self.network_driver.as_ref().map(AsRef::as_ref)
     }
 
-    pub fn hostname(&self) -> &str {
-        &self.hostname
+    /// IPv6 subnet to assign to the network. IPv6 is enabled on the network
+    /// only if this is set.
+    pub fn network_ipv6_subnet(&self) -> Option<&str> {
+        self.network_ipv6_subnet.as_ref().map(AsRef::as_ref)
     }
 
-    pub fn connect(&self) -> &Connect {
-        &self.connect
+    /// IPv4 subnet to assign to the network. Docker picks a subnet if this is
+    /// not set.
+    pub fn network_subnet(&self) -> Option<&str> {
+        self.network_subnet.as_ref().map(AsRef::as_ref)
     }
 
-    pub fn listen(&self) -> &Listen {
-        &self.listen
+    /// Gateway address for the network's IPv4 subnet. Only used if the subnet
+    /// is set.
+    pub fn network_gateway(&self) -> Option<&str> {
+        self.network_gateway.as_ref().map(AsRef::as_ref)
     }
 
-    pub fn homedir(&self) -> &Path {
-        &self.homedir
+    /// Existing networks that module containers are connected to in addition
+    /// to the edge network.
+    pub fn additional_networks(&self) -> &[String] {
+        &self.additional_networks
     }
 
-    pub fn moby_runtime(&self) -> &MobyRuntime {
-        &self.moby_runtime
+    pub fn init_retries(&self) -> u32 {
+        self.init_retries
     }
 
-    pub fn certificates(&self) -> Option<&Certificates> {
-        self.certificates.as_ref()
+    pub fn init_retry_interval(&self) -> Duration {
+        Duration::from_secs(self.init_retry_interval_secs)
     }
 
-    pub fn diff_with_cached(&self, path: PathBuf) -> Result<bool, Error> {
-        OpenOptions::new()
-            .read(true)
-            .open(path)
-            .map_err(|err| err.context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings)))
-            .and_then(|mut file: FsFile| {
-                let mut buffer = String::new();
-                file.read_to_string(&mut buffer)
-                    .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
-                let s = serde_json::to_string(self)
-                    .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
-                let s = Sha256::digest_str(&s);
-                let encoded = base64::encode(&s);
-                if encoded == buffer {
-                    debug!("Config state matches supplied config.");
-                    Ok(false)
-                } else {
-                    Ok(true)
-                }
-            })
-            .or_else(|err| {
-                log_failure(Level::Debug, &err);
-                debug!("Error reading config backup.");
-                Ok(true)
-            })
+    /// How long an image pull may take before it fails. Pulls are not timed
+    /// out if this is not set.
+    pub fn image_pull_timeout(&self) -> Option<Duration> {
+        self.image_pull_timeout_secs.map(Duration::from_secs)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use config::{Config, File, FileFormat};
-    use edgelet_docker::DockerConfig;
-    use std::io::Write;
-    use tempdir::TempDir;
+    /// How long module containers are given to exit when they are stopped
+    /// before they are killed. Docker's default is used if this is not set.
+    pub fn stop_timeout(&self) -> Option<Duration> {
+        self.stop_timeout_secs.map(Duration::from_secs)
+    }
 
-    #[cfg(unix)]
-    static GOOD_SETTINGS: &str = "test/linux/sample_settings.yaml";
-    #[cfg(unix)]
-    static GOOD_SETTINGS1: &str = "test/linux/sample_settings1.yaml";
-    #[cfg(unix)]
-    static BAD_SETTINGS: &str = "test/linux/bad_sample_settings.yaml";
-    #[cfg(unix)]
-    static GOOD_SETTINGS_TG: &str = "test/linux/sample_settings.tg.yaml";
+    /// PEM bundle of CA certificates trusted, in addition to the system's, when
+    /// talking to registries over HTTPS.
+    pub fn registry_ca_bundle(&self) -> Option<&Path> {
+        self.registry_ca_bundle.as_ref().map(AsRef::as_ref)
+    }
 
-    #[cfg(windows)]
-    static GOOD_SETTINGS: &str = "test/windows/sample_settings.yaml";
-    #[cfg(windows)]
-    static GOOD_SETTINGS1: &str = "test/windows/sample_settings1.yaml";
-    #[cfg(windows)]
-    static BAD_SETTINGS: &str = "test/windows/bad_sample_settings.yaml";
-    #[cfg(windows)]
-    static GOOD_SETTINGS_TG: &str = "test/windows/sample_settings.tg.yaml";
+    /// Credentials for pulling images from private registries, keyed by
+    /// registry hostname.
+    pub fn registries(&self) -> &BTreeMap<String, AuthConfig> {
+        &self.registries
+    }
 
-    fn unwrap_manual_provisioning(p: &Provisioning) -> String {
-        match p {
-            Provisioning::Manual(manual) => manual.device_connection_string().to_string(),
-            _ => "not implemented".to_string(),
-        }
+    /// Default log driver for module containers that don't configure their
+    /// own in their create options.
+    pub fn log_config(&self) -> Option<&LogConfig> {
+        self.log_config.as_ref()
     }
 
-    #[test]
-    fn default_in_yaml_matches_constant() {
-        let mut config = Config::default();
-        config
-            .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
-            .unwrap();
-        let settings: Settings<DockerConfig> = config.try_into().unwrap();
+    /// Default memory and CPU limits for module containers that don't set
+    /// their own in their create options.
+    pub fn default_resources(&self) -> &ResourceLimits {
+        &self.default_resources
+    }
 
-        match settings.provisioning() {
-            Provisioning::Manual(ref manual) => {
-                assert_eq!(manual.device_connection_string(), DEFAULT_CONNECTION_STRING)
-            }
-            _ => assert!(false),
-        }
+    /// Whether dangling images are removed when the device is reconfigured,
+    /// after its modules have been removed.
+    pub fn prune_images_on_reconfigure(&self) -> bool {
+        self.prune_images_on_reconfigure
     }
 
-    #[test]
-    fn no_file_gets_error() {
-        let settings = Settings::<DockerConfig>::new(Some("garbage"));
-        assert!(settings.is_err());
+    /// Whether volumes that are no longer referenced by any container are
+    /// removed when the device is reconfigured. Off by default since module
+    /// data stored in those volumes is lost.
+    pub fn prune_volumes_on_reconfigure(&self) -> bool {
+        self.prune_volumes_on_reconfigure
     }
 
-    #[test]
-    fn bad_file_gets_error() {
-        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS));
-        assert!(settings.is_err());
+    /// Labels added to every module container, configured as "key=value"
+    /// strings. A label without a '=' gets an empty value.
+    pub fn labels(&self) -> HashMap<String, String> {
+        self.labels
+            .iter()
+            .map(|label| {
+                let mut parts = label.splitn(2, '=');
+                let key = parts.next().unwrap_or_default().to_string();
+                let value = parts.next().unwrap_or_default().to_string();
+                (key, value)
+            })
+            .collect()
     }
 
-    #[test]
-    fn manual_file_gets_sample_connection_string() {
-        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS));
-        println!("{:?}", settings);
-        assert!(settings.is_ok());
-        let s = settings.unwrap();
+    /// Whether module containers get a read-only root filesystem by default.
+    /// Modules can override this with `readonlyRootfs` in their settings.
+    pub fn readonly_rootfs(&self) -> bool {
+        self.readonly_rootfs
+    }
+
+    /// DNS servers for module containers, instead of docker's default.
+    pub fn dns(&self) -> &[String] {
+        &self.dns
+    }
+
+    /// Default ulimits for module containers.
+    pub fn ulimits(&self) -> &[Ulimit] {
+        &self.ulimits
+    }
+
+    /// Default security options for module containers, e.g.
+    /// "apparmor=<profile>" or "seccomp=<path to profile>".
+    pub fn security_opt(&self) -> &[String] {
+        &self.security_opt
+    }
+
+    /// Default "hostname:IP" entries for the hosts file of module containers.
+    pub fn extra_hosts(&self) -> &[String] {
+        &self.extra_hosts
+    }
+
+    /// Docker content trust settings for pulling module images.
+    pub fn content_trust(&self) -> &ContentTrust {
+        &self.content_trust
+    }
+}
+
+/// When enabled, only images whose tag is signed on the trust server are
+/// pulled, the same as setting `DOCKER_CONTENT_TRUST=1` for the docker CLI.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ContentTrust {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default, with = "url_serde", skip_serializing_if = "Option::is_none")]
+    server: Option<Url>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    root_keys: Vec<String>,
+}
+
+impl ContentTrust {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Trust server to use for all images. Otherwise notary.docker.io is used
+    /// for Docker Hub images and the image's registry for all others.
+    pub fn server(&self) -> Option<&Url> {
+        self.server.as_ref()
+    }
+
+    /// IDs of the root keys trust collections have to be signed by.
+    /// Otherwise the root keys of a collection are trusted the first time it
+    /// is used.
+    pub fn root_keys(&self) -> &[String] {
+        &self.root_keys
+    }
+}
+
+/// Container resource limits, in the units of the docker `HostConfig` fields
+/// they map to (bytes for memory and memory swap).
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ResourceLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memory: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memory_swap: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cpu_shares: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nano_cpus: Option<i64>,
+}
+
+impl ResourceLimits {
+    pub fn memory(&self) -> Option<i64> {
+        self.memory
+    }
+
+    pub fn memory_swap(&self) -> Option<i64> {
+        self.memory_swap
+    }
+
+    pub fn cpu_shares(&self) -> Option<i32> {
+        self.cpu_shares
+    }
+
+    pub fn nano_cpus(&self) -> Option<i64> {
+        self.nano_cpus
+    }
+}
+
+/// A container ulimit, e.g. "nofile" with its soft and hard limits.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Ulimit {
+    name: String,
+    soft: i32,
+    hard: i32,
+}
+
+impl Ulimit {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn soft(&self) -> i32 {
+        self.soft
+    }
+
+    pub fn hard(&self) -> i32 {
+        self.hard
+    }
+}
+
+/// Docker log driver and its options, e.g. "json-file" with "max-size" and
+/// "max-file", or "local".
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LogConfig {
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    options: BTreeMap<String, String>,
+}
+
+impl LogConfig {
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    pub fn options(&self) -> &BTreeMap<String, String> {
+        &self.options
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Certificates {
+    device_ca_cert: PathBuf,
+    device_ca_pk: PathBuf,
+    trusted_ca_certs: PathBuf,
+}
+
+impl Certificates {
+    pub fn device_ca_cert(&self) -> &Path {
+        &self.device_ca_cert
+    }
+
+    pub fn device_ca_pk(&self) -> &Path {
+        &self.device_ca_pk
+    }
+
+    pub fn trusted_ca_certs(&self) -> &Path {
+        &self.trusted_ca_certs
+    }
+}
+
+/// Proxy server settings for the daemon and the edge runtime modules. The
+/// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` environment variables are used
+/// for any that are not set here.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Proxy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    https_proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    http_proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    no_proxy: Option<String>,
+}
+
+impl Proxy {
+    pub fn https_proxy(&self) -> Option<&str> {
+        self.https_proxy.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn http_proxy(&self) -> Option<&str> {
+        self.http_proxy.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn no_proxy(&self) -> Option<&str> {
+        self.no_proxy.as_ref().map(AsRef::as_ref)
+    }
+}
+
+/// Timezone of the edge runtime modules, which run in UTC if neither of these
+/// is set.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Timezone {
+    /// Value of the `TZ` environment variable of the modules, for example
+    /// "Europe/Berlin".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tz: Option<String>,
+    /// Whether the daemon's own `TZ` environment variable is passed on when
+    /// `tz` is not set.
+    #[serde(default)]
+    forward_host: bool,
+}
+
+impl Timezone {
+    pub fn tz(&self) -> Option<&str> {
+        self.tz.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn forward_host(&self) -> bool {
+        self.forward_host
+    }
+}
+
+/// Restricts which entries of the configured `env` of the edge agent are
+/// passed on to it. The variables the daemon sets itself are never filtered.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EnvFilter {
+    /// Only these variables are passed on, if this is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    allowlist: Option<Vec<String>>,
+    /// These variables are never passed on, even if they are in `allowlist`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    denylist: Vec<String>,
+}
+
+impl EnvFilter {
+    pub fn allowlist(&self) -> Option<&[String]> {
+        self.allowlist.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn denylist(&self) -> &[String] {
+        &self.denylist
+    }
+
+    pub fn allows(&self, key: &str) -> bool {
+        let allowed = self
+            .allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.iter().any(|k| k == key));
+        allowed && !self.denylist.iter().any(|k| k == key)
+    }
+}
+
+/// Symmetric algorithm the HSM library uses to encrypt and decrypt data for
+/// the workload API.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum EncryptionAlgorithm {
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    #[serde(rename = "chacha20-poly1305")]
+    Chacha20Poly1305,
+}
+
+impl Default for EncryptionAlgorithm {
+    fn default() -> Self {
+        EncryptionAlgorithm::Aes256Gcm
+    }
+}
+
+impl fmt::Display for EncryptionAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            EncryptionAlgorithm::Aes256Gcm => "aes-256-gcm",
+            EncryptionAlgorithm::Chacha20Poly1305 => "chacha20-poly1305",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Type of the keys the HSM library generates for the certificates it
+/// issues.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CertificateKeyType {
+    /// RSA keys, 4096 bits for CA certificates and 2048 bits otherwise.
+    Rsa,
+    /// ECC keys on the P-256 curve.
+    EcP256,
+    /// ECC keys on the P-384 curve.
+    EcP384,
+}
+
+impl fmt::Display for CertificateKeyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            CertificateKeyType::Rsa => "rsa",
+            CertificateKeyType::EcP256 => "ec-p256",
+            CertificateKeyType::EcP384 => "ec-p384",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Hsm {
+    #[serde(default)]
+    encryption_algorithm: EncryptionAlgorithm,
+    /// Keys of issued certificates are of the same type as the key of their
+    /// issuer if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cert_key_type: Option<CertificateKeyType>,
+    /// The HSM library opens its default TPM device if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tpm_device_path: Option<PathBuf>,
+    /// Issuing certificates are not checked for revocation if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crl_path: Option<PathBuf>,
+}
+
+impl Hsm {
+    pub fn encryption_algorithm(&self) -> EncryptionAlgorithm {
+        self.encryption_algorithm
+    }
+
+    pub fn cert_key_type(&self) -> Option<CertificateKeyType> {
+        self.cert_key_type
+    }
+
+    pub fn tpm_device_path(&self) -> Option<&Path> {
+        self.tpm_device_path.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn crl_path(&self) -> Option<&Path> {
+        self.crl_path.as_ref().map(AsRef::as_ref)
+    }
+}
+
+/// Restart policy docker applies to the edge agent container when it exits.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    No,
+    Always,
+    OnFailure,
+    UnlessStopped,
+}
+
+impl fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            RestartPolicy::No => "no",
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::UnlessStopped => "unless-stopped",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Protocol the edge hub uses to connect to IoT Hub.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum UpstreamProtocol {
+    Amqp,
+    AmqpWs,
+    Mqtt,
+    MqttWs,
+}
+
+impl fmt::Display for UpstreamProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            UpstreamProtocol::Amqp => "Amqp",
+            UpstreamProtocol::AmqpWs => "AmqpWs",
+            UpstreamProtocol::Mqtt => "Mqtt",
+            UpstreamProtocol::MqttWs => "MqttWs",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How the edge runtime modules authenticate when connecting to other server
+/// modules such as the edge hub.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthScheme {
+    SasToken,
+    X509Cert,
+}
+
+impl Default for AuthScheme {
+    fn default() -> Self {
+        AuthScheme::SasToken
+    }
+}
+
+impl fmt::Display for AuthScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            AuthScheme::SasToken => "sasToken",
+            AuthScheme::X509Cert => "x509Cert",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Log verbosity of the edge runtime modules.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeLogLevel {
+    Verbose,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl fmt::Display for RuntimeLogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            RuntimeLogLevel::Verbose => "verbose",
+            RuntimeLogLevel::Debug => "debug",
+            RuntimeLogLevel::Info => "info",
+            RuntimeLogLevel::Warning => "warning",
+            RuntimeLogLevel::Error => "error",
+            RuntimeLogLevel::Fatal => "fatal",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Log verbosity of the daemon.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn level(self) -> Level {
+        match self {
+            LogLevel::Error => Level::Error,
+            LogLevel::Warn => Level::Warn,
+            LogLevel::Info => Level::Info,
+            LogLevel::Debug => Level::Debug,
+            LogLevel::Trace => Level::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Agent<T> {
+    #[serde(flatten)]
+    spec: ModuleSpec<T>,
+    #[serde(
+        default = "default_agent_module_id",
+        skip_serializing_if = "is_default_agent_module_id"
+    )]
+    module_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restart_policy: Option<RestartPolicy>,
+    /// Environment variables added to the ones the daemon sets for the edge
+    /// agent.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    extra_env: BTreeMap<String, String>,
+    /// Entries of `extra_env` that collide with a variable the daemon sets are
+    /// ignored unless this is set.
+    #[serde(default, skip_serializing_if = "is_default")]
+    override_reserved_env: bool,
+    /// The API version the edge agent is told to use. The daemon's own API
+    /// version is used if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    api_version: Option<String>,
+    /// Name of the environment variable that holds the docker network id for
+    /// the edge agent. It is `NetworkId` if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_id_env_key: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    env_filter: EnvFilter,
+}
+
+fn default_agent_module_id() -> String {
+    DEFAULT_AGENT_MODULE_ID.to_string()
+}
+
+fn is_default_agent_module_id(module_id: &str) -> bool {
+    module_id == DEFAULT_AGENT_MODULE_ID
+}
+
+fn default_workload_ca_validity_secs() -> u64 {
+    DEFAULT_WORKLOAD_CA_VALIDITY_SECS
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+fn is_default_workload_ca_validity_secs(secs: &u64) -> bool {
+    *secs == DEFAULT_WORKLOAD_CA_VALIDITY_SECS
+}
+
+// Checks that a connect URI can reach the socket the daemon listens on for
+// the corresponding listen URI. Sockets activated by systemd (fd://) can't be
+// checked.
+fn check_connect_uri(connect_uri: &Url, listen_uri: &Url) -> Result<(), Error> {
+    let unspecified_host = match connect_uri.host() {
+        Some(Host::Ipv4(ip)) => ip.is_unspecified(),
+        Some(Host::Ipv6(ip)) => ip.is_unspecified(),
+        _ => false,
+    };
+    let reachable = !unspecified_host
+        && match (connect_uri.scheme(), listen_uri.scheme()) {
+            (_, "fd") => true,
+            ("unix", "unix") => connect_uri.path() == listen_uri.path(),
+            ("unix", _) | (_, "unix") => false,
+            ("npipe", "npipe") => {
+                connect_uri.host_str() == listen_uri.host_str()
+                    && connect_uri.path() == listen_uri.path()
+            }
+            ("npipe", _) | (_, "npipe") => false,
+            _ => true,
+        };
+    if reachable {
+        Ok(())
+    } else {
+        Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidConnectUri,
+        )))
+    }
+}
+
+fn check_listen_threads(threads: usize) -> Result<(), Error> {
+    if threads == 0 {
+        Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidThreadCount,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_listen_backlog(backlog: Option<i32>) -> Result<(), Error> {
+    match backlog {
+        Some(backlog) if backlog < 1 => Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidListenBacklog,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_max_connections(max_connections: Option<usize>) -> Result<(), Error> {
+    match max_connections {
+        Some(0) => Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidMaxConnections,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_tcp_keepalive(keepalive: Option<&TcpKeepalive>) -> Result<(), Error> {
+    match keepalive {
+        Some(keepalive) if keepalive.idle_secs == 0 || keepalive.interval_secs == Some(0) => {
+            Err(Error::from(ErrorKind::Initialize(
+                InitializeErrorReason::InvalidTcpKeepalive,
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_socket_mode(mode: Option<&str>) -> Result<(), Error> {
+    match mode {
+        Some(mode) if parse_socket_mode(mode).is_none() => Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidSocketMode,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_external_management_uri(uri: &Url, management_uri: &Url) -> Result<(), Error> {
+    if uri == management_uri {
+        Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidExternalManagementUri,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_rate_limit(rate_limit: Option<&RateLimit>) -> Result<(), Error> {
+    match rate_limit {
+        Some(rate_limit) if rate_limit.requests == 0 || rate_limit.period_secs == 0 => {
+            Err(Error::from(ErrorKind::Initialize(
+                InitializeErrorReason::InvalidRateLimit,
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+// An origin is a scheme, host and port without a path, as browsers send it
+// in the `Origin` header.
+fn is_cors_origin(origin: &str) -> bool {
+    origin == "*"
+        || Url::parse(origin)
+            .map(|url| url.origin().ascii_serialization() == origin)
+            .unwrap_or(false)
+}
+
+fn check_cors(cors: Option<&Cors>) -> Result<(), Error> {
+    match cors {
+        Some(cors)
+            if cors.origins.is_empty()
+                || !cors.origins.iter().all(|origin| is_cors_origin(origin))
+                || cors.methods.is_empty()
+                || cors
+                    .methods
+                    .iter()
+                    .any(|method| parse_method(method).is_none()) =>
+        {
+            Err(Error::from(ErrorKind::Initialize(
+                InitializeErrorReason::InvalidManagementCors,
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_management_auth(auth: Option<&ManagementAuth>) -> Result<(), Error> {
+    match auth {
+        Some(auth)
+            if auth.token.is_some() == auth.token_file.is_some()
+                || auth
+                    .token
+                    .as_ref()
+                    .map_or(false, |token| token.trim().is_empty()) =>
+        {
+            Err(Error::from(ErrorKind::Initialize(
+                InitializeErrorReason::InvalidManagementAuth,
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_shutdown_signals(signals: &[String]) -> Result<(), Error> {
+    if signals.iter().all(|name| signal::is_known_signal(name)) {
+        Ok(())
+    } else {
+        Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidShutdownSignal,
+        )))
+    }
+}
+
+fn check_agent_api_version(api_version: Option<&str>) -> Result<(), Error> {
+    match api_version {
+        Some(api_version) if !SUPPORTED_API_VERSIONS.contains(&api_version) => Err(Error::from(
+            ErrorKind::Initialize(InitializeErrorReason::InvalidAgentApiVersion),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_certificate_validity(validity_in_secs: u64) -> Result<(), Error> {
+    if validity_in_secs < MIN_CERTIFICATE_VALIDITY_SECS
+        || validity_in_secs > MAX_CERTIFICATE_VALIDITY_SECS
+    {
+        Err(Error::from(ErrorKind::Initialize(
+            InitializeErrorReason::InvalidCertificateValidity,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// What shuts the daemon down. It stops on any of `signals`, which default
+/// to SIGINT and SIGTERM (Ctrl+C on Windows), when `file` is created or
+/// touched, and when something is written to the named pipe `named_pipe`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Shutdown {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signals: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+    #[cfg(unix)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    named_pipe: Option<PathBuf>,
+}
+
+impl Shutdown {
+    pub fn signals(&self) -> Vec<String> {
+        self.signals.clone().unwrap_or_else(signal::default_signals)
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_ref().map(AsRef::as_ref)
+    }
+
+    #[cfg(unix)]
+    pub fn named_pipe(&self) -> Option<&Path> {
+        self.named_pipe.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn triggers(&self) -> Vec<ShutdownTrigger> {
+        let mut triggers = vec![ShutdownTrigger::Signals(self.signals())];
+        if let Some(file) = self.file() {
+            triggers.push(ShutdownTrigger::File(file.to_path_buf()));
+        }
+        #[cfg(unix)]
+        {
+            if let Some(named_pipe) = self.named_pipe() {
+                triggers.push(ShutdownTrigger::NamedPipe(named_pipe.to_path_buf()));
+            }
+        }
+        triggers
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Settings<T> {
+    /// Log verbosity of the daemon itself, which the IOTEDGE_LOG environment
+    /// variable overrides. It logs at info level if neither is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_level: Option<LogLevel>,
+    provisioning: Provisioning,
+    agent: Agent<T>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    runtime_modules: Vec<ModuleSpec<T>>,
+    hostname: String,
+    connect: Connect,
+    listen: Listen,
+    homedir: PathBuf,
+    moby_runtime: MobyRuntime,
+    certificates: Option<Certificates>,
+    #[serde(
+        default = "default_workload_ca_validity_secs",
+        skip_serializing_if = "is_default_workload_ca_validity_secs"
+    )]
+    workload_ca_validity_secs: u64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    watchdog: WatchdogSettings,
+    #[serde(default, skip_serializing_if = "is_default")]
+    certificate_expiry: CertificateExpirySettings,
+    #[serde(default, skip_serializing_if = "is_default")]
+    hsm: Hsm,
+    #[serde(default, skip_serializing_if = "is_default")]
+    proxy: Proxy,
+    #[serde(default, skip_serializing_if = "is_default")]
+    timezone: Timezone,
+    /// The edge hub picks its own default protocol if this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    upstream_protocol: Option<UpstreamProtocol>,
+    /// The edge runtime modules log at their default level if this is not
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    runtime_log_level: Option<RuntimeLogLevel>,
+    /// Common name of the edge CA certificate, for when it differs from
+    /// `hostname`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    edge_ca_common_name: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    auth_scheme: AuthScheme,
+    /// Added to the daemon version in the product information given to the
+    /// edge runtime modules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    product_info: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    shutdown: Shutdown,
+    /// Whether the logs of the modules are forwarded to the log of the
+    /// daemon.
+    #[serde(default, skip_serializing_if = "is_default")]
+    forward_module_logs: bool,
+}
+
+impl<T> Settings<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    pub fn new(filename: Option<&str>) -> Result<Self, Error> {
+        let mut config = Config::default();
+        config
+            .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
+            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+        if let Some(file) = filename {
+            config
+                .merge(File::with_name(file).required(true))
+                .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+        }
+
+        config
+            .merge(Environment::with_prefix("iotedge"))
+            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+
+        let settings: Self = config
+            .try_into()
+            .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+        check_certificate_validity(settings.workload_ca_validity_secs)?;
+        check_agent_api_version(settings.agent_api_version())?;
+        check_connect_uri(
+            settings.connect.workload_uri(),
+            settings.listen.workload_uri(),
+        )?;
+        check_connect_uri(
+            settings.connect.management_uri(),
+            settings.listen.management_uri(),
+        )?;
+        check_listen_threads(settings.listen.workload_threads())?;
+        check_listen_threads(settings.listen.management_threads())?;
+        check_listen_backlog(settings.listen.backlog())?;
+        check_tcp_keepalive(settings.listen.tcp_keepalive())?;
+        check_max_connections(settings.listen.max_connections())?;
+        check_socket_mode(settings.listen.socket_mode.as_ref().map(AsRef::as_ref))?;
+        check_rate_limit(settings.listen.management_rate_limit())?;
+        if let Some(external) = settings.listen.management_external() {
+            check_external_management_uri(external.uri(), settings.listen.management_uri())?;
+            check_rate_limit(external.rate_limit())?;
+        }
+        check_cors(settings.listen.management_cors())?;
+        check_management_auth(settings.listen.management_auth())?;
+        check_shutdown_signals(&settings.shutdown.signals())?;
+
+        Ok(settings)
+    }
+
+    pub fn log_level(&self) -> Option<LogLevel> {
+        self.log_level
+    }
+
+    pub fn provisioning(&self) -> &Provisioning {
+        &self.provisioning
+    }
+
+    pub fn agent(&self) -> &ModuleSpec<T> {
+        &self.agent.spec
+    }
+
+    pub fn agent_mut(&mut self) -> &mut ModuleSpec<T> {
+        &mut self.agent.spec
+    }
+
+    pub fn agent_module_id(&self) -> &str {
+        &self.agent.module_id
+    }
+
+    pub fn agent_restart_policy(&self) -> Option<RestartPolicy> {
+        self.agent.restart_policy
+    }
+
+    pub fn agent_extra_env(&self) -> &BTreeMap<String, String> {
+        &self.agent.extra_env
+    }
+
+    pub fn agent_override_reserved_env(&self) -> bool {
+        self.agent.override_reserved_env
+    }
+
+    pub fn agent_api_version(&self) -> Option<&str> {
+        self.agent.api_version.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn agent_network_id_env_key(&self) -> Option<&str> {
+        self.agent.network_id_env_key.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn agent_env_filter(&self) -> &EnvFilter {
+        &self.agent.env_filter
+    }
+
+    pub fn runtime_modules(&self) -> &[ModuleSpec<T>] {
+        &self.runtime_modules
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn connect(&self) -> &Connect {
+        &self.connect
+    }
+
+    pub fn listen(&self) -> &Listen {
+        &self.listen
+    }
+
+    pub fn homedir(&self) -> &Path {
+        &self.homedir
+    }
+
+    pub fn moby_runtime(&self) -> &MobyRuntime {
+        &self.moby_runtime
+    }
+
+    pub fn certificates(&self) -> Option<&Certificates> {
+        self.certificates.as_ref()
+    }
+
+    /// Validity in seconds of the workload CA certificate the daemon issues.
+    pub fn workload_ca_validity_secs(&self) -> u64 {
+        self.workload_ca_validity_secs
+    }
+
+    pub fn watchdog(&self) -> &WatchdogSettings {
+        &self.watchdog
+    }
+
+    pub fn certificate_expiry(&self) -> &CertificateExpirySettings {
+        &self.certificate_expiry
+    }
+
+    pub fn hsm(&self) -> &Hsm {
+        &self.hsm
+    }
+
+    pub fn proxy(&self) -> &Proxy {
+        &self.proxy
+    }
+
+    pub fn timezone(&self) -> &Timezone {
+        &self.timezone
+    }
+
+    pub fn upstream_protocol(&self) -> Option<UpstreamProtocol> {
+        self.upstream_protocol
+    }
+
+    pub fn runtime_log_level(&self) -> Option<RuntimeLogLevel> {
+        self.runtime_log_level
+    }
+
+    pub fn edge_ca_common_name(&self) -> Option<&str> {
+        self.edge_ca_common_name.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn auth_scheme(&self) -> AuthScheme {
+        self.auth_scheme
+    }
+
+    pub fn product_info(&self) -> Option<&str> {
+        self.product_info.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    pub fn forward_module_logs(&self) -> bool {
+        self.forward_module_logs
+    }
+
+    pub fn diff_with_cached(&self, path: PathBuf) -> Result<bool, Error> {
+        OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(|err| err.context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings)))
+            .and_then(|mut file: FsFile| {
+                let mut buffer = String::new();
+                file.read_to_string(&mut buffer)
+                    .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+                let s = serde_json::to_string(self)
+                    .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
+                let s = Sha256::digest_str(&s);
+                let encoded = base64::encode(&s);
+                if encoded == buffer {
+                    debug!("Config state matches supplied config.");
+                    Ok(false)
+                } else {
+                    Ok(true)
+                }
+            })
+            .or_else(|err| {
+                log_failure(Level::Debug, &err);
+                debug!("Error reading config backup.");
+                Ok(true)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Config, File, FileFormat};
+    use edgelet_docker::DockerConfig;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    #[cfg(unix)]
+    static GOOD_SETTINGS: &str = "test/linux/sample_settings.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS1: &str = "test/linux/sample_settings1.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS: &str = "test/linux/bad_sample_settings.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_TG: &str = "test/linux/sample_settings.tg.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_LABELS: &str = "test/linux/sample_settings.labels.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_CA_VALIDITY: &str = "test/linux/sample_settings.ca_validity.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_ENCRYPTION: &str = "test/linux/sample_settings.encryption.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_CERT_KEY_TYPE: &str = "test/linux/sample_settings.cert_key_type.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_TPM_DEVICE: &str = "test/linux/sample_settings.tpm_device.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_CONTENT_TRUST: &str = "test/linux/sample_settings.content_trust.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_CRL: &str = "test/linux/sample_settings.crl.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_EXTERNAL_MANAGEMENT: &str =
+        "test/linux/sample_settings.external_management.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_EXTERNAL_MANAGEMENT: &str =
+        "test/linux/bad_sample_settings.external_management.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_RATE_LIMIT: &str = "test/linux/sample_settings.rate_limit.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_RATE_LIMIT: &str = "test/linux/bad_sample_settings.rate_limit.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_REQUEST_LOG: &str = "test/linux/sample_settings.request_log.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_REQUEST_LOG: &str = "test/linux/bad_sample_settings.request_log.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_CORS: &str = "test/linux/sample_settings.cors.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_CORS: &str = "test/linux/bad_sample_settings.cors.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_MANAGEMENT_AUTH: &str = "test/linux/sample_settings.management_auth.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_MANAGEMENT_AUTH_FILE: &str =
+        "test/linux/sample_settings.management_auth_file.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_MANAGEMENT_AUTH: &str =
+        "test/linux/bad_sample_settings.management_auth.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_FORWARD_MODULE_LOGS: &str =
+        "test/linux/sample_settings.forward_module_logs.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_SHUTDOWN: &str = "test/linux/sample_settings.shutdown.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_SHUTDOWN: &str = "test/linux/bad_sample_settings.shutdown.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_MAX_CONNECTIONS: &str = "test/linux/sample_settings.max_connections.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_MAX_CONNECTIONS: &str =
+        "test/linux/bad_sample_settings.max_connections.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_TCP_KEEPALIVE: &str = "test/linux/sample_settings.tcp_keepalive.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_TCP_KEEPALIVE: &str = "test/linux/bad_sample_settings.tcp_keepalive.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_SOCKET_MODE: &str = "test/linux/sample_settings.socket_mode.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_SOCKET_MODE: &str = "test/linux/bad_sample_settings.socket_mode.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_WORKLOAD_TLS: &str = "test/linux/sample_settings.workload_tls.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_MANAGEMENT_TLS: &str = "test/linux/sample_settings.management_tls.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_LISTEN_BACKLOG: &str = "test/linux/sample_settings.listen_backlog.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_LISTEN_BACKLOG: &str = "test/linux/bad_sample_settings.listen_backlog.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_LISTEN_THREADS: &str = "test/linux/sample_settings.listen_threads.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_LISTEN_THREADS: &str = "test/linux/bad_sample_settings.listen_threads.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_PRODUCT_INFO: &str = "test/linux/sample_settings.product_info.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_AUTH_SCHEME: &str = "test/linux/sample_settings.auth_scheme.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_AUTH_SCHEME: &str = "test/linux/bad_sample_settings.auth_scheme.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_EDGE_CA_COMMON_NAME: &str =
+        "test/linux/sample_settings.edge_ca_common_name.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_ENV_FILTER: &str = "test/linux/sample_settings.env_filter.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_AGENT_API_VERSION: &str = "test/linux/sample_settings.api_version.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_AGENT_API_VERSION: &str = "test/linux/bad_sample_settings.api_version.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_UPSTREAM_PROTOCOL: &str =
+        "test/linux/sample_settings.upstream_protocol.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_UPSTREAM_PROTOCOL: &str =
+        "test/linux/bad_sample_settings.upstream_protocol.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_LOG_LEVEL: &str = "test/linux/sample_settings.log_level.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_LOG_LEVEL: &str = "test/linux/bad_sample_settings.log_level.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_RUNTIME_LOG_LEVEL: &str =
+        "test/linux/sample_settings.runtime_log_level.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS_RUNTIME_LOG_LEVEL: &str =
+        "test/linux/bad_sample_settings.runtime_log_level.yaml";
+
+    #[cfg(windows)]
+    static GOOD_SETTINGS: &str = "test/windows/sample_settings.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS1: &str = "test/windows/sample_settings1.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS: &str = "test/windows/bad_sample_settings.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_TG: &str = "test/windows/sample_settings.tg.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_LABELS: &str = "test/windows/sample_settings.labels.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_CA_VALIDITY: &str = "test/windows/sample_settings.ca_validity.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_ENCRYPTION: &str = "test/windows/sample_settings.encryption.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_CERT_KEY_TYPE: &str = "test/windows/sample_settings.cert_key_type.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_TPM_DEVICE: &str = "test/windows/sample_settings.tpm_device.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_CONTENT_TRUST: &str = "test/windows/sample_settings.content_trust.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_CRL: &str = "test/windows/sample_settings.crl.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_EXTERNAL_MANAGEMENT: &str =
+        "test/windows/sample_settings.external_management.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_EXTERNAL_MANAGEMENT: &str =
+        "test/windows/bad_sample_settings.external_management.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_RATE_LIMIT: &str = "test/windows/sample_settings.rate_limit.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_RATE_LIMIT: &str = "test/windows/bad_sample_settings.rate_limit.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_REQUEST_LOG: &str = "test/windows/sample_settings.request_log.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_REQUEST_LOG: &str = "test/windows/bad_sample_settings.request_log.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_CORS: &str = "test/windows/sample_settings.cors.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_CORS: &str = "test/windows/bad_sample_settings.cors.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_MANAGEMENT_AUTH: &str =
+        "test/windows/sample_settings.management_auth.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_MANAGEMENT_AUTH_FILE: &str =
+        "test/windows/sample_settings.management_auth_file.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_MANAGEMENT_AUTH: &str =
+        "test/windows/bad_sample_settings.management_auth.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_FORWARD_MODULE_LOGS: &str =
+        "test/windows/sample_settings.forward_module_logs.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_SHUTDOWN: &str = "test/windows/sample_settings.shutdown.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_SHUTDOWN: &str = "test/windows/bad_sample_settings.shutdown.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_MAX_CONNECTIONS: &str =
+        "test/windows/sample_settings.max_connections.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_MAX_CONNECTIONS: &str =
+        "test/windows/bad_sample_settings.max_connections.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_TCP_KEEPALIVE: &str = "test/windows/sample_settings.tcp_keepalive.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_TCP_KEEPALIVE: &str = "test/windows/bad_sample_settings.tcp_keepalive.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_SOCKET_MODE: &str = "test/windows/sample_settings.socket_mode.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_SOCKET_MODE: &str = "test/windows/bad_sample_settings.socket_mode.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_WORKLOAD_TLS: &str = "test/windows/sample_settings.workload_tls.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_MANAGEMENT_TLS: &str = "test/windows/sample_settings.management_tls.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_LISTEN_BACKLOG: &str = "test/windows/sample_settings.listen_backlog.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_LISTEN_BACKLOG: &str =
+        "test/windows/bad_sample_settings.listen_backlog.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_LISTEN_THREADS: &str = "test/windows/sample_settings.listen_threads.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_LISTEN_THREADS: &str =
+        "test/windows/bad_sample_settings.listen_threads.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_PRODUCT_INFO: &str = "test/windows/sample_settings.product_info.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_AUTH_SCHEME: &str = "test/windows/sample_settings.auth_scheme.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_AUTH_SCHEME: &str = "test/windows/bad_sample_settings.auth_scheme.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_EDGE_CA_COMMON_NAME: &str =
+        "test/windows/sample_settings.edge_ca_common_name.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_ENV_FILTER: &str = "test/windows/sample_settings.env_filter.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_AGENT_API_VERSION: &str = "test/windows/sample_settings.api_version.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_AGENT_API_VERSION: &str =
+        "test/windows/bad_sample_settings.api_version.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_UPSTREAM_PROTOCOL: &str =
+        "test/windows/sample_settings.upstream_protocol.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_UPSTREAM_PROTOCOL: &str =
+        "test/windows/bad_sample_settings.upstream_protocol.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_LOG_LEVEL: &str = "test/windows/sample_settings.log_level.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_LOG_LEVEL: &str = "test/windows/bad_sample_settings.log_level.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_RUNTIME_LOG_LEVEL: &str =
+        "test/windows/sample_settings.runtime_log_level.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS_RUNTIME_LOG_LEVEL: &str =
+        "test/windows/bad_sample_settings.runtime_log_level.yaml";
+
+    fn unwrap_manual_provisioning(p: &Provisioning) -> String {
+        match p {
+            Provisioning::Manual(manual) => manual.device_connection_string().to_string(),
+            _ => "not implemented".to_string(),
+        }
+    }
+
+    #[test]
+    fn default_in_yaml_matches_constant() {
+        let mut config = Config::default();
+        config
+            .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
+            .unwrap();
+        let settings: Settings<DockerConfig> = config.try_into().unwrap();
+
+        match settings.provisioning() {
+            Provisioning::Manual(ref manual) => {
+                assert_eq!(manual.device_connection_string(), DEFAULT_CONNECTION_STRING)
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn no_file_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some("garbage"));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn bad_file_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn manual_file_gets_sample_connection_string() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS));
+        println!("{:?}", settings);
+        assert!(settings.is_ok());
+        let s = settings.unwrap();
         let p = s.provisioning();
         let connection_string = unwrap_manual_provisioning(p);
         assert_eq!(
-            connection_string,
-            "HostName=something.something.com;DeviceId=something;SharedAccessKey=something"
+            connection_string,
+            "HostName=something.something.com;DeviceId=something;SharedAccessKey=something"
+        );
+    }
+
+    #[test]
+    fn manual_file_gets_sample_tg_paths() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG));
+        println!("{:?}", settings);
+        assert!(settings.is_ok());
+        let s = settings.unwrap();
+        let certificates = s.certificates();
+        certificates
+            .map(|c| {
+                assert_eq!(c.device_ca_cert().to_str().unwrap(), "device_ca_cert.pem");
+                assert_eq!(c.device_ca_pk().to_str().unwrap(), "device_ca_pk.pem");
+                assert_eq!(
+                    c.trusted_ca_certs().to_str().unwrap(),
+                    "trusted_ca_certs.pem"
+                );
+            })
+            .expect("certificates not configured");
+    }
+
+    #[test]
+    fn diff_with_same_cached_returns_false() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        let settings_to_write = serde_json::to_string(&settings).unwrap();
+        let sha_to_write = Sha256::digest_str(&settings_to_write);
+        let base64_to_write = base64::encode(&sha_to_write);
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(base64_to_write.as_bytes())
+            .unwrap();
+        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+    }
+
+    #[test]
+    fn diff_with_different_cached_returns_true() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let settings1 = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS1)).unwrap();
+        let settings_to_write = serde_json::to_string(&settings1).unwrap();
+        let sha_to_write = Sha256::digest_str(&settings_to_write);
+        let base64_to_write = base64::encode(&sha_to_write);
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(base64_to_write.as_bytes())
+            .unwrap();
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(settings.diff_with_cached(path).unwrap(), true);
+    }
+
+    #[test]
+    fn settings_without_newer_options_serialize_as_before() {
+        // The settings hash is taken over this, so settings that leave every
+        // newer option unset must serialize as they did before those options
+        // existed. Otherwise upgrading the daemon reconfigures the device,
+        // which destroys the master encryption key.
+        #[cfg(unix)]
+        let homedir = "/tmp";
+        #[cfg(windows)]
+        let homedir = r"C:\\Temp";
+        let expected = [
+            r#"{"provisioning":{"source":"manual","device_connection_string":"HostName=something.something.com;DeviceId=something;SharedAccessKey=something"},"#,
+            r#""agent":{"name":"edgeAgent","type":"docker","config":{"image":"microsoft/azureiotedge-agent:1.0","createOptions":{},"auth":{}},"env":{}},"#,
+            r#""hostname":"localhost","#,
+            r#""connect":{"workload_uri":"http://localhost:8081/","management_uri":"http://localhost:8080/"},"#,
+            r#""listen":{"workload_uri":"http://0.0.0.0:8081/","management_uri":"http://0.0.0.0:8080/"},"#,
+            r#""homedir":""#,
+            homedir,
+            r#"","moby_runtime":{"uri":"http://localhost:2375/","network":"azure-iot-edge"},"#,
+            r#""certificates":null}"#,
+        ]
+        .concat();
+
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(expected, serde_json::to_string(&settings).unwrap());
+    }
+
+    #[test]
+    fn diff_with_no_file_returns_true() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(
+            settings
+                .diff_with_cached(PathBuf::from("i dont exist"))
+                .unwrap(),
+            true
+        );
+    }
+
+    #[test]
+    fn network_default() {
+        let moby1: MobyRuntime =
+            serde_json::from_str(r#"{ "uri": "http://test", "network": "" }"#).unwrap();
+        assert_eq!(DEFAULT_NETWORKID, moby1.network());
+
+        let moby2: MobyRuntime =
+            serde_json::from_str(r#"{ "uri": "http://test", "network": "some-network" }"#).unwrap();
+        assert_eq!("some-network", moby2.network());
+    }
+
+    #[test]
+    fn moby_runtime_init_retries_have_defaults() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(DEFAULT_INIT_RETRIES, settings.moby_runtime().init_retries());
+        assert_eq!(
+            Duration::from_secs(DEFAULT_INIT_RETRY_INTERVAL_SECS),
+            settings.moby_runtime().init_retry_interval()
+        );
+    }
+
+    #[test]
+    fn runtime_modules_default_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.runtime_modules().is_empty());
+    }
+
+    #[test]
+    fn agent_restart_policy_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.agent_restart_policy());
+    }
+
+    #[test]
+    fn agent_module_id_defaults_to_edge_agent() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!("edgeAgent", settings.agent().name());
+        assert_eq!("$edgeAgent", settings.agent_module_id());
+    }
+
+    #[test]
+    fn agent_extra_env_defaults_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.agent_extra_env().is_empty());
+        assert!(!settings.agent_override_reserved_env());
+    }
+
+    fn check_connect(connect_uri: &str, listen_uri: &str) -> bool {
+        check_connect_uri(
+            &Url::parse(connect_uri).unwrap(),
+            &Url::parse(listen_uri).unwrap(),
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn connect_uri_must_reach_listen_uri() {
+        assert!(check_connect(
+            "http://localhost:8081",
+            "http://0.0.0.0:8081"
+        ));
+        assert!(check_connect(
+            "http://edge.example.com:8081",
+            "http://0.0.0.0:8081"
+        ));
+        assert!(check_connect(
+            "unix:///var/run/iotedge/workload.sock",
+            "unix:///var/run/iotedge/workload.sock"
+        ));
+        assert!(check_connect(
+            "unix:///var/run/iotedge/workload.sock",
+            "fd://iotedge.workload.socket"
+        ));
+        assert!(check_connect(
+            "npipe://./pipe/iotedge_workload",
+            "npipe://./pipe/iotedge_workload"
+        ));
+
+        assert!(!check_connect("http://0.0.0.0:8081", "http://0.0.0.0:8081"));
+        assert!(!check_connect("http://[::]:8081", "http://[::]:8081"));
+        assert!(!check_connect(
+            "unix:///var/run/iotedge/other.sock",
+            "unix:///var/run/iotedge/workload.sock"
+        ));
+        assert!(!check_connect(
+            "http://localhost:8081",
+            "unix:///var/run/iotedge/workload.sock"
+        ));
+        assert!(!check_connect(
+            "npipe://./pipe/iotedge_other",
+            "npipe://./pipe/iotedge_workload"
+        ));
+        assert!(!check_connect(
+            "http://localhost:8081",
+            "npipe://./pipe/iotedge_workload"
+        ));
+    }
+
+    #[test]
+    fn agent_env_filter_defaults_to_allow_all() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        let env_filter = settings.agent_env_filter();
+        assert_eq!(None, env_filter.allowlist());
+        assert!(env_filter.denylist().is_empty());
+        assert!(env_filter.allows("anything"));
+    }
+
+    #[test]
+    fn agent_env_filter_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_ENV_FILTER)).unwrap();
+        let env_filter = settings.agent_env_filter();
+        assert_eq!(
+            Some(&["allowed_key".to_string(), "denied_key".to_string()][..]),
+            env_filter.allowlist()
+        );
+        assert_eq!(&["denied_key".to_string()][..], env_filter.denylist());
+    }
+
+    #[test]
+    fn env_filter_denylist_wins_over_allowlist() {
+        let env_filter = EnvFilter {
+            allowlist: Some(vec!["allowed".to_string(), "denied".to_string()]),
+            denylist: vec!["denied".to_string()],
+        };
+        assert!(env_filter.allows("allowed"));
+        assert!(!env_filter.allows("denied"));
+        assert!(!env_filter.allows("unlisted"));
+    }
+
+    #[test]
+    fn agent_network_id_env_key_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.agent_network_id_env_key());
+    }
+
+    #[test]
+    fn agent_api_version_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.agent_api_version());
+    }
+
+    #[test]
+    fn agent_api_version_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_AGENT_API_VERSION)).unwrap();
+        assert_eq!(Some("2018-06-28"), settings.agent_api_version());
+    }
+
+    #[test]
+    fn unsupported_agent_api_version_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_AGENT_API_VERSION));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn agent_api_version_must_be_supported() {
+        assert!(check_agent_api_version(None).is_ok());
+        assert!(check_agent_api_version(Some("2018-06-28")).is_ok());
+        assert!(check_agent_api_version(Some("2017-01-01")).is_err());
+    }
+
+    #[test]
+    fn moby_runtime_registries_default_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().registries().is_empty());
+    }
+
+    #[test]
+    fn proxy_defaults_to_unset() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.proxy().https_proxy());
+        assert_eq!(None, settings.proxy().http_proxy());
+        assert_eq!(None, settings.proxy().no_proxy());
+    }
+
+    #[test]
+    fn timezone_defaults_to_unset() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.timezone().tz());
+        assert!(!settings.timezone().forward_host());
+    }
+
+    #[test]
+    fn workload_ca_validity_defaults_to_90_days() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(7_776_000, settings.workload_ca_validity_secs());
+    }
+
+    #[test]
+    fn workload_ca_validity_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_CA_VALIDITY)).unwrap();
+        assert_eq!(86400, settings.workload_ca_validity_secs());
+    }
+
+    #[test]
+    fn certificate_validity_outside_hsm_bounds_is_rejected() {
+        assert!(check_certificate_validity(1).is_ok());
+        assert!(check_certificate_validity(u64::from(u32::max_value() / 2)).is_ok());
+        for validity in &[0, u64::from(u32::max_value()), u64::max_value()] {
+            match check_certificate_validity(*validity).unwrap_err().kind() {
+                ErrorKind::Initialize(InitializeErrorReason::InvalidCertificateValidity) => (),
+                kind => panic!(
+                    "Expected `InvalidCertificateValidity` but got {:?} for {}",
+                    kind, validity
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn encryption_algorithm_defaults_to_aes_256_gcm() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(
+            EncryptionAlgorithm::Aes256Gcm,
+            settings.hsm().encryption_algorithm()
+        );
+    }
+
+    #[test]
+    fn encryption_algorithm_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_ENCRYPTION)).unwrap();
+        assert_eq!(
+            EncryptionAlgorithm::Chacha20Poly1305,
+            settings.hsm().encryption_algorithm()
+        );
+        assert_eq!(
+            "chacha20-poly1305",
+            settings.hsm().encryption_algorithm().to_string()
+        );
+    }
+
+    #[test]
+    fn cert_key_type_defaults_to_issuer_key_type() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.hsm().cert_key_type());
+    }
+
+    #[test]
+    fn cert_key_type_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_CERT_KEY_TYPE)).unwrap();
+        assert_eq!(
+            Some(CertificateKeyType::EcP256),
+            settings.hsm().cert_key_type()
+        );
+        assert_eq!(
+            "ec-p256",
+            settings.hsm().cert_key_type().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn auth_scheme_defaults_to_sas_token() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(AuthScheme::SasToken, settings.auth_scheme());
+        assert_eq!("sasToken", settings.auth_scheme().to_string());
+    }
+
+    #[test]
+    fn auth_scheme_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_AUTH_SCHEME)).unwrap();
+        assert_eq!(AuthScheme::X509Cert, settings.auth_scheme());
+        assert_eq!("x509Cert", settings.auth_scheme().to_string());
+    }
+
+    #[test]
+    fn unknown_auth_scheme_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_AUTH_SCHEME));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn product_info_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.product_info());
+    }
+
+    #[test]
+    fn product_info_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_PRODUCT_INFO)).unwrap();
+        assert_eq!(Some("contoso-gateway/2.1"), settings.product_info());
+    }
+
+    #[test]
+    fn listen_threads_default_to_one() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(1, settings.listen().workload_threads());
+        assert_eq!(1, settings.listen().management_threads());
+    }
+
+    #[test]
+    fn listen_threads_are_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_LISTEN_THREADS)).unwrap();
+        assert_eq!(4, settings.listen().workload_threads());
+        assert_eq!(2, settings.listen().management_threads());
+    }
+
+    #[test]
+    fn zero_listen_threads_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_LISTEN_THREADS));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn listen_backlog_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.listen().backlog());
+    }
+
+    #[test]
+    fn listen_backlog_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_LISTEN_BACKLOG)).unwrap();
+        assert_eq!(Some(1024), settings.listen().backlog());
+    }
+
+    #[test]
+    fn zero_listen_backlog_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_LISTEN_BACKLOG));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn listen_tls_defaults_to_false() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.listen().management_tls());
+        assert!(!settings.listen().workload_tls());
+    }
+
+    #[test]
+    fn management_tls_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_MANAGEMENT_TLS)).unwrap();
+        assert!(settings.listen().management_tls());
+        assert!(!settings.listen().workload_tls());
+    }
+
+    #[test]
+    fn socket_mode_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.listen().socket_mode());
+    }
+
+    #[test]
+    fn socket_mode_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_SOCKET_MODE)).unwrap();
+        assert_eq!(Some(0o660), settings.listen().socket_mode());
+    }
+
+    #[test]
+    fn invalid_socket_mode_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_SOCKET_MODE));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn socket_mode_must_be_octal_permissions() {
+        assert!(check_socket_mode(None).is_ok());
+        assert!(check_socket_mode(Some("0660")).is_ok());
+        assert!(check_socket_mode(Some("600")).is_ok());
+        assert!(check_socket_mode(Some("0888")).is_err());
+        assert!(check_socket_mode(Some("7777")).is_err());
+        assert!(check_socket_mode(Some("rw-rw----")).is_err());
+    }
+
+    #[test]
+    fn management_rate_limit_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.listen().management_rate_limit().is_none());
+    }
+
+    #[test]
+    fn management_rate_limit_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_RATE_LIMIT)).unwrap();
+        let rate_limit = settings.listen().management_rate_limit().unwrap();
+        assert_eq!(20, rate_limit.requests());
+        assert_eq!(Duration::from_secs(10), rate_limit.period());
+    }
+
+    #[test]
+    fn zero_management_rate_limit_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_RATE_LIMIT));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn management_cors_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.listen().management_cors().is_none());
+    }
+
+    #[test]
+    fn management_cors_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_CORS)).unwrap();
+        let cors = settings.listen().management_cors().unwrap();
+        assert_eq!(
+            &["https://dashboard.example.com".to_string()],
+            cors.origins()
+        );
+        assert_eq!(vec![Method::GET, Method::POST], cors.methods());
+    }
+
+    #[test]
+    fn management_cors_origin_with_path_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_CORS));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn management_auth_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.listen().management_auth().is_none());
+    }
+
+    #[test]
+    fn management_auth_token_is_read_from_settings_or_token_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_MANAGEMENT_AUTH)).unwrap();
+        let auth = settings.listen().management_auth().unwrap();
+        assert_eq!("s3cr3t", auth.token().unwrap());
+        assert!(auth.allow_unauthenticated_unix());
+
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_MANAGEMENT_AUTH_FILE)).unwrap();
+        let auth = settings.listen().management_auth().unwrap();
+        assert_eq!("s3cr3t", auth.token().unwrap());
+        assert!(!auth.allow_unauthenticated_unix());
+    }
+
+    #[test]
+    fn management_auth_with_token_and_token_file_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_MANAGEMENT_AUTH));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn forward_module_logs_defaults_to_false() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.forward_module_logs());
+    }
+
+    #[test]
+    fn forward_module_logs_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_FORWARD_MODULE_LOGS)).unwrap();
+        assert!(settings.forward_module_logs());
+    }
+
+    #[test]
+    fn shutdown_defaults_to_signals() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(signal::default_signals(), settings.shutdown().signals());
+        assert_eq!(None, settings.shutdown().file());
+        assert_eq!(1, settings.shutdown().triggers().len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_triggers_are_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_SHUTDOWN)).unwrap();
+        let shutdown = settings.shutdown();
+        assert_eq!(
+            vec!["SIGTERM".to_string(), "SIGUSR1".to_string()],
+            shutdown.signals()
+        );
+        assert_eq!(
+            Some(Path::new("/var/run/iotedge/shutdown")),
+            shutdown.file()
+        );
+        assert_eq!(
+            Some(Path::new("/var/run/iotedge/shutdown.fifo")),
+            shutdown.named_pipe()
         );
+        assert_eq!(3, shutdown.triggers().len());
     }
 
+    #[cfg(windows)]
     #[test]
-    fn manual_file_gets_sample_tg_paths() {
-        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG));
-        println!("{:?}", settings);
-        assert!(settings.is_ok());
-        let s = settings.unwrap();
-        let certificates = s.certificates();
-        certificates
-            .map(|c| {
-                assert_eq!(c.device_ca_cert().to_str().unwrap(), "device_ca_cert.pem");
-                assert_eq!(c.device_ca_pk().to_str().unwrap(), "device_ca_pk.pem");
-                assert_eq!(
-                    c.trusted_ca_certs().to_str().unwrap(),
-                    "trusted_ca_certs.pem"
-                );
-            })
-            .expect("certificates not configured");
+    fn shutdown_triggers_are_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_SHUTDOWN)).unwrap();
+        let shutdown = settings.shutdown();
+        assert!(shutdown.signals().is_empty());
+        assert_eq!(
+            Some(Path::new("C:/ProgramData/iotedge/shutdown")),
+            shutdown.file()
+        );
+        assert_eq!(2, shutdown.triggers().len());
     }
 
     #[test]
-    fn diff_with_same_cached_returns_false() {
-        let tmp_dir = TempDir::new("blah").unwrap();
-        let path = tmp_dir.path().join("cache");
+    fn unknown_shutdown_signal_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_SHUTDOWN));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn max_connections_defaults_to_none() {
         let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
-        let settings_to_write = serde_json::to_string(&settings).unwrap();
-        let sha_to_write = Sha256::digest_str(&settings_to_write);
-        let base64_to_write = base64::encode(&sha_to_write);
-        FsFile::create(path.clone())
-            .unwrap()
-            .write_all(base64_to_write.as_bytes())
-            .unwrap();
-        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+        assert_eq!(None, settings.listen().max_connections());
     }
 
     #[test]
-    fn diff_with_different_cached_returns_true() {
-        let tmp_dir = TempDir::new("blah").unwrap();
-        let path = tmp_dir.path().join("cache");
-        let settings1 = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS1)).unwrap();
-        let settings_to_write = serde_json::to_string(&settings1).unwrap();
-        let sha_to_write = Sha256::digest_str(&settings_to_write);
-        let base64_to_write = base64::encode(&sha_to_write);
-        FsFile::create(path.clone())
-            .unwrap()
-            .write_all(base64_to_write.as_bytes())
-            .unwrap();
+    fn max_connections_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_MAX_CONNECTIONS)).unwrap();
+        assert_eq!(Some(64), settings.listen().max_connections());
+    }
+
+    #[test]
+    fn zero_max_connections_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_MAX_CONNECTIONS));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn tcp_keepalive_defaults_to_none() {
         let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
-        assert_eq!(settings.diff_with_cached(path).unwrap(), true);
+        assert!(settings.listen().tcp_keepalive().is_none());
     }
 
     #[test]
-    fn diff_with_no_file_returns_true() {
+    fn tcp_keepalive_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TCP_KEEPALIVE)).unwrap();
+        let keepalive = settings.listen().tcp_keepalive().unwrap();
+        assert_eq!(Duration::from_secs(30), keepalive.idle());
+        assert_eq!(Some(Duration::from_secs(5)), keepalive.interval());
+    }
+
+    #[test]
+    fn zero_tcp_keepalive_interval_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_TCP_KEEPALIVE));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn request_log_defaults_to_plain() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(LogFormat::Plain, settings.listen().request_log().format());
+    }
+
+    #[test]
+    fn request_log_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_REQUEST_LOG)).unwrap();
+        assert_eq!(
+            LogFormat::Json(vec![
+                LogField::Time,
+                LogField::Request,
+                LogField::Status,
+                LogField::Pid,
+            ]),
+            settings.listen().request_log().format()
+        );
+    }
+
+    #[test]
+    fn json_request_log_has_all_fields_by_default() {
+        let request_log: RequestLog = serde_json::from_str(r#"{"format": "json"}"#).unwrap();
+        assert_eq!(LogFormat::Json(LogField::all()), request_log.format());
+    }
+
+    #[test]
+    fn unknown_request_log_field_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_REQUEST_LOG));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn external_management_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.listen().management_external().is_none());
+    }
+
+    #[test]
+    fn external_management_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_EXTERNAL_MANAGEMENT)).unwrap();
+        let external = settings.listen().management_external().unwrap();
+        assert_eq!("http://0.0.0.0:8090/", external.uri().as_str());
+        assert!(external.tls());
+        assert_eq!(50, external.rate_limit().unwrap().requests());
+        assert!(!settings.listen().management_tls());
+        assert!(settings.listen().management_rate_limit().is_none());
+    }
+
+    #[test]
+    fn external_management_on_primary_uri_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_EXTERNAL_MANAGEMENT));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn workload_tls_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_WORKLOAD_TLS)).unwrap();
+        assert!(settings.listen().workload_tls());
+    }
+
+    #[test]
+    fn upstream_protocol_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.upstream_protocol());
+    }
+
+    #[test]
+    fn upstream_protocol_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_UPSTREAM_PROTOCOL)).unwrap();
+        assert_eq!(Some(UpstreamProtocol::AmqpWs), settings.upstream_protocol());
+        assert_eq!("AmqpWs", settings.upstream_protocol().unwrap().to_string());
+    }
+
+    #[test]
+    fn unknown_upstream_protocol_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_UPSTREAM_PROTOCOL));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn log_level_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.log_level());
+    }
+
+    #[test]
+    fn log_level_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_LOG_LEVEL)).unwrap();
+        assert_eq!(Some(LogLevel::Debug), settings.log_level());
+        assert_eq!(Level::Debug, settings.log_level().unwrap().level());
+    }
+
+    #[test]
+    fn unknown_log_level_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_LOG_LEVEL));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn runtime_log_level_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.runtime_log_level());
+    }
+
+    #[test]
+    fn runtime_log_level_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_RUNTIME_LOG_LEVEL)).unwrap();
+        assert_eq!(Some(RuntimeLogLevel::Debug), settings.runtime_log_level());
+        assert_eq!("debug", settings.runtime_log_level().unwrap().to_string());
+    }
+
+    #[test]
+    fn unknown_runtime_log_level_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS_RUNTIME_LOG_LEVEL));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn edge_ca_common_name_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.edge_ca_common_name());
+    }
+
+    #[test]
+    fn edge_ca_common_name_is_read_from_file() {
+        let settings =
+            Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_EDGE_CA_COMMON_NAME)).unwrap();
+        assert_eq!(Some("Edge.Example.com"), settings.edge_ca_common_name());
+    }
+
+    #[test]
+    fn tpm_device_path_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.hsm().tpm_device_path());
+    }
+
+    #[test]
+    fn tpm_device_path_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TPM_DEVICE)).unwrap();
+        assert_eq!(
+            Some(Path::new("/var/run/tpm-simulator")),
+            settings.hsm().tpm_device_path()
+        );
+    }
+
+    #[test]
+    fn crl_path_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.hsm().crl_path());
+    }
+
+    #[test]
+    fn crl_path_is_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_CRL)).unwrap();
+        assert_eq!(
+            Some(Path::new("/var/lib/iotedge/crl.pem")),
+            settings.hsm().crl_path()
+        );
+    }
+
+    #[test]
+    fn certificate_expiry_defaults_to_two_weeks_checked_hourly() {
         let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
         assert_eq!(
+            Duration::from_secs(14 * 24 * 3600),
+            settings.certificate_expiry().warning_threshold()
+        );
+        assert_eq!(
+            Duration::from_secs(3600),
+            settings.certificate_expiry().check_interval()
+        );
+        assert_eq!(
+            Duration::from_secs(24 * 3600),
             settings
-                .diff_with_cached(PathBuf::from("i dont exist"))
-                .unwrap(),
-            true
+                .certificate_expiry()
+                .server_cert_renewal_threshold()
         );
     }
 
     #[test]
-    fn network_default() {
-        let moby1 = MobyRuntime {
-            uri: Url::parse("http://test").unwrap(),
-            network: "".to_string(),
-        };
-        assert_eq!(DEFAULT_NETWORKID, moby1.network());
+    fn moby_runtime_default_resources_default_to_unset() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        let resources = settings.moby_runtime().default_resources();
+        assert_eq!(None, resources.memory());
+        assert_eq!(None, resources.memory_swap());
+        assert_eq!(None, resources.cpu_shares());
+        assert_eq!(None, resources.nano_cpus());
+    }
 
-        let moby2 = MobyRuntime {
-            uri: Url::parse("http://test").unwrap(),
-            network: "some-network".to_string(),
-        };
-        assert_eq!("some-network", moby2.network());
+    #[test]
+    fn moby_runtime_does_not_prune_images_on_reconfigure_by_default() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.moby_runtime().prune_images_on_reconfigure());
+    }
+
+    #[test]
+    fn moby_runtime_labels_are_parsed() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_LABELS)).unwrap();
+        let labels = settings.moby_runtime().labels();
+        assert_eq!(2, labels.len());
+        assert_eq!(
+            Some(&"device1".to_string()),
+            labels.get("com.example.device-id")
+        );
+        assert_eq!(Some(&"".to_string()), labels.get("com.example.managed"));
+    }
+
+    #[test]
+    fn moby_runtime_does_not_prune_volumes_on_reconfigure_by_default() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.moby_runtime().prune_volumes_on_reconfigure());
+    }
+
+    #[test]
+    fn moby_runtime_log_config_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().log_config().is_none());
+    }
+
+    #[test]
+    fn moby_runtime_network_subnet_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.moby_runtime().network_subnet());
+        assert_eq!(None, settings.moby_runtime().network_gateway());
+    }
+
+    #[test]
+    fn moby_runtime_api_version_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.moby_runtime().api_version());
+    }
+
+    #[test]
+    fn moby_runtime_extra_hosts_default_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().extra_hosts().is_empty());
+    }
+
+    #[test]
+    fn moby_runtime_security_opt_defaults_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().security_opt().is_empty());
+    }
+
+    #[test]
+    fn moby_runtime_ulimits_default_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().ulimits().is_empty());
+    }
+
+    #[test]
+    fn moby_runtime_dns_defaults_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().dns().is_empty());
+    }
+
+    #[test]
+    fn moby_runtime_readonly_rootfs_defaults_to_false() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.moby_runtime().readonly_rootfs());
+    }
+
+    #[test]
+    fn moby_runtime_image_pull_timeout_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.moby_runtime().image_pull_timeout());
+    }
+
+    #[test]
+    fn moby_runtime_stop_timeout_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.moby_runtime().stop_timeout());
+    }
+
+    #[test]
+    fn moby_runtime_registry_ca_bundle_defaults_to_none() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(None, settings.moby_runtime().registry_ca_bundle());
+    }
+
+    #[test]
+    fn moby_runtime_content_trust_defaults_to_disabled() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.moby_runtime().content_trust().enabled());
+        assert_eq!(None, settings.moby_runtime().content_trust().server());
+        assert!(settings
+            .moby_runtime()
+            .content_trust()
+            .root_keys()
+            .is_empty());
+    }
+
+    #[test]
+    fn moby_runtime_content_trust_root_keys_are_read_from_file() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_CONTENT_TRUST)).unwrap();
+        assert!(settings.moby_runtime().content_trust().enabled());
+        assert_eq!(
+            &["0a2c8e0f8a3bd31e1e8e0545f1b3a1c8bde9a2a4c6a8f4f6c9d2c0b5e9f1a7d3".to_string()],
+            settings.moby_runtime().content_trust().root_keys()
+        );
+    }
+
+    #[test]
+    fn moby_runtime_additional_networks_default_to_empty() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().additional_networks().is_empty());
     }
 }
@@ -3,65 +3,294 @@
 // Adapted from the conduit proxy signal handling:
 // https://github.com/runconduit/conduit/blob/master/proxy/src/signal.rs
 
-use futures::Future;
+#[cfg(windows)]
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
-type ShutdownSignal = Box<Future<Item = (), Error = ()> + Send>;
+use futures::future::Either;
+#[cfg(windows)]
+use futures::sync::oneshot;
+use futures::{future, Future, Stream};
+use tokio::timer::Interval;
+#[cfg(windows)]
+use windows_service::service::ServiceControl;
+#[cfg(windows)]
+use windows_service::service_control_handler::ServiceControlHandlerResult;
 
+pub type ShutdownSignal = Box<Future<Item = (), Error = ()> + Send>;
+
+const FILE_POLL_INTERVAL_SECS: u64 = 1;
+
+/// A source of the shutdown signal of the daemon.
+pub enum ShutdownTrigger {
+    /// Any of the OS signals with the given names, such as `SIGTERM`.
+    Signals(Vec<String>),
+    /// The file at the path being created or touched.
+    File(PathBuf),
+    /// Something being written to the named pipe at the path.
+    #[cfg(unix)]
+    NamedPipe(PathBuf),
+    /// A future provided by the host running the daemon.
+    Custom(ShutdownSignal),
+}
+
+impl ShutdownTrigger {
+    fn into_signal(self) -> ShutdownSignal {
+        match self {
+            ShutdownTrigger::Signals(names) => imp::signals(&names),
+            ShutdownTrigger::File(path) => file(path),
+            #[cfg(unix)]
+            ShutdownTrigger::NamedPipe(path) => imp::named_pipe(path),
+            ShutdownTrigger::Custom(signal) => signal,
+        }
+    }
+}
+
+/// The names of the signals `Signals` accepts on this platform.
+pub fn is_known_signal(name: &str) -> bool {
+    imp::KNOWN_SIGNALS.contains(&name)
+}
+
+/// Shuts down on SIGINT or SIGTERM on unix, and on Ctrl+C elsewhere.
 pub fn shutdown() -> ShutdownSignal {
-    imp::shutdown()
+    shutdown_on(vec![ShutdownTrigger::Signals(default_signals())])
+}
+
+/// Shuts down on whichever of `triggers` fires first. Without triggers, the
+/// daemon is never shut down by a signal.
+pub fn shutdown_on(triggers: Vec<ShutdownTrigger>) -> ShutdownSignal {
+    if triggers.is_empty() {
+        return Box::new(future::empty());
+    }
+    let signals = triggers.into_iter().map(ShutdownTrigger::into_signal);
+    Box::new(future::select_all(signals).map(|_| ()).map_err(|_| ()))
+}
+
+/// Fires on the second interrupt (SIGINT, or Ctrl+C on Windows) if `signals`
+/// include interrupts. The first one shuts the daemon down gracefully like
+/// any other signal, with the modules given their shutdown timeout to stop,
+/// while a second one forces the daemon to exit right away. Other signals,
+/// such as SIGTERM, always wait for the graceful shutdown.
+pub fn forced(signals: &[String]) -> ShutdownSignal {
+    if signals.iter().any(|name| name == imp::INTERRUPT) {
+        forced_by(imp::interrupts())
+    } else {
+        Box::new(future::empty())
+    }
+}
+
+/// Fires on the second item of `interrupts`.
+pub fn forced_by<S>(interrupts: S) -> ShutdownSignal
+where
+    S: Stream + Send + 'static,
+{
+    let second =
+        interrupts.skip(1).into_future().map_err(|_| ()).and_then(
+            |(interrupt, _)| match interrupt {
+                Some(_) => {
+                    info!(
+                        target: "iotedged::signal",
+                        "Interrupted again, forcing shutdown",
+                    );
+                    Either::A(future::ok(()))
+                }
+                None => Either::B(future::empty()),
+            },
+        );
+    Box::new(second)
+}
+
+pub fn default_signals() -> Vec<String> {
+    imp::DEFAULT_SIGNALS
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Returns a handler for the events the Windows Service Control Manager sends
+/// to the service, and a signal that fires once the service is stopped or
+/// the system shuts down. Passing the signal to `run_until` stops the
+/// modules and the APIs gracefully before the service reports it stopped.
+#[cfg(windows)]
+pub fn service_control() -> (
+    impl Fn(ServiceControl) -> ServiceControlHandlerResult + Send + 'static,
+    ShutdownSignal,
+) {
+    let (sender, receiver) = oneshot::channel();
+    let sender = RefCell::new(Some(sender)); // register() takes Fn, not FnMut
+
+    let handler = move |control_event| match control_event {
+        ServiceControl::Shutdown | ServiceControl::Stop => {
+            info!(
+                target: "iotedged::signal",
+                "{} service is shutting down",
+                crate_name!(),
+            );
+
+            // If sender is None, then it has already been consumed by a previous shutdown / stop notification
+            // that signaled the receiver. There's nothing more to do in that case.
+            if let Some(sender) = sender.borrow_mut().take() {
+                sender.send(()).unwrap_or_else(|err| {
+                    error!(
+                        "An error occurred while raising service shutdown signal: {:?}",
+                        err
+                    );
+                });
+            }
+
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    };
+    (handler, Box::new(receiver.map_err(|_| ())))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Checks the modification time of the file every second, since there is no
+// portable way to be notified of changes to it.
+fn file(path: PathBuf) -> ShutdownSignal {
+    let initial = modified(&path);
+    let interval = Duration::from_secs(FILE_POLL_INTERVAL_SECS);
+    let touched = Interval::new(Instant::now() + interval, interval)
+        .map_err(|_| ())
+        .skip_while({
+            let path = path.clone();
+            move |_| Ok(modified(&path) == initial)
+        })
+        .into_future()
+        .map(move |_| {
+            info!(
+                target: "iotedged::signal",
+                "{} was touched, starting shutdown",
+                path.display(),
+            );
+        })
+        .map_err(|_| ());
+    Box::new(touched)
 }
 
 #[cfg(unix)]
 mod imp {
-    use std::fmt;
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::os::unix::fs::FileTypeExt;
+    use std::path::PathBuf;
+    use std::thread;
 
+    use futures::sync::oneshot;
     use futures::{future, Future, Stream};
-    use tokio_signal::unix::{Signal, SIGINT, SIGTERM};
+    use tokio_signal::unix::{Signal, SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGUSR1, SIGUSR2};
 
     use super::ShutdownSignal;
 
-    pub(super) fn shutdown() -> ShutdownSignal {
-        let signals = [SIGINT, SIGTERM].into_iter().map(|&sig| {
-            Signal::new(sig)
-                .flatten_stream()
-                .into_future()
-                .map(move |_| {
-                    info!(
-                        target: "iotedged::signal",
-                        "Received {}, starting shutdown",
-                        DisplaySignal(sig),
-                    );
-                })
-        });
+    pub(super) const DEFAULT_SIGNALS: &[&str] = &["SIGINT", "SIGTERM"];
+
+    pub(super) const INTERRUPT: &str = "SIGINT";
+
+    pub(super) const KNOWN_SIGNALS: &[&str] = &[
+        "SIGHUP", "SIGINT", "SIGQUIT", "SIGTERM", "SIGUSR1", "SIGUSR2",
+    ];
+
+    fn signal_number(name: &str) -> Option<i32> {
+        match name {
+            "SIGHUP" => Some(SIGHUP),
+            "SIGINT" => Some(SIGINT),
+            "SIGQUIT" => Some(SIGQUIT),
+            "SIGTERM" => Some(SIGTERM),
+            "SIGUSR1" => Some(SIGUSR1),
+            "SIGUSR2" => Some(SIGUSR2),
+            _ => None,
+        }
+    }
+
+    pub(super) fn signals(names: &[String]) -> ShutdownSignal {
+        let signals: Vec<_> = names
+            .iter()
+            .filter_map(|name| signal_number(name).map(|sig| (name.clone(), sig)))
+            .map(|(name, sig)| {
+                Signal::new(sig)
+                    .flatten_stream()
+                    .into_future()
+                    .map(move |_| {
+                        info!(
+                            target: "iotedged::signal",
+                            "Received {}, starting shutdown",
+                            name,
+                        );
+                    })
+            })
+            .collect();
+        if signals.is_empty() {
+            return Box::new(future::empty());
+        }
         let on_any_signal = future::select_all(signals)
             .map(|_| ())
             .map_err(|_| unreachable!("Signal never returns an error"));
         Box::new(on_any_signal)
     }
 
-    #[derive(Clone, Copy)]
-    struct DisplaySignal(i32);
+    pub(super) fn interrupts() -> impl Stream<Item = (), Error = ()> + Send {
+        Signal::new(SIGINT)
+            .flatten_stream()
+            .map(|_| ())
+            .map_err(|_| ())
+    }
 
-    impl fmt::Display for DisplaySignal {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let s = match self.0 {
-                SIGINT => "SIGINT",
-                SIGTERM => "SIGTERM",
-                other => return write!(f, "signal {}", other),
-            };
-            f.write_str(s)
-        }
+    // Opening a named pipe for reading blocks until a writer opens it too, so
+    // this waits on a thread of its own. A pipe that cannot be read never
+    // fires.
+    pub(super) fn named_pipe(path: PathBuf) -> ShutdownSignal {
+        let (tx, rx) = oneshot::channel();
+        thread::spawn(move || {
+            let is_fifo = fs::metadata(&path)
+                .map(|m| m.file_type().is_fifo())
+                .unwrap_or(false);
+            if !is_fifo {
+                warn!(
+                    "{} is not a named pipe, it cannot trigger shutdown",
+                    path.display()
+                );
+                return;
+            }
+            match File::open(&path).and_then(|mut pipe| pipe.read(&mut [0; 1])) {
+                Ok(_) => {
+                    info!(
+                        target: "iotedged::signal",
+                        "{} was written to, starting shutdown",
+                        path.display(),
+                    );
+                    tx.send(()).unwrap_or(());
+                }
+                Err(err) => warn!("Could not read named pipe {}: {}", path.display(), err),
+            }
+        });
+        Box::new(rx.or_else(|_| future::empty()))
     }
 }
 
 #[cfg(not(unix))]
 mod imp {
-    use futures::{Future, Stream};
+    use futures::{future, Future, Stream};
     use tokio_signal;
 
     use super::ShutdownSignal;
 
-    pub(super) fn shutdown() -> ShutdownSignal {
+    pub(super) const DEFAULT_SIGNALS: &[&str] = &["CTRL_C"];
+
+    pub(super) const INTERRUPT: &str = "CTRL_C";
+
+    pub(super) const KNOWN_SIGNALS: &[&str] = &["CTRL_C"];
+
+    pub(super) fn signals(names: &[String]) -> ShutdownSignal {
+        if !names.iter().any(|name| name == "CTRL_C") {
+            return Box::new(future::empty());
+        }
         let on_ctrl_c = tokio_signal::ctrl_c()
             .flatten_stream()
             .into_future()
@@ -74,4 +303,110 @@ mod imp {
             .map_err(|_| unreachable!("ctrl_c never returns errors"));
         Box::new(on_ctrl_c)
     }
+
+    pub(super) fn interrupts() -> impl Stream<Item = (), Error = ()> + Send {
+        tokio_signal::ctrl_c()
+            .flatten_stream()
+            .map(|_| ())
+            .map_err(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use futures::sync::oneshot;
+    use tempdir::TempDir;
+    use tokio::runtime::current_thread::Runtime;
+
+    use super::*;
+
+    #[test]
+    fn custom_trigger_shuts_down() {
+        let tmp_dir = TempDir::new("shutdown").unwrap();
+        let (tx, rx) = oneshot::channel();
+        let signal = shutdown_on(vec![
+            ShutdownTrigger::File(tmp_dir.path().join("never-touched")),
+            ShutdownTrigger::Custom(Box::new(rx.map_err(|_| ()))),
+        ]);
+
+        tx.send(()).unwrap();
+
+        Runtime::new().unwrap().block_on(signal).unwrap();
+    }
+
+    #[test]
+    fn touching_file_shuts_down() {
+        let tmp_dir = TempDir::new("shutdown").unwrap();
+        let path = tmp_dir.path().join("shutdown");
+        let signal = shutdown_on(vec![ShutdownTrigger::File(path.clone())]);
+
+        fs::write(&path, b"").unwrap();
+
+        Runtime::new().unwrap().block_on(signal).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn service_stop_shuts_down() {
+        let (handler, stop_signal) = service_control();
+        let signal = shutdown_on(vec![ShutdownTrigger::Custom(stop_signal)]);
+        let stopped = signal.map(|()| "stopped");
+
+        match handler(ServiceControl::Interrogate) {
+            ServiceControlHandlerResult::NoError => (),
+            _ => panic!("interrogate was not handled"),
+        }
+        match handler(ServiceControl::Pause) {
+            ServiceControlHandlerResult::NotImplemented => (),
+            _ => panic!("pause was handled"),
+        }
+        match handler(ServiceControl::Stop) {
+            ServiceControlHandlerResult::NoError => (),
+            _ => panic!("stop was not handled"),
+        }
+        // a shutdown of the system after the stop is harmless
+        match handler(ServiceControl::Shutdown) {
+            ServiceControlHandlerResult::NoError => (),
+            _ => panic!("shutdown was not handled"),
+        }
+
+        assert_eq!(
+            "stopped",
+            Runtime::new().unwrap().block_on(stopped).unwrap()
+        );
+    }
+
+    #[test]
+    fn single_interrupt_does_not_force() {
+        let forced = forced_by(stream::iter_ok::<_, ()>(vec![()]));
+
+        match forced.select2(future::ok::<_, ()>(())).wait() {
+            Ok(Either::B(_)) => (),
+            _ => panic!("a single interrupt forced shutdown"),
+        }
+    }
+
+    #[test]
+    fn second_interrupt_forces() {
+        let forced = forced_by(stream::iter_ok::<_, ()>(vec![(), ()]));
+
+        Runtime::new().unwrap().block_on(forced).unwrap();
+    }
+
+    #[test]
+    fn forcing_needs_interrupts() {
+        let forced = forced(&["SIGTERM".to_string()]);
+
+        match forced.select2(future::ok::<_, ()>(())).wait() {
+            Ok(Either::B(_)) => (),
+            _ => panic!("forced without interrupts"),
+        }
+    }
+
+    #[test]
+    fn signal_names_are_checked() {
+        assert!(default_signals().iter().all(|name| is_known_signal(name)));
+        assert!(!is_known_signal("SIGKILL"));
+    }
 }
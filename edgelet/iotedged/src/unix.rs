@@ -6,9 +6,10 @@ use signal;
 
 pub fn run() -> Result<(), Error> {
     let settings = app::init()?;
-    let main = super::Main::new(settings);
+    let shutdown_signal = signal::shutdown_on(settings.shutdown().triggers());
+    let force_signal = signal::forced(&settings.shutdown().signals());
+    let main = super::Main::new(settings).with_force_signal(force_signal);
 
-    let shutdown_signal = signal::shutdown();
     main.run_until(shutdown_signal)?;
     Ok(())
 }
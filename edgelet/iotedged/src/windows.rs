@@ -1,25 +1,21 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use std::cell::RefCell;
 use std::env;
 use std::ffi::OsString;
 use std::time::Duration;
 
 use failure::ResultExt;
 use futures::prelude::*;
-use futures::sync::oneshot;
 use windows_service::service::{
-    ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType,
-};
-use windows_service::service_control_handler::{
-    register, ServiceControlHandlerResult, ServiceStatusHandle,
+    ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType,
 };
+use windows_service::service_control_handler::{register, ServiceStatusHandle};
 use windows_service::service_dispatcher;
 
 use app;
 use error::{Error, ErrorKind, InitializeErrorReason, ServiceError};
 use logging;
-use signal;
+use signal::{self, ShutdownTrigger};
 
 const RUN_AS_CONSOLE_KEY: &str = "IOTEDGE_RUN_AS_CONSOLE";
 const IOTEDGED_SERVICE_NAME: &str = crate_name!();
@@ -44,45 +40,21 @@ fn iotedge_service_main(args: Vec<OsString>) {
 }
 
 fn run_as_service(_: Vec<OsString>) -> Result<ServiceStatusHandle, Error> {
-    // setup a channel for notifying service stop/shutdown
-    let (sender, receiver) = oneshot::channel();
-    let sender = RefCell::new(Some(sender)); // register() takes Fn, not FnMut
-
     // setup the service control handler
-    let status_handle = register(
-        IOTEDGED_SERVICE_NAME,
-        move |control_event| match control_event {
-            ServiceControl::Shutdown | ServiceControl::Stop => {
-                info!("{} service is shutting down", IOTEDGED_SERVICE_NAME);
-
-                // If sender is None, then it has already been consumed by a previous shutdown / stop notification
-                // that signaled the receiver. There's nothing more to do in that case.
-                if let Some(sender) = sender.borrow_mut().take() {
-                    sender.send(()).unwrap_or_else(|err| {
-                        error!(
-                            "An error occurred while raising service shutdown signal: {:?}",
-                            err
-                        );
-                    });
-                }
-
-                ServiceControlHandlerResult::NoError
-            }
-            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
-            _ => ServiceControlHandlerResult::NotImplemented,
-        },
-    )
-    .map_err(ServiceError::from)
-    .context(ErrorKind::Initialize(
-        InitializeErrorReason::RegisterWindowsService,
-    ))?;
+    let (control_handler, stop_signal) = signal::service_control();
+    let status_handle = register(IOTEDGED_SERVICE_NAME, control_handler)
+        .map_err(ServiceError::from)
+        .context(ErrorKind::Initialize(
+            InitializeErrorReason::RegisterWindowsService,
+        ))?;
 
     // initialize iotedged
     info!("Initializing {} service.", IOTEDGED_SERVICE_NAME);
     let settings = app::init_win_svc()?;
+    let mut triggers = settings.shutdown().triggers();
+    triggers.push(ShutdownTrigger::Custom(stop_signal));
     let main = super::Main::new(settings);
-    let shutdown_signal = signal::shutdown()
-        .select(receiver.map_err(|_| ()))
+    let shutdown_signal = signal::shutdown_on(triggers)
         .map(move |_| {
             info!("Stopping {} service.", IOTEDGED_SERVICE_NAME);
             if let Err(err) = update_service_state(status_handle, ServiceState::StopPending) {
@@ -106,9 +78,10 @@ fn run_as_service(_: Vec<OsString>) -> Result<ServiceStatusHandle, Error> {
 
 pub fn run_as_console() -> Result<(), Error> {
     let settings = app::init()?;
-    let main = super::Main::new(settings);
+    let shutdown_signal = signal::shutdown_on(settings.shutdown().triggers());
+    let force_signal = signal::forced(&settings.shutdown().signals());
+    let main = super::Main::new(settings).with_force_signal(force_signal);
 
-    let shutdown_signal = signal::shutdown();
     main.run_until(shutdown_signal)?;
     Ok(())
 }
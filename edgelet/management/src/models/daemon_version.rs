@@ -0,0 +1,75 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DaemonVersion {
+    #[serde(rename = "version")]
+    version: String,
+    #[serde(rename = "commit", skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    #[serde(rename = "apiVersions")]
+    api_versions: Vec<String>,
+}
+
+impl DaemonVersion {
+    pub fn new(version: String, api_versions: Vec<String>) -> Self {
+        DaemonVersion {
+            version,
+            commit: None,
+            api_versions,
+        }
+    }
+
+    pub fn set_version(&mut self, version: String) {
+        self.version = version;
+    }
+
+    pub fn with_version(mut self, version: String) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn version(&self) -> &String {
+        &self.version
+    }
+
+    pub fn set_commit(&mut self, commit: String) {
+        self.commit = Some(commit);
+    }
+
+    pub fn with_commit(mut self, commit: String) -> Self {
+        self.commit = Some(commit);
+        self
+    }
+
+    pub fn commit(&self) -> Option<&str> {
+        self.commit.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_commit(&mut self) {
+        self.commit = None;
+    }
+
+    pub fn set_api_versions(&mut self, api_versions: Vec<String>) {
+        self.api_versions = api_versions;
+    }
+
+    pub fn with_api_versions(mut self, api_versions: Vec<String>) -> Self {
+        self.api_versions = api_versions;
+        self
+    }
+
+    pub fn api_versions(&self) -> &[String] {
+        &self.api_versions
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncryptedItem {
+    #[serde(rename = "moduleId")]
+    module_id: String,
+    #[serde(rename = "generationId")]
+    generation_id: String,
+    #[serde(rename = "ciphertext")]
+    ciphertext: String,
+    #[serde(rename = "initializationVector")]
+    initialization_vector: String,
+}
+
+impl EncryptedItem {
+    pub fn new(
+        module_id: String,
+        generation_id: String,
+        ciphertext: String,
+        initialization_vector: String,
+    ) -> Self {
+        EncryptedItem {
+            module_id,
+            generation_id,
+            ciphertext,
+            initialization_vector,
+        }
+    }
+
+    pub fn set_module_id(&mut self, module_id: String) {
+        self.module_id = module_id;
+    }
+
+    pub fn with_module_id(mut self, module_id: String) -> Self {
+        self.module_id = module_id;
+        self
+    }
+
+    pub fn module_id(&self) -> &String {
+        &self.module_id
+    }
+
+    pub fn set_generation_id(&mut self, generation_id: String) {
+        self.generation_id = generation_id;
+    }
+
+    pub fn with_generation_id(mut self, generation_id: String) -> Self {
+        self.generation_id = generation_id;
+        self
+    }
+
+    pub fn generation_id(&self) -> &String {
+        &self.generation_id
+    }
+
+    pub fn set_ciphertext(&mut self, ciphertext: String) {
+        self.ciphertext = ciphertext;
+    }
+
+    pub fn with_ciphertext(mut self, ciphertext: String) -> Self {
+        self.ciphertext = ciphertext;
+        self
+    }
+
+    pub fn ciphertext(&self) -> &String {
+        &self.ciphertext
+    }
+
+    pub fn set_initialization_vector(&mut self, initialization_vector: String) {
+        self.initialization_vector = initialization_vector;
+    }
+
+    pub fn with_initialization_vector(mut self, initialization_vector: String) -> Self {
+        self.initialization_vector = initialization_vector;
+        self
+    }
+
+    pub fn initialization_vector(&self) -> &String {
+        &self.initialization_vector
+    }
+}
@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedItemList {
+    #[serde(rename = "items")]
+    items: Vec<::models::EncryptedItem>,
+}
+
+impl EncryptedItemList {
+    pub fn new(items: Vec<::models::EncryptedItem>) -> Self {
+        EncryptedItemList { items }
+    }
+
+    pub fn set_items(&mut self, items: Vec<::models::EncryptedItem>) {
+        self.items = items;
+    }
+
+    pub fn with_items(mut self, items: Vec<::models::EncryptedItem>) -> Self {
+        self.items = items;
+        self
+    }
+
+    pub fn items(&self) -> &[::models::EncryptedItem] {
+        &self.items
+    }
+}
@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportEncryptionKeyRequest {
+    #[serde(rename = "passphrase")]
+    passphrase: String,
+}
+
+impl ExportEncryptionKeyRequest {
+    pub fn new(passphrase: String) -> Self {
+        ExportEncryptionKeyRequest { passphrase }
+    }
+
+    pub fn set_passphrase(&mut self, passphrase: String) {
+        self.passphrase = passphrase;
+    }
+
+    pub fn with_passphrase(mut self, passphrase: String) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
+    pub fn passphrase(&self) -> &String {
+        &self.passphrase
+    }
+}
@@ -0,0 +1,79 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Health {
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "lastWatchdogCheck", skip_serializing_if = "Option::is_none")]
+    last_watchdog_check: Option<String>,
+    #[serde(rename = "hsm", skip_serializing_if = "Option::is_none")]
+    hsm: Option<String>,
+}
+
+impl Health {
+    pub fn new(status: String) -> Self {
+        Health {
+            status,
+            last_watchdog_check: None,
+            hsm: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn with_status(mut self, status: String) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> &String {
+        &self.status
+    }
+
+    pub fn set_last_watchdog_check(&mut self, last_watchdog_check: String) {
+        self.last_watchdog_check = Some(last_watchdog_check);
+    }
+
+    pub fn with_last_watchdog_check(mut self, last_watchdog_check: String) -> Self {
+        self.last_watchdog_check = Some(last_watchdog_check);
+        self
+    }
+
+    pub fn last_watchdog_check(&self) -> Option<&str> {
+        self.last_watchdog_check.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_last_watchdog_check(&mut self) {
+        self.last_watchdog_check = None;
+    }
+
+    pub fn set_hsm(&mut self, hsm: String) {
+        self.hsm = Some(hsm);
+    }
+
+    pub fn with_hsm(mut self, hsm: String) -> Self {
+        self.hsm = Some(hsm);
+        self
+    }
+
+    pub fn hsm(&self) -> Option<&str> {
+        self.hsm.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_hsm(&mut self) {
+        self.hsm = None;
+    }
+}
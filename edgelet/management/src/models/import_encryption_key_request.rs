@@ -0,0 +1,52 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportEncryptionKeyRequest {
+    #[serde(rename = "passphrase")]
+    passphrase: String,
+    #[serde(rename = "key")]
+    key: String,
+}
+
+impl ImportEncryptionKeyRequest {
+    pub fn new(passphrase: String, key: String) -> Self {
+        ImportEncryptionKeyRequest { passphrase, key }
+    }
+
+    pub fn set_passphrase(&mut self, passphrase: String) {
+        self.passphrase = passphrase;
+    }
+
+    pub fn with_passphrase(mut self, passphrase: String) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
+    pub fn passphrase(&self) -> &String {
+        &self.passphrase
+    }
+
+    pub fn set_key(&mut self, key: String) {
+        self.key = key;
+    }
+
+    pub fn with_key(mut self, key: String) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn key(&self) -> &String {
+        &self.key
+    }
+}
@@ -1,17 +1,31 @@
 mod config;
 pub use self::config::Config;
+mod daemon_version;
+pub use self::daemon_version::DaemonVersion;
+mod encrypted_item;
+pub use self::encrypted_item::EncryptedItem;
+mod encrypted_item_list;
+pub use self::encrypted_item_list::EncryptedItemList;
+mod encryption_key_backup;
+pub use self::encryption_key_backup::EncryptionKeyBackup;
 mod env_var;
 pub use self::env_var::EnvVar;
 mod error_response;
 pub use self::error_response::ErrorResponse;
 mod exit_status;
 pub use self::exit_status::ExitStatus;
+mod export_encryption_key_request;
+pub use self::export_encryption_key_request::ExportEncryptionKeyRequest;
+mod health;
+pub use self::health::Health;
 mod identity;
 pub use self::identity::Identity;
 mod identity_list;
 pub use self::identity_list::IdentityList;
 mod identity_spec;
 pub use self::identity_spec::IdentitySpec;
+mod import_encryption_key_request;
+pub use self::import_encryption_key_request::ImportEncryptionKeyRequest;
 mod update_identity;
 pub use self::update_identity::UpdateIdentity;
 mod module_details;
@@ -20,12 +34,16 @@ mod module_list;
 pub use self::module_list::ModuleList;
 mod module_spec;
 pub use self::module_spec::ModuleSpec;
+mod restart_event;
+pub use self::restart_event::RestartEvent;
 mod runtime_status;
 pub use self::runtime_status::RuntimeStatus;
 mod status;
 pub use self::status::Status;
 mod system_info;
 pub use self::system_info::SystemInfo;
+mod watchdog_details;
+pub use self::watchdog_details::WatchdogDetails;
 
 // TODO(farcaller): sort out files
 pub struct File;
@@ -0,0 +1,91 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RestartEvent {
+    #[serde(rename = "count")]
+    count: i64,
+    #[serde(rename = "reason")]
+    reason: String,
+    #[serde(rename = "exitCode", skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i64>,
+    #[serde(rename = "timestamp")]
+    timestamp: String,
+}
+
+impl RestartEvent {
+    pub fn new(count: i64, reason: String, timestamp: String) -> Self {
+        RestartEvent {
+            count,
+            reason,
+            exit_code: None,
+            timestamp,
+        }
+    }
+
+    pub fn set_count(&mut self, count: i64) {
+        self.count = count;
+    }
+
+    pub fn with_count(mut self, count: i64) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn count(&self) -> &i64 {
+        &self.count
+    }
+
+    pub fn set_reason(&mut self, reason: String) {
+        self.reason = reason;
+    }
+
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    pub fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub fn set_exit_code(&mut self, exit_code: i64) {
+        self.exit_code = Some(exit_code);
+    }
+
+    pub fn with_exit_code(mut self, exit_code: i64) -> Self {
+        self.exit_code = Some(exit_code);
+        self
+    }
+
+    pub fn exit_code(&self) -> Option<&i64> {
+        self.exit_code.as_ref()
+    }
+
+    pub fn reset_exit_code(&mut self) {
+        self.exit_code = None;
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: String) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn with_timestamp(mut self, timestamp: String) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn timestamp(&self) -> &String {
+        &self.timestamp
+    }
+}
@@ -17,6 +17,8 @@ pub struct RuntimeStatus {
     status: String,
     #[serde(rename = "description", skip_serializing_if = "Option::is_none")]
     description: Option<String>,
+    #[serde(rename = "health", skip_serializing_if = "Option::is_none")]
+    health: Option<String>,
 }
 
 impl RuntimeStatus {
@@ -24,6 +26,7 @@ impl RuntimeStatus {
         RuntimeStatus {
             status,
             description: None,
+            health: None,
         }
     }
 
@@ -56,4 +59,21 @@ impl RuntimeStatus {
     pub fn reset_description(&mut self) {
         self.description = None;
     }
+
+    pub fn set_health(&mut self, health: String) {
+        self.health = Some(health);
+    }
+
+    pub fn with_health(mut self, health: String) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn health(&self) -> Option<&str> {
+        self.health.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_health(&mut self) {
+        self.health = None;
+    }
 }
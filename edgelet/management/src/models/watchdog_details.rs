@@ -0,0 +1,111 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchdogDetails {
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "restartCount")]
+    restart_count: i64,
+    #[serde(rename = "paused")]
+    paused: bool,
+    #[serde(rename = "lastRestart", skip_serializing_if = "Option::is_none")]
+    last_restart: Option<::models::RestartEvent>,
+    #[serde(rename = "lastError", skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+impl WatchdogDetails {
+    pub fn new(status: String, restart_count: i64, paused: bool) -> Self {
+        WatchdogDetails {
+            status,
+            restart_count,
+            paused,
+            last_restart: None,
+            last_error: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn with_status(mut self, status: String) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> &String {
+        &self.status
+    }
+
+    pub fn set_restart_count(&mut self, restart_count: i64) {
+        self.restart_count = restart_count;
+    }
+
+    pub fn with_restart_count(mut self, restart_count: i64) -> Self {
+        self.restart_count = restart_count;
+        self
+    }
+
+    pub fn restart_count(&self) -> &i64 {
+        &self.restart_count
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn with_paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    pub fn paused(&self) -> &bool {
+        &self.paused
+    }
+
+    pub fn set_last_restart(&mut self, last_restart: ::models::RestartEvent) {
+        self.last_restart = Some(last_restart);
+    }
+
+    pub fn with_last_restart(mut self, last_restart: ::models::RestartEvent) -> Self {
+        self.last_restart = Some(last_restart);
+        self
+    }
+
+    pub fn last_restart(&self) -> Option<&::models::RestartEvent> {
+        self.last_restart.as_ref()
+    }
+
+    pub fn reset_last_restart(&mut self) {
+        self.last_restart = None;
+    }
+
+    pub fn set_last_error(&mut self, last_error: String) {
+        self.last_error = Some(last_error);
+    }
+
+    pub fn with_last_error(mut self, last_error: String) -> Self {
+        self.last_error = Some(last_error);
+        self
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_last_error(&mut self) {
+        self.last_error = None;
+    }
+}
@@ -25,6 +25,8 @@ use std::net::SocketAddr;
 mod error;
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(target_os = "linux")]
+mod notify;
 
 pub use self::error::{Error, ErrorKind, SocketLookupType};
 
@@ -38,4 +40,6 @@ pub enum Socket {
 }
 
 #[cfg(target_os = "linux")]
-pub use self::linux::{listener, listener_name, listeners_name};
+pub use self::linux::{listener, listener_name, listeners, listeners_name};
+#[cfg(target_os = "linux")]
+pub use self::notify::notify;
@@ -30,6 +30,13 @@ const ENV_PID: &str = "LISTEN_PID";
 const ENV_FDS: &str = "LISTEN_FDS";
 const ENV_NAMES: &str = "LISTEN_FDNAMES";
 
+/// Returns all of the listeners passed to the process, in file descriptor
+/// order.
+pub fn listeners() -> Result<Vec<Socket>, Error> {
+    debug!("Finding sockets");
+    listen_fds(false, LISTEN_FDS_START)
+}
+
 /// Returns the first listener for a file descriptor number.
 ///
 /// Note that this value is biased by `LISTEN_FDS_START`. For example, an input of 0 corresponds to fd 3.
@@ -0,0 +1,100 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Implements the daemon interface for service notifications, based off of
+//! [`sd_notify`](https://www.freedesktop.org/software/systemd/man/sd_notify.html).
+
+use std::env;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+
+use failure::ResultExt;
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockAddr, SockFlag, SockType, UnixAddr};
+use nix::unistd;
+
+use error::{Error, ErrorKind};
+
+const ENV_NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// Sends `state`, such as `READY=1`, to the service manager. Returns `false`
+/// without sending anything if the service manager does not expect
+/// notifications from the process, for example because it is not a
+/// `Type=notify` unit.
+pub fn notify(state: &str) -> Result<bool, Error> {
+    let path = match env::var_os(ENV_NOTIFY_SOCKET) {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    debug!("Notifying {:?} of {}", path, state);
+
+    let addr = notify_addr(&path)
+        .with_context(|_| ErrorKind::InvalidVar(ENV_NOTIFY_SOCKET.to_string()))?;
+    let fd = socket::socket(
+        AddressFamily::Unix,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )
+    .context(ErrorKind::Syscall("socket"))?;
+    let sent = socket::sendto(
+        fd,
+        state.as_bytes(),
+        &SockAddr::Unix(addr),
+        MsgFlags::empty(),
+    );
+    unistd::close(fd).unwrap_or(());
+    sent.context(ErrorKind::Syscall("sendto"))?;
+
+    Ok(true)
+}
+
+// Paths starting with @ are in the abstract namespace.
+fn notify_addr(path: &OsStr) -> ::nix::Result<UnixAddr> {
+    match path.as_bytes().split_first() {
+        Some((&b'@', name)) => UnixAddr::new_abstract(name),
+        _ => UnixAddr::new(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::os::unix::net::UnixDatagram;
+    use std::process;
+    use std::sync::{Mutex, MutexGuard};
+
+    lazy_static! {
+        static ref LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    fn lock_env<'a>() -> MutexGuard<'a, ()> {
+        LOCK.lock().unwrap()
+    }
+
+    #[test]
+    fn test_notify() {
+        let _l = lock_env();
+        let path = env::temp_dir().join(format!("systemd-notify-{}.sock", process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+        env::set_var(ENV_NOTIFY_SOCKET, &path);
+
+        assert!(notify("READY=1").unwrap());
+
+        let mut buf = [0; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(b"READY=1", &buf[..len]);
+
+        env::remove_var(ENV_NOTIFY_SOCKET);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_notify_without_socket() {
+        let _l = lock_env();
+        env::remove_var(ENV_NOTIFY_SOCKET);
+
+        assert!(!notify("READY=1").unwrap());
+    }
+}
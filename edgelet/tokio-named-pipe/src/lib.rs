@@ -11,7 +11,9 @@
 #![cfg_attr(feature = "cargo-clippy", allow(use_self))]
 
 extern crate futures;
+extern crate mio;
 extern crate mio_named_pipes;
+extern crate miow;
 extern crate tokio;
 extern crate winapi;
 
@@ -19,12 +21,15 @@ use std::convert::AsRef;
 use std::fs::OpenOptions;
 use std::io::{self, Read, Write};
 use std::iter::once;
+use std::mem;
 use std::os::windows::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use futures::Poll;
+use futures::{Async, Poll};
+use mio::Ready;
 use mio_named_pipes::NamedPipe;
+use miow::pipe::NamedPipeBuilder;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::reactor::PollEvented2;
 use winapi::um::namedpipeapi::WaitNamedPipeW;
@@ -97,6 +102,64 @@ impl PipeStream {
     pub fn io_mut(&mut self) -> &mut PollEvented2<NamedPipe> {
         &mut self.io
     }
+
+    /// The id of the process at the client end of a pipe accepted by a
+    /// `PipeListener`.
+    pub fn client_pid(&self) -> io::Result<u32> {
+        let mut pid = 0;
+        let ok =
+            unsafe { GetNamedPipeClientProcessId(self.io.get_ref().as_raw_handle(), &mut pid) };
+        if ok == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(pid)
+        }
+    }
+}
+
+/// The server end of a named pipe. Each client connects to its own instance
+/// of the pipe, and a new instance is created for the next client every time
+/// one is accepted.
+#[derive(Debug)]
+pub struct PipeListener {
+    path: PathBuf,
+    io: PollEvented2<NamedPipe>,
+}
+
+impl PipeListener {
+    /// Creates the first instance of the pipe. This fails if the pipe already
+    /// exists, so that another process can't serve some of its clients.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let io = PollEvented2::new(instance(path.as_ref(), true)?);
+        Ok(PipeListener {
+            path: path.as_ref().to_path_buf(),
+            io,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn poll_accept(&mut self) -> Poll<PipeStream, io::Error> {
+        match self.io.get_ref().connect() {
+            Ok(()) => {
+                let next = PollEvented2::new(instance(&self.path, false)?);
+                let io = mem::replace(&mut self.io, next);
+                Ok(Async::Ready(PipeStream { io }))
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(Ready::readable())?;
+                Ok(Async::NotReady)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn instance(path: &Path, first: bool) -> io::Result<NamedPipe> {
+    let pipe = NamedPipeBuilder::new(path).first(first).create()?;
+    Ok(unsafe { NamedPipe::from_raw_handle(pipe.into_raw_handle()) })
 }
 
 impl Read for PipeStream {
@@ -18,8 +18,10 @@ extern crate tokio;
 extern crate tokio_named_pipe;
 
 use std::io::{Read, Write};
+use std::process;
 use std::str;
 
+use futures::future;
 use futures::sink::Sink;
 use futures::stream::Stream;
 use futures::Future;
@@ -29,7 +31,7 @@ use rand::Rng;
 use tokio::codec::{FramedRead, FramedWrite, LinesCodec};
 use tokio::io as tio;
 
-use tokio_named_pipe::PipeStream;
+use tokio_named_pipe::{PipeListener, PipeStream};
 
 macro_rules! t {
     ($e:expr) => {
@@ -202,3 +204,31 @@ fn write_async() {
         }
     }
 }
+
+#[test]
+fn listener_accepts_clients() {
+    let num: u64 = rand::thread_rng().gen();
+    let path = format!(r"\\.\pipe\my-pipe-{}", num);
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let mut listener = t!(PipeListener::bind(&path));
+
+    for _ in 0..2 {
+        let client = t!(PipeStream::connect(&path, None));
+        let server = runtime
+            .block_on(future::poll_fn(|| listener.poll_accept()))
+            .unwrap();
+        assert_eq!(process::id(), t!(server.client_pid()));
+
+        runtime
+            .block_on(tio::write_all(client, b"cow say moo"))
+            .unwrap();
+        let (_, read_data) = runtime.block_on(tio::read_exact(server, [0; 11])).unwrap();
+        assert_eq!(b"cow say moo", &read_data);
+    }
+}
+
+#[test]
+fn bind_existing_pipe_fails() {
+    let (_server, path) = server();
+    assert!(PipeListener::bind(&path).is_err());
+}
@@ -0,0 +1,79 @@
+/*
+ * IoT Edge Module Workload API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Health {
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "hsm", skip_serializing_if = "Option::is_none")]
+    hsm: Option<String>,
+    #[serde(rename = "keyStore", skip_serializing_if = "Option::is_none")]
+    key_store: Option<String>,
+}
+
+impl Health {
+    pub fn new(status: String) -> Self {
+        Health {
+            status,
+            hsm: None,
+            key_store: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn with_status(mut self, status: String) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> &String {
+        &self.status
+    }
+
+    pub fn set_hsm(&mut self, hsm: String) {
+        self.hsm = Some(hsm);
+    }
+
+    pub fn with_hsm(mut self, hsm: String) -> Self {
+        self.hsm = Some(hsm);
+        self
+    }
+
+    pub fn hsm(&self) -> Option<&str> {
+        self.hsm.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_hsm(&mut self) {
+        self.hsm = None;
+    }
+
+    pub fn set_key_store(&mut self, key_store: String) {
+        self.key_store = Some(key_store);
+    }
+
+    pub fn with_key_store(mut self, key_store: String) -> Self {
+        self.key_store = Some(key_store);
+        self
+    }
+
+    pub fn key_store(&self) -> Option<&str> {
+        self.key_store.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_key_store(&mut self) {
+        self.key_store = None;
+    }
+}